@@ -350,10 +350,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode integer-to-hex --string '10'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from dec --to hex --string '10'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode integer-to-hex --string '10'
+target/debug/nutek-encode convert --from dec --to hex --string '10'
 sleep 0.5
 echo
 sleep 5
@@ -365,10 +365,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode integer-to-octal --string '10'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from dec --to oct --string '10'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode integer-to-octal --string '10'
+target/debug/nutek-encode convert --from dec --to oct --string '10'
 sleep 0.5
 echo
 sleep 5
@@ -380,10 +380,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode integer-to-binary --string '10'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from dec --to bin --string '10'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode integer-to-binary --string '10'
+target/debug/nutek-encode convert --from dec --to bin --string '10'
 sleep 0.5
 echo
 sleep 5
@@ -395,10 +395,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode hex-to-integer --string '0a'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from hex --to dec --string '0a'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode hex-to-integer --string '0a'
+target/debug/nutek-encode convert --from hex --to dec --string '0a'
 sleep 0.5
 echo
 sleep 5
@@ -410,10 +410,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode hex-to-binary --string '0a'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from hex --to bin --string '0a'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode hex-to-binary --string '0a'
+target/debug/nutek-encode convert --from hex --to bin --string '0a'
 sleep 0.5
 echo
 sleep 5
@@ -425,10 +425,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode hex-to-octal --string '0a'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from hex --to oct --string '0a'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode hex-to-octal --string '0a'
+target/debug/nutek-encode convert --from hex --to oct --string '0a'
 sleep 0.5
 echo
 sleep 5
@@ -440,10 +440,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode octal-to-integer --string '012'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from oct --to dec --string '012'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode octal-to-integer --string '012'
+target/debug/nutek-encode convert --from oct --to dec --string '012'
 sleep 0.5
 echo
 sleep 5
@@ -455,10 +455,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode octal-to-hex --string '012'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from oct --to hex --string '012'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode octal-to-hex --string '012'
+target/debug/nutek-encode convert --from oct --to hex --string '012'
 sleep 0.5
 echo
 sleep 5
@@ -470,10 +470,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode octal-to-binary --string '012'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from oct --to bin --string '012'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode octal-to-binary --string '012'
+target/debug/nutek-encode convert --from oct --to bin --string '012'
 sleep 0.5
 echo
 sleep 5
@@ -485,10 +485,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode binary-to-hex --string '00001010'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from bin --to hex --string '00001010'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode binary-to-hex --string '00001010'
+target/debug/nutek-encode convert --from bin --to hex --string '00001010'
 sleep 0.5
 echo
 sleep 5
@@ -500,10 +500,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode binary-to-octal --string '00001010'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from bin --to oct --string '00001010'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode binary-to-octal --string '00001010'
+target/debug/nutek-encode convert --from bin --to oct --string '00001010'
 sleep 0.5
 echo
 sleep 5
@@ -515,10 +515,10 @@ asciinema play --quiet examples/tmp.cast
 sleep 0.5
 echo
 sleep 5
-echo "$ nutek-encode binary-to-integer --string '00001010'" > examples/tmp.scenario && \
+echo "$ nutek-encode convert --from bin --to dec --string '00001010'" > examples/tmp.scenario && \
 asciinema-scenario examples/tmp.scenario > examples/tmp.cast && \
 asciinema play --quiet examples/tmp.cast
-target/debug/nutek-encode binary-to-integer --string '00001010'
+target/debug/nutek-encode convert --from bin --to dec --string '00001010'
 sleep 0.5
 echo
 sleep 5
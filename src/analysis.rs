@@ -0,0 +1,78 @@
+//! Helpers for ranking candidate plaintexts and measuring differences between byte strings.
+
+/// Relative frequency of the letters `a` to `z` in English text, in percent.
+const ENGLISH_LETTER_FREQUENCIES: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Scores how much the bytes look like English text, higher is better.
+///
+/// Every byte adds the log-likelihood of seeing it in English text: letters by their
+/// frequency, spaces as the most common character, other printable characters as rare
+/// and control or non-ASCII bytes as very unlikely. The result is averaged over the
+/// input length, so scores of inputs with different lengths are comparable.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::analysis;
+/// let english = analysis::score_english(b"the quick brown fox");
+/// let noise = analysis::score_english(&[0x01, 0xff, 0x13, 0x9c]);
+/// assert!(english > noise);
+/// ```
+pub fn score_english(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = data
+        .iter()
+        .map(|&b| match b {
+            b'a'..=b'z' => (ENGLISH_LETTER_FREQUENCIES[(b - b'a') as usize] / 100.0).ln(),
+            b'A'..=b'Z' => (ENGLISH_LETTER_FREQUENCIES[(b - b'A') as usize] / 100.0).ln() - 0.5,
+            b' ' => -1.8,
+            b'\n' | b'\r' | b'\t' => -4.0,
+            b'0'..=b'9' | b'.' | b',' | b'\'' | b'"' | b'!' | b'?' | b'-' => -4.0,
+            0x21..=0x7e => -6.0,
+            _ => -10.0,
+        })
+        .sum();
+    total / data.len() as f64
+}
+
+/// Counts the number of differing bits between two byte strings.
+///
+/// Only the common prefix is compared when the lengths differ.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::analysis;
+/// let distance = analysis::hamming_distance(b"this is a test", b"wokka wokka!!!");
+/// assert_eq!(distance, 37);
+/// ```
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+#[cfg(test)]
+mod tests_analysis {
+    use super::*;
+
+    #[test]
+    fn it_scores_english() {
+        let english = score_english(b"Hello world, this is plain English text.");
+        let shifted = score_english(b"Uryyb jbeyq, guvf vf cynva Ratyvfu grkg.");
+        let binary = score_english(&[0x00, 0x8f, 0x1b, 0xfe, 0x07]);
+        assert!(english > shifted);
+        assert!(shifted > binary);
+        assert_eq!(score_english(b""), 0.0);
+    }
+
+    #[test]
+    fn it_computes_hamming_distance() {
+        assert_eq!(hamming_distance(b"this is a test", b"wokka wokka!!!"), 37);
+        assert_eq!(hamming_distance(b"abc", b"abc"), 0);
+        assert_eq!(hamming_distance(&[0xff], &[0x00, 0x01]), 8);
+    }
+}
@@ -0,0 +1,450 @@
+//! Byte arrays as source code literals for C, Python, Rust, Go, Java, C#, PowerShell, NASM
+//! and JavaScript, and parsing of such literals back into bytes.
+
+use std::{error::Error, str};
+
+/// Source code literal formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayFormat {
+    /// `unsigned char buf[] = { 0x41, ... };`
+    C,
+    /// `"\x41\x42"` string lines, which C, C++, Python and others join by adjacency.
+    CString,
+    /// `buf = b""` and `buf += b"\x41..."` lines, as written by msfvenom.
+    Python,
+    /// `let buf: &[u8] = &[0x41, ...];`
+    Rust,
+    /// `buf := []byte{0x41, ...}`
+    Go,
+    /// `byte[] buf = new byte[] {0x41, (byte) 0xfc, ...};`, with casts for bytes above 0x7f.
+    Java,
+    /// `byte[] buf = new byte[2] {0x41, 0xfc};`
+    CSharp,
+    /// `[Byte[]] $buf = @(0x41, ...)`
+    PowerShell,
+    /// `buf:` followed by `db 0x41, ...` lines.
+    Nasm,
+    /// `const buf = new Uint8Array([0x41, ...]);`
+    JavaScript,
+}
+
+impl str::FromStr for ArrayFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "c" => Ok(ArrayFormat::C),
+            "c-string" | "cstring" | "string" => Ok(ArrayFormat::CString),
+            "python" | "py" => Ok(ArrayFormat::Python),
+            "rust" | "rs" => Ok(ArrayFormat::Rust),
+            "go" | "golang" => Ok(ArrayFormat::Go),
+            "java" => Ok(ArrayFormat::Java),
+            "csharp" | "c#" | "cs" => Ok(ArrayFormat::CSharp),
+            "powershell" | "ps1" => Ok(ArrayFormat::PowerShell),
+            "nasm" | "asm" => Ok(ArrayFormat::Nasm),
+            "javascript" | "js" => Ok(ArrayFormat::JavaScript),
+            _ => Err(format!(
+                "Unknown array format '{}', expected one of: c, c-string, python, rust, go, java, csharp, powershell, nasm, javascript",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for [`format_array`].
+#[derive(Debug, Clone)]
+pub struct ArrayOptions {
+    /// Name of the variable or label.
+    pub name: String,
+    /// Bytes per line.
+    pub width: usize,
+}
+
+impl Default for ArrayOptions {
+    fn default() -> Self {
+        ArrayOptions {
+            name: "buf".to_string(),
+            width: 16,
+        }
+    }
+}
+
+/// Formats bytes as a source code literal.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::array::{self, ArrayFormat, ArrayOptions};
+/// let options = ArrayOptions::default();
+/// assert_eq!(
+///     array::format_array(b"AB", ArrayFormat::C, &options),
+///     "unsigned char buf[] = {\n    0x41, 0x42,\n};"
+/// );
+/// assert_eq!(
+///     array::format_array(b"AB\xfc", ArrayFormat::Python, &options),
+///     "buf = b\"\"\nbuf += b\"\\x41\\x42\\xfc\""
+/// );
+/// ```
+pub fn format_array(data: &[u8], format: ArrayFormat, options: &ArrayOptions) -> String {
+    let width = options.width.max(1);
+    let name = &options.name;
+    let hex = |byte: &u8| format!("0x{:02x}", byte);
+    let escaped =
+        |chunk: &[u8]| -> String { chunk.iter().map(|b| format!("\\x{:02x}", b)).collect() };
+    // One line per chunk, every element followed by a comma.
+    let list = |indent: &str, element: &dyn Fn(&u8) -> String| -> String {
+        data.chunks(width)
+            .map(|chunk| {
+                let elements: Vec<String> =
+                    chunk.iter().map(|b| format!("{},", element(b))).collect();
+                format!("{}{}\n", indent, elements.join(" "))
+            })
+            .collect()
+    };
+
+    match format {
+        ArrayFormat::C => format!("unsigned char {}[] = {{\n{}}};", name, list("    ", &hex)),
+        ArrayFormat::CString => data
+            .chunks(width)
+            .map(|chunk| format!("\"{}\"", escaped(chunk)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ArrayFormat::Python => {
+            let mut lines = vec![format!("{} = b\"\"", name)];
+            lines.extend(
+                data.chunks(width)
+                    .map(|chunk| format!("{} += b\"{}\"", name, escaped(chunk))),
+            );
+            lines.join("\n")
+        }
+        ArrayFormat::Rust => format!("let {}: &[u8] = &[\n{}];", name, list("    ", &hex)),
+        ArrayFormat::Go => format!("{} := []byte{{\n{}}}", name, list("\t", &hex)),
+        ArrayFormat::Java => {
+            let element = |byte: &u8| match byte {
+                0x80.. => format!("(byte) 0x{:02x}", byte),
+                _ => hex(byte),
+            };
+            format!(
+                "byte[] {} = new byte[] {{\n{}}};",
+                name,
+                list("    ", &element)
+            )
+        }
+        ArrayFormat::CSharp => format!(
+            "byte[] {} = new byte[{}] {{\n{}}};",
+            name,
+            data.len(),
+            list("    ", &hex)
+        ),
+        ArrayFormat::PowerShell => {
+            // A trailing comma before the closing parenthesis is a syntax error.
+            let elements = list("    ", &hex);
+            let elements = elements.trim_end_matches(",\n");
+            let separator = if elements.is_empty() { "" } else { "\n" };
+            format!("[Byte[]] ${} = @(\n{}{})", name, elements, separator)
+        }
+        ArrayFormat::Nasm => {
+            let mut lines = vec![format!("{}:", name)];
+            lines.extend(data.chunks(width).map(|chunk| {
+                let elements: Vec<String> = chunk.iter().map(hex).collect();
+                format!("    db {}", elements.join(", "))
+            }));
+            lines.join("\n")
+        }
+        ArrayFormat::JavaScript => format!(
+            "const {} = new Uint8Array([\n{}]);",
+            name,
+            list("    ", &hex)
+        ),
+    }
+}
+
+/// Reads the bytes of a string literal body up to the closing quote, with C, Python and Rust
+/// escapes. Returns the bytes and the rest after the quote.
+fn parse_string_literal(body: &str, quote: char) -> Result<(Vec<u8>, &str), Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c == quote {
+            return Ok((bytes, &body[index + c.len_utf8()..]));
+        }
+        if c != '\\' {
+            bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        let (_, escape) = chars
+            .next()
+            .ok_or("Unterminated escape in string literal")?;
+        let byte = match escape {
+            'x' => {
+                let mut digits = String::new();
+                while digits.len() < 2
+                    && let Some((_, d)) = chars.next_if(|(_, d)| d.is_ascii_hexdigit())
+                {
+                    digits.push(d);
+                }
+                u8::from_str_radix(&digits, 16)
+                    .map_err(|_| format!("Invalid escape '\\x{}'", digits))?
+            }
+            '0'..='7' => {
+                let mut value = escape.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    match chars.next_if(|(_, d)| d.is_digit(8)) {
+                        Some((_, d)) => value = value * 8 + d.to_digit(8).unwrap_or(0),
+                        None => break,
+                    }
+                }
+                u8::try_from(value).map_err(|_| format!("Octal escape {:o} is above 377", value))?
+            }
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'a' => 0x07,
+            'b' => 0x08,
+            'f' => 0x0c,
+            'v' => 0x0b,
+            'e' => 0x1b,
+            '\n' => continue,
+            other => {
+                bytes.extend_from_slice(other.encode_utf8(&mut [0; 4]).as_bytes());
+                continue;
+            }
+        };
+        bytes.push(byte);
+    }
+    Err(format!("Unterminated string literal, missing {}", quote).into())
+}
+
+/// Parses a number token: `0x41`, `0b101`, `0o101`, NASM `41h`, or decimal, with an optional
+/// Rust type suffix such as `u8`.
+fn parse_number(token: &str, negative: bool) -> Result<u8, Box<dyn Error>> {
+    let cleaned = token.replace('_', "").to_ascii_lowercase();
+    let cleaned = cleaned
+        .strip_suffix("u8")
+        .or_else(|| cleaned.strip_suffix("i8"))
+        .unwrap_or(&cleaned);
+    let parsed = if let Some(digits) = cleaned.strip_prefix("0x") {
+        i64::from_str_radix(digits, 16)
+    } else if let Some(digits) = cleaned.strip_prefix("0b") {
+        i64::from_str_radix(digits, 2)
+    } else if let Some(digits) = cleaned.strip_prefix("0o") {
+        i64::from_str_radix(digits, 8)
+    } else if let Some(digits) = cleaned.strip_suffix('h') {
+        i64::from_str_radix(digits, 16)
+    } else {
+        cleaned.parse::<i64>()
+    };
+    let value = parsed.map_err(|_| format!("Invalid number '{}'", token))?;
+    let value = if negative { -value } else { value };
+    match value {
+        0..=255 => Ok(value as u8),
+        // Java's signed bytes.
+        -128..=-1 => Ok((value + 256) as u8),
+        _ => Err(format!("Number '{}' does not fit in a byte", token).into()),
+    }
+}
+
+/// Parses a source code byte array literal back into bytes.
+///
+/// Reads the numbers and string literals of any of the [`ArrayFormat`]s, or of a bare list
+/// such as `0x41,0x42` or `\x41\x42`. Array sizes in brackets after a type name, as in
+/// `new byte[4]`, Rust array types such as `[u8; 4]`, and `//`, `/* */` and `#` comments are
+/// skipped.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::array;
+/// let bytes = array::parse_array("byte[] buf = new byte[3] {0x41, (byte) -4, 0x43};").unwrap();
+/// assert_eq!(bytes, [0x41, 0xfc, 0x43]);
+/// let bytes = array::parse_array("buf = b\"\"\nbuf += b\"\\x41B\\n\"").unwrap();
+/// assert_eq!(bytes, b"AB\n");
+/// ```
+pub fn parse_array(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    // Whether any number or string literal was read, as a literal can be empty.
+    let mut found = false;
+    // For each open bracket, whether the numbers inside it are skipped.
+    let mut brackets: Vec<bool> = Vec::new();
+    let mut after_identifier = false;
+    let mut negative = false;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let mut identifier = false;
+        match c {
+            '"' | '\'' | '`' => {
+                let (literal, after) = parse_string_literal(&rest[1..], c)?;
+                if !brackets.contains(&true) {
+                    bytes.extend(literal);
+                    found = true;
+                }
+                rest = after;
+                after_identifier = false;
+                continue;
+            }
+            '\\' => {
+                // Escapes outside of a string, as in a bare \x41\x42.
+                let end = rest[1..]
+                    .find(|c: char| c.is_whitespace() || c == ',')
+                    .map_or(rest.len(), |i| i + 1);
+                let (literal, _) = parse_string_literal(&format!("{}\"", &rest[..end]), '"')?;
+                bytes.extend(literal);
+                found = true;
+                rest = &rest[end..];
+                continue;
+            }
+            '/' if rest.starts_with("//") => {
+                rest = rest.find('\n').map_or("", |i| &rest[i..]);
+                continue;
+            }
+            '/' if rest.starts_with("/*") => {
+                rest = rest.find("*/").map_or("", |i| &rest[i + 2..]);
+                continue;
+            }
+            '#' => {
+                rest = rest.find('\n').map_or("", |i| &rest[i..]);
+                continue;
+            }
+            '[' => brackets.push(after_identifier),
+            ']' => {
+                brackets.pop();
+                // `byte[]` is still a type, so a following `[4]` is a size too.
+                identifier = true;
+            }
+            // `[u8; 4]` is a Rust array type.
+            ';' => {
+                if let Some(last) = brackets.last_mut() {
+                    *last = true;
+                }
+            }
+            '-' => {
+                negative = true;
+                rest = &rest[1..];
+                continue;
+            }
+            '0'..='9' => {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                if !brackets.contains(&true) {
+                    bytes.push(parse_number(&rest[..end], negative)?);
+                    found = true;
+                }
+                rest = &rest[end..];
+                negative = false;
+                after_identifier = false;
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let end = rest
+                    .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+                    .unwrap_or(rest.len());
+                rest = &rest[end..];
+                after_identifier = true;
+                negative = false;
+                continue;
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            after_identifier = identifier;
+            negative = false;
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    if !found && !text.trim().is_empty() {
+        return Err(format!("No byte values found in '{}'", text.trim()).into());
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests_array {
+    use super::*;
+
+    const PAYLOAD: &[u8] = b"\xfc\x48\x83\xe4\xf0AB\"\\\x00\x7f\n";
+
+    const FORMATS: [ArrayFormat; 10] = [
+        ArrayFormat::C,
+        ArrayFormat::CString,
+        ArrayFormat::Python,
+        ArrayFormat::Rust,
+        ArrayFormat::Go,
+        ArrayFormat::Java,
+        ArrayFormat::CSharp,
+        ArrayFormat::PowerShell,
+        ArrayFormat::Nasm,
+        ArrayFormat::JavaScript,
+    ];
+
+    #[test]
+    fn it_formats_every_language() {
+        let options = ArrayOptions {
+            name: "sc".to_string(),
+            width: 3,
+        };
+        let data = b"\x41\xfc\x00\x0a";
+        let expected = [
+            "unsigned char sc[] = {\n    0x41, 0xfc, 0x00,\n    0x0a,\n};",
+            "\"\\x41\\xfc\\x00\"\n\"\\x0a\"",
+            "sc = b\"\"\nsc += b\"\\x41\\xfc\\x00\"\nsc += b\"\\x0a\"",
+            "let sc: &[u8] = &[\n    0x41, 0xfc, 0x00,\n    0x0a,\n];",
+            "sc := []byte{\n\t0x41, 0xfc, 0x00,\n\t0x0a,\n}",
+            "byte[] sc = new byte[] {\n    0x41, (byte) 0xfc, 0x00,\n    0x0a,\n};",
+            "byte[] sc = new byte[4] {\n    0x41, 0xfc, 0x00,\n    0x0a,\n};",
+            "[Byte[]] $sc = @(\n    0x41, 0xfc, 0x00,\n    0x0a\n)",
+            "sc:\n    db 0x41, 0xfc, 0x00\n    db 0x0a",
+            "const sc = new Uint8Array([\n    0x41, 0xfc, 0x00,\n    0x0a,\n]);",
+        ];
+        for (format, expected) in FORMATS.iter().zip(expected) {
+            assert_eq!(format_array(data, *format, &options), expected);
+        }
+        assert_eq!(
+            format_array(b"", ArrayFormat::PowerShell, &options),
+            "[Byte[]] $sc = @(\n)"
+        );
+    }
+
+    #[test]
+    fn it_parses_every_language_back() {
+        for width in [1, 5, 16] {
+            let options = ArrayOptions {
+                name: "buf".to_string(),
+                width,
+            };
+            for format in FORMATS {
+                let literal = format_array(PAYLOAD, format, &options);
+                assert_eq!(parse_array(&literal).unwrap(), PAYLOAD, "{}", literal);
+            }
+        }
+    }
+
+    #[test]
+    fn it_parses_hand_written_literals() {
+        assert_eq!(parse_array("0x41,0x42, 67").unwrap(), b"ABC");
+        assert_eq!(parse_array("\\x41\\x42 \\x43").unwrap(), b"ABC");
+        assert_eq!(
+            parse_array("char s[4] = \"A\\102\\x43\"; // trailing\n").unwrap(),
+            b"ABC"
+        );
+        assert_eq!(
+            parse_array("let buf: [u8; 3] = [0x41u8, 0b1000010, 0o103];").unwrap(),
+            b"ABC"
+        );
+        assert_eq!(parse_array("db 41h, 42h ; two bytes").unwrap(), b"AB");
+        assert_eq!(
+            parse_array("/* shellcode */ new byte[] { -1, 'A' }").unwrap(),
+            [0xff, 0x41]
+        );
+        assert_eq!(parse_array("buf = b\"\"").unwrap(), b"");
+
+        // Malformed input
+        assert!(parse_array("{0x141}").is_err());
+        assert!(parse_array("\"unterminated").is_err());
+        assert_eq!(
+            parse_array("hello").unwrap_err().to_string(),
+            "No byte values found in 'hello'"
+        );
+        assert!(parse_array("byte[4] buf;").is_err());
+    }
+}
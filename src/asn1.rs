@@ -0,0 +1,865 @@
+//! PEM armor and DER/BER structures: PEM blocks with their labels, an ASN.1 element tree
+//! with tag names and object identifiers resolved to names, and re-encoding DER as PEM.
+
+use crate::encoder::{self, ByteFormat};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use num_bigint::BigInt;
+use std::{error::Error, str};
+
+/// Deepest nesting of elements parsed, to stop crafted input from exhausting the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Object identifiers with their names, for algorithms, distinguished name attributes,
+/// certificate extensions and the PKCS containers.
+const OID_NAMES: &[(&str, &str)] = &[
+    // Public key and signature algorithms.
+    ("1.2.840.113549.1.1.1", "rsaEncryption"),
+    ("1.2.840.113549.1.1.4", "md5WithRSAEncryption"),
+    ("1.2.840.113549.1.1.5", "sha1WithRSAEncryption"),
+    ("1.2.840.113549.1.1.7", "id-RSAES-OAEP"),
+    ("1.2.840.113549.1.1.8", "id-mgf1"),
+    ("1.2.840.113549.1.1.10", "id-RSASSA-PSS"),
+    ("1.2.840.113549.1.1.11", "sha256WithRSAEncryption"),
+    ("1.2.840.113549.1.1.12", "sha384WithRSAEncryption"),
+    ("1.2.840.113549.1.1.13", "sha512WithRSAEncryption"),
+    ("1.2.840.113549.1.1.14", "sha224WithRSAEncryption"),
+    ("1.2.840.10040.4.1", "dsa"),
+    ("1.2.840.10040.4.3", "dsa-with-sha1"),
+    ("1.2.840.10045.2.1", "ecPublicKey"),
+    ("1.2.840.10045.3.1.7", "prime256v1"),
+    ("1.2.840.10045.4.1", "ecdsa-with-SHA1"),
+    ("1.2.840.10045.4.3.2", "ecdsa-with-SHA256"),
+    ("1.2.840.10045.4.3.3", "ecdsa-with-SHA384"),
+    ("1.2.840.10045.4.3.4", "ecdsa-with-SHA512"),
+    ("1.3.132.0.10", "secp256k1"),
+    ("1.3.132.0.34", "secp384r1"),
+    ("1.3.132.0.35", "secp521r1"),
+    ("1.3.101.110", "X25519"),
+    ("1.3.101.111", "X448"),
+    ("1.3.101.112", "Ed25519"),
+    ("1.3.101.113", "Ed448"),
+    ("1.2.840.113549.1.3.1", "dhKeyAgreement"),
+    // Digests, MACs and ciphers.
+    ("1.2.840.113549.2.5", "md5"),
+    ("1.3.14.3.2.26", "sha1"),
+    ("2.16.840.1.101.3.4.2.1", "sha256"),
+    ("2.16.840.1.101.3.4.2.2", "sha384"),
+    ("2.16.840.1.101.3.4.2.3", "sha512"),
+    ("2.16.840.1.101.3.4.2.4", "sha224"),
+    ("1.2.840.113549.2.7", "hmacWithSHA1"),
+    ("1.2.840.113549.2.9", "hmacWithSHA256"),
+    ("1.2.840.113549.2.10", "hmacWithSHA384"),
+    ("1.2.840.113549.2.11", "hmacWithSHA512"),
+    ("1.2.840.113549.3.7", "des-ede3-cbc"),
+    ("2.16.840.1.101.3.4.1.2", "aes128-CBC"),
+    ("2.16.840.1.101.3.4.1.22", "aes192-CBC"),
+    ("2.16.840.1.101.3.4.1.42", "aes256-CBC"),
+    ("2.16.840.1.101.3.4.1.6", "aes128-GCM"),
+    ("2.16.840.1.101.3.4.1.46", "aes256-GCM"),
+    // PKCS#5, PKCS#7, PKCS#9 and PKCS#12.
+    ("1.2.840.113549.1.5.12", "PBKDF2"),
+    ("1.2.840.113549.1.5.13", "PBES2"),
+    ("1.2.840.113549.1.7.1", "data"),
+    ("1.2.840.113549.1.7.2", "signedData"),
+    ("1.2.840.113549.1.7.3", "envelopedData"),
+    ("1.2.840.113549.1.7.6", "encryptedData"),
+    ("1.2.840.113549.1.9.1", "emailAddress"),
+    ("1.2.840.113549.1.9.3", "contentType"),
+    ("1.2.840.113549.1.9.4", "messageDigest"),
+    ("1.2.840.113549.1.9.5", "signingTime"),
+    ("1.2.840.113549.1.9.7", "challengePassword"),
+    ("1.2.840.113549.1.9.14", "extensionRequest"),
+    ("1.2.840.113549.1.9.20", "friendlyName"),
+    ("1.2.840.113549.1.9.21", "localKeyID"),
+    ("1.2.840.113549.1.12.10.1.2", "pkcs8ShroudedKeyBag"),
+    ("1.2.840.113549.1.12.10.1.3", "certBag"),
+    // Distinguished name attributes.
+    ("2.5.4.3", "commonName"),
+    ("2.5.4.4", "surname"),
+    ("2.5.4.5", "serialNumber"),
+    ("2.5.4.6", "countryName"),
+    ("2.5.4.7", "localityName"),
+    ("2.5.4.8", "stateOrProvinceName"),
+    ("2.5.4.9", "streetAddress"),
+    ("2.5.4.10", "organizationName"),
+    ("2.5.4.11", "organizationalUnitName"),
+    ("2.5.4.12", "title"),
+    ("2.5.4.15", "businessCategory"),
+    ("2.5.4.17", "postalCode"),
+    ("2.5.4.42", "givenName"),
+    ("2.5.4.97", "organizationIdentifier"),
+    ("0.9.2342.19200300.100.1.1", "userId"),
+    ("0.9.2342.19200300.100.1.25", "domainComponent"),
+    ("1.3.6.1.4.1.311.60.2.1.3", "jurisdictionCountryName"),
+    // Certificate extensions.
+    ("2.5.29.14", "subjectKeyIdentifier"),
+    ("2.5.29.15", "keyUsage"),
+    ("2.5.29.17", "subjectAltName"),
+    ("2.5.29.18", "issuerAltName"),
+    ("2.5.29.19", "basicConstraints"),
+    ("2.5.29.20", "cRLNumber"),
+    ("2.5.29.21", "cRLReason"),
+    ("2.5.29.30", "nameConstraints"),
+    ("2.5.29.31", "cRLDistributionPoints"),
+    ("2.5.29.32", "certificatePolicies"),
+    ("2.5.29.32.0", "anyPolicy"),
+    ("2.5.29.33", "policyMappings"),
+    ("2.5.29.35", "authorityKeyIdentifier"),
+    ("2.5.29.36", "policyConstraints"),
+    ("2.5.29.37", "extKeyUsage"),
+    ("2.5.29.37.0", "anyExtendedKeyUsage"),
+    ("1.3.6.1.5.5.7.1.1", "authorityInfoAccess"),
+    ("1.3.6.1.5.5.7.1.11", "subjectInfoAccess"),
+    ("1.3.6.1.5.5.7.1.24", "tlsFeature"),
+    ("1.3.6.1.4.1.11129.2.4.2", "signedCertificateTimestampList"),
+    ("1.3.6.1.4.1.11129.2.4.3", "ctPrecertificatePoison"),
+    ("2.16.840.1.113730.1.1", "netscapeCertType"),
+    ("2.16.840.1.113730.1.13", "netscapeComment"),
+    ("1.3.6.1.4.1.311.20.2", "msCertificateTemplateName"),
+    ("1.3.6.1.4.1.311.21.7", "msCertificateTemplate"),
+    // Extended key usages, access methods and policies.
+    ("1.3.6.1.5.5.7.3.1", "serverAuth"),
+    ("1.3.6.1.5.5.7.3.2", "clientAuth"),
+    ("1.3.6.1.5.5.7.3.3", "codeSigning"),
+    ("1.3.6.1.5.5.7.3.4", "emailProtection"),
+    ("1.3.6.1.5.5.7.3.8", "timeStamping"),
+    ("1.3.6.1.5.5.7.3.9", "OCSPSigning"),
+    ("1.3.6.1.4.1.311.20.2.2", "smartcardLogon"),
+    ("1.3.6.1.5.2.3.5", "pkinitKDC"),
+    ("1.3.6.1.5.5.7.48.1", "ocsp"),
+    ("1.3.6.1.5.5.7.48.2", "caIssuers"),
+    ("1.3.6.1.5.5.7.2.1", "cps"),
+    ("1.3.6.1.5.5.7.2.2", "unotice"),
+    ("2.23.140.1.1", "extendedValidation"),
+    ("2.23.140.1.2.1", "domainValidated"),
+    ("2.23.140.1.2.2", "organizationValidated"),
+    ("2.23.140.1.2.3", "individualValidated"),
+];
+
+/// A PEM block: the label of its `-----BEGIN ...-----` line and the DER it armors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PemBlock {
+    pub label: String,
+    pub data: Vec<u8>,
+}
+
+/// The class of a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagClass {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+/// The tag of an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tag {
+    pub class: TagClass,
+    pub constructed: bool,
+    pub number: u32,
+}
+
+impl Tag {
+    pub const BOOLEAN: u32 = 1;
+    pub const INTEGER: u32 = 2;
+    pub const BIT_STRING: u32 = 3;
+    pub const OCTET_STRING: u32 = 4;
+    pub const NULL: u32 = 5;
+    pub const OBJECT_IDENTIFIER: u32 = 6;
+    pub const ENUMERATED: u32 = 10;
+    pub const UTF8_STRING: u32 = 12;
+    pub const SEQUENCE: u32 = 16;
+    pub const SET: u32 = 17;
+    pub const UTC_TIME: u32 = 23;
+    pub const GENERALIZED_TIME: u32 = 24;
+
+    /// Whether this is the universal tag with the given number.
+    pub fn is(&self, number: u32) -> bool {
+        self.class == TagClass::Universal && self.number == number
+    }
+
+    /// The name of the tag: the ASN.1 type for universal tags, `[N]` for context-specific
+    /// ones and `[APPLICATION N]` or `[PRIVATE N]` for the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::asn1::{Tag, TagClass};
+    /// let tag = Tag { class: TagClass::Universal, constructed: true, number: 16 };
+    /// assert_eq!(tag.name(), "SEQUENCE");
+    /// let tag = Tag { class: TagClass::ContextSpecific, constructed: false, number: 2 };
+    /// assert_eq!(tag.name(), "[2]");
+    /// ```
+    pub fn name(&self) -> String {
+        let universal = match self.number {
+            0 => "END OF CONTENTS",
+            1 => "BOOLEAN",
+            2 => "INTEGER",
+            3 => "BIT STRING",
+            4 => "OCTET STRING",
+            5 => "NULL",
+            6 => "OBJECT IDENTIFIER",
+            7 => "ObjectDescriptor",
+            8 => "EXTERNAL",
+            9 => "REAL",
+            10 => "ENUMERATED",
+            11 => "EMBEDDED PDV",
+            12 => "UTF8String",
+            13 => "RELATIVE-OID",
+            16 => "SEQUENCE",
+            17 => "SET",
+            18 => "NumericString",
+            19 => "PrintableString",
+            20 => "T61String",
+            21 => "VideotexString",
+            22 => "IA5String",
+            23 => "UTCTime",
+            24 => "GeneralizedTime",
+            25 => "GraphicString",
+            26 => "VisibleString",
+            27 => "GeneralString",
+            28 => "UniversalString",
+            30 => "BMPString",
+            _ => "",
+        };
+        match self.class {
+            TagClass::Universal if !universal.is_empty() => universal.to_string(),
+            TagClass::Universal => format!("[UNIVERSAL {}]", self.number),
+            TagClass::Application => format!("[APPLICATION {}]", self.number),
+            TagClass::ContextSpecific => format!("[{}]", self.number),
+            TagClass::Private => format!("[PRIVATE {}]", self.number),
+        }
+    }
+}
+
+/// An element of a DER or BER encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub tag: Tag,
+    /// Offset of the tag in the parsed data.
+    pub offset: usize,
+    /// Length of the tag and length octets.
+    pub header_length: usize,
+    /// Length of the contents, not counting the end of contents octets of the indefinite form.
+    pub length: usize,
+    /// The element has the BER indefinite length form.
+    pub indefinite: bool,
+    /// The contents of a primitive element, empty for constructed ones.
+    pub value: Vec<u8>,
+    /// The elements of a constructed element, or the DER encapsulated in a BIT STRING or an
+    /// OCTET STRING, as in certificate extensions and public keys.
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// Length of the whole element, from its tag to the end of its contents.
+    pub fn encoded_length(&self) -> usize {
+        self.header_length + self.length + if self.indefinite { 2 } else { 0 }
+    }
+
+    /// The dotted form of an OBJECT IDENTIFIER.
+    pub fn oid(&self) -> Option<String> {
+        if self.tag.is(Tag::OBJECT_IDENTIFIER) {
+            decode_oid(&self.value).ok()
+        } else {
+            None
+        }
+    }
+
+    /// The text of a string type, or of a context-specific primitive holding printable ASCII
+    /// such as a `dNSName` of a subject alternative name.
+    pub fn text(&self) -> Option<String> {
+        if self.tag.constructed {
+            return None;
+        }
+        if self.tag.class == TagClass::Universal {
+            return decode_string(self.tag.number, &self.value);
+        }
+        let printable = !self.value.is_empty()
+            && self
+                .value
+                .iter()
+                .all(|byte| byte.is_ascii_graphic() || *byte == b' ');
+        printable.then(|| String::from_utf8_lossy(&self.value).into_owned())
+    }
+
+    /// The time of a UTCTime or GeneralizedTime in UTC.
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        let text = str::from_utf8(&self.value).ok()?;
+        let text = if self.tag.is(Tag::UTC_TIME) {
+            // RFC 5280: two-digit years from 50 are in the 1900s.
+            let century = if text.get(..2)? >= "50" { "19" } else { "20" };
+            format!("{}{}", century, text)
+        } else if self.tag.is(Tag::GENERALIZED_TIME) {
+            text.to_string()
+        } else {
+            return None;
+        };
+        let text = text.strip_suffix('Z')?;
+        NaiveDateTime::parse_from_str(text, "%Y%m%d%H%M%S%.f")
+            .ok()
+            .map(|time| time.and_utc())
+    }
+
+    /// The unused bit count and the bytes of a BIT STRING, or `None` if it is malformed.
+    pub fn bit_string(&self) -> Option<(u8, &[u8])> {
+        if self.tag.is(Tag::BIT_STRING) {
+            self.value
+                .split_first()
+                .map(|(unused, bits)| (*unused, bits))
+                .filter(|(unused, bits)| check_bit_string(*unused, bits).is_ok())
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks that the unused bit count of a BIT STRING is at most 7, and 0 when it is empty.
+fn check_bit_string(unused: u8, bits: &[u8]) -> Result<(), String> {
+    if unused > 7 {
+        return Err(format!("Bit string with {} unused bits", unused));
+    }
+    if unused > 0 && bits.is_empty() {
+        return Err(format!("Empty bit string with {} unused bits", unused));
+    }
+    Ok(())
+}
+
+/// The name of an object identifier, such as `sha256WithRSAEncryption` for
+/// `1.2.840.113549.1.1.11`.
+pub fn oid_name(oid: &str) -> Option<&'static str> {
+    OID_NAMES
+        .iter()
+        .find(|(known, _)| *known == oid)
+        .map(|(_, name)| *name)
+}
+
+/// Decodes the contents of an OBJECT IDENTIFIER into its dotted form.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::asn1;
+/// assert_eq!(asn1::decode_oid(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d]).unwrap(), "1.2.840.113549");
+/// ```
+pub fn decode_oid(bytes: &[u8]) -> Result<String, Box<dyn Error>> {
+    let mut arcs: Vec<u128> = Vec::new();
+    let mut value: u128 = 0;
+    for (index, &byte) in bytes.iter().enumerate() {
+        if value == 0 && byte == 0x80 {
+            return Err("Object identifier arc with a leading zero byte".into());
+        }
+        value = value
+            .checked_mul(128)
+            .ok_or("Object identifier arc is too large")?
+            | (byte & 0x7f) as u128;
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        } else if index == bytes.len() - 1 {
+            return Err("Object identifier ends in the middle of an arc".into());
+        }
+    }
+    if arcs.is_empty() {
+        return Err("Empty object identifier".into());
+    }
+    Ok(arcs
+        .iter()
+        .map(u128::to_string)
+        .collect::<Vec<_>>()
+        .join("."))
+}
+
+/// Decodes the contents of a universal string type.
+fn decode_string(number: u32, bytes: &[u8]) -> Option<String> {
+    match number {
+        // UTF8String, and the ASCII subsets NumericString, PrintableString, IA5String,
+        // VisibleString, GraphicString and GeneralString.
+        12 | 18 | 19 | 22 | 25 | 26 | 27 => str::from_utf8(bytes).ok().map(str::to_string),
+        // T61String is Latin-1 in practice.
+        20 => Some(bytes.iter().map(|&byte| byte as char).collect()),
+        // UTCTime and GeneralizedTime are ASCII.
+        23 | 24 => str::from_utf8(bytes).ok().map(str::to_string),
+        // BMPString is UTF-16BE.
+        30 => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        // UniversalString is UTF-32BE.
+        28 => bytes
+            .chunks_exact(4)
+            .map(|quad| char::from_u32(u32::from_be_bytes([quad[0], quad[1], quad[2], quad[3]])))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Finds the PEM blocks in a text, with their labels. Header lines such as `Proc-Type` of
+/// encrypted keys are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::asn1;
+/// let blocks = asn1::parse_pem("junk\n-----BEGIN A-----\nAQI=\n-----END A-----\n-----BEGIN B C-----\nAw==\n-----END B C-----\n").unwrap();
+/// assert_eq!(blocks.len(), 2);
+/// assert_eq!((blocks[0].label.as_str(), blocks[0].data.as_slice()), ("A", &[1u8, 2][..]));
+/// assert_eq!((blocks[1].label.as_str(), blocks[1].data.as_slice()), ("B C", &[3u8][..]));
+/// ```
+pub fn parse_pem(text: &str) -> Result<Vec<PemBlock>, Box<dyn Error>> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, usize, String)> = None;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let marker = |kind: &str| {
+            line.strip_prefix("-----")
+                .and_then(|rest| rest.strip_prefix(kind))
+                .and_then(|rest| rest.strip_suffix("-----"))
+                .map(str::to_string)
+        };
+        match (&mut current, marker("BEGIN "), marker("END ")) {
+            (None, Some(label), _) => current = Some((label, index + 1, String::new())),
+            (None, None, _) => {}
+            (Some((label, number, _)), Some(_), _) => {
+                return Err(format!(
+                    "Line {}: BEGIN inside the {} block started on line {}",
+                    index + 1,
+                    label,
+                    number
+                )
+                .into());
+            }
+            (Some((label, _, body)), None, Some(end)) => {
+                if &end != label {
+                    return Err(format!(
+                        "Line {}: END {} does not match BEGIN {}",
+                        index + 1,
+                        end,
+                        label
+                    )
+                    .into());
+                }
+                let data = encoder::decode_bytes(body, ByteFormat::Base64)
+                    .map_err(|e| format!("Invalid base64 in the {} block: {}", label, e))?;
+                blocks.push(PemBlock { label: end, data });
+                current = None;
+            }
+            (Some((_, _, body)), None, None) => {
+                if !line.contains(':') {
+                    body.push_str(line);
+                }
+            }
+        }
+    }
+    if let Some((label, number, _)) = current {
+        return Err(format!(
+            "The {} block started on line {} has no END line",
+            label, number
+        )
+        .into());
+    }
+    Ok(blocks)
+}
+
+/// Armors DER as PEM with the given label, in lines of 64 characters.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::asn1;
+/// assert_eq!(asn1::encode_pem("PUBLIC KEY", &[1, 2]), "-----BEGIN PUBLIC KEY-----\nAQI=\n-----END PUBLIC KEY-----\n");
+/// ```
+pub fn encode_pem(label: &str, data: &[u8]) -> String {
+    let encoded = encoder::encode_bytes(data, ByteFormat::Base64);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in encoded.chunks(64) {
+        pem.push_str(&String::from_utf8_lossy(line));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// Parses every element of DER or BER data, with the indefinite length form and DER
+/// encapsulated in BIT STRINGs and OCTET STRINGs.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::asn1::{self, Tag};
+/// let nodes = asn1::parse_der(&[0x30, 0x03, 0x02, 0x01, 0x05]).unwrap();
+/// assert!(nodes[0].tag.is(Tag::SEQUENCE));
+/// assert_eq!(nodes[0].children[0].value, vec![0x05]);
+///
+/// let err = asn1::parse_der(&[0x30, 0x05, 0x02, 0x01, 0x05]).unwrap_err();
+/// assert_eq!(err.to_string(), "Length 5 at offset 0 runs past the end of the data, 3 bytes left");
+/// ```
+pub fn parse_der(data: &[u8]) -> Result<Vec<Node>, Box<dyn Error>> {
+    parse_elements(data, 0, 0)
+}
+
+fn parse_elements(data: &[u8], base: usize, depth: usize) -> Result<Vec<Node>, Box<dyn Error>> {
+    let mut nodes = Vec::new();
+    let mut position = 0;
+    while position < data.len() {
+        let node = parse_element(&data[position..], base + position, depth)?;
+        position += node.encoded_length();
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+fn parse_element(data: &[u8], offset: usize, depth: usize) -> Result<Node, Box<dyn Error>> {
+    if depth > MAX_DEPTH {
+        return Err(format!(
+            "Elements nested deeper than {} at offset {}",
+            MAX_DEPTH, offset
+        )
+        .into());
+    }
+    let truncated = || format!("Truncated element at offset {}", offset);
+    let first = *data.first().ok_or_else(truncated)?;
+    let class = match first >> 6 {
+        0 => TagClass::Universal,
+        1 => TagClass::Application,
+        2 => TagClass::ContextSpecific,
+        _ => TagClass::Private,
+    };
+    let constructed = first & 0x20 != 0;
+    let mut position = 1;
+    let mut number = (first & 0x1f) as u32;
+    if number == 0x1f {
+        number = 0;
+        loop {
+            let byte = *data.get(position).ok_or_else(truncated)?;
+            position += 1;
+            number = number
+                .checked_mul(128)
+                .ok_or_else(|| format!("Tag number too large at offset {}", offset))?
+                | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+    let tag = Tag {
+        class,
+        constructed,
+        number,
+    };
+
+    let length_byte = *data.get(position).ok_or_else(truncated)?;
+    position += 1;
+    let length = match length_byte {
+        0x80 if constructed => None,
+        0x80 => {
+            return Err(format!(
+                "Indefinite length of a primitive element at offset {}",
+                offset
+            )
+            .into());
+        }
+        0x00..=0x7f => Some(length_byte as usize),
+        _ => {
+            let count = (length_byte & 0x7f) as usize;
+            let bytes = data.get(position..position + count).ok_or_else(truncated)?;
+            position += count;
+            if count > size_of::<usize>() {
+                return Err(format!("Length of {} bytes at offset {}", count, offset).into());
+            }
+            Some(
+                bytes
+                    .iter()
+                    .fold(0, |length, &byte| (length << 8) | byte as usize),
+            )
+        }
+    };
+    let header_length = position;
+
+    let Some(length) = length else {
+        // Indefinite length: elements up to the end of contents octets.
+        let mut children = Vec::new();
+        let mut position = header_length;
+        loop {
+            match data.get(position..position + 2) {
+                Some([0, 0]) => break,
+                Some(_) => {}
+                None => {
+                    return Err(format!(
+                        "Indefinite length element at offset {} has no end of contents",
+                        offset
+                    )
+                    .into());
+                }
+            }
+            let child = parse_element(&data[position..], offset + position, depth + 1)?;
+            position += child.encoded_length();
+            children.push(child);
+        }
+        return Ok(Node {
+            tag,
+            offset,
+            header_length,
+            length: position - header_length,
+            indefinite: true,
+            value: Vec::new(),
+            children,
+        });
+    };
+    let contents = data
+        .get(header_length..)
+        .filter(|rest| rest.len() >= length)
+        .map(|rest| &rest[..length])
+        .ok_or_else(|| {
+            format!(
+                "Length {} at offset {} runs past the end of the data, {} bytes left",
+                length,
+                offset,
+                data.len() - header_length
+            )
+        })?;
+    let base = offset + header_length;
+    let (value, children) = if constructed {
+        (Vec::new(), parse_elements(contents, base, depth + 1)?)
+    } else {
+        (contents.to_vec(), encapsulated(tag, contents, base, depth))
+    };
+    Ok(Node {
+        tag,
+        offset,
+        header_length,
+        length,
+        indefinite: false,
+        value,
+        children,
+    })
+}
+
+/// DER inside a BIT STRING or an OCTET STRING: a SEQUENCE or SET, or the single BIT STRING
+/// or OCTET STRING of extensions such as `keyUsage`, taking up all of the contents.
+fn encapsulated(tag: Tag, contents: &[u8], base: usize, depth: usize) -> Vec<Node> {
+    let (inner, base) = if tag.is(Tag::BIT_STRING) {
+        match contents.split_first() {
+            Some((0, inner)) => (inner, base + 1),
+            _ => return Vec::new(),
+        }
+    } else if tag.is(Tag::OCTET_STRING) {
+        (contents, base)
+    } else {
+        return Vec::new();
+    };
+    let Some(&first) = inner.first() else {
+        return Vec::new();
+    };
+    if !matches!(first, 0x30 | 0x31) {
+        let single = matches!(first, 0x03 | 0x04)
+            && parse_element(inner, base, depth + 1)
+                .is_ok_and(|node| node.encoded_length() == inner.len());
+        if !single {
+            return Vec::new();
+        }
+    }
+    parse_elements(inner, base, depth + 1).unwrap_or_default()
+}
+
+fn describe_value(node: &Node) -> String {
+    let tag = node.tag;
+    if tag.constructed {
+        let count = node.children.len();
+        return format!("({} element{})", count, if count == 1 { "" } else { "s" });
+    }
+    let bytes = &node.value;
+    if tag.class != TagClass::Universal {
+        return match node.text() {
+            Some(text) => format!("'{}'", text),
+            None => hex::encode(bytes),
+        };
+    }
+    match tag.number {
+        Tag::BOOLEAN => match bytes.as_slice() {
+            [0x00] => "FALSE".to_string(),
+            [_] => "TRUE".to_string(),
+            _ => hex::encode(bytes),
+        },
+        Tag::INTEGER | Tag::ENUMERATED if bytes.len() <= 16 => {
+            BigInt::from_signed_bytes_be(bytes).to_string()
+        }
+        Tag::INTEGER | Tag::ENUMERATED => {
+            let significant = bytes.iter().skip_while(|byte| **byte == 0).count();
+            format!("({} bit) {}", significant * 8, hex::encode(bytes))
+        }
+        Tag::NULL => String::new(),
+        Tag::OBJECT_IDENTIFIER => match decode_oid(bytes) {
+            Ok(oid) => match oid_name(&oid) {
+                Some(name) => format!("{} ({})", oid, name),
+                None => oid,
+            },
+            Err(e) => format!("{} ({})", hex::encode(bytes), e),
+        },
+        Tag::BIT_STRING => match bytes.split_first() {
+            Some((&unused, bits)) => match check_bit_string(unused, bits) {
+                Ok(()) => {
+                    let count = (bits.len() * 8).saturating_sub(unused as usize);
+                    if node.children.is_empty() {
+                        format!("({} bit) {}", count, hex::encode(bits))
+                    } else {
+                        format!("({} bit) encapsulates", count)
+                    }
+                }
+                Err(e) => format!("{} ({})", hex::encode(bytes), e),
+            },
+            None => "(0 bit)".to_string(),
+        },
+        Tag::OCTET_STRING if !node.children.is_empty() => {
+            format!("({} byte) encapsulates", bytes.len())
+        }
+        Tag::OCTET_STRING => format!("({} byte) {}", bytes.len(), hex::encode(bytes)),
+        Tag::UTC_TIME | Tag::GENERALIZED_TIME => {
+            let text = String::from_utf8_lossy(bytes);
+            match node.time() {
+                Some(time) => format!("{} ({})", text, time.format("%Y-%m-%d %H:%M:%S UTC")),
+                None => text.into_owned(),
+            }
+        }
+        number => match decode_string(number, bytes) {
+            Some(text) => format!("'{}'", text),
+            None => hex::encode(bytes),
+        },
+    }
+}
+
+fn format_nodes(nodes: &[Node], depth: usize, offsets: bool, lines: &mut Vec<String>) {
+    for node in nodes {
+        let value = describe_value(node);
+        let mut line = String::new();
+        if offsets {
+            line.push_str(&format!("{:>5} {:>5}: ", node.offset, node.length));
+        }
+        line.push_str(&"  ".repeat(depth));
+        line.push_str(&node.tag.name());
+        if !value.is_empty() {
+            line.push(' ');
+            line.push_str(&value);
+        }
+        lines.push(line);
+        format_nodes(&node.children, depth + 1, offsets, lines);
+    }
+}
+
+/// Formats elements as an indented tree, with the offset and contents length of every
+/// element if asked for.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::asn1;
+/// let nodes = asn1::parse_der(&hex::decode("300d06092a864886f70d0101010500").unwrap()).unwrap();
+/// assert_eq!(
+///     asn1::format_tree(&nodes, false),
+///     "SEQUENCE (2 elements)\n  OBJECT IDENTIFIER 1.2.840.113549.1.1.1 (rsaEncryption)\n  NULL"
+/// );
+/// assert_eq!(
+///     asn1::format_tree(&nodes, true).lines().nth(1).unwrap(),
+///     "    2     9:   OBJECT IDENTIFIER 1.2.840.113549.1.1.1 (rsaEncryption)"
+/// );
+/// ```
+pub fn format_tree(nodes: &[Node], offsets: bool) -> String {
+    let mut lines = Vec::new();
+    format_nodes(nodes, 0, offsets, &mut lines);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests_asn1 {
+    use super::*;
+
+    #[test]
+    fn it_parses_ber_and_long_forms() {
+        // Indefinite length SEQUENCE holding a long form OCTET STRING and a high tag number.
+        let mut data = vec![0x30, 0x80, 0x04, 0x81, 0x80];
+        data.extend([0xaa; 0x80]);
+        data.extend([0x9f, 0x81, 0x00, 0x01, 0x07, 0x00, 0x00]);
+        let nodes = parse_der(&data).unwrap();
+        assert_eq!(nodes.len(), 1);
+        let sequence = &nodes[0];
+        assert!(sequence.indefinite);
+        assert_eq!(sequence.encoded_length(), data.len());
+        assert_eq!(sequence.children[0].header_length, 3);
+        assert_eq!(sequence.children[0].value.len(), 0x80);
+        assert_eq!(sequence.children[1].tag.name(), "[128]");
+        assert_eq!(sequence.children[1].offset, 2 + 3 + 0x80);
+
+        assert!(parse_der(&[0x30, 0x80, 0x05, 0x00]).is_err());
+        assert!(parse_der(&[0x04, 0x80, 0x00, 0x00]).is_err());
+        let mut deep = [0x30, 0x80].repeat(MAX_DEPTH + 2);
+        deep.extend(vec![0x00; 2 * (MAX_DEPTH + 2)]);
+        assert!(parse_der(&deep).is_err());
+    }
+
+    #[test]
+    fn it_describes_values() {
+        // basicConstraints extension: an OCTET STRING encapsulating a SEQUENCE.
+        let data = hex::decode("300f0603551d130101ff040530030101ff").unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(
+            tree,
+            "SEQUENCE (3 elements)\n  OBJECT IDENTIFIER 2.5.29.19 (basicConstraints)\n  BOOLEAN TRUE\n  OCTET STRING (5 byte) encapsulates\n    SEQUENCE (1 element)\n      BOOLEAN TRUE"
+        );
+
+        let data = hex::decode(
+            "0201ff170d3236303130313030303030305a1e0400680069820b6578616d706c652e636f6d",
+        )
+        .unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(
+            tree,
+            "INTEGER -1\nUTCTime 260101000000Z (2026-01-01 00:00:00 UTC)\nBMPString 'hi'\n[2] 'example.com'"
+        );
+
+        assert_eq!(decode_oid(&[0x88, 0x37]).unwrap(), "2.999");
+        assert!(decode_oid(&[0x2a, 0x86]).is_err());
+
+        let data = hex::decode("030203a0").unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(tree, "BIT STRING (5 bit) a0");
+
+        // Malformed input
+        let data = hex::decode("030109").unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(tree, "BIT STRING 09 (Bit string with 9 unused bits)");
+        let data = hex::decode("030101").unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(tree, "BIT STRING 01 (Empty bit string with 1 unused bits)");
+        let data = hex::decode("03020800").unwrap();
+        let tree = format_tree(&parse_der(&data).unwrap(), false);
+        assert_eq!(tree, "BIT STRING 0800 (Bit string with 8 unused bits)");
+        assert_eq!(parse_der(&data).unwrap()[0].bit_string(), None);
+    }
+
+    #[test]
+    fn it_round_trips_pem() {
+        let data: Vec<u8> = (0..=100).collect();
+        let pem = encode_pem("CERTIFICATE", &data);
+        assert_eq!(pem.lines().nth(1).unwrap().len(), 64);
+        let blocks = parse_pem(&pem).unwrap();
+        assert_eq!(blocks[0].data, data);
+
+        let err = parse_pem("-----BEGIN A-----\nAQI=\n-----END B-----\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 3: END B does not match BEGIN A");
+        let err = parse_pem("-----BEGIN A-----\nAQI=\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The A block started on line 1 has no END line"
+        );
+    }
+}
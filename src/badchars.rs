@@ -0,0 +1,452 @@
+//! Bad characters of exploit payloads: finding them, the 0x01-0xff test sequence, comparing
+//! a memory dump against it, and encoding payloads so that they avoid them.
+
+use crate::xor::{self, XorMode};
+use std::{error::Error, str};
+
+/// Longest XOR key searched for.
+const MAX_KEY_LENGTH: usize = 8;
+
+/// Leading bytes of the test sequence looked for to find where it starts in a dump.
+const ANCHOR_LENGTH: usize = 4;
+
+/// Encoders that can avoid bad characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadcharEncoder {
+    /// Repeating XOR with a key of up to 8 bytes searched so that neither the key nor the
+    /// output contain bad characters. The output is as long as the input.
+    Xor,
+    /// Hex digits, lowercase or uppercase.
+    Hex,
+    /// Base32 without padding, lowercase or uppercase, which is letters and digits only.
+    Alphanumeric,
+}
+
+impl BadcharEncoder {
+    /// The encoders tried when none is chosen, shortest output first.
+    pub const ALL: [BadcharEncoder; 3] = [
+        BadcharEncoder::Xor,
+        BadcharEncoder::Alphanumeric,
+        BadcharEncoder::Hex,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BadcharEncoder::Xor => "xor",
+            BadcharEncoder::Hex => "hex",
+            BadcharEncoder::Alphanumeric => "alphanumeric",
+        }
+    }
+}
+
+impl str::FromStr for BadcharEncoder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "xor" => Ok(BadcharEncoder::Xor),
+            "hex" => Ok(BadcharEncoder::Hex),
+            "alphanumeric" | "alnum" | "base32" => Ok(BadcharEncoder::Alphanumeric),
+            _ => Err(format!(
+                "Unknown encoder '{}', expected one of: xor, hex, alphanumeric",
+                s
+            )),
+        }
+    }
+}
+
+/// A payload encoded to avoid bad characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedPayload {
+    pub encoder: BadcharEncoder,
+    /// The XOR key, empty for the other encoders.
+    pub key: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// A byte of the test sequence that did not arrive intact in a memory dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Offset in the test sequence.
+    pub offset: usize,
+    pub expected: u8,
+    /// The byte found instead, `None` when the dump ends before it.
+    pub found: Option<u8>,
+}
+
+/// The result of comparing a memory dump against the test sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    /// Offset in the dump where the test sequence starts.
+    pub start: usize,
+    pub mismatches: Vec<Mismatch>,
+    /// Offset in the test sequence where the dump ends, when it is too short.
+    pub truncated_at: Option<usize>,
+}
+
+impl Comparison {
+    /// Likely bad characters: the first byte of every run of mismatches, as the bytes after a
+    /// bad one are often shifted or cut off with it.
+    pub fn bad_candidates(&self) -> Vec<u8> {
+        let mut candidates = Vec::new();
+        let mut previous = None;
+        for mismatch in &self.mismatches {
+            if previous.is_none_or(|offset| offset + 1 != mismatch.offset) {
+                candidates.push(mismatch.expected);
+            }
+            previous = Some(mismatch.offset);
+        }
+        candidates
+    }
+}
+
+/// Parses one token of a bad character list: `\x00\x0a`, `0x0a` or hex digits such as `000a`.
+fn parse_badchar_token(token: &str) -> Option<Vec<u8>> {
+    let digits = match token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+    {
+        Some(digits) => digits.to_string(),
+        None if token.starts_with("\\x") => {
+            let mut parts = token.split("\\x").skip(1);
+            if !parts.all(|part| part.len() == 2) {
+                return None;
+            }
+            token.replace("\\x", "")
+        }
+        None => token.to_string(),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    hex::decode(digits).ok()
+}
+
+/// Parses a list of bad characters: `\x00\x0a`, `0x00,0x0a`, hex digits such as `000a` or
+/// `00 0a`, and inclusive ranges of single bytes such as `00-1f`. Any other token is an
+/// error, so a typo cannot silently drop a bad character. The result is sorted and without
+/// duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars;
+/// assert_eq!(badchars::parse_badchars("\\x0a\\x00").unwrap(), vec![0x00, 0x0a]);
+/// assert_eq!(badchars::parse_badchars("0x00, 0x0d").unwrap(), vec![0x00, 0x0d]);
+/// assert_eq!(badchars::parse_badchars("00 0a 0d").unwrap(), vec![0x00, 0x0a, 0x0d]);
+/// assert_eq!(badchars::parse_badchars("\\x01-\\x03").unwrap(), vec![0x01, 0x02, 0x03]);
+/// assert!(badchars::parse_badchars("x0a").is_err());
+/// ```
+pub fn parse_badchars(s: &str) -> Result<Vec<u8>, String> {
+    let invalid = |token: &str| format!("Invalid bad character '{}' in '{}'", token, s);
+    let single = |token: &str| match parse_badchar_token(token).as_deref() {
+        Some(&[byte]) => Ok(byte),
+        _ => Err(invalid(token)),
+    };
+    let mut bytes = Vec::new();
+    for token in s
+        .split([',', ' ', '\t', '\n', '\r'])
+        .filter(|t| !t.is_empty())
+    {
+        match token.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (single(start)?, single(end)?);
+                if start > end {
+                    return Err(format!("Invalid bad character range '{}'", token));
+                }
+                bytes.extend(start..=end);
+            }
+            None => bytes.extend(parse_badchar_token(token).ok_or_else(|| invalid(token))?),
+        }
+    }
+    if bytes.is_empty() {
+        return Err("No bad characters given".to_string());
+    }
+    bytes.sort_unstable();
+    bytes.dedup();
+    Ok(bytes)
+}
+
+/// Finds every bad character in the data, with its offset.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars;
+/// assert_eq!(badchars::find_badchars(b"ab\0c\n", &[0x00, 0x0a]), vec![(2, 0x00), (4, 0x0a)]);
+/// ```
+pub fn find_badchars(data: &[u8], bad: &[u8]) -> Vec<(usize, u8)> {
+    data.iter()
+        .enumerate()
+        .filter(|(_, byte)| bad.contains(byte))
+        .map(|(offset, &byte)| (offset, byte))
+        .collect()
+}
+
+/// The bytes 0x01 to 0xff without the known bad characters, to send as a payload and find
+/// the rest of them in a memory dump.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars;
+/// let sequence = badchars::test_sequence(&[0x0a]);
+/// assert_eq!(sequence.len(), 254);
+/// assert_eq!(&sequence[8..10], &[0x09, 0x0b]);
+/// ```
+pub fn test_sequence(bad: &[u8]) -> Vec<u8> {
+    (0x01..=0xff).filter(|byte| !bad.contains(byte)).collect()
+}
+
+/// Compares a memory dump against the test sequence without the known bad characters.
+///
+/// The sequence is looked for in the dump by its first bytes, so the dump may start before
+/// it. Otherwise the dump is taken to start with it.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars;
+/// let mut dump = badchars::test_sequence(&[0x00]);
+/// dump[9] = 0x00; // 0x0a was mangled.
+/// dump.truncate(20);
+/// let comparison = badchars::compare_dump(&dump, &[0x00]);
+/// assert_eq!(comparison.mismatches[0].expected, 0x0a);
+/// assert_eq!(comparison.truncated_at, Some(20));
+/// assert_eq!(comparison.bad_candidates(), vec![0x0a, 0x15]);
+/// ```
+pub fn compare_dump(dump: &[u8], bad: &[u8]) -> Comparison {
+    let expected = test_sequence(bad);
+    let anchor = &expected[..ANCHOR_LENGTH.min(expected.len())];
+    let start = dump
+        .windows(anchor.len().max(1))
+        .position(|window| window == anchor)
+        .unwrap_or(0);
+    let dump = &dump[start.min(dump.len())..];
+
+    let mut mismatches: Vec<Mismatch> = expected
+        .iter()
+        .zip(dump)
+        .enumerate()
+        .filter(|(_, (expected, found))| expected != found)
+        .map(|(offset, (&expected, &found))| Mismatch {
+            offset,
+            expected,
+            found: Some(found),
+        })
+        .collect();
+    let truncated_at = (dump.len() < expected.len()).then_some(dump.len());
+    if let Some(offset) = truncated_at {
+        mismatches.push(Mismatch {
+            offset,
+            expected: expected[offset],
+            found: None,
+        });
+    }
+    Comparison {
+        start,
+        mismatches,
+        truncated_at,
+    }
+}
+
+/// Searches a repeating XOR key whose bytes, and the output they give, avoid the bad
+/// characters. Every key position is searched on its own, shortest keys first.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars;
+/// let key = badchars::find_xor_key(b"\x00\x01\x02", &[0x00, 0x01]).unwrap();
+/// assert_eq!(key, vec![0x04]);
+/// ```
+pub fn find_xor_key(data: &[u8], bad: &[u8]) -> Option<Vec<u8>> {
+    (1..=MAX_KEY_LENGTH).find_map(|length| {
+        (0..length)
+            .map(|position| {
+                (1..=0xffu8).find(|key| {
+                    !bad.contains(key)
+                        && data
+                            .iter()
+                            .skip(position)
+                            .step_by(length)
+                            .all(|byte| !bad.contains(&(byte ^ key)))
+                })
+            })
+            .collect()
+    })
+}
+
+/// Base32 (RFC 4648) without padding.
+fn encode_base32(data: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut encoded = Vec::with_capacity(data.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u16, 0);
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[((buffer >> bits) & 0x1f) as usize]);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize]);
+    }
+    encoded
+}
+
+/// Encodes a payload so that it avoids the bad characters, with the first of the encoders
+/// that manages to.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::badchars::{self, BadcharEncoder};
+/// let payload = b"\x31\xc0\x50\x68\x2f\x2f\x73\x68\x00";
+/// let encoded = badchars::encode_avoiding(payload, &[0x00, 0x0a], &BadcharEncoder::ALL).unwrap();
+/// assert_eq!(encoded.encoder, BadcharEncoder::Xor);
+/// assert!(badchars::find_badchars(&encoded.data, &[0x00, 0x0a]).is_empty());
+///
+/// let encoded = badchars::encode_avoiding(b"\xff", b"abcdef", &[BadcharEncoder::Hex]).unwrap();
+/// assert_eq!(encoded.data, b"FF");
+/// ```
+pub fn encode_avoiding(
+    data: &[u8],
+    bad: &[u8],
+    encoders: &[BadcharEncoder],
+) -> Result<EncodedPayload, Box<dyn Error>> {
+    let clean = |candidate: &Vec<u8>| find_badchars(candidate, bad).is_empty();
+    for &encoder in encoders {
+        let (key, candidates) = match encoder {
+            BadcharEncoder::Xor => match find_xor_key(data, bad) {
+                Some(key) => {
+                    let encoded = xor::encode_xor(data, &key, XorMode::Repeating)?;
+                    (key, vec![encoded])
+                }
+                None => continue,
+            },
+            BadcharEncoder::Hex => {
+                let lower = hex::encode(data).into_bytes();
+                let upper = lower.to_ascii_uppercase();
+                (Vec::new(), vec![lower, upper])
+            }
+            BadcharEncoder::Alphanumeric => {
+                let upper = encode_base32(data);
+                let lower = upper.to_ascii_lowercase();
+                (Vec::new(), vec![upper, lower])
+            }
+        };
+        if let Some(data) = candidates.into_iter().find(clean) {
+            return Ok(EncodedPayload { encoder, key, data });
+        }
+    }
+    let names: Vec<&str> = encoders.iter().map(BadcharEncoder::name).collect();
+    Err(format!(
+        "None of the encoders ({}) avoids the bad characters",
+        names.join(", ")
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests_badchars {
+    use super::*;
+
+    #[test]
+    fn it_builds_the_test_sequence() {
+        let sequence = test_sequence(&[]);
+        assert_eq!(sequence.len(), 255);
+        assert_eq!(sequence[0], 0x01);
+        assert_eq!(sequence[254], 0xff);
+        let sequence = test_sequence(&[0x00, 0x0a, 0x0d]);
+        assert_eq!(sequence.len(), 253);
+        assert!(find_badchars(&sequence, &[0x0a, 0x0d]).is_empty());
+    }
+
+    #[test]
+    fn it_compares_dumps() {
+        let bad = [0x00];
+        let mut dump = vec![0x90, 0x90, 0x90];
+        dump.extend(test_sequence(&bad));
+        // 0x0a is mangled into 0x00 and 0x0d cuts the rest of the payload.
+        dump[3 + 9] = 0x00;
+        dump.truncate(3 + 12);
+        let comparison = compare_dump(&dump, &bad);
+        assert_eq!(comparison.start, 3);
+        assert_eq!(
+            comparison.mismatches,
+            vec![
+                Mismatch {
+                    offset: 9,
+                    expected: 0x0a,
+                    found: Some(0x00),
+                },
+                Mismatch {
+                    offset: 12,
+                    expected: 0x0d,
+                    found: None,
+                },
+            ]
+        );
+        assert_eq!(comparison.truncated_at, Some(12));
+        assert_eq!(comparison.bad_candidates(), vec![0x0a, 0x0d]);
+
+        let intact = compare_dump(&test_sequence(&bad), &bad);
+        assert!(intact.mismatches.is_empty());
+        assert_eq!(intact.truncated_at, None);
+    }
+
+    #[test]
+    fn it_searches_xor_keys() {
+        // Every key byte meets every byte value, so one of them always XORs to zero.
+        let data: Vec<u8> = (0..=0xff).flat_map(|byte| [byte; MAX_KEY_LENGTH]).collect();
+        assert_eq!(find_xor_key(&data, &[0x00]), None);
+        let data: Vec<u8> = (0..=0xff).collect();
+        assert_eq!(find_xor_key(&data, &[0x00]), Some(vec![0x01, 0x02]));
+
+        let data = [0x00, 0x01, 0x02, 0x03];
+        let key = find_xor_key(&data, &[0x00, 0x01, 0x02, 0x03]).unwrap();
+        assert_eq!(key, vec![0x04]);
+        let data = [0x04, 0x00, 0x05, 0x01];
+        let key = find_xor_key(&data, &[0x00, 0x01, 0x04, 0x05]).unwrap();
+        assert_eq!(key.len(), 1);
+        let encoded = xor::encode_xor(&data, &key, XorMode::Repeating).unwrap();
+        assert!(find_badchars(&encoded, &[0x00, 0x01, 0x04, 0x05]).is_empty());
+    }
+
+    #[test]
+    fn it_encodes_around_bad_characters() {
+        assert_eq!(encode_base32(b"foobar"), b"MZXW6YTBOI");
+        assert_eq!(encode_base32(b"f"), b"MY");
+
+        let payload: Vec<u8> = (0..=0xff).flat_map(|byte| [byte; MAX_KEY_LENGTH]).collect();
+        let encoded = encode_avoiding(&payload, &[0x00], &BadcharEncoder::ALL).unwrap();
+        assert_eq!(encoded.encoder, BadcharEncoder::Alphanumeric);
+        assert!(encoded.key.is_empty());
+
+        let err = encode_avoiding(&payload, b"0a", &[BadcharEncoder::Xor, BadcharEncoder::Hex])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "None of the encoders (xor, hex) avoids the bad characters"
+        );
+        assert!(parse_badchars("\\xzz").is_err());
+    }
+
+    #[test]
+    fn it_parses_badchars_strictly() {
+        assert_eq!(parse_badchars("00-ff").unwrap().len(), 256);
+        assert_eq!(
+            parse_badchars("0x00-0x02,\\x0a\\x0d 20").unwrap(),
+            vec![0x00, 0x01, 0x02, 0x0a, 0x0d, 0x20]
+        );
+
+        // Malformed input
+        for bad in [
+            "x0a", "zz", "hello", "0a-", "ff-00", "\\x0", "0x", "abc", "", " , ",
+        ] {
+            assert!(parse_badchars(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,510 @@
+//! Transcoding of text between character sets: UTF-8, UTF-16, UTF-32, Latin-1, EBCDIC and
+//! the legacy encodings of the WHATWG Encoding Standard, e.g. Windows-1252, ISO-8859-2,
+//! Shift_JIS or GB18030.
+
+use encoding_rs::{DecoderResult, EncoderResult, Encoding};
+use std::{error::Error, str};
+
+/// EBCDIC code page 037 (US/Canada), the Latin-1 code point of every byte.
+const CP037: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9c, 0x09, 0x86, 0x7f, 0x97, 0x8d, 0x8e, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x9d, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8f, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0a, 0x17, 0x1b, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9a, 0x9b, 0x14, 0x15, 0x9e, 0x1a,
+    0x20, 0xa0, 0xe2, 0xe4, 0xe0, 0xe1, 0xe3, 0xe5, 0xe7, 0xf1, 0xa2, 0x2e, 0x3c, 0x28, 0x2b, 0x7c,
+    0x26, 0xe9, 0xea, 0xeb, 0xe8, 0xed, 0xee, 0xef, 0xec, 0xdf, 0x21, 0x24, 0x2a, 0x29, 0x3b, 0xac,
+    0x2d, 0x2f, 0xc2, 0xc4, 0xc0, 0xc1, 0xc3, 0xc5, 0xc7, 0xd1, 0xa6, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xf8, 0xc9, 0xca, 0xcb, 0xc8, 0xcd, 0xce, 0xcf, 0xcc, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xd8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xab, 0xbb, 0xf0, 0xfd, 0xfe, 0xb1,
+    0xb0, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0xaa, 0xba, 0xe6, 0xb8, 0xc6, 0xa4,
+    0xb5, 0x7e, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xa1, 0xbf, 0xd0, 0xdd, 0xde, 0xae,
+    0x5e, 0xa3, 0xa5, 0xb7, 0xa9, 0xa7, 0xb6, 0xbc, 0xbd, 0xbe, 0x5b, 0x5d, 0xaf, 0xa8, 0xb4, 0xd7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xad, 0xf4, 0xf6, 0xf2, 0xf3, 0xf5,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xb9, 0xfb, 0xfc, 0xf9, 0xfa, 0xff,
+    0x5c, 0xf7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xb2, 0xd4, 0xd6, 0xd2, 0xd3, 0xd5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xb3, 0xdb, 0xdc, 0xd9, 0xda, 0x9f,
+];
+
+/// The bytes where EBCDIC code page 500 (International) differs from code page 037.
+const CP500_CHANGES: [(u8, u8); 7] = [
+    (0x4a, 0x5b),
+    (0x4f, 0x21),
+    (0x5a, 0x5d),
+    (0x5f, 0x5e),
+    (0xb0, 0xa2),
+    (0xba, 0xac),
+    (0xbb, 0x7c),
+];
+
+/// Supported character sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    /// UTF-16 in the byte order of the byte order mark, little endian without one.
+    Utf16,
+    Utf16Le,
+    Utf16Be,
+    /// UTF-32 in the byte order of the byte order mark, little endian without one.
+    Utf32,
+    Utf32Le,
+    Utf32Be,
+    /// ISO-8859-1, where every byte is the code point of the same value. Note that the
+    /// WHATWG label `iso-8859-1` means Windows-1252 instead.
+    Latin1,
+    /// EBCDIC code page 037, US/Canada.
+    Cp037,
+    /// EBCDIC code page 500, International.
+    Cp500,
+    /// Any other encoding of the WHATWG Encoding Standard.
+    Legacy(&'static Encoding),
+}
+
+impl Charset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::Utf16 => "UTF-16",
+            Charset::Utf16Le => "UTF-16LE",
+            Charset::Utf16Be => "UTF-16BE",
+            Charset::Utf32 => "UTF-32",
+            Charset::Utf32Le => "UTF-32LE",
+            Charset::Utf32Be => "UTF-32BE",
+            Charset::Latin1 => "ISO-8859-1",
+            Charset::Cp037 => "IBM037",
+            Charset::Cp500 => "IBM500",
+            Charset::Legacy(encoding) => encoding.name(),
+        }
+    }
+
+    /// The byte order mark written for this character set, `None` if it has none.
+    pub fn bom(&self) -> Option<&'static [u8]> {
+        match self {
+            Charset::Utf8 => Some(b"\xef\xbb\xbf"),
+            Charset::Utf16 | Charset::Utf16Le => Some(b"\xff\xfe"),
+            Charset::Utf16Be => Some(b"\xfe\xff"),
+            Charset::Utf32 | Charset::Utf32Le => Some(b"\xff\xfe\x00\x00"),
+            Charset::Utf32Be => Some(b"\x00\x00\xfe\xff"),
+            _ => None,
+        }
+    }
+
+    /// The Latin-1 code point of every byte of a single byte character set.
+    fn latin1_table(&self) -> Option<[u8; 256]> {
+        match self {
+            Charset::Latin1 => Some(std::array::from_fn(|i| i as u8)),
+            Charset::Cp037 => Some(CP037),
+            Charset::Cp500 => {
+                let mut table = CP037;
+                for (byte, code_point) in CP500_CHANGES {
+                    table[byte as usize] = code_point;
+                }
+                Some(table)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl str::FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let label = s.trim().to_ascii_lowercase();
+        let charset = match label.as_str() {
+            "utf8" | "utf-8" => Charset::Utf8,
+            "utf16" | "utf-16" => Charset::Utf16,
+            "utf16le" | "utf-16le" => Charset::Utf16Le,
+            "utf16be" | "utf-16be" => Charset::Utf16Be,
+            "utf32" | "utf-32" => Charset::Utf32,
+            "utf32le" | "utf-32le" => Charset::Utf32Le,
+            "utf32be" | "utf-32be" => Charset::Utf32Be,
+            "latin1" | "latin-1" | "iso-8859-1" | "iso8859-1" | "l1" => Charset::Latin1,
+            "ebcdic" | "cp037" | "ibm037" | "ibm-037" => Charset::Cp037,
+            "cp500" | "ibm500" | "ibm-500" => Charset::Cp500,
+            _ => match Encoding::for_label(label.as_bytes()) {
+                Some(encoding) if encoding == encoding_rs::UTF_8 => Charset::Utf8,
+                Some(encoding) if encoding == encoding_rs::UTF_16LE => Charset::Utf16Le,
+                Some(encoding) if encoding == encoding_rs::UTF_16BE => Charset::Utf16Be,
+                Some(encoding) if encoding != encoding_rs::REPLACEMENT => Charset::Legacy(encoding),
+                _ => {
+                    return Err(format!(
+                        "Unknown character set '{}', expected one of: utf-8, utf-16, utf-16le, utf-16be, utf-32, utf-32le, utf-32be, latin1, cp037, cp500, or a label such as windows-1252, iso-8859-2, shift_jis or gb18030",
+                        s
+                    ));
+                }
+            },
+        };
+        Ok(charset)
+    }
+}
+
+/// Detects a byte order mark, returning the character set it stands for and its length.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::charset::{self, Charset};
+/// assert_eq!(charset::detect_bom(b"\xfe\xff\x00A"), Some((Charset::Utf16Be, 2)));
+/// assert_eq!(charset::detect_bom(b"A"), None);
+/// ```
+pub fn detect_bom(bytes: &[u8]) -> Option<(Charset, usize)> {
+    // UTF-32LE first, its mark starts with the one of UTF-16LE.
+    [
+        Charset::Utf32Le,
+        Charset::Utf32Be,
+        Charset::Utf8,
+        Charset::Utf16Le,
+        Charset::Utf16Be,
+    ]
+    .into_iter()
+    .find_map(|charset| {
+        let bom = charset.bom()?;
+        bytes.starts_with(bom).then_some((charset, bom.len()))
+    })
+}
+
+/// Decodes bytes in the given character set into text.
+///
+/// A byte order mark matching the character set is skipped. Malformed input is replaced with
+/// U+FFFD, or is an error when `strict` is set.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::charset::{self, Charset};
+/// let text = charset::decode_text(b"\xff\xfeh\x00i\x00", Charset::Utf16, true).unwrap();
+/// assert_eq!(text, "hi");
+/// let text = charset::decode_text(b"\x88\xc5\x81\x99", Charset::Cp037, true).unwrap();
+/// assert_eq!(text, "hEar");
+/// ```
+pub fn decode_text(bytes: &[u8], charset: Charset, strict: bool) -> Result<String, Box<dyn Error>> {
+    let (charset, bytes) = match (charset, detect_bom(bytes)) {
+        (Charset::Utf16, Some((found @ (Charset::Utf16Le | Charset::Utf16Be), len)))
+        | (Charset::Utf32, Some((found @ (Charset::Utf32Le | Charset::Utf32Be), len))) => {
+            (found, &bytes[len..])
+        }
+        (Charset::Utf16, _) => (Charset::Utf16Le, bytes),
+        (Charset::Utf32, _) => (Charset::Utf32Le, bytes),
+        (charset, Some((found, len))) if found == charset => (charset, &bytes[len..]),
+        (charset, _) => (charset, bytes),
+    };
+    let invalid = |offset: usize| format!("Invalid {} sequence at byte {}", charset.name(), offset);
+    let malformed = |offset: usize| -> Result<char, Box<dyn Error>> {
+        if strict {
+            Err(invalid(offset).into())
+        } else {
+            Ok(char::REPLACEMENT_CHARACTER)
+        }
+    };
+
+    match charset {
+        Charset::Utf8 => match str::from_utf8(bytes) {
+            Ok(text) => Ok(text.to_string()),
+            Err(e) if strict => Err(invalid(e.valid_up_to()).into()),
+            Err(_) => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        },
+        Charset::Utf16Le | Charset::Utf16Be => {
+            let units = bytes.chunks(2).map(|pair| match (pair, charset) {
+                ([low, high], Charset::Utf16Le) => Ok(u16::from_le_bytes([*low, *high])),
+                ([high, low], _) => Ok(u16::from_be_bytes([*high, *low])),
+                _ => Err(bytes.len() - 1),
+            });
+            let mut text = String::new();
+            let mut offset = 0;
+            let mut pending = Vec::new();
+            for unit in units {
+                match unit {
+                    Ok(unit) => pending.push(unit),
+                    Err(at) => {
+                        flush_utf16(&mut pending, &mut offset, &mut text, &malformed)?;
+                        text.push(malformed(at)?);
+                    }
+                }
+            }
+            flush_utf16(&mut pending, &mut offset, &mut text, &malformed)?;
+            Ok(text)
+        }
+        Charset::Utf32Le | Charset::Utf32Be => bytes
+            .chunks(4)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let code_point = match (<[u8; 4]>::try_from(chunk), charset) {
+                    (Ok(quad), Charset::Utf32Le) => Some(u32::from_le_bytes(quad)),
+                    (Ok(quad), _) => Some(u32::from_be_bytes(quad)),
+                    (Err(_), _) => None,
+                };
+                code_point
+                    .and_then(char::from_u32)
+                    .map_or_else(|| malformed(index * 4), Ok)
+            })
+            .collect(),
+        Charset::Latin1 | Charset::Cp037 | Charset::Cp500 => {
+            let table = charset.latin1_table().unwrap_or([0; 256]);
+            Ok(bytes.iter().map(|&b| table[b as usize] as char).collect())
+        }
+        Charset::Legacy(encoding) if strict => {
+            let mut decoder = encoding.new_decoder_without_bom_handling();
+            let capacity = decoder
+                .max_utf8_buffer_length_without_replacement(bytes.len())
+                .ok_or("Input is too long")?;
+            let mut text = String::with_capacity(capacity);
+            let (result, read) =
+                decoder.decode_to_string_without_replacement(bytes, &mut text, true);
+            match result {
+                DecoderResult::InputEmpty => Ok(text),
+                DecoderResult::Malformed(bad, after) => {
+                    Err(invalid(read - bad as usize - after as usize).into())
+                }
+                DecoderResult::OutputFull => Err("Decoded text does not fit the buffer".into()),
+            }
+        }
+        Charset::Legacy(encoding) => Ok(encoding.decode_without_bom_handling(bytes).0.into_owned()),
+        Charset::Utf16 | Charset::Utf32 => unreachable!("byte order resolved above"),
+    }
+}
+
+/// Appends UTF-16 code units to the text, handling unpaired surrogates as malformed input.
+fn flush_utf16(
+    units: &mut Vec<u16>,
+    offset: &mut usize,
+    text: &mut String,
+    malformed: &dyn Fn(usize) -> Result<char, Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    for c in char::decode_utf16(units.drain(..)) {
+        match c {
+            Ok(c) => {
+                text.push(c);
+                *offset += c.len_utf16() * 2;
+            }
+            Err(_) => {
+                text.push(malformed(*offset)?);
+                *offset += 2;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Encodes text in the given character set, with a byte order mark first when `bom` is set.
+///
+/// Characters the character set cannot represent are replaced with `?`, or are an error when
+/// `strict` is set.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::charset::{self, Charset};
+/// let bytes = charset::encode_text("hi", Charset::Utf16Be, false, true).unwrap();
+/// assert_eq!(bytes, b"\xfe\xff\x00h\x00i");
+/// let bytes = charset::encode_text("日本", "shift_jis".parse().unwrap(), true, false).unwrap();
+/// assert_eq!(bytes, b"\x93\xfa\x96\x7b");
+/// assert!(charset::encode_text("€", Charset::Latin1, true, false).is_err());
+/// ```
+pub fn encode_text(
+    text: &str,
+    charset: Charset,
+    strict: bool,
+    bom: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = match (bom, charset.bom()) {
+        (false, _) => Vec::new(),
+        (true, Some(mark)) => mark.to_vec(),
+        (true, None) => {
+            return Err(format!("{} has no byte order mark", charset.name()).into());
+        }
+    };
+    let unmappable = |c: char| -> Result<(), Box<dyn Error>> {
+        if strict {
+            Err(format!(
+                "'{}' (U+{:04X}) cannot be encoded in {}",
+                c,
+                c as u32,
+                charset.name()
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    };
+
+    match charset {
+        Charset::Utf8 => bytes.extend_from_slice(text.as_bytes()),
+        Charset::Utf16 | Charset::Utf16Le => {
+            bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes))
+        }
+        Charset::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+        Charset::Utf32 | Charset::Utf32Le => {
+            bytes.extend(text.chars().flat_map(|c| (c as u32).to_le_bytes()))
+        }
+        Charset::Utf32Be => bytes.extend(text.chars().flat_map(|c| (c as u32).to_be_bytes())),
+        Charset::Latin1 | Charset::Cp037 | Charset::Cp500 => {
+            let table = charset.latin1_table().unwrap_or([0; 256]);
+            let mut inverse = [0u8; 256];
+            for (byte, &code_point) in table.iter().enumerate() {
+                inverse[code_point as usize] = byte as u8;
+            }
+            for c in text.chars() {
+                match u8::try_from(c) {
+                    Ok(code_point) => bytes.push(inverse[code_point as usize]),
+                    Err(_) => {
+                        unmappable(c)?;
+                        bytes.push(inverse[b'?' as usize]);
+                    }
+                }
+            }
+        }
+        Charset::Legacy(encoding) => {
+            let mut encoder = encoding.new_encoder();
+            let mut rest = text;
+            loop {
+                let needed = encoder
+                    .max_buffer_length_from_utf8_without_replacement(rest.len())
+                    .ok_or("Input is too long")?;
+                bytes.reserve(needed + 1);
+                let (result, read) =
+                    encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut bytes, true);
+                rest = &rest[read..];
+                match result {
+                    EncoderResult::InputEmpty => break,
+                    EncoderResult::OutputFull => continue,
+                    EncoderResult::Unmappable(c) => {
+                        unmappable(c)?;
+                        bytes.push(b'?');
+                    }
+                }
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Transcodes bytes from one character set into another.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::charset::{self, Charset};
+/// let cp1252 = "windows-1252".parse().unwrap();
+/// let bytes = charset::transcode(b"caf\xe9 \x80", cp1252, Charset::Utf8, true, false).unwrap();
+/// assert_eq!(bytes, "café €".as_bytes());
+/// ```
+pub fn transcode(
+    bytes: &[u8],
+    from: Charset,
+    to: Charset,
+    strict: bool,
+    bom: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    encode_text(&decode_text(bytes, from, strict)?, to, strict, bom)
+}
+
+#[cfg(test)]
+mod tests_charset {
+    use super::*;
+
+    #[test]
+    fn it_parses_charset_labels() {
+        assert_eq!("UTF-16".parse::<Charset>().unwrap(), Charset::Utf16);
+        assert_eq!("latin1".parse::<Charset>().unwrap(), Charset::Latin1);
+        assert_eq!("iso-8859-1".parse::<Charset>().unwrap(), Charset::Latin1);
+        assert_eq!("ebcdic".parse::<Charset>().unwrap(), Charset::Cp037);
+        assert_eq!("unicodefffe".parse::<Charset>().unwrap(), Charset::Utf16Be);
+        assert_eq!("cp1250".parse::<Charset>().unwrap().name(), "windows-1250");
+        assert_eq!("sjis".parse::<Charset>().unwrap().name(), "Shift_JIS");
+        assert!("replacement".parse::<Charset>().is_err());
+        assert!("klingon".parse::<Charset>().is_err());
+    }
+
+    #[test]
+    fn it_detects_and_writes_byte_order_marks() {
+        assert_eq!(
+            detect_bom(b"\xff\xfe\x00\x00A\x00\x00\x00"),
+            Some((Charset::Utf32Le, 4))
+        );
+        assert_eq!(detect_bom(b"\xef\xbb\xbfA"), Some((Charset::Utf8, 3)));
+        assert_eq!(
+            decode_text(b"\x00\x00\xfe\xff\x00\x01\xf6\x00", Charset::Utf32, true).unwrap(),
+            "😀"
+        );
+        assert_eq!(
+            decode_text(b"h\x00i\x00", Charset::Utf16, true).unwrap(),
+            "hi"
+        );
+        assert_eq!(
+            decode_text(b"\xef\xbb\xbfhi", Charset::Utf8, true).unwrap(),
+            "hi"
+        );
+        assert_eq!(
+            encode_text("A", Charset::Utf8, true, true).unwrap(),
+            b"\xef\xbb\xbfA"
+        );
+        assert!(encode_text("A", Charset::Cp037, true, true).is_err());
+    }
+
+    #[test]
+    fn it_round_trips_every_charset() {
+        let text = "Hello, world! [x] {y} ^ |";
+        for label in [
+            "utf-8",
+            "utf-16le",
+            "utf-16be",
+            "utf-32be",
+            "latin1",
+            "cp037",
+            "cp500",
+            "windows-1251",
+            "iso-8859-2",
+            "shift_jis",
+            "gb18030",
+        ] {
+            let charset: Charset = label.parse().unwrap();
+            let bytes = encode_text(text, charset, true, false).unwrap();
+            assert_eq!(
+                decode_text(&bytes, charset, true).unwrap(),
+                text,
+                "{}",
+                label
+            );
+        }
+        let bytes = encode_text("中文 𝄞", "gb18030".parse().unwrap(), true, false).unwrap();
+        assert_eq!(bytes, b"\xd6\xd0\xce\xc4 \x94\x32\xbe\x34");
+        assert_eq!(
+            encode_text("[]", Charset::Cp037, true, false).unwrap(),
+            b"\xba\xbb"
+        );
+        assert_eq!(
+            encode_text("[]", Charset::Cp500, true, false).unwrap(),
+            b"\x4a\x5a"
+        );
+    }
+
+    #[test]
+    fn it_replaces_or_rejects_malformed_input() {
+        assert_eq!(
+            decode_text(b"a\xffb", Charset::Utf8, false).unwrap(),
+            "a\u{fffd}b"
+        );
+        let err = decode_text(b"a\xffb", Charset::Utf8, true).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-8 sequence at byte 1");
+        let err = decode_text(b"a\x00\x00\xd8b\x00", Charset::Utf16Le, true).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-16LE sequence at byte 2");
+        assert_eq!(
+            decode_text(b"a\x00b", Charset::Utf16Le, false).unwrap(),
+            "a\u{fffd}"
+        );
+        let sjis: Charset = "shift_jis".parse().unwrap();
+        let err = decode_text(b"ab\x81", sjis, true).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid Shift_JIS sequence at byte 2");
+
+        assert_eq!(
+            encode_text("a€b", Charset::Latin1, false, false).unwrap(),
+            b"a?b"
+        );
+        assert_eq!(encode_text("a😀b", sjis, false, false).unwrap(), b"a?b");
+        let err = encode_text("a😀b", sjis, true, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'😀' (U+1F600) cannot be encoded in Shift_JIS"
+        );
+    }
+}
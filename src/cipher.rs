@@ -0,0 +1,507 @@
+//! Classical ciphers: ROT-N, ROT47, ROT18, Atbash, Vigenère, Beaufort, affine and rail fence.
+
+use crate::analysis::score_english;
+use std::error::Error;
+
+/// A Caesar shift tried by [`bruteforce_caesar`], with the resulting text and its English score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaesarCandidate {
+    pub shift: u8,
+    pub score: f64,
+    pub plaintext: String,
+}
+
+/// Shifts an ASCII letter by `shift` places, keeping its case. Other characters are returned as they are.
+fn shift_letter(c: char, shift: i64) -> char {
+    if c.is_ascii_alphabetic() {
+        let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+        let offset = (c as u8 - first) as i64;
+        (first + (offset + shift.rem_euclid(26)).rem_euclid(26) as u8) as char
+    } else {
+        c
+    }
+}
+
+/// Turns a key into Vigenère shifts, one per letter of the key.
+fn key_shifts(key: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+    let shifts: Vec<i64> = key
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| (c.to_ascii_lowercase() as u8 - b'a') as i64)
+        .collect();
+    if shifts.is_empty() {
+        return Err("Key must contain at least one ASCII letter".into());
+    }
+    Ok(shifts)
+}
+
+/// Encodes a string with ROT-N over ASCII letters, keeping their case.
+///
+/// A negative shift rotates backwards, so `encode_rot(data, -n)` decodes `encode_rot(data, n)`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_rot("Hello", 3).unwrap();
+/// assert_eq!(encoded, "Khoor");
+/// ```
+pub fn encode_rot(data: &str, shift: i64) -> Result<String, Box<dyn Error>> {
+    Ok(data.chars().map(|c| shift_letter(c, shift)).collect())
+}
+
+/// Encodes a string with ROT-N over a custom alphabet.
+///
+/// Only characters of the alphabet are rotated, everything else is kept as it is.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_rot_alphabet("abc-123", 1, "abc123").unwrap();
+/// assert_eq!(encoded, "bc1-23a");
+/// ```
+pub fn encode_rot_alphabet(
+    data: &str,
+    shift: i64,
+    alphabet: &str,
+) -> Result<String, Box<dyn Error>> {
+    let symbols: Vec<char> = alphabet.chars().collect();
+    if symbols.is_empty() {
+        return Err("Alphabet must not be empty".into());
+    }
+    for (i, c) in symbols.iter().enumerate() {
+        if symbols[..i].contains(c) {
+            return Err(format!("Alphabet contains '{}' more than once", c).into());
+        }
+    }
+    let length = symbols.len() as i64;
+    let shift = shift.rem_euclid(length);
+    Ok(data
+        .chars()
+        .map(|c| match symbols.iter().position(|&s| s == c) {
+            Some(index) => symbols[(index as i64 + shift).rem_euclid(length) as usize],
+            None => c,
+        })
+        .collect())
+}
+
+/// Encodes a string with ROT47, which rotates all printable ASCII characters from `!` to `~`.
+///
+/// ROT47 is symmetric, so encoding again decodes it.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_rot47("Hello, World!").unwrap();
+/// assert_eq!(encoded, "w6==@[ (@C=5P");
+/// ```
+pub fn encode_rot47(data: &str) -> Result<String, Box<dyn Error>> {
+    Ok(data
+        .chars()
+        .map(|c| match c {
+            '!'..='~' => (b'!' + (c as u8 - b'!' + 47) % 94) as char,
+            _ => c,
+        })
+        .collect())
+}
+
+/// Encodes a string with ROT18, which is ROT13 for letters and ROT5 for digits.
+///
+/// ROT18 is symmetric, so encoding again decodes it.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_rot18("abc 123").unwrap();
+/// assert_eq!(encoded, "nop 678");
+/// ```
+pub fn encode_rot18(data: &str) -> Result<String, Box<dyn Error>> {
+    Ok(data
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => (b'0' + (c as u8 - b'0' + 5) % 10) as char,
+            _ => shift_letter(c, 13),
+        })
+        .collect())
+}
+
+/// Encodes a string with the Atbash cipher, which maps `a` to `z`, `b` to `y` and so on.
+///
+/// Atbash is symmetric, so encoding again decodes it.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_atbash("Hello").unwrap();
+/// assert_eq!(encoded, "Svool");
+/// ```
+pub fn encode_atbash(data: &str) -> Result<String, Box<dyn Error>> {
+    Ok(data
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => (b'z' - (c as u8 - b'a')) as char,
+            'A'..='Z' => (b'Z' - (c as u8 - b'A')) as char,
+            _ => c,
+        })
+        .collect())
+}
+
+/// Encodes a string with the Vigenère cipher.
+///
+/// Only ASCII letters are encoded and only they advance the position in the key.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_vigenere("Attack at dawn", "LEMON").unwrap();
+/// assert_eq!(encoded, "Lxfopv ef rnhr");
+/// ```
+pub fn encode_vigenere(data: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    vigenere(data, key, 1)
+}
+
+/// Decodes a string encoded with the Vigenère cipher.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let decoded = cipher::decode_vigenere("Lxfopv ef rnhr", "LEMON").unwrap();
+/// assert_eq!(decoded, "Attack at dawn");
+/// ```
+pub fn decode_vigenere(data: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    vigenere(data, key, -1)
+}
+
+/// Applies the Vigenère key forwards (`direction` 1) or backwards (`direction` -1).
+fn vigenere(data: &str, key: &str, direction: i64) -> Result<String, Box<dyn Error>> {
+    let shifts = key_shifts(key)?;
+    let mut position = 0;
+    Ok(data
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                let shift = shifts[position % shifts.len()];
+                position += 1;
+                shift_letter(c, direction * shift)
+            } else {
+                c
+            }
+        })
+        .collect())
+}
+
+/// Encodes a string with the Beaufort cipher, where each letter becomes `key - letter`.
+///
+/// Beaufort is symmetric, so encoding again with the same key decodes it.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_beaufort("Attack at dawn", "LEMON").unwrap();
+/// assert_eq!(encoded, "Lltolb et lnpr");
+/// ```
+pub fn encode_beaufort(data: &str, key: &str) -> Result<String, Box<dyn Error>> {
+    let shifts = key_shifts(key)?;
+    let mut position = 0;
+    Ok(data
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                let shift = shifts[position % shifts.len()];
+                position += 1;
+                let offset = (c as u8 - first) as i64;
+                (first + (shift - offset).rem_euclid(26) as u8) as char
+            } else {
+                c
+            }
+        })
+        .collect())
+}
+
+/// Returns the inverse of `a` modulo 26, if `a` is coprime to 26.
+fn inverse_mod_26(a: i64) -> Option<i64> {
+    let a = a.rem_euclid(26);
+    (1..26).find(|x| (a * x).rem_euclid(26) == 1)
+}
+
+/// Encodes a string with the affine cipher, mapping each letter `x` to `(a * x + b) mod 26`.
+///
+/// `a` must be coprime to 26.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_affine("Affine cipher", 5, 8).unwrap();
+/// assert_eq!(encoded, "Ihhwvc swfrcp");
+/// ```
+pub fn encode_affine(data: &str, a: i64, b: i64) -> Result<String, Box<dyn Error>> {
+    if inverse_mod_26(a).is_none() {
+        return Err(format!("Affine key a={} is not coprime to 26", a).into());
+    }
+    let (a, b) = (a.rem_euclid(26), b.rem_euclid(26));
+    Ok(data.chars().map(|c| affine_letter(c, a, b)).collect())
+}
+
+/// Decodes a string encoded with the affine cipher.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let decoded = cipher::decode_affine("Ihhwvc swfrcp", 5, 8).unwrap();
+/// assert_eq!(decoded, "Affine cipher");
+/// ```
+pub fn decode_affine(data: &str, a: i64, b: i64) -> Result<String, Box<dyn Error>> {
+    let inverse =
+        inverse_mod_26(a).ok_or_else(|| format!("Affine key a={} is not coprime to 26", a))?;
+    let b = b.rem_euclid(26);
+    Ok(data
+        .chars()
+        .map(|c| affine_letter(c, inverse, -inverse * b))
+        .collect())
+}
+
+/// Maps an ASCII letter `x` to `(a * x + b) mod 26`, keeping its case.
+fn affine_letter(c: char, a: i64, b: i64) -> char {
+    if c.is_ascii_alphabetic() {
+        let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+        let x = (c as u8 - first) as i64;
+        (first + (a * x + b).rem_euclid(26) as u8) as char
+    } else {
+        c
+    }
+}
+
+/// Returns the rail of every position when zig-zagging over the given number of rails.
+fn rail_pattern(length: usize, rails: usize) -> Vec<usize> {
+    let cycle = 2 * (rails - 1);
+    (0..length)
+        .map(|i| {
+            let step = i % cycle;
+            if step < rails { step } else { cycle - step }
+        })
+        .collect()
+}
+
+/// Encodes a string with the rail fence cipher.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let encoded = cipher::encode_rail_fence("WEAREDISCOVERED", 3).unwrap();
+/// assert_eq!(encoded, "WECRERDSOEEAIVD");
+/// ```
+pub fn encode_rail_fence(data: &str, rails: usize) -> Result<String, Box<dyn Error>> {
+    if rails < 2 {
+        return Err("Rail fence needs at least 2 rails".into());
+    }
+    let chars: Vec<char> = data.chars().collect();
+    let pattern = rail_pattern(chars.len(), rails);
+    Ok((0..rails)
+        .flat_map(|rail| {
+            chars
+                .iter()
+                .zip(&pattern)
+                .filter(move |(_, r)| **r == rail)
+                .map(|(c, _)| *c)
+        })
+        .collect())
+}
+
+/// Decodes a string encoded with the rail fence cipher.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let decoded = cipher::decode_rail_fence("WECRERDSOEEAIVD", 3).unwrap();
+/// assert_eq!(decoded, "WEAREDISCOVERED");
+/// ```
+pub fn decode_rail_fence(data: &str, rails: usize) -> Result<String, Box<dyn Error>> {
+    if rails < 2 {
+        return Err("Rail fence needs at least 2 rails".into());
+    }
+    let chars: Vec<char> = data.chars().collect();
+    let pattern = rail_pattern(chars.len(), rails);
+    // Positions ordered the way encoding reads them, rail by rail.
+    let mut order: Vec<usize> = (0..chars.len()).collect();
+    order.sort_by_key(|&i| pattern[i]);
+    let mut result = vec![' '; chars.len()];
+    for (c, position) in chars.into_iter().zip(order) {
+        result[position] = c;
+    }
+    Ok(result.into_iter().collect())
+}
+
+/// Tries all 25 Caesar shifts and returns them ranked by English score.
+///
+/// The shift is the one that was used to encode, so `shift` 3 means the text was ROT3 encoded.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::cipher;
+/// let candidates = cipher::bruteforce_caesar("Wkh vhfuhw phhwlqj lv dw whq");
+/// assert_eq!(candidates[0].shift, 3);
+/// assert_eq!(candidates[0].plaintext, "The secret meeting is at ten");
+/// ```
+pub fn bruteforce_caesar(data: &str) -> Vec<CaesarCandidate> {
+    let mut candidates: Vec<CaesarCandidate> = (1..26u8)
+        .map(|shift| {
+            let plaintext: String = data
+                .chars()
+                .map(|c| shift_letter(c, -(shift as i64)))
+                .collect();
+            CaesarCandidate {
+                shift,
+                score: score_english(plaintext.as_bytes()),
+                plaintext,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+#[cfg(test)]
+mod tests_cipher {
+    use super::*;
+
+    #[test]
+    fn it_encodes_rot() {
+        assert_eq!(encode_rot("Hello, World!", 13).unwrap(), "Uryyb, Jbeyq!");
+        assert_eq!(encode_rot("Khoor", -3).unwrap(), "Hello");
+        assert_eq!(encode_rot("xyz", 29).unwrap(), "abc");
+    }
+
+    #[test]
+    fn it_encodes_rot_alphabet() {
+        let encoded = encode_rot_alphabet("hello 42", 5, "0123456789").unwrap();
+        assert_eq!(encoded, "hello 97");
+        let decoded = encode_rot_alphabet("hello 97", -5, "0123456789").unwrap();
+        assert_eq!(decoded, "hello 42");
+
+        // Malformed input
+        assert!(encode_rot_alphabet("hello", 1, "").is_err());
+        assert!(encode_rot_alphabet("hello", 1, "abca").is_err());
+    }
+
+    #[test]
+    fn it_reduces_large_shifts() {
+        assert_eq!(encode_rot("Hello", i64::MAX).unwrap(), "Olssv");
+        assert_eq!(encode_rot("Hello", i64::MIN).unwrap(), "Zwddg");
+        assert_eq!(encode_rot_alphabet("abc", i64::MAX, "abc").unwrap(), "bca");
+        assert_eq!(
+            encode_affine("affine", 5 + 26 * 1000, i64::MAX).unwrap(),
+            "hggvub"
+        );
+        assert_eq!(
+            decode_affine("hggvub", 5 + 26 * 1000, i64::MAX).unwrap(),
+            "affine"
+        );
+
+        // Malformed input
+        assert!(encode_affine("affine", 4611686018427387904, 1).is_err());
+    }
+
+    #[test]
+    fn it_encodes_rot47() {
+        let encoded = encode_rot47("The Quick Brown Fox").unwrap();
+        assert_eq!(encoded, "%96 \"F:4< qC@H? u@I");
+        assert_eq!(encode_rot47(&encoded).unwrap(), "The Quick Brown Fox");
+    }
+
+    #[test]
+    fn it_encodes_rot18() {
+        let encoded = encode_rot18("Hello 2025").unwrap();
+        assert_eq!(encoded, "Uryyb 7570");
+        assert_eq!(encode_rot18(&encoded).unwrap(), "Hello 2025");
+    }
+
+    #[test]
+    fn it_encodes_atbash() {
+        assert_eq!(encode_atbash("Hello, World!").unwrap(), "Svool, Dliow!");
+        assert_eq!(encode_atbash("Svool, Dliow!").unwrap(), "Hello, World!");
+    }
+
+    #[test]
+    fn it_encodes_vigenere() {
+        let encoded = encode_vigenere("ATTACKATDAWN", "LEMON").unwrap();
+        assert_eq!(encoded, "LXFOPVEFRNHR");
+
+        // Malformed input
+        assert!(encode_vigenere("hello", "123").is_err());
+    }
+
+    #[test]
+    fn it_decodes_vigenere() {
+        let decoded = decode_vigenere("LXFOPVEFRNHR", "lemon").unwrap();
+        assert_eq!(decoded, "ATTACKATDAWN");
+
+        // Malformed input
+        assert!(decode_vigenere("hello", "").is_err());
+    }
+
+    #[test]
+    fn it_encodes_beaufort() {
+        let encoded = encode_beaufort("hello world", "key").unwrap();
+        assert_eq!(encoded, "danzq cwnnh");
+        assert_eq!(encode_beaufort(&encoded, "key").unwrap(), "hello world");
+    }
+
+    #[test]
+    fn it_encodes_affine() {
+        assert_eq!(encode_affine("affine", 5, 8).unwrap(), "ihhwvc");
+
+        // Malformed input
+        assert!(encode_affine("affine", 13, 8).is_err());
+    }
+
+    #[test]
+    fn it_decodes_affine() {
+        assert_eq!(decode_affine("ihhwvc", 5, 8).unwrap(), "affine");
+
+        // Malformed input
+        assert!(decode_affine("ihhwvc", 2, 8).is_err());
+    }
+
+    #[test]
+    fn it_encodes_rail_fence() {
+        assert_eq!(encode_rail_fence("hello world", 2).unwrap(), "hlowrdel ol");
+
+        // Malformed input
+        assert!(encode_rail_fence("hello world", 1).is_err());
+    }
+
+    #[test]
+    fn it_decodes_rail_fence() {
+        for rails in 2..8 {
+            let encoded = encode_rail_fence("hello world", rails).unwrap();
+            assert_eq!(decode_rail_fence(&encoded, rails).unwrap(), "hello world");
+        }
+
+        // Malformed input
+        assert!(decode_rail_fence("hello world", 0).is_err());
+    }
+
+    #[test]
+    fn it_bruteforces_caesar() {
+        let candidates = bruteforce_caesar("Uryyb, jbeyq! Guvf vf n frperg zrffntr.");
+        assert_eq!(candidates.len(), 25);
+        assert_eq!(candidates[0].shift, 13);
+        assert_eq!(
+            candidates[0].plaintext,
+            "Hello, world! This is a secret message."
+        );
+    }
+}
@@ -1,5 +1,25 @@
+use chrono::FixedOffset;
 use clap::{Parser, Subcommand};
 use clap_stdin::MaybeStdin;
+use nutek_encode_lib::array::ArrayFormat;
+use nutek_encode_lib::badchars::BadcharEncoder;
+use nutek_encode_lib::charset::Charset;
+use nutek_encode_lib::compression::CompressionFormat;
+use nutek_encode_lib::crypto::SymmetricCipher;
+use nutek_encode_lib::encoder::{ByteFormat, DigestFormat};
+use nutek_encode_lib::epoch::{self, EpochFormat};
+use nutek_encode_lib::firmware::{self, FirmwareFormat};
+use nutek_encode_lib::hexdump::HexdumpStyle;
+use nutek_encode_lib::id::{IdKind, SnowflakeEpoch};
+use nutek_encode_lib::jwt::{JwtAlgorithm, JwtField};
+use nutek_encode_lib::key::KeyFormat;
+use nutek_encode_lib::pack::PackFormat;
+use nutek_encode_lib::radix::Radix;
+use nutek_encode_lib::saml::SamlBinding;
+use nutek_encode_lib::session_cookie::{CookieDigest, CookieFramework};
+use nutek_encode_lib::unicode::{NormalizationForm, Variant};
+use nutek_encode_lib::viewstate::{self, ViewStateMac};
+use nutek_encode_lib::xor::XorMode;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -54,6 +74,9 @@ pub enum Commands {
         /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Character set of the text, e.g. utf-16le: text is converted to it before encoding and decoded bytes are read in it
+        #[arg(long, value_name = "CHARSET")]
+        input_charset: Option<Charset>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -72,6 +95,9 @@ pub enum Commands {
         /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Character set of the text, e.g. utf-16le: text is converted to it before encoding and decoded bytes are read in it
+        #[arg(long, value_name = "CHARSET")]
+        input_charset: Option<Charset>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -90,6 +116,9 @@ pub enum Commands {
         /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Character set of the text, e.g. utf-16le: text is converted to it before encoding and decoded bytes are read in it
+        #[arg(long, value_name = "CHARSET")]
+        input_charset: Option<Charset>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -108,10 +137,210 @@ pub enum Commands {
         /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Character set of the text, e.g. utf-16le: text is converted to it before encoding and decoded bytes are read in it
+        #[arg(long, value_name = "CHARSET")]
+        input_charset: Option<Charset>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
+    /// Hexdump in the xxd or hexdump -C layout, or parse a dump from xxd, hexdump, Wireshark or tcpdump back into bytes
+    Hexdump {
+        /// File to dump, or with the dump to parse
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Parse a dump back into bytes, like xxd -r
+        #[arg(short, long, default_value_t = false)]
+        reverse: bool,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to dump, or the dump to parse
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Layout: xxd or canonical (hexdump -C)
+        #[arg(long, value_name = "STYLE", default_value = "xxd")]
+        style: HexdumpStyle,
+        /// Bytes per line
+        #[arg(short, long, value_name = "COLUMNS", default_value_t = 16)]
+        columns: usize,
+        /// Bytes per group, 2 for xxd and 8 for canonical by default, 0 for no grouping
+        #[arg(short, long, value_name = "BYTES")]
+        group: Option<usize>,
+        /// Color NUL, printable, control and non-ASCII bytes
+        #[arg(long, default_value_t = false)]
+        color: bool,
+        /// Format of the input to dump: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the parsed bytes: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Convert firmware images between Intel HEX, Motorola S-records, TI-TXT and raw binary, verifying every checksum
+    Firmware {
+        /// File with the firmware image
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String with the firmware image
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Format of the image: ihex, srec, ti-txt or bin; detected from the first character by default
+        #[arg(long, value_name = "FORMAT")]
+        from: Option<FirmwareFormat>,
+        /// Format to convert to: ihex, srec, ti-txt or bin
+        #[arg(short, long, value_name = "FORMAT", default_value = "bin")]
+        to: FirmwareFormat,
+        /// Data bytes per Intel HEX or S-record record
+        #[arg(short = 'l', long, value_name = "BYTES", default_value_t = 16)]
+        record_length: usize,
+        /// Hex byte filling the gaps between segments of a binary
+        #[arg(long, value_name = "HEX", default_value = "ff", value_parser = firmware::parse_fill)]
+        fill: u8,
+        /// Hex address a binary image is loaded at
+        #[arg(long, value_name = "HEX", default_value = "0", value_parser = firmware::parse_address)]
+        base_address: u32,
+        /// List the segments and start address instead of converting
+        #[arg(long, default_value_t = false)]
+        info: bool,
+        /// Format of the input: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of a binary result: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Write bytes as a C, Python, Rust, Go, Java, C#, PowerShell, NASM or JavaScript array literal, or parse one back into bytes
+    Array {
+        /// File with the bytes, or with the literal to parse
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Parse a literal back into bytes
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String with the bytes, or the literal to parse
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Language: c, c-string, python, rust, go, java, csharp, powershell, nasm or javascript
+        #[arg(long, value_name = "FORMAT", default_value = "c")]
+        format: ArrayFormat,
+        /// Name of the variable or label
+        #[arg(short, long, value_name = "NAME", default_value = "buf")]
+        name: String,
+        /// Bytes per line
+        #[arg(short, long, value_name = "BYTES", default_value_t = 16)]
+        width: usize,
+        /// Format of the input bytes: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the parsed bytes: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Parse PEM or DER/BER into an ASN.1 tree with tag names and OIDs resolved, strip PEM armor, or re-encode DER as PEM
+    Asn1 {
+        /// File with PEM or DER
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String with PEM or DER
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Show the offset and length of every element
+        #[arg(long, default_value_t = false)]
+        offsets: bool,
+        /// Write the DER as PEM with this label, such as CERTIFICATE or PUBLIC KEY
+        #[arg(long, value_name = "LABEL")]
+        pem: Option<String>,
+        /// Write the DER without the PEM armor
+        #[arg(long, default_value_t = false, conflicts_with = "pem")]
+        der: bool,
+        /// Format of DER input: raw, hex, escaped or base64; PEM is detected
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        input_format: ByteFormat,
+        /// Format of the DER written by --der: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Inspect PEM or DER certificates, chains and certificate requests: subject, issuer, validity, SANs, key, extensions, fingerprints and SPKI pins
+    X509 {
+        /// File with the certificates or requests
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String with the certificates or requests
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Only print the pin-sha256 SPKI pin of every certificate
+        #[arg(long, default_value_t = false)]
+        pin: bool,
+        /// Format of DER input: raw, hex, escaped or base64; PEM is detected
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        input_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Convert RSA, EC and Ed25519 keys between PKCS#1, PKCS#8, SEC1, SubjectPublicKeyInfo, JWK, JWKS and OpenSSH, or print their OpenSSH fingerprints and JWK thumbprints
+    Key {
+        /// File with the keys
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String with the keys
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Format to convert to: pkcs1, pkcs8, sec1, spki, jwk, jwks or openssh; without it the keys and their fingerprints are printed
+        #[arg(short, long, value_name = "FORMAT")]
+        to: Option<KeyFormat>,
+        /// Write only the public key of private keys
+        #[arg(long, default_value_t = false)]
+        public: bool,
+        /// Write PKCS#1, PKCS#8, SEC1 and SubjectPublicKeyInfo as DER instead of PEM
+        #[arg(long, default_value_t = false)]
+        der: bool,
+        /// Comment of OpenSSH keys
+        #[arg(long, value_name = "COMMENT", default_value = "")]
+        comment: String,
+        /// Format of DER input: raw, hex, escaped or base64; PEM, JWK and OpenSSH are detected
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        input_format: ByteFormat,
+        /// Format of DER output: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Bad characters of exploit payloads: find them, generate the 0x01-0xff test sequence, compare a memory dump against it, and encode around them
+    Badchars {
+        #[command(subcommand)]
+        badchars: BadcharsCommands,
+    },
     /// Rot13 encode or decode
     /// Rot13 is symmetric, so encoding again will decode it.
     /// This is a simple letter substitution cipher that replaces a letter with the 13th letter after it in the alphabet.
@@ -129,6 +358,310 @@ pub enum Commands {
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
+    /// XOR encode or decode with a repeating, incrementing or rolling key
+    Xor {
+        /// File to encode or decode
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Decode the input
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to encode or decode
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Key to XOR the input with
+        #[arg(
+            short,
+            long,
+            value_name = "KEY",
+            required_unless_present = "bruteforce"
+        )]
+        key: Option<String>,
+        /// Format of the key: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        key_format: ByteFormat,
+        /// Format of the input: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the result: text, hex, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        output_format: ByteFormat,
+        /// How the key is applied: repeating, incrementing or rolling
+        #[arg(short, long, value_name = "MODE", default_value = "repeating")]
+        mode: XorMode,
+        /// Recover the key by trying all single-byte keys, or repeating keys
+        /// up to --max-key-length, ranked by how much the result looks like English
+        #[arg(short, long, default_value_t = false, conflicts_with = "key")]
+        bruteforce: bool,
+        /// Longest repeating key to try when brute forcing
+        #[arg(long, value_name = "LENGTH", default_value_t = 1)]
+        max_key_length: usize,
+        /// Number of brute force candidates to show
+        #[arg(long, value_name = "COUNT", default_value_t = 5)]
+        top: usize,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Classical ciphers: ROT-N, ROT47, ROT18, Atbash, Vigenère, Beaufort, affine and rail fence
+    Cipher {
+        #[command(subcommand)]
+        cipher: CipherCommands,
+    },
+    /// JSON Web Tokens: decode, sign, verify and attack variants for authorised testing
+    Jwt {
+        #[command(subcommand)]
+        jwt: JwtCommands,
+    },
+    /// SAML messages: decode SAMLRequest and SAMLResponse values and encode edited XML
+    Saml {
+        #[command(subcommand)]
+        saml: SamlCommands,
+    },
+    /// Signed session cookies: decode, verify, sign and brute force Flask, Django, Express and Rails cookies
+    SessionCookie {
+        #[command(subcommand)]
+        session_cookie: SessionCookieCommands,
+    },
+    /// ASP.NET ViewState and EventValidation: decode the object tree, detect the MAC and verify or compute it with a known key
+    Viewstate {
+        /// File with the value or form body
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Value or form body
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// The validationKey of the machineKey element, in hex, to verify the MAC
+        #[arg(short, long, value_name = "HEX")]
+        key: Option<String>,
+        /// MAC algorithm: md5, sha1, sha256, sha384 or sha512, guessed from the MAC length by default
+        #[arg(short, long, value_name = "ALGORITHM")]
+        algorithm: Option<ViewStateMac>,
+        /// The __VIEWSTATEGENERATOR value of the page, in hex
+        #[arg(short, long, value_name = "HEX", value_parser = viewstate::parse_generator)]
+        generator: Option<u32>,
+        /// The ViewStateUserKey of the page, often the session ID
+        #[arg(long, value_name = "KEY")]
+        user_key: Option<String>,
+        /// Replace the MAC with one computed with --key and write the signed ViewState
+        #[arg(long, default_value_t = false, requires = "key")]
+        sign: bool,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// PowerShell -EncodedCommand: encode a script, or decode one with nested Base64 layers unwrapped
+    Powershell {
+        /// File to encode or decode
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Decode an -EncodedCommand argument, a command line holding one, or a script
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to encode or decode
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Undo string concatenation, -f format strings, [char] casts and backticks when decoding
+        #[arg(long, default_value_t = false, requires = "decode")]
+        deobfuscate: bool,
+        /// Write a whole powershell command line when encoding
+        #[arg(short, long, default_value_t = false, conflicts_with = "decode")]
+        command: bool,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Transcode text between character sets: UTF-8, UTF-16, UTF-32, Latin-1, Windows-125x, ISO-8859-x, EBCDIC, Shift_JIS, GB18030 and more
+    Charset {
+        /// File to transcode
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to transcode
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Character set of the input, detected from a byte order mark by default and UTF-8 without one
+        #[arg(long, value_name = "CHARSET")]
+        from: Option<Charset>,
+        /// Character set of the result
+        #[arg(long, value_name = "CHARSET", default_value = "utf-8")]
+        to: Charset,
+        /// Write a byte order mark, for UTF-8, UTF-16 and UTF-32 only
+        #[arg(long, default_value_t = false)]
+        bom: bool,
+        /// Fail on malformed input or characters the target cannot represent, instead of replacing them
+        #[arg(long, default_value_t = false)]
+        strict: bool,
+        /// Format of the input: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the result: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Unicode: list code points, normalize, reveal invisible and bidi characters, confusable skeletons and look-alike variants
+    Unicode {
+        #[command(subcommand)]
+        unicode: UnicodeCommands,
+    },
+    /// Encrypt with AES, ChaCha20-Poly1305, RC4, DES or 3DES
+    Encrypt {
+        /// File to encrypt
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to encrypt
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Cipher: aes-128|192|256-ecb|cbc|ctr|gcm, chacha20-poly1305, rc4, des-ecb|cbc or 3des-ecb|cbc
+        #[arg(short, long, value_name = "CIPHER")]
+        cipher: SymmetricCipher,
+        /// Secret key
+        #[arg(short, long, value_name = "KEY")]
+        key: String,
+        /// Format of the key: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        key_format: ByteFormat,
+        /// IV for CBC and CTR, or nonce for GCM and ChaCha20-Poly1305
+        #[arg(long, visible_alias = "nonce", value_name = "IV")]
+        iv: Option<String>,
+        /// Format of the IV or nonce: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        iv_format: ByteFormat,
+        /// Additional authenticated data for GCM and ChaCha20-Poly1305
+        #[arg(long, value_name = "AAD")]
+        aad: Option<String>,
+        /// Format of the additional authenticated data: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        aad_format: ByteFormat,
+        /// Disable PKCS#7 padding for ECB and CBC modes
+        #[arg(long, default_value_t = false)]
+        no_padding: bool,
+        /// Format of the input: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the result: text, hex, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "base64")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Decrypt with AES, ChaCha20-Poly1305, RC4, DES or 3DES
+    Decrypt {
+        /// File to decrypt
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to decrypt
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Cipher: aes-128|192|256-ecb|cbc|ctr|gcm, chacha20-poly1305, rc4, des-ecb|cbc or 3des-ecb|cbc
+        #[arg(short, long, value_name = "CIPHER")]
+        cipher: SymmetricCipher,
+        /// Secret key
+        #[arg(short, long, value_name = "KEY")]
+        key: String,
+        /// Format of the key: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        key_format: ByteFormat,
+        /// IV for CBC and CTR, or nonce for GCM and ChaCha20-Poly1305
+        #[arg(long, visible_alias = "nonce", value_name = "IV")]
+        iv: Option<String>,
+        /// Format of the IV or nonce: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        iv_format: ByteFormat,
+        /// Additional authenticated data for GCM and ChaCha20-Poly1305
+        #[arg(long, value_name = "AAD")]
+        aad: Option<String>,
+        /// Format of the additional authenticated data: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        aad_format: ByteFormat,
+        /// Disable PKCS#7 padding for ECB and CBC modes
+        #[arg(long, default_value_t = false)]
+        no_padding: bool,
+        /// Format of the input: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "base64")]
+        input_format: ByteFormat,
+        /// Format of the result: text, hex, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Compress with gzip, zlib, raw deflate, bzip2, xz, lzma, zstd, lz4 or brotli
+    Compress {
+        /// File containing the data to compress
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Data to compress
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Compression format: gzip, zlib, deflate, bzip2, xz, lzma, zstd, lz4 or brotli
+        #[arg(short, long, value_name = "FORMAT", default_value = "gzip")]
+        algorithm: CompressionFormat,
+        /// Compression level: 0-9 for gzip, zlib, deflate, xz and lzma, 1-9 for bzip2, 1-22 for zstd and 0-11 for brotli
+        #[arg(short, long, value_name = "LEVEL")]
+        level: Option<u32>,
+        /// Format of the input: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the result: hex, escaped, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "base64")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Decompress gzip, zlib, raw deflate, bzip2, xz, lzma, zstd, lz4 or brotli data
+    Decompress {
+        /// File containing the compressed data
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Compressed data
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Compression format: gzip, zlib, deflate, bzip2, xz, lzma, zstd, lz4 or brotli; detected from magic bytes by default
+        #[arg(short, long, value_name = "FORMAT")]
+        algorithm: Option<CompressionFormat>,
+        /// Maximum size of the decompressed data in bytes, to stop decompression bombs
+        #[arg(long, value_name = "BYTES", default_value_t = 100 * 1024 * 1024)]
+        max_size: usize,
+        /// Format of the input: hex, escaped, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "base64")]
+        input_format: ByteFormat,
+        /// Format of the result: text, hex, escaped, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
     /// Binary encode or decode
     Binary {
         /// File to encode or decode
@@ -220,6 +753,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -235,6 +777,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -250,6 +801,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -265,6 +825,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -280,6 +849,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -295,6 +873,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -310,6 +897,15 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
@@ -325,186 +921,873 @@ pub enum Commands {
         /// String to hash
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Digest format: hex, upper-hex, base64, base64url, colon or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        format: DigestFormat,
+        /// Truncate the digest to its leading N bits
+        #[arg(long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Output a Subresource Integrity string, e.g. sha256-<base64>
+        #[arg(long, default_value_t = false, conflicts_with_all = ["format", "bits"])]
+        sri: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert integer to hex
-    IntegerToHex {
-        /// File containing the integer to convert
+    /// Convert integers of any size between bases, with two's complement and digit grouping
+    Convert {
+        /// File containing the integers to convert, one per line
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
         /// Integer to convert
+        #[arg(short, long, value_name = "STRING", allow_negative_numbers = true)]
+        string: Option<String>,
+        /// Input base: bin, oct, dec, hex or 2 to 36, detected from a 0x, 0o or 0b prefix by default
+        #[arg(long, value_name = "RADIX")]
+        from: Option<Radix>,
+        /// Output base: bin, oct, dec, hex or 2 to 36
+        #[arg(short, long, value_name = "RADIX")]
+        to: Radix,
+        /// Bit width: negative values are written in two's complement and output is zero-padded
+        #[arg(short, long, value_name = "BITS")]
+        bits: Option<usize>,
+        /// Read the input as a two's complement value of --bits width
+        #[arg(long, default_value_t = false, requires = "bits")]
+        signed: bool,
+        /// Group digits, e.g. 4 for nibbles in binary or 2 for bytes in hex
+        #[arg(short, long, value_name = "DIGITS")]
+        group: Option<usize>,
+        /// Separator between digit groups
+        #[arg(long, value_name = "SEPARATOR", default_value = " ")]
+        separator: String,
+        /// Add the 0x, 0o or 0b prefix
+        #[arg(short, long, default_value_t = false)]
+        prefix: bool,
+        /// Write digits above 9 in uppercase
+        #[arg(short, long, default_value_t = false)]
+        uppercase: bool,
+        /// Pad with zeros to at least this many digits
+        #[arg(long, value_name = "DIGITS", default_value_t = 0)]
+        pad: usize,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Write an IP address in alternate notations (decimal, hex, octal, 127.1, ::ffff:...) or expand a CIDR range
+    Ip {
+        /// File containing IP addresses or CIDR ranges, one per line
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// IP address in any notation, or a CIDR range like 10.0.0.0/24 to list its addresses
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Prefix each notation with its name
+        #[arg(short, long, default_value_t = false)]
+        labels: bool,
+        /// Maximum number of addresses a CIDR range may expand to
+        #[arg(long, value_name = "COUNT", default_value_t = 65536)]
+        limit: u128,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert integer to octal
-    IntegerToOctal {
-        /// File containing the integer to convert
+    /// Convert timestamps between dates and Unix, FILETIME, .NET ticks, Cocoa, WebKit, GPS, DOS or ObjectId epochs
+    Time {
+        /// File containing timestamps or dates, one per line
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Integer to convert
+        /// Timestamp (decimal or 0x hex) to convert to a date, or a date to convert to timestamps
+        #[arg(short, long, value_name = "STRING", allow_negative_numbers = true)]
+        string: Option<String>,
+        /// Epoch format: unix, unix-ms, unix-us, unix-ns, filetime, ticks, webkit, cocoa, gps, dos or objectid; detected by default
+        #[arg(short, long, value_name = "FORMAT")]
+        epoch: Option<EpochFormat>,
+        /// UTC offset for written dates and for dates read without one, e.g. +02:00
+        #[arg(long, value_name = "OFFSET", default_value = "Z", allow_hyphen_values = true, value_parser = epoch::parse_offset)]
+        offset: FixedOffset,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// UUID, ULID, ObjectId and Snowflake IDs: decode fields, generate and predict next values
+    Id {
+        #[command(subcommand)]
+        id: IdCommands,
+    },
+    /// Pack integers and floats into bytes with a struct-like format, e.g. <IhQd
+    Pack {
+        /// File containing the values, separated by whitespace or commas
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Values to pack, separated by whitespace or commas
+        #[arg(short, long, value_name = "STRING", allow_hyphen_values = true)]
+        string: Option<String>,
+        /// Byte order (<, >, !, @ or =) and type codes: x b B h H i I l L q Q o O e f d, with optional repeat counts
+        #[arg(long, value_name = "FORMAT", allow_hyphen_values = true)]
+        format: PackFormat,
+        /// Format of the result: hex, escaped, base64 or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        output_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Unpack bytes into integers and floats with a struct-like format, e.g. <IhQd
+    Unpack {
+        /// File containing the bytes to unpack
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Bytes to unpack
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Byte order (<, >, !, @ or =) and type codes: x b B h H i I l L q Q o O e f d, with optional repeat counts
+        #[arg(long, value_name = "FORMAT", allow_hyphen_values = true)]
+        format: PackFormat,
+        /// Format of the input: hex, escaped, base64, text or raw
+        #[arg(long, value_name = "FORMAT", default_value = "hex")]
+        input_format: ByteFormat,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert integer to binary
-    IntegerToBinary {
-        /// File containing the integer to convert
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CipherCommands {
+    /// ROT-N over ASCII letters or a custom alphabet, or Caesar brute force
+    Rot {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Integer to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Decode the input
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Number of places to rotate by
+        #[arg(
+            short = 'n',
+            long,
+            value_name = "SHIFT",
+            default_value_t = 13,
+            allow_negative_numbers = true
+        )]
+        shift: i64,
+        /// Rotate over this alphabet instead of the ASCII letters
+        #[arg(short, long, value_name = "ALPHABET")]
+        alphabet: Option<String>,
+        /// List all 25 Caesar shifts ranked by how much the result looks like English
+        #[arg(short, long, default_value_t = false, conflicts_with_all = ["shift", "alphabet", "decode"])]
+        bruteforce: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert hex to integer
-    HexToInteger {
-        /// File containing the hex to convert
+    /// ROT47, rotates all printable ASCII characters, encoding again decodes it
+    Rot47 {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Hex to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert hex to binary
-    HexToBinary {
-        /// File containing the hex to convert
+    /// ROT18, ROT13 for letters and ROT5 for digits, encoding again decodes it
+    Rot18 {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Hex to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert hex to octal
-    HexToOctal {
-        /// File containing the hex to convert
+    /// Atbash, maps a to z, b to y and so on, encoding again decodes it
+    Atbash {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Hex to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert octal to integer
-    OctalToInteger {
-        /// File containing the octal to convert
+    /// Vigenère cipher with a letter key
+    Vigenere {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Octal to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Decode the input
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Key, only its ASCII letters are used
+        #[arg(short, long, value_name = "KEY")]
+        key: String,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert octal to hex
-    OctalToHex {
-        /// File containing the octal to convert
+    /// Beaufort cipher with a letter key, encoding again decodes it
+    Beaufort {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Octal to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Key, only its ASCII letters are used
+        #[arg(short, long, value_name = "KEY")]
+        key: String,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert octal to binary
-    OctalToBinary {
-        /// File containing the octal to convert
+    /// Affine cipher, maps each letter x to (a * x + b) mod 26
+    Affine {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Octal to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Decode the input
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Multiplier, must be coprime to 26
+        #[arg(short, long, value_name = "A")]
+        a: i64,
+        /// Shift
+        #[arg(short, long, value_name = "B")]
+        b: i64,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert binary to hex
-    BinaryToHex {
-        /// File containing the binary to convert
+    /// Rail fence transposition cipher
+    RailFence {
+        /// File to encode or decode
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Binary to convert
+        /// String to encode or decode
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Decode the input
+        #[arg(short, long, default_value_t = false)]
+        decode: bool,
+        /// Number of rails
+        #[arg(short, long, value_name = "RAILS", default_value_t = 3)]
+        rails: usize,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert binary to octal
-    BinaryToOctal {
-        /// File containing the binary to convert
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JwtCommands {
+    /// Pretty-print the header and claims, with exp, iat and nbf as dates
+    Decode {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Sign JSON claims into a token
+    Sign {
+        /// File with the JSON claims
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// JSON claims
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Signature algorithm: none, HS256|384|512, RS256|384|512, ES256|384|512 or PS256|384|512
+        #[arg(short, long, value_name = "ALG", default_value = "HS256")]
+        alg: JwtAlgorithm,
+        /// Secret for HS algorithms
+        #[arg(long, value_name = "SECRET")]
+        secret: Option<String>,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// PEM private key file for RS, ES and PS algorithms
+        #[arg(short, long, value_name = "KEY_FILE", conflicts_with = "secret")]
+        key: Option<PathBuf>,
+        /// Header field to set, e.g. kid=../../dev/null or jku=https://..., the value is parsed as JSON when possible
+        #[arg(long, value_name = "NAME=VALUE")]
+        header: Vec<JwtField>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Verify the signature with the algorithm from the token header
+    Verify {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Secret for HS algorithms
+        #[arg(long, value_name = "SECRET")]
+        secret: Option<String>,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// PEM public or private key file for RS, ES and PS algorithms
+        #[arg(short, long, value_name = "KEY_FILE", conflicts_with = "secret")]
+        key: Option<PathBuf>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Brute force the secret of an HS256, HS384 or HS512 token with a wordlist
+    Crack {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Wordlist file with one candidate secret per line
+        #[arg(short, long, value_name = "WORDLIST")]
+        wordlist: PathBuf,
+        /// Continue after the first matching secret and report all of them
+        #[arg(short, long, default_value_t = false)]
+        all: bool,
+        /// Number of threads, all CPU cores by default
+        #[arg(short, long, value_name = "THREADS", default_value_t = 0)]
+        threads: usize,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Unsigned alg=none variants in several spellings
+    None {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Claim to set, as name=value, the value is parsed as JSON when possible
+        #[arg(long, value_name = "NAME=VALUE")]
+        claim: Vec<JwtField>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Change header fields and claims, re-signing with --alg or keeping the original signature
+    Tamper {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Header field to set, e.g. kid=../../dev/null or jku=https://..., the value is parsed as JSON when possible
+        #[arg(long, value_name = "NAME=VALUE")]
+        header: Vec<JwtField>,
+        /// Claim to set, as name=value, the value is parsed as JSON when possible
+        #[arg(long, value_name = "NAME=VALUE")]
+        claim: Vec<JwtField>,
+        /// Re-sign with this algorithm: none, HS256|384|512, RS256|384|512, ES256|384|512 or PS256|384|512
+        #[arg(short, long, value_name = "ALG")]
+        alg: Option<JwtAlgorithm>,
+        /// Secret for HS algorithms
+        #[arg(long, value_name = "SECRET")]
+        secret: Option<String>,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// PEM private key file for RS, ES and PS algorithms
+        #[arg(short, long, value_name = "KEY_FILE", conflicts_with = "secret")]
+        key: Option<PathBuf>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// HS256 variants signed with the server's public key PEM as the HMAC secret
+    Confusion {
+        /// File with the token
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Token
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// PEM public key file of the server
+        #[arg(short, long, value_name = "KEY_FILE")]
+        public_key: PathBuf,
+        /// Claim to set, as name=value, the value is parsed as JSON when possible
+        #[arg(long, value_name = "NAME=VALUE")]
+        claim: Vec<JwtField>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IdCommands {
+    /// Explain the fields of UUIDs, ULIDs, ObjectIds and Snowflakes, such as embedded timestamps and MAC addresses
+    Decode {
+        /// File with IDs, one per line
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// ID to decode
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Snowflake epoch: twitter, discord or Unix milliseconds; both twitter and discord by default
+        #[arg(long, value_name = "EPOCH")]
+        snowflake: Option<SnowflakeEpoch>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Generate new IDs stamped with the current time
+    Generate {
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Kind of ID: uuid-v1, uuid-v4, uuid-v6, uuid-v7, ulid, objectid or snowflake
+        #[arg(short, long, value_name = "KIND", default_value = "uuid-v4")]
+        kind: IdKind,
+        /// Number of IDs to generate
+        #[arg(short = 'n', long, value_name = "COUNT", default_value_t = 1)]
+        count: usize,
+        /// Snowflake epoch: twitter, discord or Unix milliseconds
+        #[arg(long, value_name = "EPOCH", default_value = "twitter")]
+        snowflake: SnowflakeEpoch,
+    },
+    /// Predict the IDs a sequential generator makes after the given one, for authorised enumeration testing
+    Next {
+        /// File with the ID
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// ID to continue from
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Number of IDs to predict
+        #[arg(short = 'n', long, value_name = "COUNT", default_value_t = 10)]
+        count: usize,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SamlCommands {
+    /// Decode a SAMLRequest or SAMLResponse from the redirect or POST binding and show its key fields and XML
+    Decode {
+        /// File with the value, query string, form body or URL
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Value, query string, form body or URL
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Write only the XML exactly as sent, ready to edit and encode again
+        #[arg(long, default_value_t = false)]
+        xml: bool,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Encode XML as a SAMLRequest or SAMLResponse value
+    Encode {
+        /// File with the XML
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// XML to encode
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Binding: redirect (deflate, base64 and URL encoding) or post (base64)
+        #[arg(short, long, value_name = "BINDING", default_value = "redirect")]
+        binding: SamlBinding,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SessionCookieCommands {
+    /// Show the framework, payload, timestamp and signature, and verify or decrypt with a secret
+    Decode {
+        /// File with the cookie
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Cookie value or name=value
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Secret key, needed to decrypt Rails cookies
+        #[arg(long, value_name = "SECRET")]
+        secret: Option<String>,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// Salt, the framework's session cookie salt by default
+        #[arg(long, value_name = "SALT")]
+        salt: Option<String>,
+        /// Digest: sha1 or sha256, detected by default
+        #[arg(short, long, value_name = "DIGEST")]
+        digest: Option<CookieDigest>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Sign a payload, usually JSON, into a cookie, or encrypt it for Rails
+    Sign {
+        /// File with the payload
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Payload: JSON for Flask, Django and Rails, the session ID for Express
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Framework: flask, django, express or rails
+        #[arg(long, value_name = "FRAMEWORK")]
+        framework: CookieFramework,
+        /// Secret key
+        #[arg(long, value_name = "SECRET")]
+        secret: String,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// Salt, the framework's session cookie salt by default
+        #[arg(long, value_name = "SALT")]
+        salt: Option<String>,
+        /// Digest: sha1 or sha256, the framework's default by default
+        #[arg(short, long, value_name = "DIGEST")]
+        digest: Option<CookieDigest>,
+        /// Timestamp in Unix seconds for Flask and Django, now by default
+        #[arg(long, value_name = "SECONDS")]
+        timestamp: Option<i64>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Verify the signature, or the encryption for Rails, with a secret
+    Verify {
+        /// File with the cookie
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Cookie value or name=value
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Secret key
+        #[arg(long, value_name = "SECRET")]
+        secret: String,
+        /// Format of the secret: text, hex or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        secret_format: ByteFormat,
+        /// Salt, the framework's session cookie salt by default
+        #[arg(long, value_name = "SALT")]
+        salt: Option<String>,
+        /// Digest: sha1 or sha256, detected by default
+        #[arg(short, long, value_name = "DIGEST")]
+        digest: Option<CookieDigest>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Brute force the secret key with a wordlist
+    Crack {
+        /// File with the cookie
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Cookie value or name=value
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Wordlist file with one candidate secret per line
+        #[arg(short, long, value_name = "WORDLIST")]
+        wordlist: PathBuf,
+        /// Salt, the framework's session cookie salt by default
+        #[arg(long, value_name = "SALT")]
+        salt: Option<String>,
+        /// Digest: sha1 or sha256, detected by default
+        #[arg(short, long, value_name = "DIGEST")]
+        digest: Option<CookieDigest>,
+        /// Continue after the first matching secret and report all of them
+        #[arg(short, long, default_value_t = false)]
+        all: bool,
+        /// Number of threads, all CPU cores by default
+        #[arg(short, long, value_name = "THREADS", default_value_t = 0)]
+        threads: usize,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UnicodeCommands {
+    /// List every code point with its category, UTF-8 bytes and name
+    Inspect {
+        /// File with the text
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Text to inspect
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Normalize to NFC, NFD, NFKC or NFKD
+    Normalize {
+        /// File with the text
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Text to normalize
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Normalization form: nfc, nfd, nfkc or nfkd
+        #[arg(short = 'n', long, value_name = "FORM", default_value = "nfc")]
+        form: NormalizationForm,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Show zero-width, bidi control and tag characters as <U+XXXX LABEL> and warn about unterminated bidi controls (Trojan Source)
+    Reveal {
+        /// File with the text
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Text to reveal
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Map to the UTS #39 confusable skeleton, the same for strings that look alike
+    Skeleton {
+        /// File with the text
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Text to map
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Another text to check for being confusable with the input
+        #[arg(short, long, value_name = "STRING")]
+        compare: Option<String>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Rewrite ASCII in fullwidth or mathematical alphanumeric look-alikes, in every style by default
+    Variant {
+        /// File with the text
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Text to rewrite
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Style: fullwidth, bold, italic, bold-italic, script, bold-script, fraktur, double-struck, bold-fraktur, sans-serif, sans-serif-bold, sans-serif-italic, sans-serif-bold-italic or monospace
+        #[arg(long, value_name = "STYLE")]
+        style: Option<Variant>,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BadcharsCommands {
+    /// List the offsets of bad characters in the input, exiting with 1 when any is found
+    Find {
+        /// File to search
+        #[arg(short, long, value_name = "FILE")]
+        file: Option<PathBuf>,
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// String to search
+        #[arg(short, long, value_name = "STRING")]
+        string: Option<String>,
+        /// Bad characters: \x00\x0a, 0x00,0x0a or 000a
+        #[arg(short, long, value_name = "BYTES")]
+        bad: String,
+        /// Format of the input: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Standard input
+        #[arg(default_value = "")]
+        stdin: MaybeStdin<String>,
+    },
+    /// Generate the bytes 0x01 to 0xff without the known bad characters
+    Generate {
+        /// Output file to write the result
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<PathBuf>,
+        /// Known bad characters to leave out
+        #[arg(short, long, value_name = "BYTES")]
+        bad: Option<String>,
+        /// Format of the sequence: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "escaped")]
+        output_format: ByteFormat,
+    },
+    /// Compare a memory dump against the test sequence to find mangled bytes
+    Compare {
+        /// File with the memory dump
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Binary to convert
+        /// String with the memory dump
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Bad characters left out of the test sequence
+        #[arg(short, long, value_name = "BYTES")]
+        bad: Option<String>,
+        /// Format of the dump: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "raw")]
+        input_format: ByteFormat,
+        /// The dump is a hex dump, as written by xxd, hexdump -C or WinDbg's db
+        #[arg(long, default_value_t = false)]
+        hexdump: bool,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
     },
-    /// Convert binary to integer
-    BinaryToInteger {
-        /// File containing the binary to convert
+    /// Encode a payload so that it avoids the bad characters, with XOR, alphanumeric or hex
+    Encode {
+        /// File with the payload
         #[arg(short, long, value_name = "FILE")]
         file: Option<PathBuf>,
         /// Output file to write the result
         #[arg(short, long, value_name = "OUTPUT_FILE")]
         output: Option<PathBuf>,
-        /// Binary to convert
+        /// String with the payload
         #[arg(short, long, value_name = "STRING")]
         string: Option<String>,
+        /// Bad characters to avoid
+        #[arg(short, long, value_name = "BYTES")]
+        bad: String,
+        /// Encoder: xor, alphanumeric or hex, the first that works by default
+        #[arg(short, long, value_name = "ENCODER")]
+        encoder: Option<BadcharEncoder>,
+        /// Format of the payload: text, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        input_format: ByteFormat,
+        /// Format of the encoded payload: raw, hex, escaped or base64
+        #[arg(long, value_name = "FORMAT", default_value = "escaped")]
+        output_format: ByteFormat,
         /// Standard input
         #[arg(default_value = "")]
         stdin: MaybeStdin<String>,
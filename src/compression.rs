@@ -0,0 +1,278 @@
+//! Compression and decompression of bytes with gzip, zlib, raw deflate, bzip2, xz, lzma,
+//! zstd, lz4 and brotli.
+
+use std::{
+    error::Error,
+    io::{Read, Write},
+    str,
+};
+
+/// Supported compression formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionFormat {
+    /// Deflate with a gzip header and CRC-32, as in `Content-Encoding: gzip`.
+    Gzip,
+    /// Deflate with a zlib header and Adler-32, as in PDF streams and `ViewState`.
+    Zlib,
+    /// Deflate without a header, as in the SAML redirect binding.
+    Deflate,
+    Bzip2,
+    /// The `.xz` container.
+    Xz,
+    /// The legacy `.lzma` (LZMA alone) container.
+    Lzma,
+    Zstd,
+    /// The LZ4 frame format.
+    Lz4,
+    Brotli,
+}
+
+impl CompressionFormat {
+    /// The lowest, highest and default compression level, or `None` if levels are not supported.
+    pub fn levels(&self) -> Option<(u32, u32, u32)> {
+        match self {
+            CompressionFormat::Gzip | CompressionFormat::Zlib | CompressionFormat::Deflate => {
+                Some((0, 9, 6))
+            }
+            CompressionFormat::Bzip2 => Some((1, 9, 9)),
+            CompressionFormat::Xz | CompressionFormat::Lzma => Some((0, 9, 6)),
+            CompressionFormat::Zstd => Some((1, 22, 3)),
+            CompressionFormat::Lz4 => None,
+            CompressionFormat::Brotli => Some((0, 11, 11)),
+        }
+    }
+}
+
+impl str::FromStr for CompressionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gzip" | "gz" => Ok(CompressionFormat::Gzip),
+            "zlib" => Ok(CompressionFormat::Zlib),
+            "deflate" | "raw-deflate" => Ok(CompressionFormat::Deflate),
+            "bzip2" | "bz2" => Ok(CompressionFormat::Bzip2),
+            "xz" => Ok(CompressionFormat::Xz),
+            "lzma" => Ok(CompressionFormat::Lzma),
+            "zstd" | "zst" => Ok(CompressionFormat::Zstd),
+            "lz4" => Ok(CompressionFormat::Lz4),
+            "brotli" | "br" => Ok(CompressionFormat::Brotli),
+            _ => Err(format!(
+                "Unknown compression format '{}', expected one of: gzip, zlib, deflate, bzip2, xz, lzma, zstd, lz4, brotli",
+                s
+            )),
+        }
+    }
+}
+
+/// Detects the compression format from the magic bytes at the start of `data`.
+///
+/// Raw deflate and brotli have no header and are never detected.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::compression::{self, CompressionFormat};
+/// assert_eq!(compression::detect_compression(b"\x1f\x8b\x08\x00"), Some(CompressionFormat::Gzip));
+/// assert_eq!(compression::detect_compression(b"\x78\x9c\xcb\x48"), Some(CompressionFormat::Zlib));
+/// assert_eq!(compression::detect_compression(b"hello"), None);
+/// ```
+pub fn detect_compression(data: &[u8]) -> Option<CompressionFormat> {
+    match data {
+        [0x1f, 0x8b, ..] => Some(CompressionFormat::Gzip),
+        [b'B', b'Z', b'h', ..] => Some(CompressionFormat::Bzip2),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(CompressionFormat::Xz),
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(CompressionFormat::Zstd),
+        [0x04, 0x22, 0x4d, 0x18, ..] => Some(CompressionFormat::Lz4),
+        [0x5d, 0x00, 0x00, ..] => Some(CompressionFormat::Lzma),
+        // A zlib header names deflate and checks itself modulo 31.
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8 && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) =>
+        {
+            Some(CompressionFormat::Zlib)
+        }
+        _ => None,
+    }
+}
+
+/// Compresses bytes at the given level, or at the format's default level.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::compression::{self, CompressionFormat};
+/// let compressed = compression::compress(b"hello hello hello", CompressionFormat::Zlib, Some(9)).unwrap();
+/// assert_eq!(&compressed[..2], b"\x78\xda");
+/// let decompressed = compression::decompress(&compressed, CompressionFormat::Zlib, 1024).unwrap();
+/// assert_eq!(decompressed, b"hello hello hello");
+/// ```
+pub fn compress(
+    data: &[u8],
+    format: CompressionFormat,
+    level: Option<u32>,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let level = match (format.levels(), level) {
+        (None, Some(_)) => {
+            return Err(format!("{:?} does not support compression levels", format).into());
+        }
+        (None, None) => 0,
+        (Some((min, max, _)), Some(level)) if !(min..=max).contains(&level) => {
+            return Err(format!(
+                "Invalid {:?} compression level {}, expected {} to {}",
+                format, level, min, max
+            )
+            .into());
+        }
+        (Some(_), Some(level)) => level,
+        (Some((_, _, default)), None) => default,
+    };
+    let deflate_level = flate2::Compression::new(level);
+    Ok(match format {
+        CompressionFormat::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), deflate_level);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Zlib => {
+            let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), deflate_level);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Deflate => {
+            let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), deflate_level);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Bzip2 => {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::new(level));
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Xz => {
+            let mut encoder = xz2::write::XzEncoder::new(Vec::new(), level);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Lzma => {
+            let options = xz2::stream::LzmaOptions::new_preset(level)?;
+            let stream = xz2::stream::Stream::new_lzma_encoder(&options)?;
+            let mut encoder = xz2::write::XzEncoder::new_stream(Vec::new(), stream);
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Zstd => zstd::stream::encode_all(data, level as i32)?,
+        CompressionFormat::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            encoder.write_all(data)?;
+            encoder.finish()?
+        }
+        CompressionFormat::Brotli => {
+            let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, level, 22);
+            encoder.write_all(data)?;
+            encoder.into_inner()
+        }
+    })
+}
+
+/// Decompresses bytes, failing once the output would exceed `max_size` bytes so that
+/// decompression bombs cannot exhaust memory.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::compression::{self, CompressionFormat};
+/// let bomb = compression::compress(&[0; 100_000], CompressionFormat::Gzip, None).unwrap();
+/// assert!(compression::decompress(&bomb, CompressionFormat::Gzip, 1000).is_err());
+/// ```
+pub fn decompress(
+    data: &[u8],
+    format: CompressionFormat,
+    max_size: usize,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let reader: Box<dyn Read + '_> = match format {
+        CompressionFormat::Gzip => Box::new(flate2::read::MultiGzDecoder::new(data)),
+        CompressionFormat::Zlib => Box::new(flate2::read::ZlibDecoder::new(data)),
+        CompressionFormat::Deflate => Box::new(flate2::read::DeflateDecoder::new(data)),
+        CompressionFormat::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(data)),
+        CompressionFormat::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(data)),
+        CompressionFormat::Lzma => {
+            let stream = xz2::stream::Stream::new_lzma_decoder(u64::MAX)?;
+            Box::new(xz2::read::XzDecoder::new_stream(data, stream))
+        }
+        CompressionFormat::Zstd => Box::new(zstd::stream::read::Decoder::new(data)?),
+        CompressionFormat::Lz4 => Box::new(lz4_flex::frame::FrameDecoder::new(data)),
+        CompressionFormat::Brotli => Box::new(brotli::Decompressor::new(data, 4096)),
+    };
+    let mut output = Vec::new();
+    reader
+        .take((max_size as u64).saturating_add(1))
+        .read_to_end(&mut output)
+        .map_err(|e| format!("Invalid {:?} data: {}", format, e))?;
+    if output.len() > max_size {
+        return Err(format!("Decompressed data exceeds the limit of {} bytes", max_size).into());
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests_compression {
+    use super::*;
+
+    const ALL: [CompressionFormat; 9] = [
+        CompressionFormat::Gzip,
+        CompressionFormat::Zlib,
+        CompressionFormat::Deflate,
+        CompressionFormat::Bzip2,
+        CompressionFormat::Xz,
+        CompressionFormat::Lzma,
+        CompressionFormat::Zstd,
+        CompressionFormat::Lz4,
+        CompressionFormat::Brotli,
+    ];
+
+    #[test]
+    fn it_round_trips_every_format() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+        for format in ALL {
+            let compressed = compress(&data, format, None).unwrap();
+            assert!(compressed.len() < data.len(), "{:?}", format);
+            assert_eq!(decompress(&compressed, format, data.len()).unwrap(), data);
+            let detected = detect_compression(&compressed);
+            match format {
+                CompressionFormat::Deflate | CompressionFormat::Brotli => {
+                    assert_ne!(detected, Some(format))
+                }
+                _ => assert_eq!(detected, Some(format), "{:?}", format),
+            }
+            if let Some((min, max, _)) = format.levels() {
+                for level in [min, max] {
+                    let compressed = compress(&data, format, Some(level)).unwrap();
+                    assert_eq!(decompress(&compressed, format, data.len()).unwrap(), data);
+                }
+                assert!(compress(&data, format, Some(max + 1)).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn it_decompresses_known_data() {
+        // `printf hello | gzip -n` and the raw deflate body inside it.
+        let gzip = hex::decode("1f8b0800000000000003cb48cdc9c9070086a6103605000000").unwrap();
+        assert_eq!(
+            decompress(&gzip, CompressionFormat::Gzip, 5).unwrap(),
+            b"hello"
+        );
+        assert_eq!(
+            decompress(&gzip[10..], CompressionFormat::Deflate, 5).unwrap(),
+            b"hello"
+        );
+        assert!(decompress(&gzip, CompressionFormat::Gzip, 4).is_err());
+
+        // Malformed input
+        assert!(decompress(b"hello", CompressionFormat::Gzip, 100).is_err());
+        assert!(decompress(b"hello", CompressionFormat::Zstd, 100).is_err());
+        assert!(compress(b"hello", CompressionFormat::Lz4, Some(1)).is_err());
+        assert!("zip".parse::<CompressionFormat>().is_err());
+    }
+}
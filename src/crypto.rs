@@ -0,0 +1,613 @@
+//! Symmetric encryption and decryption of bytes with AES, ChaCha20-Poly1305, RC4, DES and 3DES.
+
+use aes::cipher::{
+    BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit, StreamCipher,
+    block_padding::{NoPadding, Pkcs7},
+};
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::{
+    Aes128Gcm, Aes256Gcm, AesGcm,
+    aead::{Aead, Payload, consts::U12},
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use des::{Des, TdesEde2, TdesEde3};
+use std::{error::Error, str};
+
+/// AES-192 in GCM mode, which `aes-gcm` does not name.
+type Aes192Gcm = AesGcm<Aes192, U12>;
+
+/// Block cipher modes of operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockMode {
+    Ecb,
+    Cbc,
+    Ctr,
+    Gcm,
+}
+
+/// Supported symmetric ciphers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymmetricCipher {
+    /// AES with a 16, 24 or 32 byte key, given as the key size in bits.
+    Aes(usize, BlockMode),
+    /// ChaCha20-Poly1305 with a 32 byte key and a 12 byte nonce.
+    ChaCha20Poly1305,
+    /// RC4 with a 1 to 256 byte key.
+    Rc4,
+    /// Single DES with an 8 byte key, in ECB or CBC mode.
+    Des(BlockMode),
+    /// Triple DES with a 16 or 24 byte key, in ECB or CBC mode.
+    TripleDes(BlockMode),
+}
+
+impl str::FromStr for SymmetricCipher {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase();
+        let mode = |m: &str| match m {
+            "ecb" => Some(BlockMode::Ecb),
+            "cbc" => Some(BlockMode::Cbc),
+            "ctr" => Some(BlockMode::Ctr),
+            "gcm" => Some(BlockMode::Gcm),
+            _ => None,
+        };
+        let parts: Vec<&str> = name.split('-').collect();
+        let cipher = match parts.as_slice() {
+            ["aes", bits @ ("128" | "192" | "256"), m] => {
+                mode(m).map(|m| SymmetricCipher::Aes(bits.parse().unwrap_or_default(), m))
+            }
+            ["chacha20", "poly1305"] => Some(SymmetricCipher::ChaCha20Poly1305),
+            ["rc4"] => Some(SymmetricCipher::Rc4),
+            ["des", m @ ("ecb" | "cbc")] => mode(m).map(SymmetricCipher::Des),
+            ["3des" | "des3" | "tdes", m @ ("ecb" | "cbc")] => {
+                mode(m).map(SymmetricCipher::TripleDes)
+            }
+            _ => None,
+        };
+        cipher.ok_or_else(|| {
+            format!(
+                "Unknown cipher '{}', expected one of: aes-128|192|256-ecb|cbc|ctr|gcm, chacha20-poly1305, rc4, des-ecb|cbc, 3des-ecb|cbc",
+                s
+            )
+        })
+    }
+}
+
+/// Key material and options for [`encrypt`] and [`decrypt`].
+#[derive(Debug, Clone, Default)]
+pub struct CipherParams {
+    /// The secret key.
+    pub key: Vec<u8>,
+    /// IV for CBC and CTR, or nonce for GCM and ChaCha20-Poly1305.
+    pub iv: Vec<u8>,
+    /// Additional authenticated data for GCM and ChaCha20-Poly1305.
+    pub aad: Vec<u8>,
+    /// Disables PKCS#7 padding for ECB and CBC, the input must then be a multiple of the block size.
+    pub no_padding: bool,
+}
+
+/// Checks that a key or IV has one of the expected lengths.
+fn check_length(
+    what: &str,
+    cipher: &str,
+    value: &[u8],
+    expected: &[usize],
+) -> Result<(), Box<dyn Error>> {
+    if expected.contains(&value.len()) {
+        return Ok(());
+    }
+    let expected: Vec<String> = expected.iter().map(|l| l.to_string()).collect();
+    Err(format!(
+        "Invalid {} length for {}: expected {} bytes, got {}",
+        what,
+        cipher,
+        expected.join(" or "),
+        value.len()
+    )
+    .into())
+}
+
+/// Encrypts bytes with the given cipher.
+///
+/// GCM and ChaCha20-Poly1305 append the 16 byte authentication tag to the ciphertext.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::crypto::{self, CipherParams};
+/// let params = CipherParams {
+///     key: b"0123456789abcdef".to_vec(),
+///     iv: b"fedcba9876543210".to_vec(),
+///     ..Default::default()
+/// };
+/// let cipher = "aes-128-cbc".parse().unwrap();
+/// let encrypted = crypto::encrypt(cipher, b"hello", &params).unwrap();
+/// assert_eq!(encrypted.len(), 16);
+/// assert_eq!(crypto::decrypt(cipher, &encrypted, &params).unwrap(), b"hello");
+/// ```
+pub fn encrypt(
+    cipher: SymmetricCipher,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    check_params(cipher, params)?;
+    match cipher {
+        SymmetricCipher::Aes(128, BlockMode::Gcm) => {
+            aead_encrypt(&Aes128Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(192, BlockMode::Gcm) => {
+            aead_encrypt(&Aes192Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(_, BlockMode::Gcm) => {
+            aead_encrypt(&Aes256Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(128, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes128>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(192, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes192>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(_, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes256>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(128, mode) => encrypt_block::<Aes128>(mode, data, params),
+        SymmetricCipher::Aes(192, mode) => encrypt_block::<Aes192>(mode, data, params),
+        SymmetricCipher::Aes(_, mode) => encrypt_block::<Aes256>(mode, data, params),
+        SymmetricCipher::ChaCha20Poly1305 => {
+            let aead = ChaCha20Poly1305::new_from_slice(&params.key)?;
+            aead_encrypt(&aead, data, params)
+        }
+        SymmetricCipher::Rc4 => Ok(rc4(&params.key, data)),
+        SymmetricCipher::Des(mode) => encrypt_block::<Des>(mode, data, params),
+        SymmetricCipher::TripleDes(mode) if params.key.len() == 16 => {
+            encrypt_block::<TdesEde2>(mode, data, params)
+        }
+        SymmetricCipher::TripleDes(mode) => encrypt_block::<TdesEde3>(mode, data, params),
+    }
+}
+
+/// Decrypts bytes with the given cipher.
+///
+/// Fails with a padding error when the key or IV is wrong for ECB and CBC, and with an
+/// authentication error when the key, nonce, AAD or data is wrong for GCM and ChaCha20-Poly1305.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::crypto::{self, CipherParams};
+/// let params = CipherParams {
+///     key: vec![0x42; 32],
+///     iv: vec![0x24; 12],
+///     aad: b"header".to_vec(),
+///     ..Default::default()
+/// };
+/// let cipher = "chacha20-poly1305".parse().unwrap();
+/// let mut encrypted = crypto::encrypt(cipher, b"hello", &params).unwrap();
+/// assert_eq!(crypto::decrypt(cipher, &encrypted, &params).unwrap(), b"hello");
+/// encrypted[0] ^= 1;
+/// assert!(crypto::decrypt(cipher, &encrypted, &params).is_err());
+/// ```
+pub fn decrypt(
+    cipher: SymmetricCipher,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    check_params(cipher, params)?;
+    match cipher {
+        SymmetricCipher::Aes(128, BlockMode::Gcm) => {
+            aead_decrypt(&Aes128Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(192, BlockMode::Gcm) => {
+            aead_decrypt(&Aes192Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(_, BlockMode::Gcm) => {
+            aead_decrypt(&Aes256Gcm::new_from_slice(&params.key)?, data, params)
+        }
+        SymmetricCipher::Aes(128, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes128>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(192, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes192>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(_, BlockMode::Ctr) => Ok(apply_keystream(
+            ctr::Ctr128BE::<Aes256>::new_from_slices(&params.key, &params.iv)?,
+            data,
+        )),
+        SymmetricCipher::Aes(128, mode) => decrypt_block::<Aes128>(mode, data, params),
+        SymmetricCipher::Aes(192, mode) => decrypt_block::<Aes192>(mode, data, params),
+        SymmetricCipher::Aes(_, mode) => decrypt_block::<Aes256>(mode, data, params),
+        SymmetricCipher::ChaCha20Poly1305 => {
+            let aead = ChaCha20Poly1305::new_from_slice(&params.key)?;
+            aead_decrypt(&aead, data, params)
+        }
+        SymmetricCipher::Rc4 => Ok(rc4(&params.key, data)),
+        SymmetricCipher::Des(mode) => decrypt_block::<Des>(mode, data, params),
+        SymmetricCipher::TripleDes(mode) if params.key.len() == 16 => {
+            decrypt_block::<TdesEde2>(mode, data, params)
+        }
+        SymmetricCipher::TripleDes(mode) => decrypt_block::<TdesEde3>(mode, data, params),
+    }
+}
+
+/// Validates the key and IV lengths, so errors name the cipher and the expected sizes.
+fn check_params(cipher: SymmetricCipher, params: &CipherParams) -> Result<(), Box<dyn Error>> {
+    let (name, key_lengths, block_size) = match cipher {
+        SymmetricCipher::Aes(bits, _) => (format!("aes-{}", bits), vec![bits / 8], 16),
+        SymmetricCipher::ChaCha20Poly1305 => ("chacha20-poly1305".to_string(), vec![32], 0),
+        SymmetricCipher::Rc4 => {
+            if params.key.is_empty() || params.key.len() > 256 {
+                return Err(format!(
+                    "Invalid key length for rc4: expected 1 to 256 bytes, got {}",
+                    params.key.len()
+                )
+                .into());
+            }
+            return Ok(());
+        }
+        SymmetricCipher::Des(_) => ("des".to_string(), vec![8], 8),
+        SymmetricCipher::TripleDes(_) => ("3des".to_string(), vec![16, 24], 8),
+    };
+    check_length("key", &name, &params.key, &key_lengths)?;
+    match cipher {
+        SymmetricCipher::Aes(_, BlockMode::Gcm) | SymmetricCipher::ChaCha20Poly1305 => {
+            check_length("nonce", &name, &params.iv, &[12])
+        }
+        SymmetricCipher::Aes(_, BlockMode::Cbc | BlockMode::Ctr)
+        | SymmetricCipher::Des(BlockMode::Cbc)
+        | SymmetricCipher::TripleDes(BlockMode::Cbc) => {
+            check_length("IV", &name, &params.iv, &[block_size])
+        }
+        SymmetricCipher::Des(BlockMode::Ctr | BlockMode::Gcm)
+        | SymmetricCipher::TripleDes(BlockMode::Ctr | BlockMode::Gcm) => {
+            Err(format!("{} supports only ECB and CBC modes", name).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Encrypts with a block cipher in ECB or CBC mode.
+fn encrypt_block<C>(
+    mode: BlockMode,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    C: BlockCipher + BlockEncryptMut + BlockDecryptMut + KeyInit + Clone,
+{
+    match mode {
+        BlockMode::Ecb => {
+            let encryptor = ecb::Encryptor::<C>::new_from_slice(&params.key)?;
+            if params.no_padding {
+                check_block_aligned(data, C::block_size())?;
+                Ok(encryptor.encrypt_padded_vec_mut::<NoPadding>(data))
+            } else {
+                Ok(encryptor.encrypt_padded_vec_mut::<Pkcs7>(data))
+            }
+        }
+        BlockMode::Cbc => {
+            let encryptor = cbc::Encryptor::<C>::new_from_slices(&params.key, &params.iv)?;
+            if params.no_padding {
+                check_block_aligned(data, C::block_size())?;
+                Ok(encryptor.encrypt_padded_vec_mut::<NoPadding>(data))
+            } else {
+                Ok(encryptor.encrypt_padded_vec_mut::<Pkcs7>(data))
+            }
+        }
+        BlockMode::Ctr | BlockMode::Gcm => {
+            Err("CTR and GCM modes are only supported with AES".into())
+        }
+    }
+}
+
+/// Decrypts with a block cipher in ECB or CBC mode.
+fn decrypt_block<C>(
+    mode: BlockMode,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    C: BlockCipher + BlockEncryptMut + BlockDecryptMut + KeyInit + Clone,
+{
+    let padding_error = |_| -> Box<dyn Error> {
+        "Invalid padding: wrong key or IV, corrupted data, or the data is not PKCS#7 padded".into()
+    };
+    match mode {
+        BlockMode::Ecb => {
+            check_block_aligned(data, C::block_size())?;
+            let decryptor = ecb::Decryptor::<C>::new_from_slice(&params.key)?;
+            if params.no_padding {
+                decryptor
+                    .decrypt_padded_vec_mut::<NoPadding>(data)
+                    .map_err(padding_error)
+            } else {
+                decryptor
+                    .decrypt_padded_vec_mut::<Pkcs7>(data)
+                    .map_err(padding_error)
+            }
+        }
+        BlockMode::Cbc => {
+            check_block_aligned(data, C::block_size())?;
+            let decryptor = cbc::Decryptor::<C>::new_from_slices(&params.key, &params.iv)?;
+            if params.no_padding {
+                decryptor
+                    .decrypt_padded_vec_mut::<NoPadding>(data)
+                    .map_err(padding_error)
+            } else {
+                decryptor
+                    .decrypt_padded_vec_mut::<Pkcs7>(data)
+                    .map_err(padding_error)
+            }
+        }
+        BlockMode::Ctr | BlockMode::Gcm => {
+            Err("CTR and GCM modes are only supported with AES".into())
+        }
+    }
+}
+
+/// Checks that the data is a whole number of blocks.
+fn check_block_aligned(data: &[u8], block_size: usize) -> Result<(), Box<dyn Error>> {
+    if data.is_empty() || !data.len().is_multiple_of(block_size) {
+        return Err(format!(
+            "Invalid data length {}: expected a non-empty multiple of the {} byte block size",
+            data.len(),
+            block_size
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Applies a stream cipher keystream, which both encrypts and decrypts.
+fn apply_keystream(mut cipher: impl StreamCipher, data: &[u8]) -> Vec<u8> {
+    let mut buffer = data.to_vec();
+    cipher.apply_keystream(&mut buffer);
+    buffer
+}
+
+/// Encrypts with an AEAD cipher, appending the tag.
+fn aead_encrypt<A: Aead>(
+    aead: &A,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    aead.encrypt(
+        params.iv.as_slice().into(),
+        Payload {
+            msg: data,
+            aad: &params.aad,
+        },
+    )
+    .map_err(|_| "Encryption failed".into())
+}
+
+/// Decrypts with an AEAD cipher and verifies the appended tag.
+fn aead_decrypt<A: Aead>(
+    aead: &A,
+    data: &[u8],
+    params: &CipherParams,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    aead.decrypt(
+        params.iv.as_slice().into(),
+        Payload {
+            msg: data,
+            aad: &params.aad,
+        },
+    )
+    .map_err(|_| {
+        "Authentication tag mismatch: wrong key, nonce or AAD, or the data was modified".into()
+    })
+}
+
+/// Applies the RC4 keystream, which both encrypts and decrypts.
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state: Vec<u8> = (0..=255).collect();
+    let mut j: u8 = 0;
+    for i in 0..256 {
+        j = j.wrapping_add(state[i]).wrapping_add(key[i % key.len()]);
+        state.swap(i, j as usize);
+    }
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|b| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(state[i as usize]);
+            state.swap(i as usize, j as usize);
+            let k = state[state[i as usize].wrapping_add(state[j as usize]) as usize];
+            b ^ k
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_crypto {
+    use super::*;
+
+    fn params(key: &str, iv: &str) -> CipherParams {
+        CipherParams {
+            key: hex::decode(key).unwrap(),
+            iv: hex::decode(iv).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_parses_cipher_names() {
+        assert_eq!(
+            "AES-256-GCM".parse::<SymmetricCipher>().unwrap(),
+            SymmetricCipher::Aes(256, BlockMode::Gcm)
+        );
+        assert_eq!(
+            "3des-cbc".parse::<SymmetricCipher>().unwrap(),
+            SymmetricCipher::TripleDes(BlockMode::Cbc)
+        );
+
+        // Malformed input
+        assert!("aes-512-cbc".parse::<SymmetricCipher>().is_err());
+        assert!("des-gcm".parse::<SymmetricCipher>().is_err());
+    }
+
+    #[test]
+    fn it_encrypts_aes_ecb() {
+        // FIPS-197 appendix C.1
+        let mut params = params("000102030405060708090a0b0c0d0e0f", "");
+        params.no_padding = true;
+        let plaintext = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let encrypted = encrypt("aes-128-ecb".parse().unwrap(), &plaintext, &params).unwrap();
+        assert_eq!(hex::encode(encrypted), "69c4e0d86a7b0430d8cdb78070b4c55a");
+
+        // Malformed input
+        let result = encrypt("aes-128-ecb".parse().unwrap(), b"not aligned", &params);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_decrypts_aes_cbc() {
+        // NIST SP 800-38A F.2.1
+        let mut params = params(
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "000102030405060708090a0b0c0d0e0f",
+        );
+        params.no_padding = true;
+        let ciphertext = hex::decode("7649abac8119b246cee98e9b12e9197d").unwrap();
+        let decrypted = decrypt("aes-128-cbc".parse().unwrap(), &ciphertext, &params).unwrap();
+        assert_eq!(hex::encode(decrypted), "6bc1bee22e409f96e93d7e117393172a");
+
+        // Malformed input
+        params.no_padding = false;
+        let result = decrypt("aes-128-cbc".parse().unwrap(), &ciphertext, &params);
+        assert!(result.unwrap_err().to_string().contains("Invalid padding"));
+    }
+
+    #[test]
+    fn it_encrypts_aes_ctr() {
+        // NIST SP 800-38A F.5.5
+        let params = params(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+            "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+        );
+        let plaintext = hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap();
+        let encrypted = encrypt("aes-256-ctr".parse().unwrap(), &plaintext, &params).unwrap();
+        assert_eq!(hex::encode(encrypted), "601ec313775789a5b7a7f504bbf3d228");
+    }
+
+    #[test]
+    fn it_encrypts_aes_gcm() {
+        // GCM spec test case 2
+        let params = params(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+        );
+        let plaintext = hex::decode("00000000000000000000000000000000").unwrap();
+        let encrypted = encrypt("aes-128-gcm".parse().unwrap(), &plaintext, &params).unwrap();
+        assert_eq!(
+            hex::encode(&encrypted),
+            "0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf"
+        );
+        for bits in [192, 256] {
+            let params = CipherParams {
+                key: vec![1; bits / 8],
+                iv: vec![2; 12],
+                aad: b"aad".to_vec(),
+                ..Default::default()
+            };
+            let cipher = SymmetricCipher::Aes(bits, BlockMode::Gcm);
+            let encrypted = encrypt(cipher, b"hello", &params).unwrap();
+            assert_eq!(decrypt(cipher, &encrypted, &params).unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn it_decrypts_aes_gcm() {
+        let mut params = params(
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+        );
+        let ciphertext =
+            hex::decode("0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf")
+                .unwrap();
+        let decrypted = decrypt("aes-128-gcm".parse().unwrap(), &ciphertext, &params).unwrap();
+        assert_eq!(hex::encode(decrypted), "00000000000000000000000000000000");
+
+        // Malformed input
+        params.aad = b"tampered".to_vec();
+        let result = decrypt("aes-128-gcm".parse().unwrap(), &ciphertext, &params);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Authentication tag mismatch")
+        );
+    }
+
+    #[test]
+    fn it_encrypts_rc4() {
+        let params = CipherParams {
+            key: b"Key".to_vec(),
+            ..Default::default()
+        };
+        let encrypted = encrypt(SymmetricCipher::Rc4, b"Plaintext", &params).unwrap();
+        assert_eq!(hex::encode(&encrypted), "bbf316e8d940af0ad3");
+        let decrypted = decrypt(SymmetricCipher::Rc4, &encrypted, &params).unwrap();
+        assert_eq!(decrypted, b"Plaintext");
+
+        // Malformed input
+        let result = encrypt(SymmetricCipher::Rc4, b"Plaintext", &CipherParams::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_encrypts_des() {
+        let mut params = params("133457799bbcdff1", "");
+        params.no_padding = true;
+        let plaintext = hex::decode("0123456789abcdef").unwrap();
+        let encrypted = encrypt("des-ecb".parse().unwrap(), &plaintext, &params).unwrap();
+        assert_eq!(hex::encode(encrypted), "85e813540f0ab405");
+
+        for key_length in [16, 24] {
+            let params = CipherParams {
+                key: vec![7; key_length],
+                iv: vec![9; 8],
+                ..Default::default()
+            };
+            let cipher = "3des-cbc".parse().unwrap();
+            let encrypted = encrypt(cipher, b"hello world", &params).unwrap();
+            assert_eq!(encrypted.len(), 16);
+            assert_eq!(
+                decrypt(cipher, &encrypted, &params).unwrap(),
+                b"hello world"
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_invalid_lengths() {
+        let params = params("00", "00");
+        for name in ["aes-256-cbc", "chacha20-poly1305", "des-cbc", "3des-ecb"] {
+            let result = encrypt(name.parse().unwrap(), b"hello", &params);
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .contains("Invalid key length")
+            );
+        }
+        let params = CipherParams {
+            key: vec![0; 16],
+            iv: vec![0; 8],
+            ..Default::default()
+        };
+        let result = encrypt("aes-128-cbc".parse().unwrap(), b"hello", &params);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid IV length")
+        );
+    }
+}
@@ -0,0 +1,566 @@
+//! Timestamp conversion between dates and the epochs found in logs, tokens and file formats:
+//! Unix, Windows FILETIME, .NET ticks, Apple Cocoa, Chrome/WebKit, GPS, MS-DOS and MongoDB ObjectId.
+
+use crate::radix::parse_integer;
+use chrono::{
+    DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, Offset, SecondsFormat, TimeZone,
+    Timelike, Utc,
+};
+use std::{error::Error, str};
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Start of GPS time, 1980-01-06 UTC, in Unix seconds.
+const GPS_EPOCH: i128 = 315_964_800;
+
+/// Unix seconds of each UTC day that started after a leap second, since the GPS epoch.
+const LEAP_SECONDS: [i128; 18] = [
+    362_793_600,
+    394_329_600,
+    425_865_600,
+    489_024_000,
+    567_993_600,
+    631_152_000,
+    662_688_000,
+    709_948_800,
+    741_484_800,
+    773_020_800,
+    820_454_400,
+    867_715_200,
+    915_148_800,
+    1_136_073_600,
+    1_230_768_000,
+    1_341_100_800,
+    1_435_708_800,
+    1_483_228_800,
+];
+
+/// Supported timestamp formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochFormat {
+    /// Seconds since 1970-01-01 UTC.
+    Unix,
+    /// Milliseconds since 1970-01-01 UTC, as in JavaScript `Date.now()`.
+    UnixMillis,
+    /// Microseconds since 1970-01-01 UTC.
+    UnixMicros,
+    /// Nanoseconds since 1970-01-01 UTC.
+    UnixNanos,
+    /// Windows FILETIME: 100 nanosecond intervals since 1601-01-01 UTC.
+    FileTime,
+    /// .NET `DateTime.Ticks`: 100 nanosecond intervals since 0001-01-01.
+    DotNetTicks,
+    /// Chrome and WebKit time: microseconds since 1601-01-01 UTC.
+    WebKit,
+    /// Apple Cocoa and Core Data absolute time: seconds since 2001-01-01 UTC.
+    Cocoa,
+    /// GPS time: seconds since 1980-01-06 UTC, not counting leap seconds.
+    Gps,
+    /// MS-DOS and FAT date and time: the date in the high 16 bits and the time in the low
+    /// 16 bits, with a 2 second resolution. The fields are read as UTC.
+    Dos,
+    /// MongoDB ObjectId: 12 bytes in hex, starting with big-endian Unix seconds.
+    ObjectId,
+}
+
+impl EpochFormat {
+    /// All formats, in the order [`detect_epoch`] tries them.
+    pub const ALL: [EpochFormat; 11] = [
+        EpochFormat::Unix,
+        EpochFormat::UnixMillis,
+        EpochFormat::UnixMicros,
+        EpochFormat::UnixNanos,
+        EpochFormat::FileTime,
+        EpochFormat::DotNetTicks,
+        EpochFormat::WebKit,
+        EpochFormat::Cocoa,
+        EpochFormat::Gps,
+        EpochFormat::Dos,
+        EpochFormat::ObjectId,
+    ];
+
+    /// The name accepted by [`str::FromStr`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            EpochFormat::Unix => "unix",
+            EpochFormat::UnixMillis => "unix-ms",
+            EpochFormat::UnixMicros => "unix-us",
+            EpochFormat::UnixNanos => "unix-ns",
+            EpochFormat::FileTime => "filetime",
+            EpochFormat::DotNetTicks => "ticks",
+            EpochFormat::WebKit => "webkit",
+            EpochFormat::Cocoa => "cocoa",
+            EpochFormat::Gps => "gps",
+            EpochFormat::Dos => "dos",
+            EpochFormat::ObjectId => "objectid",
+        }
+    }
+
+    /// The start of the epoch in Unix seconds and the length of one unit in nanoseconds.
+    fn scale(&self) -> (i128, i128) {
+        match self {
+            EpochFormat::Unix | EpochFormat::Dos | EpochFormat::ObjectId => (0, NANOS_PER_SECOND),
+            EpochFormat::UnixMillis => (0, 1_000_000),
+            EpochFormat::UnixMicros => (0, 1_000),
+            EpochFormat::UnixNanos => (0, 1),
+            EpochFormat::FileTime => (-11_644_473_600, 100),
+            EpochFormat::DotNetTicks => (-62_135_596_800, 100),
+            EpochFormat::WebKit => (-11_644_473_600, 1_000),
+            EpochFormat::Cocoa => (978_307_200, NANOS_PER_SECOND),
+            EpochFormat::Gps => (GPS_EPOCH, NANOS_PER_SECOND),
+        }
+    }
+}
+
+impl str::FromStr for EpochFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "unix" | "s" | "seconds" => Ok(EpochFormat::Unix),
+            "unix-ms" | "ms" | "millis" => Ok(EpochFormat::UnixMillis),
+            "unix-us" | "us" | "micros" => Ok(EpochFormat::UnixMicros),
+            "unix-ns" | "ns" | "nanos" => Ok(EpochFormat::UnixNanos),
+            "filetime" | "windows" => Ok(EpochFormat::FileTime),
+            "ticks" | "dotnet" | ".net" => Ok(EpochFormat::DotNetTicks),
+            "webkit" | "chrome" => Ok(EpochFormat::WebKit),
+            "cocoa" | "apple" | "mac" => Ok(EpochFormat::Cocoa),
+            "gps" => Ok(EpochFormat::Gps),
+            "dos" | "fat" => Ok(EpochFormat::Dos),
+            "objectid" | "oid" | "mongodb" => Ok(EpochFormat::ObjectId),
+            _ => Err(format!(
+                "Unknown epoch format '{}', expected one of: unix, unix-ms, unix-us, unix-ns, filetime, ticks, webkit, cocoa, gps, dos, objectid",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses a decimal number with an optional fraction, or a `0x`, `0o` or `0b` prefixed
+/// integer, and multiplies it by `unit`.
+fn parse_scaled(value: &str, unit: i128) -> Result<i128, Box<dyn Error>> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let whole = i128::try_from(parse_integer(whole, None)?)
+        .map_err(|_| format!("Timestamp '{}' is out of range", value))?;
+    if fraction.len() > 18 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid timestamp '{}'", value).into());
+    }
+    let fraction = match fraction {
+        "" => 0,
+        digits => digits.parse::<i128>()? * unit / 10i128.pow(digits.len() as u32),
+    };
+    let fraction = if value.trim_start().starts_with('-') {
+        -fraction
+    } else {
+        fraction
+    };
+    whole
+        .checked_mul(unit)
+        .and_then(|n| n.checked_add(fraction))
+        .ok_or_else(|| format!("Timestamp '{}' is out of range", value).into())
+}
+
+/// Writes `value / unit` exactly, with a decimal fraction when needed. `unit` is a power of ten.
+fn format_scaled(value: i128, unit: i128) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let (whole, fraction) = (value.abs() / unit, value.abs() % unit);
+    if fraction == 0 {
+        return format!("{}{}", sign, whole);
+    }
+    let width = unit.to_string().len() - 1;
+    let fraction = format!("{:0width$}", fraction, width = width);
+    format!("{}{}.{}", sign, whole, fraction.trim_end_matches('0'))
+}
+
+fn datetime_from_nanos(nanos: i128) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    i64::try_from(nanos.div_euclid(NANOS_PER_SECOND))
+        .ok()
+        .and_then(|seconds| {
+            DateTime::from_timestamp(seconds, nanos.rem_euclid(NANOS_PER_SECOND) as u32)
+        })
+        .ok_or_else(|| "Timestamp is out of the supported date range".into())
+}
+
+fn datetime_nanos(date: &DateTime<Utc>) -> i128 {
+    date.timestamp() as i128 * NANOS_PER_SECOND + date.timestamp_subsec_nanos() as i128
+}
+
+/// Unpacks an MS-DOS date and time, checking that every field is valid.
+fn dos_to_datetime(value: u32) -> Option<DateTime<Utc>> {
+    let (date, time) = (value >> 16, value & 0xffff);
+    NaiveDate::from_ymd_opt(1980 + (date >> 9) as i32, (date >> 5) & 0xf, date & 0x1f)?
+        .and_hms_opt(time >> 11, (time >> 5) & 0x3f, (time & 0x1f) * 2)
+        .map(|date| date.and_utc())
+}
+
+/// Converts a timestamp in the given format to a date.
+///
+/// Numbers may be decimal with an optional fraction, or `0x`, `0o` or `0b` prefixed.
+/// ObjectIds are 24 hex digits.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch::{self, EpochFormat};
+/// let date = epoch::epoch_to_datetime("133444736000000000", EpochFormat::FileTime).unwrap();
+/// assert_eq!(date.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+/// let date = epoch::epoch_to_datetime("1700000000.5", EpochFormat::Unix).unwrap();
+/// assert_eq!(date.timestamp_millis(), 1700000000500);
+/// ```
+pub fn epoch_to_datetime(
+    value: &str,
+    format: EpochFormat,
+) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let value = value.trim();
+    match format {
+        EpochFormat::ObjectId => {
+            if value.len() != 24 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!("Invalid ObjectId '{}', expected 24 hex digits", value).into());
+            }
+            let seconds = i64::from_str_radix(&value[..8], 16)?;
+            datetime_from_nanos(seconds as i128 * NANOS_PER_SECOND)
+        }
+        EpochFormat::Dos => {
+            let packed = u32::try_from(parse_integer(value, None)?)
+                .map_err(|_| format!("DOS timestamp '{}' does not fit in 32 bits", value))?;
+            dos_to_datetime(packed)
+                .ok_or_else(|| format!("Invalid DOS date or time in '{}'", value).into())
+        }
+        EpochFormat::Gps => {
+            let nanos = parse_scaled(value, NANOS_PER_SECOND)?;
+            let seconds = nanos.div_euclid(NANOS_PER_SECOND) + GPS_EPOCH;
+            let leaps = LEAP_SECONDS
+                .iter()
+                .zip(1..)
+                .filter(|(leap, count)| seconds >= *leap + count)
+                .count() as i128;
+            datetime_from_nanos(nanos + (GPS_EPOCH - leaps) * NANOS_PER_SECOND)
+        }
+        _ => {
+            let (start, unit) = format.scale();
+            datetime_from_nanos(parse_scaled(value, unit)? + start * NANOS_PER_SECOND)
+        }
+    }
+}
+
+/// Converts a date to a timestamp in the given format. Precision beyond the format's unit
+/// is kept as a decimal fraction, except for DOS and ObjectId which round down.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch::{self, EpochFormat};
+/// let date = chrono::DateTime::from_timestamp(1700000000, 0).unwrap();
+/// assert_eq!(epoch::datetime_to_epoch(&date, EpochFormat::UnixMillis).unwrap(), "1700000000000");
+/// assert_eq!(epoch::datetime_to_epoch(&date, EpochFormat::Cocoa).unwrap(), "721692800");
+/// assert_eq!(
+///     epoch::datetime_to_epoch(&date, EpochFormat::ObjectId).unwrap(),
+///     "6553f1000000000000000000"
+/// );
+/// ```
+pub fn datetime_to_epoch(
+    date: &DateTime<Utc>,
+    format: EpochFormat,
+) -> Result<String, Box<dyn Error>> {
+    match format {
+        EpochFormat::ObjectId => {
+            let seconds = u32::try_from(date.timestamp())
+                .map_err(|_| format!("{} does not fit in an ObjectId", date))?;
+            Ok(format!("{:08x}{:016x}", seconds, 0))
+        }
+        EpochFormat::Dos => {
+            if !(1980..=2107).contains(&date.year()) {
+                return Err(format!("{} is outside the DOS date range 1980 to 2107", date).into());
+            }
+            let day = ((date.year() as u32 - 1980) << 9) | (date.month() << 5) | date.day();
+            let time = (date.hour() << 11) | (date.minute() << 5) | (date.second() / 2);
+            Ok(((day << 16) | time).to_string())
+        }
+        EpochFormat::Gps => {
+            let leaps = LEAP_SECONDS
+                .iter()
+                .filter(|leap| date.timestamp() as i128 >= **leap)
+                .count() as i128;
+            let nanos = datetime_nanos(date) + (leaps - GPS_EPOCH) * NANOS_PER_SECOND;
+            Ok(format_scaled(nanos, NANOS_PER_SECOND))
+        }
+        _ => {
+            let (start, unit) = format.scale();
+            Ok(format_scaled(
+                datetime_nanos(date) - start * NANOS_PER_SECOND,
+                unit,
+            ))
+        }
+    }
+}
+
+/// A timestamp format and the date it gives, as found by [`detect_epoch`].
+pub type EpochCandidate = (EpochFormat, DateTime<Utc>);
+
+/// Lists the formats that turn a timestamp into a date between 1980 and 2100, most likely
+/// first. 24 hex digits are read as an ObjectId.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch::{self, EpochFormat};
+/// let candidates = epoch::detect_epoch("1700000000000").unwrap();
+/// assert_eq!(candidates[0].0, EpochFormat::UnixMillis);
+/// let candidates = epoch::detect_epoch("0x01da1747c66d0000").unwrap();
+/// assert_eq!(candidates[0].0, EpochFormat::FileTime);
+/// ```
+pub fn detect_epoch(value: &str) -> Result<Vec<EpochCandidate>, Box<dyn Error>> {
+    let value = value.trim();
+    if value.len() == 24 && value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let date = epoch_to_datetime(value, EpochFormat::ObjectId)?;
+        return Ok(vec![(EpochFormat::ObjectId, date)]);
+    }
+    parse_scaled(value, 1)?;
+    let plausible = |date: &DateTime<Utc>| (1980..2100).contains(&date.year());
+    let candidates: Vec<EpochCandidate> = EpochFormat::ALL
+        .iter()
+        .filter(|format| **format != EpochFormat::ObjectId)
+        .filter_map(|format| {
+            epoch_to_datetime(value, *format)
+                .ok()
+                .filter(plausible)
+                .map(|date| (*format, date))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Err(format!(
+            "No epoch format gives a date between 1980 and 2100 for '{}'",
+            value
+        )
+        .into());
+    }
+    Ok(candidates)
+}
+
+/// Parses a UTC offset like `Z`, `UTC`, `+02:00`, `-0530` or `+9`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch;
+/// assert_eq!(epoch::parse_offset("-05:30").unwrap().local_minus_utc(), -19800);
+/// assert_eq!(epoch::parse_offset("Z").unwrap().local_minus_utc(), 0);
+/// ```
+pub fn parse_offset(s: &str) -> Result<FixedOffset, String> {
+    let invalid = || format!("Invalid UTC offset '{}', expected Z or +HH:MM", s);
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") {
+        return Ok(Utc.fix());
+    }
+    let (sign, rest) = match s.split_at_checked(1) {
+        Some(("+", rest)) => (1, rest),
+        Some(("-", rest)) => (-1, rest),
+        _ => return Err(invalid()),
+    };
+    let rest = rest.replace(':', "");
+    let (hours, minutes) = match rest.len() {
+        1 | 2 => (rest.as_str(), "0"),
+        4 => rest.split_at(2),
+        _ => return Err(invalid()),
+    };
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// Parses a date in RFC 3339, RFC 2822 or `YYYY-MM-DD[ HH:MM[:SS[.f]]]` form. Dates
+/// without an offset are read in the given `offset`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch;
+/// let utc = epoch::parse_offset("Z").unwrap();
+/// let date = epoch::parse_datetime("2023-11-14T23:13:20+01:00", utc).unwrap();
+/// assert_eq!(date.timestamp(), 1700000000);
+/// let date = epoch::parse_datetime("Tue, 14 Nov 2023 22:13:20 GMT", utc).unwrap();
+/// assert_eq!(date.timestamp(), 1700000000);
+/// let date = epoch::parse_datetime("2023-11-14 22:13:20", utc).unwrap();
+/// assert_eq!(date.timestamp(), 1700000000);
+/// ```
+pub fn parse_datetime(data: &str, offset: FixedOffset) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    let data = data.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(data).or(DateTime::parse_from_rfc2822(data)) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let naive = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(data, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(data, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .ok_or_else(|| format!("Invalid date '{}'", data))?;
+    offset
+        .from_local_datetime(&naive)
+        .single()
+        .map(|date| date.with_timezone(&Utc))
+        .ok_or_else(|| format!("Invalid date '{}'", data).into())
+}
+
+/// Writes a date in RFC 3339 at the given offset, with a fraction only when needed.
+pub fn format_datetime(date: &DateTime<Utc>, offset: FixedOffset) -> String {
+    date.with_timezone(&offset)
+        .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Converts each non-empty line of `data`: dates become timestamps and timestamps become
+/// RFC 3339 dates at `offset`.
+///
+/// With a `format`, only that format is used and each line gives one result. Without one,
+/// dates are written in every format and timestamps in every format that [`detect_epoch`]
+/// finds plausible, each prefixed with the format name.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::epoch::{self, EpochFormat};
+/// let utc = epoch::parse_offset("Z").unwrap();
+/// let result = epoch::convert_timestamp("1700000000000", None, utc).unwrap();
+/// assert_eq!(result, "unix-ms: 2023-11-14T22:13:20Z");
+/// let result = epoch::convert_timestamp("2023-11-14T22:13:20Z", Some(EpochFormat::Unix), utc);
+/// assert_eq!(result.unwrap(), "1700000000");
+/// ```
+pub fn convert_timestamp(
+    data: &str,
+    format: Option<EpochFormat>,
+    offset: FixedOffset,
+) -> Result<String, Box<dyn Error>> {
+    let mut lines = Vec::new();
+    for line in data.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Ok(date) = parse_datetime(line, offset) {
+            match format {
+                Some(format) => lines.push(datetime_to_epoch(&date, format)?),
+                None => lines.extend(EpochFormat::ALL.iter().filter_map(|format| {
+                    datetime_to_epoch(&date, *format)
+                        .ok()
+                        .map(|value| format!("{}: {}", format.name(), value))
+                })),
+            }
+            continue;
+        }
+        match format {
+            Some(format) => lines.push(format_datetime(&epoch_to_datetime(line, format)?, offset)),
+            None => lines.extend(detect_epoch(line)?.iter().map(|(format, date)| {
+                format!("{}: {}", format.name(), format_datetime(date, offset))
+            })),
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests_epoch {
+    use super::*;
+
+    fn utc() -> FixedOffset {
+        parse_offset("Z").unwrap()
+    }
+
+    #[test]
+    fn it_converts_epochs_both_ways() {
+        let date = parse_datetime("2023-11-14T22:13:20.25Z", utc()).unwrap();
+        let cases = [
+            (EpochFormat::Unix, "1700000000.25"),
+            (EpochFormat::UnixMillis, "1700000000250"),
+            (EpochFormat::UnixMicros, "1700000000250000"),
+            (EpochFormat::UnixNanos, "1700000000250000000"),
+            (EpochFormat::FileTime, "133444736002500000"),
+            (EpochFormat::DotNetTicks, "638355968002500000"),
+            (EpochFormat::WebKit, "13344473600250000"),
+            (EpochFormat::Cocoa, "721692800.25"),
+            (EpochFormat::Gps, "1384035218.25"),
+        ];
+        for (format, value) in cases {
+            assert_eq!(
+                datetime_to_epoch(&date, format).unwrap(),
+                value,
+                "{:?}",
+                format
+            );
+            assert_eq!(
+                epoch_to_datetime(value, format).unwrap(),
+                date,
+                "{:?}",
+                format
+            );
+        }
+
+        // DOS and ObjectId round down to their resolution.
+        let rounded = parse_datetime("2023-11-14T22:13:20Z", utc()).unwrap();
+        let dos = datetime_to_epoch(&date, EpochFormat::Dos).unwrap();
+        assert_eq!(dos, format!("{}", 0x576e_b1aa_u32));
+        assert_eq!(epoch_to_datetime(&dos, EpochFormat::Dos).unwrap(), rounded);
+        let oid = datetime_to_epoch(&date, EpochFormat::ObjectId).unwrap();
+        assert_eq!(
+            epoch_to_datetime(&oid, EpochFormat::ObjectId).unwrap(),
+            rounded
+        );
+
+        // GPS time before the first leap second, and negative Unix time.
+        let date = epoch_to_datetime("0", EpochFormat::Gps).unwrap();
+        assert_eq!(format_datetime(&date, utc()), "1980-01-06T00:00:00Z");
+        let date = epoch_to_datetime("-1.5", EpochFormat::Unix).unwrap();
+        assert_eq!(format_datetime(&date, utc()), "1969-12-31T23:59:58.500Z");
+        assert_eq!(datetime_to_epoch(&date, EpochFormat::Unix).unwrap(), "-1.5");
+
+        // Malformed input
+        assert!(epoch_to_datetime("abc", EpochFormat::Unix).is_err());
+        assert!(epoch_to_datetime("1.2.3", EpochFormat::Unix).is_err());
+        assert!(epoch_to_datetime("0", EpochFormat::Dos).is_err());
+        assert!(epoch_to_datetime("6553f100", EpochFormat::ObjectId).is_err());
+        assert!(epoch_to_datetime("99999999999999999999", EpochFormat::Unix).is_err());
+    }
+
+    #[test]
+    fn it_detects_epochs() {
+        let formats = |value: &str| -> Vec<EpochFormat> {
+            detect_epoch(value)
+                .unwrap()
+                .iter()
+                .map(|(f, _)| *f)
+                .collect()
+        };
+        assert_eq!(formats("1700000000")[0], EpochFormat::Unix);
+        assert!(formats("1700000000").contains(&EpochFormat::Cocoa));
+        assert_eq!(formats("1700000000000000"), [EpochFormat::UnixMicros]);
+        assert_eq!(formats("13344473600000000"), [EpochFormat::WebKit]);
+        assert_eq!(
+            formats("638355968000000000"),
+            [EpochFormat::UnixNanos, EpochFormat::DotNetTicks]
+        );
+        assert_eq!(formats("6553f1000000000000000000"), [EpochFormat::ObjectId]);
+        assert!(detect_epoch("5000000000000000000").is_err());
+        assert!(detect_epoch("hello").is_err());
+    }
+
+    #[test]
+    fn it_converts_timestamps() {
+        let offset = parse_offset("+02:00").unwrap();
+        let result = convert_timestamp("1700000000\n\n", Some(EpochFormat::Unix), offset).unwrap();
+        assert_eq!(result, "2023-11-15T00:13:20+02:00");
+
+        let result = convert_timestamp("2023-11-15 00:13:20", None, offset).unwrap();
+        assert!(result.starts_with("unix: 1700000000\nunix-ms: 1700000000000\n"));
+        assert!(result.ends_with("objectid: 6553f1000000000000000000"));
+
+        assert_eq!(parse_offset("+9").unwrap().local_minus_utc(), 9 * 3600);
+        assert_eq!(parse_offset("-0530").unwrap().local_minus_utc(), -19800);
+        assert!(parse_offset("0200").is_err());
+        assert!(parse_offset("+02:75").is_err());
+    }
+}
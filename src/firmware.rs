@@ -0,0 +1,897 @@
+//! Firmware image formats: Intel HEX, Motorola S-records and TI-TXT, parsed into a sparse
+//! memory image and written back in any of them or as raw binary with the gaps filled.
+
+use std::{collections::BTreeMap, error::Error, str};
+
+/// Largest raw binary written, as far apart segments are joined by filling the gap.
+const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// End of the 32-bit address space.
+const ADDRESS_SPACE: u64 = 1 << 32;
+
+/// Supported firmware formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareFormat {
+    /// Intel HEX with extended segment and linear addressing.
+    IntelHex,
+    /// Motorola S-records with 16, 24 or 32-bit addresses.
+    SRecord,
+    /// TI-TXT, as used by MSP430 programmers.
+    TiTxt,
+    /// Raw bytes loaded at a base address.
+    Binary,
+}
+
+impl FirmwareFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirmwareFormat::IntelHex => "Intel HEX",
+            FirmwareFormat::SRecord => "S-record",
+            FirmwareFormat::TiTxt => "TI-TXT",
+            FirmwareFormat::Binary => "binary",
+        }
+    }
+}
+
+impl str::FromStr for FirmwareFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ihex" | "intel-hex" | "intelhex" | "hex" => Ok(FirmwareFormat::IntelHex),
+            "srec" | "s-record" | "srecord" | "s19" | "s28" | "s37" | "mot" => {
+                Ok(FirmwareFormat::SRecord)
+            }
+            "ti-txt" | "titxt" | "txt" => Ok(FirmwareFormat::TiTxt),
+            "bin" | "binary" | "raw" => Ok(FirmwareFormat::Binary),
+            _ => Err(format!(
+                "Unknown firmware format '{}', expected one of: ihex, srec, ti-txt, bin",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for reading and writing firmware images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmwareOptions {
+    /// Data bytes per Intel HEX or S-record record.
+    pub record_length: usize,
+    /// Byte written into the gaps between segments of a raw binary.
+    pub fill: u8,
+    /// Address a raw binary is loaded at.
+    pub base_address: u32,
+}
+
+impl Default for FirmwareOptions {
+    fn default() -> Self {
+        FirmwareOptions {
+            record_length: 16,
+            fill: 0xff,
+            base_address: 0,
+        }
+    }
+}
+
+/// A sparse memory image: runs of bytes at their addresses, and the start address if the
+/// file has one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryImage {
+    /// Contiguous runs of bytes by start address, never overlapping nor touching.
+    segments: BTreeMap<u32, Vec<u8>>,
+    /// Entry point, from Intel HEX records 03 and 05 or S-records S7, S8 and S9. Segment
+    /// start addresses (CS:IP) are kept as the linear address `CS * 16 + IP`.
+    pub start_address: Option<u32>,
+}
+
+impl MemoryImage {
+    pub fn new() -> Self {
+        MemoryImage::default()
+    }
+
+    /// An image of raw bytes loaded at a base address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::firmware::MemoryImage;
+    /// let image = MemoryImage::from_binary(b"\x01\x02", 0x0800_0000).unwrap();
+    /// assert_eq!(image.segments().collect::<Vec<_>>(), vec![(0x0800_0000, &[1u8, 2][..])]);
+    /// ```
+    pub fn from_binary(data: &[u8], base_address: u32) -> Result<Self, Box<dyn Error>> {
+        let mut image = MemoryImage::new();
+        image.write(base_address, data)?;
+        Ok(image)
+    }
+
+    /// Writes bytes at an address, joining them with the runs they touch. Writing over bytes
+    /// already in the image is an error, as firmware files never mean to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::firmware::MemoryImage;
+    /// let mut image = MemoryImage::new();
+    /// image.write(0x10, b"\x03\x04").unwrap();
+    /// image.write(0x0e, b"\x01\x02").unwrap();
+    /// assert_eq!(image.segments().collect::<Vec<_>>(), vec![(0x0e, &[1u8, 2, 3, 4][..])]);
+    /// assert!(image.write(0x11, b"\x05").is_err());
+    /// ```
+    pub fn write(&mut self, address: u32, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let end = address as u64 + data.len() as u64;
+        if end > ADDRESS_SPACE {
+            return Err(format!(
+                "Data at 0x{:08x} runs past the end of the 32-bit address space",
+                address
+            )
+            .into());
+        }
+        if let Some((&start, segment)) = self.segments.range(..=address).next_back()
+            && start as u64 + segment.len() as u64 > address as u64
+        {
+            return Err(format!("Overlapping data at address 0x{:08x}", address).into());
+        }
+        if let Some((&start, _)) = self.segments.range(address..).next()
+            && (start as u64) < end
+        {
+            return Err(format!("Overlapping data at address 0x{:08x}", start).into());
+        }
+
+        let mut bytes = data.to_vec();
+        if end < ADDRESS_SPACE
+            && let Some(next) = self.segments.remove(&(end as u32))
+        {
+            bytes.extend(next);
+        }
+        match self.segments.range_mut(..address).next_back() {
+            Some((&start, segment)) if start as u64 + segment.len() as u64 == address as u64 => {
+                segment.extend(bytes);
+            }
+            _ => {
+                self.segments.insert(address, bytes);
+            }
+        }
+        Ok(())
+    }
+
+    /// The runs of bytes with their start addresses, lowest first.
+    pub fn segments(&self) -> impl Iterator<Item = (u32, &[u8])> {
+        self.segments
+            .iter()
+            .map(|(&address, data)| (address, data.as_slice()))
+    }
+
+    /// Number of bytes in the image, not counting gaps.
+    pub fn len(&self) -> usize {
+        self.segments.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The image as raw bytes from its lowest to its highest address, with the gaps filled,
+    /// and the address the bytes start at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::firmware::MemoryImage;
+    /// let mut image = MemoryImage::new();
+    /// image.write(0x100, b"\x01").unwrap();
+    /// image.write(0x103, b"\x02").unwrap();
+    /// assert_eq!(image.to_binary(0xff).unwrap(), (0x100, vec![0x01, 0xff, 0xff, 0x02]));
+    /// ```
+    pub fn to_binary(&self, fill: u8) -> Result<(u32, Vec<u8>), Box<dyn Error>> {
+        let (Some((&first, _)), Some((&last, segment))) = (
+            self.segments.first_key_value(),
+            self.segments.last_key_value(),
+        ) else {
+            return Ok((0, Vec::new()));
+        };
+        let size = last as u64 + segment.len() as u64 - first as u64;
+        if size > MAX_SIZE {
+            return Err(format!(
+                "Filling the gaps from 0x{:08x} to 0x{:08x} takes {} bytes, more than the largest binary of {} bytes",
+                first,
+                last as u64 + segment.len() as u64 - 1,
+                size,
+                MAX_SIZE
+            )
+            .into());
+        }
+        let mut binary = vec![fill; size as usize];
+        for (address, data) in self.segments() {
+            let offset = (address - first) as usize;
+            binary[offset..offset + data.len()].copy_from_slice(data);
+        }
+        Ok((first, binary))
+    }
+}
+
+/// Guesses the format of a firmware file from its first character: `:` for Intel HEX, `S` and
+/// a digit for S-records, `@` for TI-TXT, and binary otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware::{self, FirmwareFormat};
+/// assert_eq!(firmware::detect_format(b":00000001FF\n"), FirmwareFormat::IntelHex);
+/// assert_eq!(firmware::detect_format(b"S9030000FC\n"), FirmwareFormat::SRecord);
+/// assert_eq!(firmware::detect_format(b"@F000\nq\n"), FirmwareFormat::TiTxt);
+/// assert_eq!(firmware::detect_format(b"\x7fELF"), FirmwareFormat::Binary);
+/// ```
+pub fn detect_format(data: &[u8]) -> FirmwareFormat {
+    let start = data.trim_ascii_start();
+    match start {
+        [b':', next, ..] if next.is_ascii_hexdigit() => FirmwareFormat::IntelHex,
+        [b'S', next, ..] if next.is_ascii_digit() => FirmwareFormat::SRecord,
+        [b'@', next, ..] if next.is_ascii_hexdigit() => FirmwareFormat::TiTxt,
+        _ => FirmwareFormat::Binary,
+    }
+}
+
+/// Parses a firmware file in the given format into a memory image. Raw binaries are loaded at
+/// the base address of the options.
+pub fn parse_firmware(
+    data: &[u8],
+    format: FirmwareFormat,
+    options: &FirmwareOptions,
+) -> Result<MemoryImage, Box<dyn Error>> {
+    let text = String::from_utf8_lossy(data);
+    match format {
+        FirmwareFormat::IntelHex => parse_intel_hex(&text),
+        FirmwareFormat::SRecord => parse_srecord(&text),
+        FirmwareFormat::TiTxt => parse_ti_txt(&text),
+        FirmwareFormat::Binary => MemoryImage::from_binary(data, options.base_address),
+    }
+}
+
+/// Writes a memory image in the given format. Raw binaries start at the lowest address of the
+/// image, with the gaps filled with the fill byte of the options.
+pub fn write_firmware(
+    image: &MemoryImage,
+    format: FirmwareFormat,
+    options: &FirmwareOptions,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    match format {
+        FirmwareFormat::IntelHex => Ok(write_intel_hex(image, options.record_length)?.into_bytes()),
+        FirmwareFormat::SRecord => Ok(write_srecord(image, options.record_length)?.into_bytes()),
+        FirmwareFormat::TiTxt => Ok(write_ti_txt(image).into_bytes()),
+        FirmwareFormat::Binary => Ok(image.to_binary(options.fill)?.1),
+    }
+}
+
+/// Lists the segments of an image, its size and start address.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware::{self, MemoryImage};
+/// let mut image = MemoryImage::from_binary(&[0; 0x200], 0x0800_0000).unwrap();
+/// image.start_address = Some(0x0800_0131);
+/// assert_eq!(
+///     firmware::describe(&image),
+///     "0x08000000-0x080001ff  512 bytes\n1 segment, 512 bytes\nStart address: 0x08000131"
+/// );
+/// ```
+pub fn describe(image: &MemoryImage) -> String {
+    let mut lines: Vec<String> = image
+        .segments()
+        .map(|(address, data)| {
+            format!(
+                "0x{:08x}-0x{:08x}  {} bytes",
+                address,
+                address as u64 + data.len() as u64 - 1,
+                data.len()
+            )
+        })
+        .collect();
+    let count = image.segments.len();
+    lines.push(format!(
+        "{} segment{}, {} bytes",
+        count,
+        if count == 1 { "" } else { "s" },
+        image.len()
+    ));
+    if let Some(start) = image.start_address {
+        lines.push(format!("Start address: 0x{:08x}", start));
+    }
+    lines.join("\n")
+}
+
+/// Decodes the hex digits of a record, `column` being where they start on the line.
+fn record_bytes(digits: &str, number: usize, column: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    if let Some((index, c)) = digits
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_hexdigit())
+    {
+        return Err(format!(
+            "Line {}, column {}: '{}' is not a hex digit",
+            number,
+            column + index,
+            c
+        )
+        .into());
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("Line {}: odd number of hex digits", number).into());
+    }
+    Ok(hex::decode(digits)?)
+}
+
+fn be_address(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0, |address, &byte| (address << 8) | byte as u32)
+}
+
+/// Parses Intel HEX: data (00), end of file (01), extended segment address (02), start
+/// segment address (03), extended linear address (04) and start linear address (05) records.
+/// Every checksum is verified, and errors give the line and column.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware;
+/// let image = firmware::parse_intel_hex(
+///     ":020000040800F2\n:0400000001020304F2\n:0400000508000131BD\n:00000001FF\n",
+/// )
+/// .unwrap();
+/// assert_eq!(image.segments().collect::<Vec<_>>(), vec![(0x0800_0000, &[1u8, 2, 3, 4][..])]);
+/// assert_eq!(image.start_address, Some(0x0800_0131));
+///
+/// let err = firmware::parse_intel_hex(":0400000001020304F3\n").unwrap_err();
+/// assert_eq!(err.to_string(), "Line 1, column 18: checksum F3 should be F2");
+/// ```
+pub fn parse_intel_hex(text: &str) -> Result<MemoryImage, Box<dyn Error>> {
+    let mut image = MemoryImage::new();
+    let mut base: u32 = 0;
+    let mut ended = false;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let record = line.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if ended {
+            return Err(format!("Line {}: record after the end of file record", number).into());
+        }
+        let digits = record.strip_prefix(':').ok_or_else(|| {
+            format!(
+                "Line {}, column {}: expected ':' to start an Intel HEX record",
+                number,
+                indent + 1
+            )
+        })?;
+        let bytes = record_bytes(digits, number, indent + 2)?;
+        if bytes.len() < 5 {
+            return Err(format!(
+                "Line {}: record of {} bytes is shorter than an empty one of 5",
+                number,
+                bytes.len()
+            )
+            .into());
+        }
+        let length = bytes[0] as usize;
+        if bytes.len() != length + 5 {
+            return Err(format!(
+                "Line {}: byte count is {} but the record has {} data bytes",
+                number,
+                length,
+                bytes.len() - 5
+            )
+            .into());
+        }
+        let (checksum, fields) = bytes.split_last().expect("records have at least 5 bytes");
+        let expected = fields
+            .iter()
+            .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+            .wrapping_neg();
+        if *checksum != expected {
+            return Err(format!(
+                "Line {}, column {}: checksum {:02X} should be {:02X}",
+                number,
+                indent + 2 + 2 * fields.len(),
+                checksum,
+                expected
+            )
+            .into());
+        }
+
+        let offset = u16::from_be_bytes([fields[1], fields[2]]);
+        let data = &fields[4..];
+        let expect_length = |expected: usize| -> Result<(), Box<dyn Error>> {
+            if length == expected {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Line {}: record type {:02X} needs {} data bytes, not {}",
+                    number, fields[3], expected, length
+                )
+                .into())
+            }
+        };
+        match fields[3] {
+            0x00 => {
+                let address = base as u64 + offset as u64;
+                let address = u32::try_from(address).map_err(|_| {
+                    format!(
+                        "Line {}: address 0x{:x} is past the 32-bit address space",
+                        number, address
+                    )
+                })?;
+                image
+                    .write(address, data)
+                    .map_err(|e| format!("Line {}: {}", number, e))?;
+            }
+            0x01 => ended = true,
+            0x02 => {
+                expect_length(2)?;
+                base = (be_address(data)) << 4;
+            }
+            0x03 => {
+                expect_length(4)?;
+                let segment = be_address(&data[..2]);
+                let pointer = be_address(&data[2..]);
+                image.start_address = Some((segment << 4) + pointer);
+            }
+            0x04 => {
+                expect_length(2)?;
+                base = be_address(data) << 16;
+            }
+            0x05 => {
+                expect_length(4)?;
+                image.start_address = Some(be_address(data));
+            }
+            kind => {
+                return Err(format!(
+                    "Line {}, column {}: unknown record type {:02X}",
+                    number,
+                    indent + 8,
+                    kind
+                )
+                .into());
+            }
+        }
+    }
+    Ok(image)
+}
+
+/// Parses Motorola S-records: the header (S0), data with 16, 24 and 32-bit addresses (S1, S2,
+/// S3), record counts (S5, S6) which are checked, and start addresses (S7, S8, S9). Every
+/// checksum is verified, and errors give the line and column.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware;
+/// let image = firmware::parse_srecord("S0030000FC\nS107010001020304ED\nS9030100FB\n").unwrap();
+/// assert_eq!(image.segments().collect::<Vec<_>>(), vec![(0x100, &[1u8, 2, 3, 4][..])]);
+/// assert_eq!(image.start_address, Some(0x100));
+///
+/// let err = firmware::parse_srecord("S1070100010203046E\n").unwrap_err();
+/// assert_eq!(err.to_string(), "Line 1, column 17: checksum 6E should be ED");
+/// ```
+pub fn parse_srecord(text: &str) -> Result<MemoryImage, Box<dyn Error>> {
+    let mut image = MemoryImage::new();
+    let mut data_records: u32 = 0;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let record = line.trim();
+        if record.is_empty() {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let mut chars = record.chars();
+        if chars.next() != Some('S') {
+            return Err(format!(
+                "Line {}, column {}: expected 'S' to start an S-record",
+                number,
+                indent + 1
+            )
+            .into());
+        }
+        let kind = chars.next().unwrap_or(' ');
+        let address_length = match kind {
+            '0' | '1' | '5' | '9' => 2,
+            '2' | '6' | '8' => 3,
+            '3' | '7' => 4,
+            _ => {
+                return Err(format!(
+                    "Line {}, column {}: unknown record type 'S{}'",
+                    number,
+                    indent + 2,
+                    kind
+                )
+                .into());
+            }
+        };
+        let bytes = record_bytes(chars.as_str(), number, indent + 3)?;
+        let Some((&count, rest)) = bytes.split_first() else {
+            return Err(format!("Line {}: record without a byte count", number).into());
+        };
+        if rest.len() != count as usize {
+            return Err(format!(
+                "Line {}: byte count is {} but the record has {} bytes after it",
+                number,
+                count,
+                rest.len()
+            )
+            .into());
+        }
+        if rest.len() < address_length + 1 {
+            return Err(format!(
+                "Line {}: S{} record is too short for its {}-byte address",
+                number, kind, address_length
+            )
+            .into());
+        }
+        let (checksum, fields) = bytes.split_last().expect("records have a byte count");
+        let expected = !fields.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        if *checksum != expected {
+            return Err(format!(
+                "Line {}, column {}: checksum {:02X} should be {:02X}",
+                number,
+                indent + 3 + 2 * fields.len(),
+                checksum,
+                expected
+            )
+            .into());
+        }
+
+        let address = be_address(&fields[1..1 + address_length]);
+        let data = &fields[1 + address_length..];
+        match kind {
+            '1' | '2' | '3' => {
+                image
+                    .write(address, data)
+                    .map_err(|e| format!("Line {}: {}", number, e))?;
+                data_records += 1;
+            }
+            '5' | '6' if address != data_records => {
+                return Err(format!(
+                    "Line {}: record count {} does not match the {} data records before it",
+                    number, address, data_records
+                )
+                .into());
+            }
+            '7' | '8' | '9' => image.start_address = Some(address),
+            _ => {}
+        }
+    }
+    Ok(image)
+}
+
+/// Parses TI-TXT: `@ADDR` lines setting the address, lines of hex bytes, and `q` at the end.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware;
+/// let image = firmware::parse_ti_txt("@F000\n31 40 00 03\n@FFFE\n00 F0\nq\n").unwrap();
+/// assert_eq!(
+///     image.segments().collect::<Vec<_>>(),
+///     vec![(0xf000, &[0x31u8, 0x40, 0x00, 0x03][..]), (0xfffe, &[0x00u8, 0xf0][..])]
+/// );
+/// ```
+pub fn parse_ti_txt(text: &str) -> Result<MemoryImage, Box<dyn Error>> {
+    let mut image = MemoryImage::new();
+    let mut address: Option<u64> = None;
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.eq_ignore_ascii_case("q") {
+            break;
+        }
+        if let Some(digits) = trimmed.strip_prefix('@') {
+            let value = u32::from_str_radix(digits, 16)
+                .map_err(|_| format!("Line {}: invalid address '{}'", number, digits))?;
+            address = Some(value as u64);
+            continue;
+        }
+        let start = address
+            .ok_or_else(|| format!("Line {}: data before the first @address line", number))?;
+        let mut bytes = Vec::new();
+        let mut column = 1;
+        for piece in line.split_inclusive(char::is_whitespace) {
+            let token = piece.trim_end();
+            if !token.is_empty() {
+                if token.len() != 2 || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!(
+                        "Line {}, column {}: expected a hex byte, found '{}'",
+                        number, column, token
+                    )
+                    .into());
+                }
+                bytes.push(u8::from_str_radix(token, 16)?);
+            }
+            column += piece.chars().count();
+        }
+        let start = u32::try_from(start).map_err(|_| {
+            format!(
+                "Line {}: data runs past the end of the 32-bit address space",
+                number
+            )
+        })?;
+        image
+            .write(start, &bytes)
+            .map_err(|e| format!("Line {}: {}", number, e))?;
+        address = Some(start as u64 + bytes.len() as u64);
+    }
+    Ok(image)
+}
+
+fn intel_record(kind: u8, offset: u16, data: &[u8]) -> String {
+    let mut bytes = vec![data.len() as u8];
+    bytes.extend(offset.to_be_bytes());
+    bytes.push(kind);
+    bytes.extend(data);
+    let checksum = bytes
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+        .wrapping_neg();
+    bytes.push(checksum);
+    format!(":{}\n", hex::encode_upper(bytes))
+}
+
+/// Writes a memory image as Intel HEX, with extended linear address records (04) where the
+/// upper 16 bits of the address change and a start linear address record (05).
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware::{self, MemoryImage};
+/// let image = MemoryImage::from_binary(b"\x01\x02\x03\x04", 0x0800_0000).unwrap();
+/// assert_eq!(
+///     firmware::write_intel_hex(&image, 16).unwrap(),
+///     ":020000040800F2\n:0400000001020304F2\n:00000001FF\n"
+/// );
+/// ```
+pub fn write_intel_hex(
+    image: &MemoryImage,
+    record_length: usize,
+) -> Result<String, Box<dyn Error>> {
+    if !(1..=255).contains(&record_length) {
+        return Err(format!(
+            "Intel HEX records hold 1 to 255 bytes, not {}",
+            record_length
+        )
+        .into());
+    }
+    let mut text = String::new();
+    let mut upper = 0;
+    for (start, data) in image.segments() {
+        let mut position = 0;
+        while position < data.len() {
+            let address = start + position as u32;
+            // A record cannot cross a 64 KiB boundary, as its offset is 16 bits.
+            let length = record_length
+                .min(data.len() - position)
+                .min(0x10000 - (address & 0xffff) as usize);
+            if address >> 16 != upper {
+                upper = address >> 16;
+                text.push_str(&intel_record(0x04, 0, &(upper as u16).to_be_bytes()));
+            }
+            text.push_str(&intel_record(
+                0x00,
+                address as u16,
+                &data[position..position + length],
+            ));
+            position += length;
+        }
+    }
+    if let Some(start) = image.start_address {
+        text.push_str(&intel_record(0x05, 0, &start.to_be_bytes()));
+    }
+    text.push_str(&intel_record(0x01, 0, &[]));
+    Ok(text)
+}
+
+fn srecord(kind: u8, address: u32, address_length: usize, data: &[u8]) -> String {
+    let mut bytes = vec![(address_length + data.len() + 1) as u8];
+    bytes.extend(&address.to_be_bytes()[4 - address_length..]);
+    bytes.extend(data);
+    let checksum = !bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+    bytes.push(checksum);
+    format!("S{}{}\n", kind, hex::encode_upper(bytes))
+}
+
+/// Writes a memory image as S-records, with the shortest addresses that reach its end: S1
+/// and S9 up to 64 KiB, S2 and S8 up to 16 MiB, S3 and S7 above. A record count (S5 or S6)
+/// follows the data.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware::{self, MemoryImage};
+/// let mut image = MemoryImage::from_binary(b"\x01\x02\x03\x04", 0x100).unwrap();
+/// image.start_address = Some(0x100);
+/// assert_eq!(
+///     firmware::write_srecord(&image, 16).unwrap(),
+///     "S0030000FC\nS107010001020304ED\nS5030001FB\nS9030100FB\n"
+/// );
+/// ```
+pub fn write_srecord(image: &MemoryImage, record_length: usize) -> Result<String, Box<dyn Error>> {
+    let end = image
+        .segments()
+        .last()
+        .map_or(0, |(address, data)| address as u64 + data.len() as u64);
+    let highest = end.max(image.start_address.map_or(0, |start| start as u64 + 1));
+    let (address_length, data_kind, start_kind) = match highest {
+        0..=0x10000 => (2, 1, 9),
+        0x10001..=0x100_0000 => (3, 2, 8),
+        _ => (4, 3, 7),
+    };
+    let longest = 255 - address_length - 1;
+    if !(1..=longest).contains(&record_length) {
+        return Err(format!(
+            "S{} records hold 1 to {} bytes, not {}",
+            data_kind, longest, record_length
+        )
+        .into());
+    }
+    let mut text = srecord(0, 0, 2, &[]);
+    let mut count: u32 = 0;
+    for (start, data) in image.segments() {
+        for (index, chunk) in data.chunks(record_length).enumerate() {
+            let address = start + (index * record_length) as u32;
+            text.push_str(&srecord(data_kind, address, address_length, chunk));
+            count += 1;
+        }
+    }
+    if count <= 0xffff {
+        text.push_str(&srecord(5, count, 2, &[]));
+    } else if count <= 0xff_ffff {
+        text.push_str(&srecord(6, count, 3, &[]));
+    }
+    text.push_str(&srecord(
+        start_kind,
+        image.start_address.unwrap_or(0),
+        address_length,
+        &[],
+    ));
+    Ok(text)
+}
+
+/// Writes a memory image as TI-TXT, 16 bytes per line.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::firmware::{self, MemoryImage};
+/// let image = MemoryImage::from_binary(b"\x31\x40\x00\x03", 0xf000).unwrap();
+/// assert_eq!(firmware::write_ti_txt(&image), "@F000\n31 40 00 03\nq\n");
+/// ```
+pub fn write_ti_txt(image: &MemoryImage) -> String {
+    let mut text = String::new();
+    for (address, data) in image.segments() {
+        text.push_str(&format!("@{:04X}\n", address));
+        for line in data.chunks(16) {
+            let bytes: Vec<String> = line.iter().map(|byte| format!("{:02X}", byte)).collect();
+            text.push_str(&bytes.join(" "));
+            text.push('\n');
+        }
+    }
+    text.push_str("q\n");
+    text
+}
+
+/// Parses a hex address such as `08000000` or `0x08000000`.
+pub fn parse_address(s: &str) -> Result<u32, String> {
+    let digits = s.trim();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+    u32::from_str_radix(digits, 16)
+        .map_err(|_| format!("Invalid address '{}', expected up to 8 hex digits", s))
+}
+
+/// Parses a hex fill byte such as `ff` or `0x00`.
+pub fn parse_fill(s: &str) -> Result<u8, String> {
+    parse_address(s)
+        .ok()
+        .and_then(|value| u8::try_from(value).ok())
+        .ok_or_else(|| format!("Invalid fill byte '{}', expected 2 hex digits", s))
+}
+
+#[cfg(test)]
+mod tests_firmware {
+    use super::*;
+
+    #[test]
+    fn it_parses_intel_hex_addressing() {
+        // Extended segment addressing: 0x1000 * 16 + 0x0010.
+        let image =
+            parse_intel_hex(":020000021000EC\n:02001000AABB89\n:0400000312340005AE\n:00000001FF\n")
+                .unwrap();
+        assert_eq!(
+            image.segments().collect::<Vec<_>>(),
+            vec![(0x10010, &[0xaau8, 0xbb][..])]
+        );
+        assert_eq!(image.start_address, Some(0x12345));
+
+        let err = parse_intel_hex(":00000001FF\n:0400000001020304F2\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 2: record after the end of file record"
+        );
+        let err = parse_intel_hex("\n  :04000000010203G4F2\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 2, column 18: 'G' is not a hex digit");
+        let err = parse_intel_hex(":0500000001020304F2\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 1: byte count is 5 but the record has 4 data bytes"
+        );
+        let err = parse_intel_hex(":00000006FA\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 1, column 8: unknown record type 06");
+        let err = parse_intel_hex(":020000000102FB\n:0100010005F9\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 2: Overlapping data at address 0x00000001"
+        );
+    }
+
+    #[test]
+    fn it_round_trips_every_format() {
+        let mut image = MemoryImage::new();
+        image.write(0x0000_fff8, &[0x11; 20]).unwrap();
+        image
+            .write(0x0002_0000, &(0..=255).collect::<Vec<u8>>())
+            .unwrap();
+        image.start_address = Some(0x0000_fff8);
+        let options = FirmwareOptions::default();
+        for format in [
+            FirmwareFormat::IntelHex,
+            FirmwareFormat::SRecord,
+            FirmwareFormat::TiTxt,
+        ] {
+            let written = write_firmware(&image, format, &options).unwrap();
+            assert_eq!(detect_format(&written), format);
+            let parsed = parse_firmware(&written, format, &options).unwrap();
+            assert_eq!(
+                parsed.segments().collect::<Vec<_>>(),
+                image.segments().collect::<Vec<_>>()
+            );
+            if format != FirmwareFormat::TiTxt {
+                assert_eq!(parsed.start_address, image.start_address);
+            }
+        }
+        // The record crossing 0x10000 is split at the boundary.
+        let intel = write_intel_hex(&image, 16).unwrap();
+        assert!(intel.starts_with(":08FFF800111111111111111179\n:020000040001F9\n"));
+        let srecord = write_srecord(&image, 16).unwrap();
+        assert!(srecord.starts_with("S0030000FC\nS21400FFF8"));
+        assert!(srecord.ends_with("S5030012EA\nS80400FFF804\n"));
+    }
+
+    #[test]
+    fn it_fills_gaps_in_binaries() {
+        let image = parse_intel_hex(":0100000001FE\n:0100040002F9\n").unwrap();
+        let options = FirmwareOptions {
+            fill: 0x00,
+            ..Default::default()
+        };
+        assert_eq!(
+            write_firmware(&image, FirmwareFormat::Binary, &options).unwrap(),
+            vec![0x01, 0x00, 0x00, 0x00, 0x02]
+        );
+
+        let mut image = MemoryImage::new();
+        image.write(0, &[0]).unwrap();
+        image.write(0xffff_ffff, &[0]).unwrap();
+        assert!(image.to_binary(0xff).is_err());
+        assert!(image.write(0xffff_ffff, &[0, 0]).is_err());
+        assert_eq!(parse_fill("0xFF"), Ok(0xff));
+        assert!(parse_fill("100").is_err());
+    }
+}
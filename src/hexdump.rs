@@ -0,0 +1,450 @@
+//! Hexdumps in the layouts of `xxd` and `hexdump -C`, and parsing of dumps from xxd,
+//! hexdump, Wireshark and tcpdump back into bytes.
+
+use std::{error::Error, str};
+
+/// Largest parsed dump, so that a stray offset cannot allocate gigabytes of padding.
+const MAX_SIZE: usize = 256 * 1024 * 1024;
+
+/// ANSI colors of the byte classes in colorized dumps.
+const COLOR_NUL: &str = "\x1b[90m";
+const COLOR_PRINTABLE: &str = "\x1b[32m";
+const COLOR_CONTROL: &str = "\x1b[33m";
+const COLOR_HIGH: &str = "\x1b[31m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+/// Layouts of a hexdump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HexdumpStyle {
+    /// `00000000: 4865 6c6c 6f0a  Hello.`, as written by `xxd`.
+    #[default]
+    Xxd,
+    /// `00000000  48 65 6c 6c 6f 0a  |Hello.|`, as written by `hexdump -C`, with repeated
+    /// lines squeezed into `*` and the length on the last line.
+    Canonical,
+}
+
+impl str::FromStr for HexdumpStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "xxd" => Ok(HexdumpStyle::Xxd),
+            "canonical" | "hexdump" | "c" => Ok(HexdumpStyle::Canonical),
+            _ => Err(format!(
+                "Unknown hexdump style '{}', expected one of: xxd, canonical",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for [`hexdump`].
+#[derive(Debug, Clone)]
+pub struct HexdumpOptions {
+    pub style: HexdumpStyle,
+    /// Bytes per line.
+    pub columns: usize,
+    /// Bytes per group: written without spaces between them for xxd, and followed by an extra
+    /// space for canonical. `None` takes 2 for xxd and 8 for canonical, 0 puts the whole line
+    /// in one group.
+    pub group: Option<usize>,
+    /// Colors NUL, printable, control and non-ASCII bytes with ANSI escapes.
+    pub color: bool,
+}
+
+impl Default for HexdumpOptions {
+    fn default() -> Self {
+        HexdumpOptions {
+            style: HexdumpStyle::Xxd,
+            columns: 16,
+            group: None,
+            color: false,
+        }
+    }
+}
+
+/// The ANSI color of a byte's class.
+fn byte_color(byte: u8) -> &'static str {
+    match byte {
+        0 => COLOR_NUL,
+        b' '..=b'~' => COLOR_PRINTABLE,
+        0x01..=0x1f | 0x7f => COLOR_CONTROL,
+        _ => COLOR_HIGH,
+    }
+}
+
+/// Appends text to a line, in the color of the byte when colors are on.
+fn push_colored(line: &mut String, text: &str, byte: u8, color: bool) {
+    if color {
+        line.push_str(byte_color(byte));
+        line.push_str(text);
+        line.push_str(COLOR_RESET);
+    } else {
+        line.push_str(text);
+    }
+}
+
+/// Formats bytes as a hexdump.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::hexdump::{self, HexdumpOptions, HexdumpStyle};
+/// let dump = hexdump::hexdump(b"Hello, world!\n", &HexdumpOptions::default()).unwrap();
+/// assert_eq!(dump, "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.");
+///
+/// let options = HexdumpOptions {
+///     style: HexdumpStyle::Canonical,
+///     columns: 8,
+///     ..Default::default()
+/// };
+/// let dump = hexdump::hexdump(b"Hello", &options).unwrap();
+/// assert_eq!(dump, "00000000  48 65 6c 6c 6f           |Hello|\n00000005");
+/// ```
+pub fn hexdump(data: &[u8], options: &HexdumpOptions) -> Result<String, Box<dyn Error>> {
+    if options.columns == 0 {
+        return Err("Columns must be at least 1".into());
+    }
+    let columns = options.columns;
+    let default_group = match options.style {
+        HexdumpStyle::Xxd => 2,
+        HexdumpStyle::Canonical => 8,
+    };
+    let group = match options.group.unwrap_or(default_group) {
+        0 => columns,
+        group => group.min(columns),
+    };
+    let hex_width = match options.style {
+        HexdumpStyle::Xxd => columns * 2 + columns.div_ceil(group) - 1,
+        HexdumpStyle::Canonical => columns * 3 + columns.div_ceil(group),
+    };
+
+    let mut lines = Vec::new();
+    let mut previous: Option<&[u8]> = None;
+    let mut squeezing = false;
+    for (index, chunk) in data.chunks(columns).enumerate() {
+        if options.style == HexdumpStyle::Canonical {
+            if previous == Some(chunk) && chunk.len() == columns {
+                if !squeezing {
+                    lines.push("*".to_string());
+                    squeezing = true;
+                }
+                continue;
+            }
+            previous = Some(chunk);
+            squeezing = false;
+        }
+
+        let offset = index * columns;
+        let mut line = match options.style {
+            HexdumpStyle::Xxd => format!("{:08x}: ", offset),
+            HexdumpStyle::Canonical => format!("{:08x}  ", offset),
+        };
+        let mut width = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            let hex = format!("{:02x}", byte);
+            match options.style {
+                HexdumpStyle::Xxd => {
+                    if i > 0 && i % group == 0 {
+                        line.push(' ');
+                        width += 1;
+                    }
+                    push_colored(&mut line, &hex, byte, options.color);
+                    width += 2;
+                }
+                HexdumpStyle::Canonical => {
+                    push_colored(&mut line, &hex, byte, options.color);
+                    line.push(' ');
+                    width += 3;
+                    if (i + 1) % group == 0 {
+                        line.push(' ');
+                        width += 1;
+                    }
+                }
+            }
+        }
+        line.push_str(&" ".repeat(hex_width - width));
+
+        let mut gutter = String::new();
+        for &byte in chunk {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            push_colored(&mut gutter, c.encode_utf8(&mut [0; 4]), byte, options.color);
+        }
+        match options.style {
+            HexdumpStyle::Xxd => line.push_str(&format!("  {}", gutter)),
+            HexdumpStyle::Canonical => line.push_str(&format!("|{}|", gutter)),
+        }
+        lines.push(line);
+    }
+    if options.style == HexdumpStyle::Canonical && !data.is_empty() {
+        lines.push(format!("{:08x}", data.len()));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Splits a leading offset off a dump line: `00000010:` of xxd, `0x0010:` of tcpdump, or an
+/// offset followed by two spaces as in hexdump and Wireshark. Once earlier lines had offsets, a
+/// line with only an offset is taken as one too, like the length at the end of `hexdump -C`.
+fn split_offset(line: &str, had_offsets: bool) -> (Option<usize>, &str) {
+    let end = line.find(char::is_whitespace).unwrap_or(line.len());
+    let (token, rest) = line.split_at(end);
+    let parse = |digits: &str| {
+        // 64-bit WinDbg addresses are written as 00000000`0012ff40.
+        let digits = digits.trim_start_matches("0x").replace('`', "");
+        (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| usize::from_str_radix(&digits, 16).ok())
+            .flatten()
+    };
+    let offset = match token.strip_suffix(':') {
+        Some(digits) => parse(digits),
+        None if token.len() >= 4 && rest.starts_with("  ") => parse(token),
+        None if had_offsets && rest.trim().is_empty() => parse(token),
+        None => None,
+    };
+    match offset {
+        Some(offset) => (Some(offset), rest),
+        None => (None, line),
+    }
+}
+
+/// Reads the hex bytes of a dump line up to the ASCII gutter. The gutter is found where it
+/// starts with `|`, where a field is not hex, or after a gap of two spaces when the rest of
+/// the line has exactly one character per byte read.
+fn parse_hex_fields(fields: &str, number: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    let mut rest = fields;
+    loop {
+        let trimmed = rest.trim_start();
+        let gap = rest.len() - trimmed.len();
+        rest = trimmed;
+        if rest.is_empty() || rest.starts_with('|') {
+            break;
+        }
+        if gap >= 2 && !bytes.is_empty() && rest.chars().count() == bytes.len() {
+            break;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = &rest[..end];
+        // WinDbg joins the two halves of a line with a dash, as in `07 08-09 0a`.
+        let decoded = if token.split('-').all(|pair| pair.len() == 2) {
+            hex::decode(token.replace('-', ""))
+        } else {
+            hex::decode(token)
+        };
+        match decoded {
+            Ok(decoded) => bytes.extend(decoded),
+            Err(_) if !bytes.is_empty() => break,
+            Err(_) => {
+                return Err(format!("Line {}: '{}' is not a hexdump", number, token).into());
+            }
+        }
+        rest = &rest[end..];
+    }
+    Ok(bytes)
+}
+
+/// Parses a hexdump back into bytes, like `xxd -r`.
+///
+/// Reads the layouts of xxd, `xxd -p`, `hexdump -C` (including `*` for repeated lines),
+/// Wireshark's hex and ASCII dumps, `tcpdump -X` and WinDbg's `db`. Offsets are relative to
+/// the first line, so a dump copied from the middle of a file does not start with padding.
+/// Gaps between offsets are filled with zeros.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::hexdump;
+/// let xxd = "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.";
+/// assert_eq!(hexdump::parse_hexdump(xxd).unwrap(), b"Hello, world!\n");
+///
+/// let wireshark = "0000   48 65 6c 6c 6f   Hello";
+/// assert_eq!(hexdump::parse_hexdump(wireshark).unwrap(), b"Hello");
+/// ```
+pub fn parse_hexdump(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut base = None;
+    let mut previous = Vec::new();
+    let mut repeat = false;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_start();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.trim() == "*" {
+            repeat = true;
+            continue;
+        }
+        let (offset, fields) = split_offset(line, base.is_some());
+        let bytes = parse_hex_fields(fields, index + 1)?;
+        match offset {
+            Some(offset) => {
+                let base = *base.get_or_insert(offset);
+                let position = offset.checked_sub(base).ok_or_else(|| {
+                    format!(
+                        "Line {}: offset {:x} is before the first line",
+                        index + 1,
+                        offset
+                    )
+                })?;
+                if position.saturating_add(bytes.len()) > MAX_SIZE {
+                    return Err(format!(
+                        "Line {}: offset {:x} is past the largest dump of {} bytes",
+                        index + 1,
+                        offset,
+                        MAX_SIZE
+                    )
+                    .into());
+                }
+                if repeat && !previous.is_empty() {
+                    while data.len() < position {
+                        let take = previous.len().min(position - data.len());
+                        data.extend_from_slice(&previous[..take]);
+                    }
+                }
+                if data.len() < position + bytes.len() {
+                    data.resize(position + bytes.len(), 0);
+                }
+                data[position..position + bytes.len()].copy_from_slice(&bytes);
+            }
+            None => data.extend_from_slice(&bytes),
+        }
+        repeat = false;
+        if !bytes.is_empty() {
+            previous = bytes;
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests_hexdump {
+    use super::*;
+
+    const DATA: &[u8] = b"Hello, world!\n\x00\x01\xff";
+
+    #[test]
+    fn it_dumps_like_xxd() {
+        assert_eq!(
+            hexdump(DATA, &HexdumpOptions::default()).unwrap(),
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 0001  Hello, world!...\n00000010: ff                                       ."
+        );
+        let options = HexdumpOptions {
+            columns: 20,
+            group: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            hexdump(b"abcdefghijklmnopq", &options).unwrap(),
+            "00000000: 616263 646566 676869 6a6b6c 6d6e6f 7071         abcdefghijklmnopq"
+        );
+        let options = HexdumpOptions {
+            group: Some(0),
+            columns: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            hexdump(b"abc", &options).unwrap(),
+            "00000000: 616263    abc"
+        );
+        let options = HexdumpOptions {
+            columns: 0,
+            ..Default::default()
+        };
+        assert!(hexdump(DATA, &options).is_err());
+    }
+
+    #[test]
+    fn it_dumps_like_hexdump_canonical() {
+        let options = HexdumpOptions {
+            style: HexdumpStyle::Canonical,
+            ..Default::default()
+        };
+        assert_eq!(
+            hexdump(DATA, &options).unwrap(),
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |Hello, world!...|\n00000010  ff                                                |.|\n00000011"
+        );
+        let mut data = vec![0u8; 64];
+        data.push(b'A');
+        assert_eq!(
+            hexdump(&data, &options).unwrap(),
+            "00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|\n*\n00000040  41                                                |A|\n00000041"
+        );
+        assert_eq!(
+            parse_hexdump(&hexdump(&data, &options).unwrap()).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn it_colors_byte_classes() {
+        let options = HexdumpOptions {
+            color: true,
+            ..Default::default()
+        };
+        let dump = hexdump(b"A\0", &options).unwrap();
+        assert_eq!(
+            dump,
+            format!(
+                "00000000: \x1b[32m41\x1b[0m\x1b[90m00\x1b[0m{}\x1b[32mA\x1b[0m\x1b[90m.\x1b[0m",
+                " ".repeat(37)
+            )
+        );
+    }
+
+    #[test]
+    fn it_parses_dumps_back() {
+        for options in [
+            HexdumpOptions::default(),
+            HexdumpOptions {
+                columns: 7,
+                group: Some(4),
+                ..Default::default()
+            },
+            HexdumpOptions {
+                style: HexdumpStyle::Canonical,
+                columns: 12,
+                ..Default::default()
+            },
+        ] {
+            let dump = hexdump(DATA, &options).unwrap();
+            assert_eq!(parse_hexdump(&dump).unwrap(), DATA, "{}", dump);
+        }
+
+        // Wireshark, with the old double space in the middle and a gutter that looks like hex.
+        let wireshark = "0000  ca fe ba be 00 00 00 00  00 00 00 00 00 00 00 00   ................\n0010  63 61 66 65                                        cafe\n";
+        let mut expected = vec![0xca, 0xfe, 0xba, 0xbe];
+        expected.extend([0; 12]);
+        expected.extend(b"cafe");
+        assert_eq!(parse_hexdump(wireshark).unwrap(), expected);
+
+        // tcpdump -X, starting in the middle of a packet.
+        let tcpdump = "\t0x0010:  4500 003c  E..<\n\t0x0014:  1c46       .F";
+        assert_eq!(
+            parse_hexdump(tcpdump).unwrap(),
+            [0x45, 0x00, 0x00, 0x3c, 0x1c, 0x46]
+        );
+
+        // WinDbg db, with a 64-bit address.
+        let windbg =
+            "00000000`0012ff40  01 02 03 04 05 06 07 08-09 0a 0b 0c 0d 0e 0f 10  ................";
+        assert_eq!(
+            parse_hexdump(windbg).unwrap(),
+            (1..=16).collect::<Vec<u8>>()
+        );
+
+        // xxd -p
+        assert_eq!(
+            parse_hexdump("48656c6c6f2c\n20776f726c64\n").unwrap(),
+            b"Hello, world"
+        );
+
+        assert!(parse_hexdump("Frame 1: 60 bytes").is_err());
+        assert!(parse_hexdump("00000010: 41\n00000000: 42").is_err());
+        assert!(parse_hexdump("00000000: 41\nffffffff: 42").is_err());
+    }
+}
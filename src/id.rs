@@ -0,0 +1,783 @@
+//! Decoding, generation and prediction of UUIDs, ULIDs, MongoDB ObjectIds and Snowflake IDs.
+//!
+//! Many identifiers leak when and where they were made: UUIDv1 and v6 carry a timestamp and
+//! a node (often a MAC address), UUIDv7 and ULID a millisecond timestamp, ObjectIds a
+//! timestamp, machine and counter, and Snowflakes a timestamp and worker. Sequential schemes
+//! can be predicted with [`next_ids`] for authorised enumeration testing.
+
+use crate::epoch::{self, EpochFormat};
+use chrono::{DateTime, Offset, Utc};
+use rand::Rng;
+use std::{error::Error, str};
+
+/// 100 nanosecond intervals between the UUID epoch, 1582-10-15, and the Unix epoch.
+const UUID_EPOCH_OFFSET: i128 = 122_192_928_000_000_000;
+
+/// Largest number of identifiers generated or predicted at once.
+pub const MAX_COUNT: usize = 65536;
+
+/// Crockford's base32 alphabet used by ULIDs.
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The epoch a Snowflake ID counts milliseconds from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnowflakeEpoch {
+    /// Twitter (X): 2010-11-04T01:42:54.657Z, with datacenter and worker fields.
+    Twitter,
+    /// Discord: 2015-01-01T00:00:00Z, with worker and process fields.
+    Discord,
+    /// Any other epoch, in Unix milliseconds.
+    Custom(i64),
+}
+
+impl SnowflakeEpoch {
+    /// The epoch in Unix milliseconds.
+    pub fn millis(&self) -> i64 {
+        match self {
+            SnowflakeEpoch::Twitter => 1_288_834_974_657,
+            SnowflakeEpoch::Discord => 1_420_070_400_000,
+            SnowflakeEpoch::Custom(millis) => *millis,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            SnowflakeEpoch::Twitter => "Twitter Snowflake".to_string(),
+            SnowflakeEpoch::Discord => "Discord Snowflake".to_string(),
+            SnowflakeEpoch::Custom(millis) => format!("Snowflake with epoch {} ms", millis),
+        }
+    }
+
+    /// Names of the two 5 bit fields between the timestamp and the sequence.
+    fn field_names(&self) -> (&'static str, &'static str) {
+        match self {
+            SnowflakeEpoch::Discord => ("worker", "process"),
+            _ => ("datacenter", "worker"),
+        }
+    }
+}
+
+impl str::FromStr for SnowflakeEpoch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "twitter" | "x" => Ok(SnowflakeEpoch::Twitter),
+            "discord" => Ok(SnowflakeEpoch::Discord),
+            millis => millis.parse().map(SnowflakeEpoch::Custom).map_err(|_| {
+                format!(
+                    "Unknown Snowflake epoch '{}', expected twitter, discord or Unix milliseconds",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+/// Kinds of identifier that [`generate_id`] can make.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdKind {
+    /// Time-based UUID with a random multicast node instead of a MAC address.
+    UuidV1,
+    /// Random UUID.
+    UuidV4,
+    /// Time-based UUID with the timestamp in sortable order.
+    UuidV6,
+    /// Unix millisecond time-based UUID.
+    UuidV7,
+    Ulid,
+    ObjectId,
+    Snowflake,
+}
+
+impl str::FromStr for IdKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "uuidv1" | "uuid1" => Ok(IdKind::UuidV1),
+            "uuid" | "uuidv4" | "uuid4" => Ok(IdKind::UuidV4),
+            "uuidv6" | "uuid6" => Ok(IdKind::UuidV6),
+            "uuidv7" | "uuid7" => Ok(IdKind::UuidV7),
+            "ulid" => Ok(IdKind::Ulid),
+            "objectid" | "oid" => Ok(IdKind::ObjectId),
+            "snowflake" => Ok(IdKind::Snowflake),
+            _ => Err(format!(
+                "Unknown ID kind '{}', expected one of: uuid-v1, uuid-v4, uuid-v6, uuid-v7, ulid, objectid, snowflake",
+                s
+            )),
+        }
+    }
+}
+
+/// A parsed identifier, as its raw bits.
+enum ParsedId {
+    Uuid(u128),
+    Ulid(u128),
+    ObjectId([u8; 12]),
+    Snowflake(u64),
+}
+
+fn parse_uuid(data: &str) -> Option<u128> {
+    let lower = data.to_ascii_lowercase();
+    let data = lower.strip_prefix("urn:uuid:").unwrap_or(&lower);
+    let data = data
+        .strip_prefix('{')
+        .and_then(|d| d.strip_suffix('}'))
+        .unwrap_or(data);
+    let hyphens_ok = match data.len() {
+        32 => !data.contains('-'),
+        36 => data
+            .char_indices()
+            .all(|(i, c)| (c == '-') == matches!(i, 8 | 13 | 18 | 23)),
+        _ => false,
+    };
+    let digits = data.replace('-', "");
+    if !hyphens_ok || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u128::from_str_radix(&digits, 16).ok()
+}
+
+fn parse_ulid(data: &str) -> Option<u128> {
+    if data.len() != 26 {
+        return None;
+    }
+    let mut value: u128 = 0;
+    for (i, c) in data.to_ascii_uppercase().bytes().enumerate() {
+        let c = match c {
+            b'I' | b'L' => b'1',
+            b'O' => b'0',
+            c => c,
+        };
+        let digit = CROCKFORD.iter().position(|d| *d == c)? as u128;
+        // The first character only holds 3 of the 130 bits.
+        if i == 0 && digit > 7 {
+            return None;
+        }
+        value = value << 5 | digit;
+    }
+    Some(value)
+}
+
+fn parse_id(data: &str) -> Result<ParsedId, Box<dyn Error>> {
+    let data = data.trim();
+    if data.len() == 24 && data.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let mut bytes = [0u8; 12];
+        hex::decode_to_slice(data, &mut bytes)?;
+        return Ok(ParsedId::ObjectId(bytes));
+    }
+    if let Some(uuid) = parse_uuid(data) {
+        return Ok(ParsedId::Uuid(uuid));
+    }
+    if let Some(ulid) = parse_ulid(data) {
+        return Ok(ParsedId::Ulid(ulid));
+    }
+    if !data.is_empty() && data.bytes().all(|b| b.is_ascii_digit()) {
+        // Snowflakes are signed 64-bit integers, so the top bit is always clear.
+        return data
+            .parse::<u64>()
+            .ok()
+            .filter(|id| *id <= i64::MAX as u64)
+            .map(ParsedId::Snowflake)
+            .ok_or_else(|| format!("Snowflake ID '{}' does not fit in 63 bits", data).into());
+    }
+    Err(format!(
+        "Unrecognised ID '{}', expected a UUID, ULID, ObjectId or Snowflake",
+        data
+    )
+    .into())
+}
+
+fn format_uuid(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn format_ulid(ulid: u128) -> String {
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[(ulid >> (5 * i) & 0x1f) as usize] as char)
+        .collect()
+}
+
+fn format_millis(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .map(|date| epoch::format_datetime(&date, Utc.fix()))
+        .unwrap_or_else(|| "out of range".to_string())
+}
+
+fn uuid_version(uuid: u128) -> u8 {
+    (uuid >> 76 & 0xf) as u8
+}
+
+/// The 60 bit timestamp of a version 1 or 6 UUID, in 100 nanosecond intervals since 1582-10-15.
+fn uuid_timestamp(uuid: u128) -> u64 {
+    let (first, middle, last) = (
+        (uuid >> 96) as u64,
+        (uuid >> 80 & 0xffff) as u64,
+        (uuid >> 64 & 0xfff) as u64,
+    );
+    match uuid_version(uuid) {
+        6 => first << 28 | middle << 12 | last,
+        _ => last << 48 | middle << 32 | first,
+    }
+}
+
+/// Replaces the timestamp of a version 1 or 6 UUID.
+fn with_uuid_timestamp(uuid: u128, timestamp: u64) -> u128 {
+    let timestamp = timestamp as u128 & 0x0fff_ffff_ffff_ffff;
+    let version = uuid_version(uuid) as u128;
+    let time_fields = match version {
+        6 => (timestamp >> 12) << 16 | version << 12 | (timestamp & 0xfff),
+        _ => {
+            (timestamp & 0xffff_ffff) << 32
+                | (timestamp >> 32 & 0xffff) << 16
+                | version << 12
+                | timestamp >> 48
+        }
+    };
+    time_fields << 64 | (uuid & u64::MAX as u128)
+}
+
+fn uuid_timestamp_date(timestamp: u64) -> String {
+    let nanos = (timestamp as i128 - UUID_EPOCH_OFFSET) * 100;
+    DateTime::from_timestamp(
+        nanos.div_euclid(1_000_000_000) as i64,
+        nanos.rem_euclid(1_000_000_000) as u32,
+    )
+    .map(|date| epoch::format_datetime(&date, Utc.fix()))
+    .unwrap_or_else(|| "out of range".to_string())
+}
+
+fn decode_uuid(uuid: u128) -> Vec<(&'static str, String)> {
+    let version = uuid_version(uuid);
+    let variant = (uuid >> 61 & 0x7) as u8;
+    let mut fields = vec![("type", "UUID".to_string())];
+    if uuid == 0 || uuid == u128::MAX {
+        fields.push((
+            "version",
+            format!("{} UUID", if uuid == 0 { "Nil" } else { "Max" }),
+        ));
+        return fields;
+    }
+    let description = match version {
+        1 => "time-based",
+        2 => "DCE security",
+        3 => "name-based, MD5",
+        4 => "random",
+        5 => "name-based, SHA-1",
+        6 => "reordered time-based",
+        7 => "Unix time-based",
+        8 => "custom",
+        _ => "unknown",
+    };
+    fields.push(("version", format!("{} ({})", version, description)));
+    let variant = match variant {
+        0..=3 => "NCS (reserved)",
+        4..=5 => "RFC 9562",
+        6 => "Microsoft (reserved)",
+        _ => "future (reserved)",
+    };
+    fields.push(("variant", variant.to_string()));
+    match version {
+        1 | 6 => {
+            let timestamp = uuid_timestamp(uuid);
+            let node = (uuid & 0xffff_ffff_ffff) as u64;
+            let mac: Vec<String> = node.to_be_bytes()[2..]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            // A set multicast bit marks a random node rather than a real MAC address.
+            let source = if node >> 40 & 1 == 1 {
+                "random"
+            } else {
+                "MAC address"
+            };
+            fields.push(("timestamp", uuid_timestamp_date(timestamp)));
+            fields.push(("timestamp (100 ns since 1582)", timestamp.to_string()));
+            fields.push(("clock sequence", (uuid >> 48 & 0x3fff).to_string()));
+            fields.push(("node", format!("{} ({})", mac.join(":"), source)));
+        }
+        7 => {
+            let millis = (uuid >> 80) as i64;
+            fields.push(("timestamp", format_millis(millis)));
+            fields.push(("timestamp (ms)", millis.to_string()));
+            fields.push((
+                "random",
+                format!(
+                    "{:03x}{:016x}",
+                    uuid >> 64 & 0xfff,
+                    uuid as u64 & 0x3fff_ffff_ffff_ffff
+                ),
+            ));
+        }
+        3..=5 | 8 => {
+            let bits = uuid & !(0xf << 76) & !(0x3 << 62);
+            fields.push(("data", format!("{:032x}", bits)));
+        }
+        _ => {}
+    }
+    fields
+}
+
+fn decode_ulid(ulid: u128) -> Vec<(&'static str, String)> {
+    let millis = (ulid >> 80) as i64;
+    vec![
+        ("type", "ULID".to_string()),
+        ("timestamp", format_millis(millis)),
+        ("timestamp (ms)", millis.to_string()),
+        ("random", format!("{:020x}", ulid & ((1 << 80) - 1))),
+    ]
+}
+
+fn decode_object_id(bytes: &[u8; 12]) -> Vec<(&'static str, String)> {
+    let oid = hex::encode(bytes);
+    let timestamp = epoch::epoch_to_datetime(&oid, EpochFormat::ObjectId)
+        .map(|date| epoch::format_datetime(&date, Utc.fix()))
+        .unwrap_or_else(|_| "out of range".to_string());
+    vec![
+        ("type", "MongoDB ObjectId".to_string()),
+        ("timestamp", timestamp),
+        (
+            "timestamp (s)",
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).to_string(),
+        ),
+        ("random", hex::encode(&bytes[4..9])),
+        ("legacy machine", hex::encode(&bytes[4..7])),
+        (
+            "legacy process id",
+            u16::from_be_bytes([bytes[7], bytes[8]]).to_string(),
+        ),
+        (
+            "counter",
+            u32::from_be_bytes([0, bytes[9], bytes[10], bytes[11]]).to_string(),
+        ),
+    ]
+}
+
+fn decode_snowflake(
+    id: u64,
+    epoch: SnowflakeEpoch,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let millis = ((id >> 22) as i64)
+        .checked_add(epoch.millis())
+        .ok_or("Snowflake timestamp is out of range for the epoch")?;
+    let (first, second) = epoch.field_names();
+    Ok(vec![
+        ("type".to_string(), epoch.name()),
+        ("timestamp".to_string(), format_millis(millis)),
+        ("timestamp (ms)".to_string(), millis.to_string()),
+        (first.to_string(), (id >> 17 & 0x1f).to_string()),
+        (second.to_string(), (id >> 12 & 0x1f).to_string()),
+        ("sequence".to_string(), (id & 0xfff).to_string()),
+    ])
+}
+
+/// Returns the milliseconds from the Snowflake epoch to `now`, or zero before the epoch.
+fn snowflake_millis(epoch: SnowflakeEpoch, now: DateTime<Utc>) -> Result<u64, Box<dyn Error>> {
+    let millis = now
+        .timestamp_millis()
+        .checked_sub(epoch.millis())
+        .ok_or("Snowflake timestamp is out of range for the epoch")?;
+    Ok(millis.max(0) as u64)
+}
+
+/// Builds a Snowflake from its timestamp and sequence, checking that it fits in 63 bits.
+fn snowflake_id(millis: u64, sequence: u64) -> Result<u64, Box<dyn Error>> {
+    millis
+        .checked_shl(22)
+        .filter(|shifted| *shifted >> 22 == millis && *shifted <= i64::MAX as u64)
+        .map(|shifted| shifted | sequence)
+        .ok_or_else(|| "Snowflake timestamp is out of range for the epoch".into())
+}
+
+/// Checks that a count of identifiers is at most [`MAX_COUNT`].
+fn check_count(count: usize) -> Result<(), Box<dyn Error>> {
+    if count > MAX_COUNT {
+        return Err(format!("Count {} is above the maximum of {}", count, MAX_COUNT).into());
+    }
+    Ok(())
+}
+
+/// Decodes a UUID, ULID, ObjectId or Snowflake ID into named fields.
+///
+/// Snowflakes are read with the given epoch, or with both the Twitter and Discord epochs.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::id;
+/// let fields = id::decode_id("01ARZ3NDEKTSV4RRFFQ69G5FAV", None).unwrap();
+/// assert_eq!(fields[0], ("type".to_string(), "ULID".to_string()));
+/// assert_eq!(fields[1].1, "2016-07-30T23:54:10.259Z");
+/// let fields = id::decode_id("175928847299117063", Some(id::SnowflakeEpoch::Discord)).unwrap();
+/// assert_eq!(fields[1].1, "2016-04-30T11:18:25.796Z");
+/// ```
+pub fn decode_id(
+    data: &str,
+    snowflake: Option<SnowflakeEpoch>,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let named = |fields: Vec<(&str, String)>| {
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    };
+    Ok(match parse_id(data)? {
+        ParsedId::Uuid(uuid) => named(decode_uuid(uuid)),
+        ParsedId::Ulid(ulid) => named(decode_ulid(ulid)),
+        ParsedId::ObjectId(bytes) => named(decode_object_id(&bytes)),
+        ParsedId::Snowflake(id) => match snowflake {
+            Some(epoch) => decode_snowflake(id, epoch)?,
+            None => {
+                let mut fields = decode_snowflake(id, SnowflakeEpoch::Twitter)?;
+                fields.extend(decode_snowflake(id, SnowflakeEpoch::Discord)?);
+                fields
+            }
+        },
+    })
+}
+
+/// Generates a new identifier of the given kind at time `now`. Snowflakes use the given
+/// epoch with all worker fields and the sequence at zero.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::id::{self, IdKind, SnowflakeEpoch};
+/// let now = chrono::DateTime::from_timestamp(1700000000, 0).unwrap();
+/// let uuid = id::generate_id(IdKind::UuidV7, SnowflakeEpoch::Twitter, now).unwrap();
+/// assert!(uuid.starts_with("018bcfe5-6800-7"));
+/// let snowflake = id::generate_id(IdKind::Snowflake, SnowflakeEpoch::Discord, now).unwrap();
+/// assert_eq!(snowflake, "1174109840998400000");
+/// ```
+pub fn generate_id(
+    kind: IdKind,
+    snowflake: SnowflakeEpoch,
+    now: DateTime<Utc>,
+) -> Result<String, Box<dyn Error>> {
+    let mut rng = rand::thread_rng();
+    let millis = now.timestamp_millis();
+    let ticks = (now.timestamp() as i128 * 10_000_000
+        + now.timestamp_subsec_nanos() as i128 / 100
+        + UUID_EPOCH_OFFSET) as u64;
+    // The variant bits and a random clock sequence and node with the multicast bit set.
+    let clock_and_node = |rng: &mut rand::rngs::ThreadRng| {
+        1 << 63
+            | (rng.r#gen::<u64>() & 0x3fff_0000_0000_0000)
+            | 1 << 40
+            | rng.r#gen::<u64>() & 0xffff_ffff_ffff
+    };
+    Ok(match kind {
+        IdKind::UuidV1 | IdKind::UuidV6 => {
+            let version: u128 = if kind == IdKind::UuidV1 { 1 } else { 6 };
+            let uuid = version << 76 | clock_and_node(&mut rng) as u128;
+            format_uuid(with_uuid_timestamp(uuid, ticks))
+        }
+        IdKind::UuidV4 => {
+            format_uuid(rng.r#gen::<u128>() & !(0xf << 76) & !(0x3 << 62) | 4 << 76 | 2 << 62)
+        }
+        IdKind::UuidV7 => {
+            let random = rng.r#gen::<u128>() & ((1 << 76) - 1) & !(0x3 << 62);
+            format_uuid((millis as u128) << 80 | 7 << 76 | 2 << 62 | random)
+        }
+        IdKind::Ulid => format_ulid((millis as u128) << 80 | rng.r#gen::<u128>() & ((1 << 80) - 1)),
+        IdKind::ObjectId => {
+            let mut bytes = [0u8; 12];
+            bytes[..4].copy_from_slice(&(now.timestamp() as u32).to_be_bytes());
+            rng.fill(&mut bytes[4..]);
+            hex::encode(bytes)
+        }
+        IdKind::Snowflake => snowflake_id(snowflake_millis(snowflake, now)?, 0)?.to_string(),
+    })
+}
+
+/// Generates `count` identifiers of the given kind at time `now`. Snowflakes made in the
+/// same millisecond increment the 12-bit sequence and move on to the next millisecond
+/// when it runs out, like a generator would, so every Snowflake is unique.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::id::{self, IdKind, SnowflakeEpoch};
+/// let now = chrono::DateTime::from_timestamp(1700000000, 0).unwrap();
+/// let ids = id::generate_ids(IdKind::Snowflake, SnowflakeEpoch::Discord, now, 2).unwrap();
+/// assert_eq!(ids, ["1174109840998400000", "1174109840998400001"]);
+/// ```
+pub fn generate_ids(
+    kind: IdKind,
+    snowflake: SnowflakeEpoch,
+    now: DateTime<Utc>,
+    count: usize,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    check_count(count)?;
+    if kind != IdKind::Snowflake {
+        return (0..count)
+            .map(|_| generate_id(kind, snowflake, now))
+            .collect();
+    }
+    let millis = snowflake_millis(snowflake, now)?;
+    (0..count as u64)
+        .map(|i| Ok(snowflake_id(millis + i / 4096, i % 4096)?.to_string()))
+        .collect()
+}
+
+/// Predicts the `count` identifiers that a sequential generator makes after `data`.
+///
+/// UUIDv1 and v6 advance the timestamp by one tick, UUIDv7 and ULID increment the random
+/// part like monotonic generators, ObjectIds increment the counter and Snowflakes the
+/// sequence. Random and name-based UUIDs cannot be predicted.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::id;
+/// let next = id::next_ids("507f1f77bcf86cd799439011", 2).unwrap();
+/// assert_eq!(next, ["507f1f77bcf86cd799439012", "507f1f77bcf86cd799439013"]);
+/// ```
+pub fn next_ids(data: &str, count: usize) -> Result<Vec<String>, Box<dyn Error>> {
+    check_count(count)?;
+    let parsed = parse_id(data)?;
+    let mut ids = Vec::with_capacity(count);
+    match parsed {
+        ParsedId::Uuid(uuid) => {
+            let version = uuid_version(uuid);
+            for i in 1..=count as u128 {
+                let next = match version {
+                    1 | 6 => with_uuid_timestamp(uuid, uuid_timestamp(uuid).wrapping_add(i as u64)),
+                    7 => {
+                        // Treat the timestamp and both random fields as one counter.
+                        let counter = (uuid >> 80) << 74
+                            | (uuid >> 64 & 0xfff) << 62
+                            | uuid & 0x3fff_ffff_ffff_ffff;
+                        let counter = counter.wrapping_add(i) & ((1 << 122) - 1);
+                        (counter >> 74) << 80
+                            | 7 << 76
+                            | (counter >> 62 & 0xfff) << 64
+                            | 2 << 62
+                            | counter & 0x3fff_ffff_ffff_ffff
+                    }
+                    _ => {
+                        return Err(format!(
+                            "UUID version {} is not sequential and cannot be predicted",
+                            version
+                        )
+                        .into());
+                    }
+                };
+                ids.push(format_uuid(next));
+            }
+        }
+        ParsedId::Ulid(ulid) => {
+            for i in 1..=count as u128 {
+                let next = ulid.checked_add(i).ok_or("ULID overflow")?;
+                ids.push(format_ulid(next));
+            }
+        }
+        ParsedId::ObjectId(bytes) => {
+            let counter = u32::from_be_bytes([0, bytes[9], bytes[10], bytes[11]]);
+            for i in 1..=u32::try_from(count)? {
+                let mut next = bytes;
+                next[9..]
+                    .copy_from_slice(&(counter.wrapping_add(i) & 0xff_ffff).to_be_bytes()[1..]);
+                ids.push(hex::encode(next));
+            }
+        }
+        ParsedId::Snowflake(id) => {
+            let (mut millis, workers, mut sequence) = (id >> 22, id >> 12 & 0x3ff, id & 0xfff);
+            for _ in 0..count {
+                sequence += 1;
+                if sequence > 0xfff {
+                    millis = millis.checked_add(1).ok_or("Snowflake overflow")?;
+                    sequence = 0;
+                }
+                let next = millis
+                    .checked_shl(22)
+                    .map(|millis| millis | workers << 12 | sequence)
+                    .filter(|next| *next <= i64::MAX as u64)
+                    .ok_or("Snowflake overflow")?;
+                ids.push(next.to_string());
+            }
+        }
+    }
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests_id {
+    use super::*;
+
+    fn field(fields: &[(String, String)], name: &str) -> String {
+        fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn it_decodes_uuids() {
+        let fields = decode_id("c232ab00-9414-11ec-b3c8-9f6bdeced846", None).unwrap();
+        assert_eq!(field(&fields, "version"), "1 (time-based)");
+        assert_eq!(field(&fields, "variant"), "RFC 9562");
+        assert_eq!(field(&fields, "timestamp"), "2022-02-22T19:22:22Z");
+        assert_eq!(field(&fields, "clock sequence"), "13256");
+        assert_eq!(field(&fields, "node"), "9f:6b:de:ce:d8:46 (random)");
+
+        let fields = decode_id("{1EC9414C-232A-6B00-B3C8-9F6BDECED846}", None).unwrap();
+        assert_eq!(field(&fields, "version"), "6 (reordered time-based)");
+        assert_eq!(field(&fields, "timestamp"), "2022-02-22T19:22:22Z");
+
+        let fields = decode_id("017f22e2-79b0-7cc3-98c4-dc0c0c07398f", None).unwrap();
+        assert_eq!(field(&fields, "version"), "7 (Unix time-based)");
+        assert_eq!(field(&fields, "timestamp"), "2022-02-22T19:22:22Z");
+        assert_eq!(field(&fields, "random"), "cc318c4dc0c0c07398f");
+
+        let fields = decode_id("urn:uuid:919108f7-52d1-4320-9bac-f847db4148a8", None).unwrap();
+        assert_eq!(field(&fields, "version"), "4 (random)");
+
+        let fields = decode_id("00000000-0000-0000-0000-000000000000", None).unwrap();
+        assert_eq!(field(&fields, "version"), "Nil UUID");
+
+        // Malformed input
+        assert!(decode_id("c232ab00-9414-11ec-b3c89f6b-deced846", None).is_err());
+        assert!(decode_id("not an id", None).is_err());
+    }
+
+    #[test]
+    fn it_decodes_other_ids() {
+        let fields = decode_id("507f1f77bcf86cd799439011", None).unwrap();
+        assert_eq!(field(&fields, "type"), "MongoDB ObjectId");
+        assert_eq!(field(&fields, "timestamp"), "2012-10-17T21:13:27Z");
+        assert_eq!(field(&fields, "legacy machine"), "bcf86c");
+        assert_eq!(field(&fields, "legacy process id"), "55193");
+        assert_eq!(field(&fields, "counter"), "4427793");
+
+        let fields = decode_id("1541815603606036480", Some(SnowflakeEpoch::Twitter)).unwrap();
+        assert_eq!(field(&fields, "timestamp"), "2022-06-28T16:07:40.105Z");
+        assert_eq!(field(&fields, "datacenter"), "11");
+        assert_eq!(field(&fields, "worker"), "26");
+        assert_eq!(field(&fields, "sequence"), "0");
+
+        // Both epochs without a hint.
+        let fields = decode_id("175928847299117063", None).unwrap();
+        assert_eq!(fields.iter().filter(|(n, _)| n == "type").count(), 2);
+
+        assert!(decode_id("01ARZ3NDEKTSV4RRFFQ69G5FA", None).is_err());
+        assert!(decode_id("99999999999999999999", None).is_err());
+        assert!(decode_id("175928847299117063", Some(SnowflakeEpoch::Custom(i64::MAX))).is_err());
+        assert!("-x".parse::<SnowflakeEpoch>().is_err());
+    }
+
+    #[test]
+    fn it_generates_ids() {
+        let now = DateTime::from_timestamp(1645557742, 0).unwrap();
+        for kind in [
+            IdKind::UuidV1,
+            IdKind::UuidV4,
+            IdKind::UuidV6,
+            IdKind::UuidV7,
+            IdKind::Ulid,
+            IdKind::ObjectId,
+            IdKind::Snowflake,
+        ] {
+            let id = generate_id(kind, SnowflakeEpoch::Twitter, now).unwrap();
+            let fields = decode_id(&id, Some(SnowflakeEpoch::Twitter)).unwrap();
+            if kind == IdKind::UuidV4 {
+                assert_eq!(field(&fields, "version"), "4 (random)");
+                assert_eq!(field(&fields, "variant"), "RFC 9562");
+            } else {
+                assert_eq!(
+                    field(&fields, "timestamp"),
+                    "2022-02-22T19:22:22Z",
+                    "{:?}",
+                    kind
+                );
+            }
+            if kind == IdKind::UuidV1 {
+                assert!(field(&fields, "node").ends_with("(random)"));
+            }
+        }
+    }
+
+    #[test]
+    fn it_predicts_next_ids() {
+        let next = next_ids("c232ab00-9414-11ec-b3c8-9f6bdeced846", 2).unwrap();
+        assert_eq!(
+            next,
+            [
+                "c232ab01-9414-11ec-b3c8-9f6bdeced846",
+                "c232ab02-9414-11ec-b3c8-9f6bdeced846"
+            ]
+        );
+        let next = next_ids("1ec9414c-232a-6fff-b3c8-9f6bdeced846", 1).unwrap();
+        assert_eq!(next, ["1ec9414c-232b-6000-b3c8-9f6bdeced846"]);
+        let next = next_ids("017f22e2-79b0-7fff-bfff-ffffffffffff", 1).unwrap();
+        assert_eq!(next, ["017f22e2-79b1-7000-8000-000000000000"]);
+        let next = next_ids("01ARZ3NDEKTSV4RRFFQ69G5FAZ", 1).unwrap();
+        assert_eq!(next, ["01ARZ3NDEKTSV4RRFFQ69G5FB0"]);
+        let next = next_ids("507f1f77bcf86cd799ffffff", 1).unwrap();
+        assert_eq!(next, ["507f1f77bcf86cd799000000"]);
+        let next = next_ids("1541815603606040575", 2).unwrap();
+        assert_eq!(next, ["1541815603610230784", "1541815603610230785"]);
+
+        assert!(next_ids("919108f7-52d1-4320-9bac-f847db4148a8", 1).is_err());
+
+        // Malformed input
+        assert_eq!(
+            next_ids("18446744073709551615", 1).unwrap_err().to_string(),
+            "Snowflake ID '18446744073709551615' does not fit in 63 bits"
+        );
+        assert!(next_ids("9223372036854775808", 1).is_err());
+        assert!(next_ids("9223372036854775807", 1).is_err());
+        assert_eq!(
+            next_ids("9223372036854775806", 1).unwrap(),
+            ["9223372036854775807"]
+        );
+        assert!(next_ids("507f1f77bcf86cd799439011", MAX_COUNT + 1).is_err());
+    }
+
+    #[test]
+    fn it_generates_unique_snowflakes() {
+        let now = DateTime::from_timestamp(1645557742, 0).unwrap();
+        let ids = generate_ids(IdKind::Snowflake, SnowflakeEpoch::Twitter, now, 5000).unwrap();
+        let unique: std::collections::HashSet<&String> = ids.iter().collect();
+        assert_eq!(unique.len(), 5000);
+        let last = decode_id(&ids[4999], Some(SnowflakeEpoch::Twitter)).unwrap();
+        assert_eq!(field(&last, "timestamp"), "2022-02-22T19:22:22.001Z");
+        assert_eq!(field(&last, "sequence"), "903");
+        assert_eq!(
+            generate_ids(IdKind::UuidV4, SnowflakeEpoch::Twitter, now, 3)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        // Malformed input
+        assert_eq!(
+            generate_ids(IdKind::UuidV4, SnowflakeEpoch::Twitter, now, MAX_COUNT + 1)
+                .unwrap_err()
+                .to_string(),
+            "Count 65537 is above the maximum of 65536"
+        );
+        assert!(generate_ids(IdKind::Snowflake, SnowflakeEpoch::Custom(i64::MIN), now, 1).is_err());
+        assert!(
+            generate_ids(
+                IdKind::Snowflake,
+                SnowflakeEpoch::Custom(-(1 << 62)),
+                now,
+                1
+            )
+            .is_err()
+        );
+        assert!(generate_id(IdKind::Snowflake, SnowflakeEpoch::Custom(i64::MIN), now).is_err());
+    }
+}
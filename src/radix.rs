@@ -0,0 +1,440 @@
+//! Arbitrary-precision integer conversion between bases, with two's complement and digit grouping.
+//!
+//! The fixed-base helpers in [`crate::encoder`], such as `encode_integer_to_hex`, are
+//! shortcuts for [`convert_integer`], which also handles signed values, bit widths and
+//! digit grouping.
+
+use num_bigint::{BigInt, Sign};
+use std::{error::Error, str};
+
+/// A number base from 2 to 36.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Radix(u32);
+
+impl Radix {
+    pub const BINARY: Radix = Radix(2);
+    pub const OCTAL: Radix = Radix(8);
+    pub const DECIMAL: Radix = Radix(10);
+    pub const HEXADECIMAL: Radix = Radix(16);
+
+    /// Creates a radix, which must be between 2 and 36.
+    pub fn new(radix: u32) -> Result<Radix, Box<dyn Error>> {
+        if !(2..=36).contains(&radix) {
+            return Err(format!("Invalid radix {}, expected 2 to 36", radix).into());
+        }
+        Ok(Radix(radix))
+    }
+
+    /// Returns the numeric value of the radix.
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the `0x`, `0o` or `0b` prefix of the radix, if it has one.
+    pub fn prefix(&self) -> Option<&'static str> {
+        match self.0 {
+            2 => Some("0b"),
+            8 => Some("0o"),
+            16 => Some("0x"),
+            _ => None,
+        }
+    }
+
+    /// Returns the name used in error messages.
+    fn name(&self) -> String {
+        match self.0 {
+            2 => "binary".to_string(),
+            8 => "octal".to_string(),
+            10 => "decimal".to_string(),
+            16 => "hex".to_string(),
+            n => format!("base {}", n),
+        }
+    }
+}
+
+impl Default for Radix {
+    fn default() -> Self {
+        Radix::DECIMAL
+    }
+}
+
+impl str::FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bin" | "binary" => Ok(Radix::BINARY),
+            "oct" | "octal" => Ok(Radix::OCTAL),
+            "dec" | "decimal" | "int" | "integer" => Ok(Radix::DECIMAL),
+            "hex" | "hexadecimal" => Ok(Radix::HEXADECIMAL),
+            other => other
+                .parse::<u32>()
+                .ok()
+                .and_then(|radix| Radix::new(radix).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown radix '{}', expected one of: bin, oct, dec, hex, or a number from 2 to 36",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+/// Options for [`convert_integer`] and [`format_integer`].
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// Base of the input, detected from a `0x`, `0o` or `0b` prefix, or decimal, when `None`.
+    pub from: Option<Radix>,
+    /// Base of the output.
+    pub to: Radix,
+    /// Bit width: values must fit, negative values are written in two's complement in
+    /// non-decimal bases, and binary, octal and hex output is zero-padded to the width.
+    pub bits: Option<usize>,
+    /// Reads the input as a two's complement value of `bits` width, so `ff` at 8 bits is -1.
+    pub signed: bool,
+    /// Splits the digits into groups of this many digits, counted from the right.
+    pub group: Option<usize>,
+    /// Separator between digit groups.
+    pub separator: String,
+    /// Adds the `0x`, `0o` or `0b` prefix to the output.
+    pub prefix: bool,
+    /// Writes digits above 9 in uppercase.
+    pub uppercase: bool,
+    /// Pads the output with zeros to at least this many digits.
+    pub min_digits: usize,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            from: None,
+            to: Radix::DECIMAL,
+            bits: None,
+            signed: false,
+            group: None,
+            separator: " ".to_string(),
+            prefix: false,
+            uppercase: false,
+            min_digits: 0,
+        }
+    }
+}
+
+/// Parses an integer of any size, with an optional sign and `0x`, `0o` or `0b` prefix.
+///
+/// Without `from` the base is detected from the prefix and is decimal otherwise.
+/// Underscores and whitespace between digits are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::radix::{self, Radix};
+/// let value = radix::parse_integer("0xffff_ffff_ffff_ffff", None).unwrap();
+/// assert_eq!(value.to_string(), "18446744073709551615");
+/// let value = radix::parse_integer("-777", Some(Radix::OCTAL)).unwrap();
+/// assert_eq!(value.to_string(), "-511");
+/// ```
+pub fn parse_integer(data: &str, from: Option<Radix>) -> Result<BigInt, Box<dyn Error>> {
+    let cleaned: String = data
+        .chars()
+        .filter(|c| *c != '_' && !c.is_whitespace())
+        .collect();
+    let (negative, unsigned) = match cleaned.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, cleaned.strip_prefix('+').unwrap_or(&cleaned)),
+    };
+    let lower = unsigned.to_ascii_lowercase();
+    let detected = [Radix::HEXADECIMAL, Radix::OCTAL, Radix::BINARY]
+        .into_iter()
+        .find(|radix| {
+            radix
+                .prefix()
+                .is_some_and(|prefix| lower.starts_with(prefix))
+        });
+    let (radix, digits) = match (from, detected) {
+        (None, Some(radix)) => (radix, &unsigned[2..]),
+        (None, None) => (Radix::DECIMAL, unsigned),
+        (Some(radix), Some(detected)) if radix == detected => (radix, &unsigned[2..]),
+        (Some(radix), _) => (radix, unsigned),
+    };
+    let magnitude = BigInt::parse_bytes(digits.as_bytes(), radix.value())
+        .filter(|_| !digits.starts_with(['+', '-']))
+        .ok_or_else(|| format!("Invalid {} number '{}'", radix.name(), data.trim()))?;
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+/// Largest bit width and zero padding, so a mistyped width fails instead of exhausting memory.
+pub const MAX_BITS: usize = 65536;
+
+/// Returns 2 to the power of `bits`, which must be checked with [`check_bits`] first.
+fn power_of_two(bits: usize) -> BigInt {
+    BigInt::from(1) << bits
+}
+
+fn check_bits(bits: usize) -> Result<(), Box<dyn Error>> {
+    if bits == 0 {
+        return Err("Bit width must be at least 1".into());
+    }
+    if bits > MAX_BITS {
+        return Err(format!("Bit width {} is above the maximum of {}", bits, MAX_BITS).into());
+    }
+    Ok(())
+}
+
+/// Whether a value fits in `bits` as an unsigned or two's complement integer.
+fn fits_in_bits(value: &BigInt, bits: usize) -> bool {
+    let bits = bits as u64;
+    match value.sign() {
+        // -2^(bits - 1) is the smallest value, so the magnitude minus one needs fewer bits.
+        Sign::Minus => (value.magnitude() - 1u32).bits() < bits,
+        _ => value.bits() <= bits,
+    }
+}
+
+/// Formats an integer in the requested base.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::radix::{self, ConvertOptions, Radix};
+/// let options = ConvertOptions {
+///     to: Radix::HEXADECIMAL,
+///     bits: Some(16),
+///     group: Some(2),
+///     prefix: true,
+///     ..Default::default()
+/// };
+/// let value = radix::parse_integer("-2", None).unwrap();
+/// assert_eq!(radix::format_integer(&value, &options).unwrap(), "0xff fe");
+/// ```
+pub fn format_integer(value: &BigInt, options: &ConvertOptions) -> Result<String, Box<dyn Error>> {
+    let radix = options.to.value();
+    let mut value = value.clone();
+    let mut min_digits = options.min_digits;
+    if min_digits > MAX_BITS {
+        return Err(format!(
+            "Padding of {} digits is above the maximum of {}",
+            min_digits, MAX_BITS
+        )
+        .into());
+    }
+    if let Some(bits) = options.bits {
+        check_bits(bits)?;
+        if !fits_in_bits(&value, bits) {
+            return Err(format!("{} does not fit in {} bits", value, bits).into());
+        }
+        if radix.is_power_of_two() {
+            let digit_bits = radix.trailing_zeros() as usize;
+            min_digits = min_digits.max(bits.div_ceil(digit_bits));
+        }
+        if value.sign() == Sign::Minus && radix != 10 {
+            value += power_of_two(bits);
+        }
+    }
+
+    let mut digits = value.magnitude().to_str_radix(radix);
+    if options.uppercase {
+        digits.make_ascii_uppercase();
+    }
+    if digits.len() < min_digits {
+        digits = format!("{}{}", "0".repeat(min_digits - digits.len()), digits);
+    }
+    if let Some(size) = options.group.filter(|size| *size > 0) {
+        let first = match digits.len() % size {
+            0 => size,
+            n => n,
+        };
+        let (head, tail) = digits.split_at(first);
+        let mut groups = vec![head];
+        // Digits are ASCII, so chunks of bytes are chunks of digits.
+        groups.extend(
+            tail.as_bytes()
+                .chunks(size)
+                .filter_map(|c| str::from_utf8(c).ok()),
+        );
+        digits = groups.join(&options.separator);
+    }
+
+    let sign = if value.sign() == Sign::Minus { "-" } else { "" };
+    let prefix = match options.to.prefix() {
+        Some(prefix) if options.prefix => prefix,
+        _ => "",
+    };
+    Ok(format!("{}{}{}", sign, prefix, digits))
+}
+
+/// Converts integers of any size between bases, one per line.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::radix::{self, ConvertOptions, Radix};
+/// let options = ConvertOptions {
+///     from: Some(Radix::HEXADECIMAL),
+///     bits: Some(8),
+///     signed: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(radix::convert_integer("ff\n7f", &options).unwrap(), "-1\n127");
+/// ```
+pub fn convert_integer(data: &str, options: &ConvertOptions) -> Result<String, Box<dyn Error>> {
+    let lines: Vec<String> = data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut value = parse_integer(line, options.from)?;
+            if options.signed {
+                let bits = options
+                    .bits
+                    .ok_or("Reading signed input needs a bit width")?;
+                check_bits(bits)?;
+                if value.sign() == Sign::Minus || value.bits() > bits as u64 {
+                    return Err(format!(
+                        "{} is not a {} bit two's complement value",
+                        line.trim(),
+                        bits
+                    )
+                    .into());
+                }
+                if value >= power_of_two(bits - 1) {
+                    value -= power_of_two(bits);
+                }
+            }
+            format_integer(&value, options)
+        })
+        .collect::<Result<_, Box<dyn Error>>>()?;
+    if lines.is_empty() {
+        return Err("No number to convert".into());
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests_radix {
+    use super::*;
+
+    fn options(from: Option<Radix>, to: Radix) -> ConvertOptions {
+        ConvertOptions {
+            from,
+            to,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_parses_radix() {
+        assert_eq!("hex".parse::<Radix>().unwrap(), Radix::HEXADECIMAL);
+        assert_eq!("Integer".parse::<Radix>().unwrap(), Radix::DECIMAL);
+        assert_eq!("36".parse::<Radix>().unwrap().value(), 36);
+
+        // Malformed input
+        assert!("37".parse::<Radix>().is_err());
+        assert!("sexagesimal".parse::<Radix>().is_err());
+    }
+
+    #[test]
+    fn it_parses_integers() {
+        let value = parse_integer("0xDEAD_BEEF", None).unwrap();
+        assert_eq!(value, BigInt::from(0xdeadbeefu32));
+        assert_eq!(parse_integer("0b1010", None).unwrap(), BigInt::from(10));
+        assert_eq!(parse_integer("-0o17", None).unwrap(), BigInt::from(-15));
+        assert_eq!(
+            parse_integer("0b1", Some(Radix::HEXADECIMAL)).unwrap(),
+            BigInt::from(0xb1)
+        );
+        assert_eq!(
+            parse_integer("1111 0000", Some(Radix::BINARY)).unwrap(),
+            BigInt::from(240)
+        );
+
+        // Malformed input
+        assert!(parse_integer("ten", None).is_err());
+        assert!(parse_integer("0x", None).is_err());
+        assert!(parse_integer("--1", None).is_err());
+        assert!(parse_integer("12", Some(Radix::BINARY)).is_err());
+    }
+
+    #[test]
+    fn it_converts_big_integers() {
+        let modulus = "340282366920938463463374607431768211455";
+        let hex = convert_integer(modulus, &options(None, Radix::HEXADECIMAL)).unwrap();
+        assert_eq!(hex, "ffffffffffffffffffffffffffffffff");
+        let back = convert_integer(&format!("0x{}", hex), &options(None, Radix::DECIMAL)).unwrap();
+        assert_eq!(back, modulus);
+    }
+
+    #[test]
+    fn it_converts_twos_complement() {
+        let mut options = options(None, Radix::HEXADECIMAL);
+        options.bits = Some(32);
+        assert_eq!(convert_integer("-1", &options).unwrap(), "ffffffff");
+        assert_eq!(convert_integer("255", &options).unwrap(), "000000ff");
+
+        options.to = Radix::DECIMAL;
+        assert_eq!(convert_integer("-1", &options).unwrap(), "-1");
+
+        options.from = Some(Radix::HEXADECIMAL);
+        options.signed = true;
+        options.bits = Some(16);
+        assert_eq!(
+            convert_integer("8000\n7fff", &options).unwrap(),
+            "-32768\n32767"
+        );
+
+        // Malformed input
+        assert!(convert_integer("10000", &options).is_err());
+        options.signed = false;
+        options.bits = Some(8);
+        assert!(convert_integer("-81", &options).is_err());
+        options.bits = None;
+        options.signed = true;
+        assert!(convert_integer("ff", &options).is_err());
+    }
+
+    #[test]
+    fn it_groups_digits() {
+        let mut options = options(Some(Radix::HEXADECIMAL), Radix::BINARY);
+        options.group = Some(4);
+        assert_eq!(convert_integer("1ff", &options).unwrap(), "1 1111 1111");
+
+        options.to = Radix::HEXADECIMAL;
+        options.group = Some(2);
+        options.separator = ":".to_string();
+        options.uppercase = true;
+        options.prefix = true;
+        options.min_digits = 8;
+        assert_eq!(
+            convert_integer("c0ffee", &options).unwrap(),
+            "0x00:C0:FF:EE"
+        );
+    }
+
+    #[test]
+    fn it_checks_bit_ranges() {
+        let mut options = options(None, Radix::HEXADECIMAL);
+        options.bits = Some(8);
+        assert_eq!(convert_integer("-128", &options).unwrap(), "80");
+        assert_eq!(convert_integer("255", &options).unwrap(), "ff");
+        assert!(convert_integer("-129", &options).is_err());
+        assert!(convert_integer("256", &options).is_err());
+        options.bits = Some(MAX_BITS);
+        assert_eq!(convert_integer("5", &options).unwrap().len(), MAX_BITS / 4);
+
+        // Malformed input
+        options.bits = Some(64_000_000_000);
+        assert_eq!(
+            convert_integer("5", &options).unwrap_err().to_string(),
+            "Bit width 64000000000 is above the maximum of 65536"
+        );
+        options.signed = true;
+        assert!(convert_integer("5", &options).is_err());
+        options.signed = false;
+        options.bits = None;
+        options.min_digits = 64_000_000_000;
+        assert_eq!(
+            convert_integer("5", &options).unwrap_err().to_string(),
+            "Padding of 64000000000 digits is above the maximum of 65536"
+        );
+    }
+}
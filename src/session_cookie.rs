@@ -0,0 +1,725 @@
+//! Decoding, verification, signing and secret brute force of signed session cookies: Flask
+//! (`itsdangerous`), Django signed cookies, Express `s:` cookies and Rails encrypted cookies.
+
+use crate::compression::{self, CompressionFormat};
+use crate::crypto::{self, BlockMode, CipherParams, SymmetricCipher};
+use crate::encoder::{self, ByteFormat, decode_bytes, decode_url, encode_bytes};
+use crate::epoch;
+use crate::wordlist::{self, CrackResult};
+use base64::{
+    Engine as _, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig, general_purpose},
+};
+use chrono::FixedOffset;
+use hmac::Hmac;
+use rand::RngCore;
+use serde_json::Value;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{error::Error, str};
+
+/// URL-safe Base64 engine that accepts values with or without padding.
+const BASE64_URL_LENIENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Alphabet of the base 62 timestamps in Django signed values.
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Iterations of PBKDF2 in Rails' `ActiveSupport::KeyGenerator`.
+const RAILS_ITERATIONS: u32 = 1000;
+
+/// Largest decompressed payload accepted.
+const MAX_PAYLOAD_SIZE: usize = 10 * 1024 * 1024;
+
+/// Web frameworks whose session cookies are supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieFramework {
+    /// `payload.timestamp.signature`, with a `.` prefix when the payload is zlib-compressed.
+    Flask,
+    /// `payload:timestamp:signature`, with a `.` prefix when the payload is zlib-compressed.
+    Django,
+    /// `s:value.signature` from `cookie-signature`, used by `express-session`.
+    Express,
+    /// `ciphertext--iv--tag` in AES-256-GCM, used since Rails 5.2.
+    Rails,
+}
+
+impl CookieFramework {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CookieFramework::Flask => "Flask",
+            CookieFramework::Django => "Django",
+            CookieFramework::Express => "Express",
+            CookieFramework::Rails => "Rails",
+        }
+    }
+
+    /// The salt the framework uses for session cookies.
+    pub fn default_salt(&self) -> &'static str {
+        match self {
+            CookieFramework::Flask => "cookie-session",
+            CookieFramework::Django => "django.contrib.sessions.backends.signed_cookies",
+            CookieFramework::Express => "",
+            CookieFramework::Rails => "authenticated encrypted cookie",
+        }
+    }
+
+    /// The digest the framework uses unless configured otherwise.
+    pub fn default_digest(&self) -> CookieDigest {
+        match self {
+            CookieFramework::Flask => CookieDigest::Sha1,
+            _ => CookieDigest::Sha256,
+        }
+    }
+}
+
+impl str::FromStr for CookieFramework {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "flask" | "itsdangerous" => Ok(CookieFramework::Flask),
+            "django" => Ok(CookieFramework::Django),
+            "express" | "connect" => Ok(CookieFramework::Express),
+            "rails" => Ok(CookieFramework::Rails),
+            _ => Err(format!(
+                "Unknown framework '{}', expected one of: flask, django, express, rails",
+                s
+            )),
+        }
+    }
+}
+
+/// Hash used for the signature, or for the PBKDF2 key derivation of Rails cookies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieDigest {
+    /// Flask's default, Django before 3.1 and Rails before 7.0.
+    Sha1,
+    Sha256,
+}
+
+impl CookieDigest {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CookieDigest::Sha1 => "SHA1",
+            CookieDigest::Sha256 => "SHA256",
+        }
+    }
+
+    fn from_length(length: usize) -> Option<Self> {
+        match length {
+            20 => Some(CookieDigest::Sha1),
+            32 => Some(CookieDigest::Sha256),
+            _ => None,
+        }
+    }
+
+    fn hmac(&self, key: &[u8], input: &[u8]) -> Vec<u8> {
+        match self {
+            CookieDigest::Sha1 => encoder::hmac::<Hmac<Sha1>>(key, input),
+            CookieDigest::Sha256 => encoder::hmac::<Hmac<Sha256>>(key, input),
+        }
+    }
+
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        match self {
+            CookieDigest::Sha1 => Sha1::digest(input).to_vec(),
+            CookieDigest::Sha256 => Sha256::digest(input).to_vec(),
+        }
+    }
+
+    /// PBKDF2 with HMAC of this digest.
+    fn pbkdf2(&self, password: &[u8], salt: &[u8], iterations: u32, length: usize) -> Vec<u8> {
+        let mut key = Vec::with_capacity(length);
+        for block in 1u32.. {
+            if key.len() >= length {
+                break;
+            }
+            let mut u = self.hmac(password, &[salt, &block.to_be_bytes()].concat());
+            let mut t = u.clone();
+            for _ in 1..iterations {
+                u = self.hmac(password, &u);
+                t.iter_mut().zip(&u).for_each(|(t, u)| *t ^= u);
+            }
+            key.extend(t);
+        }
+        key.truncate(length);
+        key
+    }
+}
+
+impl str::FromStr for CookieDigest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha1" => Ok(CookieDigest::Sha1),
+            "sha256" => Ok(CookieDigest::Sha256),
+            _ => Err(format!("Unknown digest '{}', expected sha1 or sha256", s)),
+        }
+    }
+}
+
+/// Secret key and options for verifying and signing cookies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieSecret {
+    /// Flask's `SECRET_KEY`, Django's `SECRET_KEY`, the Express session secret or Rails'
+    /// `secret_key_base`.
+    pub secret: Vec<u8>,
+    /// The salt, or the framework's default salt when not set.
+    pub salt: Option<String>,
+    /// The digest, or the one implied by the signature (Flask and Django) or each one in
+    /// turn (Rails) when not set.
+    pub digest: Option<CookieDigest>,
+}
+
+/// A decoded session cookie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionCookie {
+    pub framework: CookieFramework,
+    /// The text the signature covers. Empty for Rails, whose tag covers the ciphertext.
+    pub signed_value: String,
+    /// The decoded payload, or the ciphertext for Rails.
+    pub payload: Vec<u8>,
+    /// Whether the payload was zlib-compressed.
+    pub compressed: bool,
+    /// When the cookie was signed, in Unix seconds.
+    pub timestamp: Option<i64>,
+    /// The signature, or the GCM authentication tag for Rails.
+    pub signature: Vec<u8>,
+    /// The GCM nonce for Rails, empty for the others.
+    pub iv: Vec<u8>,
+}
+
+/// Decodes a URL-safe Base64 payload, inflating it when it has the `.` compression prefix.
+fn decode_payload(text: &str) -> Result<(Vec<u8>, bool), Box<dyn Error>> {
+    let (text, compressed) = match text.strip_prefix('.') {
+        Some(text) => (text, true),
+        None => (text, false),
+    };
+    let bytes = BASE64_URL_LENIENT
+        .decode(text)
+        .map_err(|e| format!("Invalid payload encoding: {}", e))?;
+    if !compressed {
+        return Ok((bytes, false));
+    }
+    let bytes = compression::decompress(&bytes, CompressionFormat::Zlib, MAX_PAYLOAD_SIZE)?;
+    Ok((bytes, true))
+}
+
+/// Encodes a payload in URL-safe Base64, compressing it when that makes it shorter, as
+/// `itsdangerous` and Django do.
+fn encode_payload(payload: &[u8]) -> Result<String, Box<dyn Error>> {
+    let compressed = compression::compress(payload, CompressionFormat::Zlib, None)?;
+    Ok(if compressed.len() < payload.len().saturating_sub(1) {
+        format!(".{}", general_purpose::URL_SAFE_NO_PAD.encode(compressed))
+    } else {
+        general_purpose::URL_SAFE_NO_PAD.encode(payload)
+    })
+}
+
+fn decode_base62(text: &str) -> Option<i64> {
+    text.bytes().try_fold(0i64, |value, b| {
+        let digit = BASE62.iter().position(|&c| c == b)?;
+        value.checked_mul(62)?.checked_add(digit as i64)
+    })
+}
+
+fn encode_base62(mut value: i64) -> String {
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push(BASE62[(value % 62) as usize]);
+        value /= 62;
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap_or_default()
+}
+
+/// Takes the value from a `name=value` pair, e.g. a pasted `Cookie` header.
+fn cookie_value(data: &str) -> &str {
+    let data = data.split(';').next().unwrap_or_default().trim();
+    match data.split_once('=') {
+        // Base64 padding is followed by more padding or by Rails' `--` separator.
+        Some((name, value))
+            if !name.is_empty() && !value.is_empty() && !value.starts_with(['=', '-']) =>
+        {
+            value
+        }
+        _ => data,
+    }
+}
+
+/// Decodes a session cookie and detects its framework, without verifying it. Accepts the
+/// value alone or as `name=value`, URL-encoded or not.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::session_cookie::{self, CookieFramework};
+/// let cookie = session_cookie::decode_session_cookie("session=eyJ1c2VyIjoiYWRtaW4ifQ.ZVPxAA.b21YWOCvVNzaNu_2LblI06bRMjw").unwrap();
+/// assert_eq!(cookie.framework, CookieFramework::Flask);
+/// assert_eq!(cookie.payload, br#"{"user":"admin"}"#);
+/// assert_eq!(cookie.timestamp, Some(1700000000));
+/// ```
+pub fn decode_session_cookie(data: &str) -> Result<SessionCookie, Box<dyn Error>> {
+    let data = decode_url(cookie_value(data))?;
+    let invalid =
+        |framework: CookieFramework| format!("Invalid {} session cookie", framework.name());
+    if let Some(signed) = data.strip_prefix("s:") {
+        let (value, signature) = signed
+            .rsplit_once('.')
+            .ok_or_else(|| invalid(CookieFramework::Express))?;
+        return Ok(SessionCookie {
+            framework: CookieFramework::Express,
+            signed_value: value.to_string(),
+            payload: value.as_bytes().to_vec(),
+            compressed: false,
+            timestamp: None,
+            signature: decode_bytes(signature, ByteFormat::Base64)?,
+            iv: Vec::new(),
+        });
+    }
+    if data.contains("--") {
+        let parts = data
+            .split("--")
+            .map(|part| decode_bytes(part, ByteFormat::Base64))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [ciphertext, iv, tag] = <[Vec<u8>; 3]>::try_from(parts).map_err(|_| {
+            "Only AES-256-GCM Rails cookies (ciphertext--iv--tag) are supported".to_string()
+        })?;
+        return Ok(SessionCookie {
+            framework: CookieFramework::Rails,
+            signed_value: String::new(),
+            payload: ciphertext,
+            compressed: false,
+            timestamp: None,
+            signature: tag,
+            iv,
+        });
+    }
+    let (framework, separator) = if data.contains(':') {
+        (CookieFramework::Django, ':')
+    } else {
+        (CookieFramework::Flask, '.')
+    };
+    let (signed, signature) = data
+        .rsplit_once(separator)
+        .ok_or_else(|| invalid(framework))?;
+    let (payload, timestamp) = match (framework, signed.rsplit_once(separator)) {
+        (_, Some(("", _))) | (CookieFramework::Flask, None) => {
+            return Err(invalid(framework).into());
+        }
+        (CookieFramework::Flask, Some((payload, timestamp))) => {
+            let bytes = BASE64_URL_LENIENT
+                .decode(timestamp)
+                .map_err(|_| invalid(framework))?;
+            if bytes.len() > 8 {
+                return Err(invalid(framework).into());
+            }
+            let timestamp = bytes.iter().fold(0i64, |t, &b| t << 8 | b as i64);
+            (payload, Some(timestamp))
+        }
+        (_, Some((payload, timestamp))) => (payload, decode_base62(timestamp)),
+        // Django values signed without a timestamp.
+        (_, None) => (signed, None),
+    };
+    let (payload, compressed) = decode_payload(payload)?;
+    Ok(SessionCookie {
+        framework,
+        signed_value: signed.to_string(),
+        payload,
+        compressed,
+        timestamp,
+        signature: BASE64_URL_LENIENT
+            .decode(signature)
+            .map_err(|e| format!("Invalid signature encoding: {}", e))?,
+        iv: Vec::new(),
+    })
+}
+
+/// Computes the signature of a Flask, Django or Express signed value.
+fn signature(
+    framework: CookieFramework,
+    secret: &[u8],
+    salt: &str,
+    digest: CookieDigest,
+    value: &str,
+) -> Vec<u8> {
+    match framework {
+        // itsdangerous' "hmac" key derivation, as configured by Flask.
+        CookieFramework::Flask => {
+            digest.hmac(&digest.hmac(secret, salt.as_bytes()), value.as_bytes())
+        }
+        // Django's salted_hmac with the signer's salt.
+        CookieFramework::Django => {
+            let key = digest.hash(&[salt.as_bytes(), b"signer", secret].concat());
+            digest.hmac(&key, value.as_bytes())
+        }
+        CookieFramework::Express | CookieFramework::Rails => {
+            CookieDigest::Sha256.hmac(secret, value.as_bytes())
+        }
+    }
+}
+
+/// Derives the AES-256-GCM key of Rails encrypted cookies from `secret_key_base`.
+fn rails_params(secret: &[u8], salt: &str, digest: CookieDigest, iv: &[u8]) -> CipherParams {
+    CipherParams {
+        key: digest.pbkdf2(secret, salt.as_bytes(), RAILS_ITERATIONS, 32),
+        iv: iv.to_vec(),
+        ..Default::default()
+    }
+}
+
+/// Verifies a cookie with a secret and returns the digest that matched and the payload,
+/// decrypted for Rails, or `None` when the secret is wrong or the cookie was modified.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::session_cookie::{self, CookieDigest, CookieSecret};
+/// let cookie = session_cookie::decode_session_cookie("s:abc123.WuWsgCoaXJT7aD4b+hIfn3AKJplSE/8vwcUD60PsccY").unwrap();
+/// let secret = CookieSecret { secret: b"secret".to_vec(), ..Default::default() };
+/// let (digest, payload) = session_cookie::open_session_cookie(&cookie, &secret).unwrap();
+/// assert_eq!((digest, payload.as_slice()), (CookieDigest::Sha256, &b"abc123"[..]));
+/// ```
+pub fn open_session_cookie(
+    cookie: &SessionCookie,
+    secret: &CookieSecret,
+) -> Option<(CookieDigest, Vec<u8>)> {
+    let framework = cookie.framework;
+    let salt = secret
+        .salt
+        .as_deref()
+        .unwrap_or_else(|| framework.default_salt());
+    let digests = match (framework, secret.digest) {
+        (CookieFramework::Express, _) => vec![CookieDigest::Sha256],
+        (_, Some(digest)) => vec![digest],
+        (CookieFramework::Rails, None) => vec![CookieDigest::Sha256, CookieDigest::Sha1],
+        (_, None) => vec![CookieDigest::from_length(cookie.signature.len())?],
+    };
+    digests.into_iter().find_map(|digest| {
+        if framework == CookieFramework::Rails {
+            let params = rails_params(&secret.secret, salt, digest, &cookie.iv);
+            let sealed = [cookie.payload.as_slice(), &cookie.signature].concat();
+            let cipher = SymmetricCipher::Aes(256, BlockMode::Gcm);
+            return crypto::decrypt(cipher, &sealed, &params)
+                .ok()
+                .map(|payload| (digest, payload));
+        }
+        let expected = signature(
+            framework,
+            &secret.secret,
+            salt,
+            digest,
+            &cookie.signed_value,
+        );
+        (expected == cookie.signature).then(|| (digest, cookie.payload.clone()))
+    })
+}
+
+/// Signs or, for Rails, encrypts a payload into a session cookie, with the framework's
+/// default digest unless one is set. JSON payloads are compacted first, as the frameworks
+/// write them; `timestamp` (Unix seconds) is used by Flask and Django.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::session_cookie::{self, CookieFramework, CookieSecret};
+/// let secret = CookieSecret { secret: b"secret".to_vec(), ..Default::default() };
+/// let cookie = session_cookie::sign_session_cookie(
+///     CookieFramework::Flask,
+///     b"{\n  \"user\": \"admin\"\n}",
+///     &secret,
+///     1700000000,
+/// )
+/// .unwrap();
+/// assert_eq!(cookie, "eyJ1c2VyIjoiYWRtaW4ifQ.ZVPxAA.b21YWOCvVNzaNu_2LblI06bRMjw");
+/// ```
+pub fn sign_session_cookie(
+    framework: CookieFramework,
+    payload: &[u8],
+    secret: &CookieSecret,
+    timestamp: i64,
+) -> Result<String, Box<dyn Error>> {
+    let payload = match serde_json::from_slice::<Value>(payload) {
+        Ok(json) => serde_json::to_vec(&json)?,
+        Err(_) => payload.trim_ascii().to_vec(),
+    };
+    let salt = secret
+        .salt
+        .as_deref()
+        .unwrap_or_else(|| framework.default_salt());
+    let digest = secret.digest.unwrap_or_else(|| framework.default_digest());
+    let value = match framework {
+        CookieFramework::Flask => {
+            let bytes = timestamp.to_be_bytes();
+            let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+            format!(
+                "{}.{}",
+                encode_payload(&payload)?,
+                general_purpose::URL_SAFE_NO_PAD.encode(&bytes[start..])
+            )
+        }
+        CookieFramework::Django => {
+            format!("{}:{}", encode_payload(&payload)?, encode_base62(timestamp))
+        }
+        CookieFramework::Express => String::from_utf8(payload)?,
+        CookieFramework::Rails => {
+            let mut iv = [0u8; 12];
+            rand::thread_rng().fill_bytes(&mut iv);
+            let params = rails_params(&secret.secret, salt, digest, &iv);
+            let sealed =
+                crypto::encrypt(SymmetricCipher::Aes(256, BlockMode::Gcm), &payload, &params)?;
+            let (ciphertext, tag) = sealed.split_at(sealed.len() - 16);
+            let base64 = |bytes: &[u8]| String::from_utf8(encode_bytes(bytes, ByteFormat::Base64));
+            return Ok(format!(
+                "{}--{}--{}",
+                base64(ciphertext)?,
+                base64(&iv)?,
+                base64(tag)?
+            ));
+        }
+    };
+    let signature = signature(framework, &secret.secret, salt, digest, &value);
+    Ok(match framework {
+        CookieFramework::Express => format!(
+            "s:{}.{}",
+            value,
+            general_purpose::STANDARD_NO_PAD.encode(signature)
+        ),
+        CookieFramework::Flask => format!(
+            "{}.{}",
+            value,
+            general_purpose::URL_SAFE_NO_PAD.encode(signature)
+        ),
+        _ => format!(
+            "{}:{}",
+            value,
+            general_purpose::URL_SAFE_NO_PAD.encode(signature)
+        ),
+    })
+}
+
+/// Writes a payload for reading: JSON pretty-printed, anything else with escapes.
+fn format_payload(payload: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(payload) {
+        Ok(json) => serde_json::to_string_pretty(&json).unwrap_or_default(),
+        Err(_) => String::from_utf8_lossy(payload).escape_debug().to_string(),
+    }
+}
+
+/// Formats a decoded cookie for reading: the framework, timestamp, signature and its likely
+/// digest, the result of verifying it when a secret is given, and the payload. Rails
+/// payloads are only shown once decrypted with the right secret.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::session_cookie;
+/// let cookie = session_cookie::decode_session_cookie("eyJ1c2VyIjoiYWRtaW4ifQ.ZVPxAA.b21YWOCvVNzaNu_2LblI06bRMjw").unwrap();
+/// let text = session_cookie::format_session_cookie(&cookie, None);
+/// assert!(text.contains("Timestamp: 1700000000 (2023-11-14T22:13:20Z)\n"));
+/// assert!(text.ends_with("Payload:\n{\n  \"user\": \"admin\"\n}"));
+/// ```
+pub fn format_session_cookie(cookie: &SessionCookie, secret: Option<&CookieSecret>) -> String {
+    let mut text = format!("Framework: {}\n", cookie.framework.name());
+    if cookie.compressed {
+        text.push_str("Compressed: yes\n");
+    }
+    if let Some(timestamp) = cookie.timestamp {
+        let date = epoch::epoch_to_datetime(&timestamp.to_string(), epoch::EpochFormat::Unix)
+            .map(|date| epoch::format_datetime(&date, FixedOffset::east_opt(0).unwrap()))
+            .unwrap_or_else(|_| "out of range".to_string());
+        text.push_str(&format!("Timestamp: {} ({})\n", timestamp, date));
+    }
+    if cookie.framework == CookieFramework::Rails {
+        text.push_str(&format!(
+            "Ciphertext: {} bytes\nIV: {}\nAuthentication tag: {}\n",
+            cookie.payload.len(),
+            hex::encode(&cookie.iv),
+            hex::encode(&cookie.signature)
+        ));
+    } else {
+        let digest = match cookie.framework {
+            CookieFramework::Express => Some(CookieDigest::Sha256),
+            _ => CookieDigest::from_length(cookie.signature.len()),
+        };
+        let digest = digest
+            .map(|digest| format!("probably HMAC-{}", digest.name()))
+            .unwrap_or_else(|| "unknown digest".to_string());
+        text.push_str(&format!(
+            "Signature: {} ({} bytes, {})\n",
+            hex::encode(&cookie.signature),
+            cookie.signature.len(),
+            digest
+        ));
+    }
+    let opened = secret.map(|secret| open_session_cookie(cookie, secret));
+    match &opened {
+        Some(Some((digest, _))) => {
+            text.push_str(&format!("Signature check: valid with {}\n", digest.name()))
+        }
+        Some(None) => text.push_str("Signature check: invalid\n"),
+        None => {}
+    }
+    let payload = match (cookie.framework, opened) {
+        (CookieFramework::Rails, Some(Some((_, payload)))) => payload,
+        (CookieFramework::Rails, _) => return text.trim_end().to_string(),
+        _ => cookie.payload.clone(),
+    };
+    text.push_str(&format!("Payload:\n{}", format_payload(&payload)));
+    // Rails 6 and later wrap the session in an envelope with a Base64 message.
+    let message = serde_json::from_slice::<Value>(&payload)
+        .ok()
+        .and_then(|json| json["_rails"]["message"].as_str().map(str::to_string))
+        .and_then(|message| decode_bytes(&message, ByteFormat::Base64).ok());
+    if let Some(message) = message {
+        text.push_str(&format!("\nMessage:\n{}", format_payload(&message)));
+    }
+    text
+}
+
+/// Brute forces the secret of a cookie with a wordlist of one secret per line, with the
+/// threading and results of [`crate::jwt::crack_jwt`]. The salt and digest of `options`
+/// are used, its secret is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::session_cookie::{self, CookieSecret};
+/// let cookie = session_cookie::decode_session_cookie("eyJ1c2VyIjoiYWRtaW4ifQ:1r31eq:ECw2rgiPOD8vcIRjo14kISs_e87q8Auem4zUjhUx1Dg").unwrap();
+/// let result = session_cookie::crack_session_cookie(&cookie, b"password\nsecret\n", &CookieSecret::default(), false, 0);
+/// assert_eq!(result.secrets, vec![b"secret".to_vec()]);
+/// ```
+pub fn crack_session_cookie(
+    cookie: &SessionCookie,
+    wordlist: &[u8],
+    options: &CookieSecret,
+    all_hits: bool,
+    threads: usize,
+) -> CrackResult {
+    wordlist::crack_wordlist(wordlist, all_hits, threads, |candidate| {
+        let secret = CookieSecret {
+            secret: candidate.to_vec(),
+            ..options.clone()
+        };
+        open_session_cookie(cookie, &secret).is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests_session_cookie {
+    use super::*;
+
+    const FLASK: &str = "eyJ1c2VyIjoiYWRtaW4ifQ.ZVPxAA.b21YWOCvVNzaNu_2LblI06bRMjw";
+    const FLASK_COMPRESSED: &str =
+        ".eJyrVsosSc0tVrKKVkokEijp0ERpbC0A8-wy7Q.ZVPxAA.JOxYPXYve8Rq59RueON_7WnjWf4";
+    const DJANGO: &str =
+        "eyJ1c2VyIjoiYWRtaW4ifQ:1r31eq:ECw2rgiPOD8vcIRjo14kISs_e87q8Auem4zUjhUx1Dg";
+    const EXPRESS: &str = "s%3Aabc123.WuWsgCoaXJT7aD4b%2BhIfn3AKJplSE%2F8vwcUD60PsccY";
+    const RAILS: &str = "G9sMh6N0AbXCaTi+Y2oFBrZojCXC7Qdk2EDANlHmGDTjIxrRu2cH4XGn0eA/bkv0JueVUVzBJOR1aBJe+vBJNiRCs3eZYNNPzfQlV/FDe00y+kEBR9AoHA==--AAECAwQFBgcICQoL--pou0DxNmk9dmfZ8U8tqfGg==";
+    const RAILS_SHA1: &str = "AJ51Xjh5VPM2me/aJzSqvQ==--AAECAwQFBgcICQoL--RCJVDAf/cXkBgnVU1TfrPQ==";
+
+    fn secret(secret: &str) -> CookieSecret {
+        CookieSecret {
+            secret: secret.as_bytes().to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_verifies_every_framework() {
+        for (cookie, framework, digest) in [
+            (FLASK, CookieFramework::Flask, CookieDigest::Sha1),
+            (FLASK_COMPRESSED, CookieFramework::Flask, CookieDigest::Sha1),
+            (DJANGO, CookieFramework::Django, CookieDigest::Sha256),
+            (EXPRESS, CookieFramework::Express, CookieDigest::Sha256),
+            (RAILS, CookieFramework::Rails, CookieDigest::Sha256),
+            (RAILS_SHA1, CookieFramework::Rails, CookieDigest::Sha1),
+        ] {
+            let decoded = decode_session_cookie(cookie).unwrap();
+            assert_eq!(decoded.framework, framework);
+            let (found, _) = open_session_cookie(&decoded, &secret("secret")).unwrap();
+            assert_eq!(found, digest, "{}", cookie);
+            assert!(open_session_cookie(&decoded, &secret("wrong")).is_none());
+        }
+
+        let decoded = decode_session_cookie(FLASK_COMPRESSED).unwrap();
+        assert!(decoded.compressed);
+        assert!(decoded.payload.starts_with(br#"{"items":["aaaa"#));
+        let decoded = decode_session_cookie(DJANGO).unwrap();
+        assert_eq!(decoded.timestamp, Some(1700000000));
+
+        let decoded = decode_session_cookie(RAILS).unwrap();
+        assert!(!format_session_cookie(&decoded, None).contains("Payload"));
+        let text = format_session_cookie(&decoded, Some(&secret("secret")));
+        assert!(text.contains("Signature check: valid with SHA256\n"));
+        assert!(text.ends_with("Message:\n{\n  \"user\": \"admin\"\n}"));
+    }
+
+    #[test]
+    fn it_signs_every_framework() {
+        let payload = br#"{"user":"admin"}"#;
+        let key = secret("secret");
+        assert_eq!(
+            sign_session_cookie(CookieFramework::Flask, payload, &key, 1700000000).unwrap(),
+            FLASK
+        );
+        assert_eq!(
+            sign_session_cookie(CookieFramework::Django, payload, &key, 1700000000).unwrap(),
+            DJANGO
+        );
+        assert_eq!(
+            sign_session_cookie(CookieFramework::Express, b"abc123", &key, 0).unwrap(),
+            decode_url(EXPRESS).unwrap()
+        );
+        let compressed = br#"{"items":["aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa","aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa","aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"]}"#;
+        let signed =
+            sign_session_cookie(CookieFramework::Flask, compressed, &key, 1700000000).unwrap();
+        assert!(signed.starts_with(".eJ"));
+        assert_eq!(decode_session_cookie(&signed).unwrap().payload, compressed);
+
+        let signed = sign_session_cookie(CookieFramework::Rails, payload, &key, 0).unwrap();
+        let decoded = decode_session_cookie(&signed).unwrap();
+        let (digest, opened) = open_session_cookie(&decoded, &key).unwrap();
+        assert_eq!(
+            (digest, opened.as_slice()),
+            (CookieDigest::Sha256, &payload[..])
+        );
+
+        let sha256 = CookieSecret {
+            digest: Some(CookieDigest::Sha256),
+            salt: Some("custom".to_string()),
+            ..key
+        };
+        let signed = sign_session_cookie(CookieFramework::Flask, payload, &sha256, 1).unwrap();
+        let decoded = decode_session_cookie(&signed).unwrap();
+        assert_eq!(decoded.signature.len(), 32);
+        assert!(open_session_cookie(&decoded, &sha256).is_some());
+        assert!(open_session_cookie(&decoded, &secret("secret")).is_none());
+    }
+
+    #[test]
+    fn it_cracks_and_rejects_invalid_cookies() {
+        let decoded = decode_session_cookie(&format!("session={}; Path=/", FLASK)).unwrap();
+        let result = crack_session_cookie(
+            &decoded,
+            b"a\nb\nsecret\nc\n",
+            &CookieSecret::default(),
+            true,
+            2,
+        );
+        assert_eq!(result.secrets, vec![b"secret".to_vec()]);
+        assert_eq!(result.tried, 4);
+
+        assert!(decode_session_cookie("hello").is_err());
+        assert!(decode_session_cookie("a.b").is_err());
+        assert!(decode_session_cookie("s:nosignature").is_err());
+        assert!(decode_session_cookie("YQ==--YQ==").is_err());
+        assert!("php".parse::<CookieFramework>().is_err());
+        assert!(decode_base62("!").is_none());
+        assert_eq!(encode_base62(1700000000), "1r31eq");
+    }
+}
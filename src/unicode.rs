@@ -0,0 +1,550 @@
+//! Inspection and transformation of Unicode text: code point listings, normalization,
+//! invisible and bidi control characters, confusable skeletons and look-alike variants.
+
+use std::str;
+use unicode_general_category::{GeneralCategory, get_general_category};
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::confusable_detection;
+
+/// Unicode normalization forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizationForm {
+    /// Canonical composition.
+    Nfc,
+    /// Canonical decomposition.
+    Nfd,
+    /// Compatibility composition, e.g. `ｆｉ` and `ﬁ` become `fi`.
+    Nfkc,
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+impl str::FromStr for NormalizationForm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nfc" => Ok(NormalizationForm::Nfc),
+            "nfd" => Ok(NormalizationForm::Nfd),
+            "nfkc" => Ok(NormalizationForm::Nfkc),
+            "nfkd" => Ok(NormalizationForm::Nfkd),
+            _ => Err(format!(
+                "Unknown normalization form '{}', expected one of: nfc, nfd, nfkc, nfkd",
+                s
+            )),
+        }
+    }
+}
+
+/// Styles of look-alike variants of ASCII letters and digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The Halfwidth and Fullwidth Forms block, for every printable ASCII character.
+    Fullwidth,
+    Bold,
+    Italic,
+    BoldItalic,
+    Script,
+    BoldScript,
+    Fraktur,
+    DoubleStruck,
+    BoldFraktur,
+    SansSerif,
+    SansSerifBold,
+    SansSerifItalic,
+    SansSerifBoldItalic,
+    Monospace,
+}
+
+impl Variant {
+    /// Every variant, fullwidth first and then the styles in the order of the Mathematical
+    /// Alphanumeric Symbols block.
+    pub const ALL: [Variant; 14] = [
+        Variant::Fullwidth,
+        Variant::Bold,
+        Variant::Italic,
+        Variant::BoldItalic,
+        Variant::Script,
+        Variant::BoldScript,
+        Variant::Fraktur,
+        Variant::DoubleStruck,
+        Variant::BoldFraktur,
+        Variant::SansSerif,
+        Variant::SansSerifBold,
+        Variant::SansSerifItalic,
+        Variant::SansSerifBoldItalic,
+        Variant::Monospace,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Fullwidth => "fullwidth",
+            Variant::Bold => "bold",
+            Variant::Italic => "italic",
+            Variant::BoldItalic => "bold-italic",
+            Variant::Script => "script",
+            Variant::BoldScript => "bold-script",
+            Variant::Fraktur => "fraktur",
+            Variant::DoubleStruck => "double-struck",
+            Variant::BoldFraktur => "bold-fraktur",
+            Variant::SansSerif => "sans-serif",
+            Variant::SansSerifBold => "sans-serif-bold",
+            Variant::SansSerifItalic => "sans-serif-italic",
+            Variant::SansSerifBoldItalic => "sans-serif-bold-italic",
+            Variant::Monospace => "monospace",
+        }
+    }
+
+    /// Letters that were encoded in Letterlike Symbols before the Mathematical Alphanumeric
+    /// Symbols block, which leaves a hole at their place.
+    fn letterlike(&self, c: char) -> Option<char> {
+        let letterlike = match (self, c) {
+            (Variant::Italic, 'h') => '\u{210e}',
+            (Variant::Script, 'B') => '\u{212c}',
+            (Variant::Script, 'E') => '\u{2130}',
+            (Variant::Script, 'F') => '\u{2131}',
+            (Variant::Script, 'H') => '\u{210b}',
+            (Variant::Script, 'I') => '\u{2110}',
+            (Variant::Script, 'L') => '\u{2112}',
+            (Variant::Script, 'M') => '\u{2133}',
+            (Variant::Script, 'R') => '\u{211b}',
+            (Variant::Script, 'e') => '\u{212f}',
+            (Variant::Script, 'g') => '\u{210a}',
+            (Variant::Script, 'o') => '\u{2134}',
+            (Variant::Fraktur, 'C') => '\u{212d}',
+            (Variant::Fraktur, 'H') => '\u{210c}',
+            (Variant::Fraktur, 'I') => '\u{2111}',
+            (Variant::Fraktur, 'R') => '\u{211c}',
+            (Variant::Fraktur, 'Z') => '\u{2128}',
+            (Variant::DoubleStruck, 'C') => '\u{2102}',
+            (Variant::DoubleStruck, 'H') => '\u{210d}',
+            (Variant::DoubleStruck, 'N') => '\u{2115}',
+            (Variant::DoubleStruck, 'P') => '\u{2119}',
+            (Variant::DoubleStruck, 'Q') => '\u{211a}',
+            (Variant::DoubleStruck, 'R') => '\u{211d}',
+            (Variant::DoubleStruck, 'Z') => '\u{2124}',
+            _ => return None,
+        };
+        Some(letterlike)
+    }
+
+    /// The first code point of the digits of this style, `None` if it has no digits.
+    fn digits(&self) -> Option<u32> {
+        match self {
+            Variant::Bold => Some(0x1d7ce),
+            Variant::DoubleStruck => Some(0x1d7d8),
+            Variant::SansSerif => Some(0x1d7e2),
+            Variant::SansSerifBold => Some(0x1d7ec),
+            Variant::Monospace => Some(0x1d7f6),
+            _ => None,
+        }
+    }
+
+    /// Maps one character, characters without a variant are returned as they are.
+    fn apply(&self, c: char) -> char {
+        if *self == Variant::Fullwidth {
+            return match c {
+                ' ' => '\u{3000}',
+                '!'..='~' => char::from_u32(c as u32 + 0xfee0).unwrap_or(c),
+                _ => c,
+            };
+        }
+        if let Some(letterlike) = self.letterlike(c) {
+            return letterlike;
+        }
+        // The styles follow each other in the block with 52 letters each.
+        let style = Variant::ALL.iter().position(|v| v == self).unwrap_or(1) as u32 - 1;
+        let code_point = match c {
+            'A'..='Z' => 0x1d400 + style * 52 + (c as u32 - 'A' as u32),
+            'a'..='z' => 0x1d400 + style * 52 + 26 + (c as u32 - 'a' as u32),
+            '0'..='9' => match self.digits() {
+                Some(zero) => zero + (c as u32 - '0' as u32),
+                None => return c,
+            },
+            _ => return c,
+        };
+        char::from_u32(code_point).unwrap_or(c)
+    }
+}
+
+impl str::FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase().replace(['_', ' '], "-");
+        Variant::ALL
+            .into_iter()
+            .find(|variant| variant.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Variant::ALL.iter().map(Variant::name).collect();
+                format!(
+                    "Unknown variant '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A code point with its name and general category.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodePoint {
+    pub char: char,
+    /// The Unicode name, or a label such as `<control>` for code points without one.
+    pub name: String,
+    /// The two letter general category, e.g. `Lu` or `Cf`.
+    pub category: &'static str,
+}
+
+/// The two letter abbreviation of a general category.
+fn category_code(category: GeneralCategory) -> &'static str {
+    match category {
+        GeneralCategory::UppercaseLetter => "Lu",
+        GeneralCategory::LowercaseLetter => "Ll",
+        GeneralCategory::TitlecaseLetter => "Lt",
+        GeneralCategory::ModifierLetter => "Lm",
+        GeneralCategory::OtherLetter => "Lo",
+        GeneralCategory::NonspacingMark => "Mn",
+        GeneralCategory::SpacingMark => "Mc",
+        GeneralCategory::EnclosingMark => "Me",
+        GeneralCategory::DecimalNumber => "Nd",
+        GeneralCategory::LetterNumber => "Nl",
+        GeneralCategory::OtherNumber => "No",
+        GeneralCategory::ConnectorPunctuation => "Pc",
+        GeneralCategory::DashPunctuation => "Pd",
+        GeneralCategory::OpenPunctuation => "Ps",
+        GeneralCategory::ClosePunctuation => "Pe",
+        GeneralCategory::InitialPunctuation => "Pi",
+        GeneralCategory::FinalPunctuation => "Pf",
+        GeneralCategory::OtherPunctuation => "Po",
+        GeneralCategory::MathSymbol => "Sm",
+        GeneralCategory::CurrencySymbol => "Sc",
+        GeneralCategory::ModifierSymbol => "Sk",
+        GeneralCategory::OtherSymbol => "So",
+        GeneralCategory::SpaceSeparator => "Zs",
+        GeneralCategory::LineSeparator => "Zl",
+        GeneralCategory::ParagraphSeparator => "Zp",
+        GeneralCategory::Control => "Cc",
+        GeneralCategory::Format => "Cf",
+        GeneralCategory::Surrogate => "Cs",
+        GeneralCategory::PrivateUse => "Co",
+        _ => "Cn",
+    }
+}
+
+/// Lists the code points of a text with their names and general categories.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// let code_points = unicode::code_points("a\u{200b}");
+/// assert_eq!(code_points[0].name, "LATIN SMALL LETTER A");
+/// assert_eq!(code_points[1].category, "Cf");
+/// ```
+pub fn code_points(text: &str) -> Vec<CodePoint> {
+    text.chars()
+        .map(|c| {
+            let category = category_code(get_general_category(c));
+            let name = match unicode_names2::name(c) {
+                Some(name) => name.to_string(),
+                None => match category {
+                    "Cc" => "<control>",
+                    "Co" => "<private use>",
+                    _ => "<unassigned>",
+                }
+                .to_string(),
+            };
+            CodePoint {
+                char: c,
+                name,
+                category,
+            }
+        })
+        .collect()
+}
+
+/// Formats the code points of a text as a table with one code point per line: the code
+/// point, its category, its UTF-8 bytes, the character itself and its name.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// assert_eq!(
+///     unicode::format_code_points("é"),
+///     "U+00E9   Ll  c3a9      é  LATIN SMALL LETTER E WITH ACUTE"
+/// );
+/// ```
+pub fn format_code_points(text: &str) -> String {
+    code_points(text)
+        .iter()
+        .map(|code_point| {
+            let c = code_point.char;
+            let glyph = match code_point.category {
+                "Mn" | "Mc" | "Me" => format!("\u{25cc}{}", c),
+                "Cc" | "Cf" | "Zs" | "Zl" | "Zp" | "Cn" | "Co" | "Cs" => " ".to_string(),
+                _ => c.to_string(),
+            };
+            let utf8: String = c
+                .encode_utf8(&mut [0; 4])
+                .bytes()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            format!(
+                "{:<9}{:<4}{:<10}{}  {}",
+                format!("U+{:04X}", c as u32),
+                code_point.category,
+                utf8,
+                glyph,
+                code_point.name
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Normalizes a text to the given form.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode::{self, NormalizationForm};
+/// assert_eq!(unicode::normalize("e\u{301}", NormalizationForm::Nfc), "é");
+/// assert_eq!(unicode::normalize("ｓｃｒｉｐｔ", NormalizationForm::Nfkc), "script");
+/// ```
+pub fn normalize(text: &str, form: NormalizationForm) -> String {
+    match form {
+        NormalizationForm::Nfc => text.nfc().collect(),
+        NormalizationForm::Nfd => text.nfd().collect(),
+        NormalizationForm::Nfkc => text.nfkc().collect(),
+        NormalizationForm::Nfkd => text.nfkd().collect(),
+    }
+}
+
+/// A short label for characters that are invisible or reorder the text around them, `None`
+/// for any other character.
+///
+/// Tag characters carry the ASCII character they stand for, as they are used to smuggle
+/// hidden text.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// assert_eq!(unicode::invisible_label('\u{202e}').as_deref(), Some("RLO"));
+/// assert_eq!(unicode::invisible_label('\u{e0061}').as_deref(), Some("TAG a"));
+/// assert_eq!(unicode::invisible_label('a'), None);
+/// ```
+pub fn invisible_label(c: char) -> Option<String> {
+    let label = match c {
+        '\u{00ad}' => "SHY",
+        '\u{034f}' => "CGJ",
+        '\u{061c}' => "ALM",
+        '\u{115f}' | '\u{1160}' | '\u{3164}' | '\u{ffa0}' => "HANGUL FILLER",
+        '\u{180e}' => "MVS",
+        '\u{200b}' => "ZWSP",
+        '\u{200c}' => "ZWNJ",
+        '\u{200d}' => "ZWJ",
+        '\u{200e}' => "LRM",
+        '\u{200f}' => "RLM",
+        '\u{2028}' => "LS",
+        '\u{2029}' => "PS",
+        '\u{202a}' => "LRE",
+        '\u{202b}' => "RLE",
+        '\u{202c}' => "PDF",
+        '\u{202d}' => "LRO",
+        '\u{202e}' => "RLO",
+        '\u{2060}' => "WJ",
+        '\u{2066}' => "LRI",
+        '\u{2067}' => "RLI",
+        '\u{2068}' => "FSI",
+        '\u{2069}' => "PDI",
+        '\u{feff}' => "ZWNBSP",
+        '\u{fe00}'..='\u{fe0f}' => return Some(format!("VS{}", c as u32 - 0xfe00 + 1)),
+        '\u{e0100}'..='\u{e01ef}' => return Some(format!("VS{}", c as u32 - 0xe0100 + 17)),
+        '\u{e0020}'..='\u{e007e}' => {
+            return Some(format!("TAG {}", (c as u32 - 0xe0000) as u8 as char));
+        }
+        '\u{e0001}' | '\u{e007f}' => "TAG",
+        _ if get_general_category(c) == GeneralCategory::Format => "FORMAT",
+        _ => return None,
+    };
+    Some(label.to_string())
+}
+
+/// Replaces invisible and bidi control characters with visible `<U+XXXX LABEL>` markers, and
+/// warns about lines where bidi embeddings, overrides or isolates are left open, which makes
+/// the rest of the line display in another order than it is read (Trojan Source).
+///
+/// Returns the revealed text, the number of characters replaced and the warnings.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// let (revealed, count, warnings) = unicode::reveal_invisible("if admin\u{202e} {");
+/// assert_eq!(revealed, "if admin<U+202E RLO> {");
+/// assert_eq!(count, 1);
+/// assert_eq!(warnings, vec!["Line 1: 1 bidi control is not terminated"]);
+/// ```
+pub fn reveal_invisible(text: &str) -> (String, usize, Vec<String>) {
+    let mut revealed = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut warnings = Vec::new();
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        // Open embeddings and overrides as false, open isolates as true.
+        let mut open: Vec<bool> = Vec::new();
+        for c in line.chars() {
+            match c {
+                '\u{202a}' | '\u{202b}' | '\u{202d}' | '\u{202e}' => open.push(false),
+                '\u{202c}' if open.last() == Some(&false) => {
+                    open.pop();
+                }
+                '\u{2066}'..='\u{2068}' => open.push(true),
+                // Closing an isolate also closes the embeddings opened inside it.
+                '\u{2069}' if open.contains(&true) => while open.pop() == Some(false) {},
+                _ => {}
+            }
+            match invisible_label(c) {
+                Some(label) => {
+                    count += 1;
+                    revealed.push_str(&format!("<U+{:04X} {}>", c as u32, label));
+                }
+                None => revealed.push(c),
+            }
+        }
+        let open = open.len();
+        if open > 0 {
+            warnings.push(format!(
+                "Line {}: {} bidi control{} not terminated",
+                index + 1,
+                open,
+                if open == 1 { " is" } else { "s are" }
+            ));
+        }
+    }
+    (revealed, count, warnings)
+}
+
+/// Maps a text to its confusable skeleton per UTS #39, so that two strings that look alike
+/// have the same skeleton.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// assert_eq!(unicode::skeleton("pаypаl"), unicode::skeleton("paypal"));
+/// ```
+pub fn skeleton(text: &str) -> String {
+    confusable_detection::skeleton(text).collect()
+}
+
+/// Checks whether two strings are confusable, i.e. have the same skeleton.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode;
+/// assert!(unicode::is_confusable("ｐａｙｐａｌ", "paypal"));
+/// assert!(!unicode::is_confusable("paypal", "paypa1x"));
+/// ```
+pub fn is_confusable(a: &str, b: &str) -> bool {
+    skeleton(a) == skeleton(b)
+}
+
+/// Rewrites ASCII letters and digits, or every printable ASCII character for fullwidth, in a
+/// look-alike style.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::unicode::{self, Variant};
+/// assert_eq!(unicode::to_variant("<svg>", Variant::Fullwidth), "＜ｓｖｇ＞");
+/// assert_eq!(unicode::to_variant("alert(1)", Variant::Bold), "𝐚𝐥𝐞𝐫𝐭(𝟏)");
+/// ```
+pub fn to_variant(text: &str, variant: Variant) -> String {
+    text.chars().map(|c| variant.apply(c)).collect()
+}
+
+#[cfg(test)]
+mod tests_unicode {
+    use super::*;
+
+    #[test]
+    fn it_lists_code_points() {
+        let code_points = code_points("A\u{0}\u{301}\u{e000}\u{378}");
+        let summary: Vec<(&str, &str)> = code_points
+            .iter()
+            .map(|cp| (cp.name.as_str(), cp.category))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("LATIN CAPITAL LETTER A", "Lu"),
+                ("<control>", "Cc"),
+                ("COMBINING ACUTE ACCENT", "Mn"),
+                ("<private use>", "Co"),
+                ("<unassigned>", "Cn"),
+            ]
+        );
+        assert_eq!(
+            format_code_points("\u{1f600}\u{200d}"),
+            "U+1F600  So  f09f9880  \u{1f600}  GRINNING FACE\nU+200D   Cf  e2808d       ZERO WIDTH JOINER"
+        );
+    }
+
+    #[test]
+    fn it_normalizes_every_form() {
+        let text = "ﬁ é";
+        assert_eq!(normalize(text, NormalizationForm::Nfc), "ﬁ é");
+        assert_eq!(normalize(text, NormalizationForm::Nfd), "ﬁ e\u{301}");
+        assert_eq!(normalize(text, NormalizationForm::Nfkc), "fi é");
+        assert_eq!(normalize(text, NormalizationForm::Nfkd), "fi e\u{301}");
+        assert!("nfx".parse::<NormalizationForm>().is_err());
+    }
+
+    #[test]
+    fn it_reveals_trojan_source() {
+        let source = "/*\u{202e} } \u{2066}if (isAdmin)\u{2069} \u{2066} begin admins only */\nok\u{200b}\u{e0068}\u{e0069}";
+        let (revealed, count, warnings) = reveal_invisible(source);
+        assert_eq!(
+            revealed,
+            "/*<U+202E RLO> } <U+2066 LRI>if (isAdmin)<U+2069 PDI> <U+2066 LRI> begin admins only */\nok<U+200B ZWSP><U+E0068 TAG h><U+E0069 TAG i>"
+        );
+        assert_eq!(count, 7);
+        assert_eq!(warnings, vec!["Line 1: 2 bidi controls are not terminated"]);
+
+        let (_, _, warnings) = reveal_invisible("\u{202b}x\u{202c} \u{2067}y\u{2069}");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn it_finds_confusables() {
+        assert_eq!(skeleton("ɑdmin"), skeleton("admin"));
+        assert!(is_confusable("Ꭺpple", "Apple"));
+        assert!(is_confusable("𝐚𝐝𝐦𝐢𝐧", "admin"));
+        assert!(!is_confusable("admin", "root"));
+    }
+
+    #[test]
+    fn it_generates_variants_that_normalize_back() {
+        let payload = "The quick brown fox jumps over the lazy dog 0123456789";
+        for variant in Variant::ALL {
+            let styled = to_variant(payload, variant);
+            assert_ne!(styled, payload, "{}", variant.name());
+            assert_eq!(
+                normalize(&styled, NormalizationForm::Nfkc),
+                payload,
+                "{}",
+                variant.name()
+            );
+        }
+        assert_eq!(to_variant("Hh", Variant::Italic), "𝐻ℎ");
+        assert_eq!(
+            "Sans Serif Bold".parse::<Variant>().unwrap(),
+            Variant::SansSerifBold
+        );
+        assert!("comic-sans".parse::<Variant>().is_err());
+    }
+}
@@ -0,0 +1,639 @@
+//! Decoding of ASP.NET ViewState, EventValidation and other blobs written by the
+//! ObjectStateFormatter (and the LosFormatter that wraps it), with legacy MAC verification.
+//!
+//! A blob is `base64(0xff 0x01 object [mac])`, where the object is a tree of typed tokens
+//! and the MAC is an HMAC of the serialized object and a page specific modifier.
+
+use crate::encoder::{ByteFormat, decode_bytes, decode_url, encode_bytes, hmac};
+use crate::epoch::{self, EpochFormat};
+use chrono::FixedOffset;
+use hmac::Hmac;
+use sha1::Sha1;
+use sha2::{Sha256, Sha384, Sha512};
+use std::{error::Error, str};
+
+/// Deepest nesting of containers accepted, so that hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 100;
+
+/// Form fields that hold ObjectStateFormatter blobs.
+const FIELDS: [&str; 3] = ["__VIEWSTATE", "__EVENTVALIDATION", "__CONTROLSTATE"];
+
+/// Algorithms of the `validation` attribute of `<machineKey>` used for the ViewState MAC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewStateMac {
+    /// `MD5` in legacy mode: a plain MD5 of the data, modifier and key.
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl ViewStateMac {
+    /// The algorithm that produces MACs of `length` bytes.
+    pub fn from_length(length: usize) -> Option<Self> {
+        match length {
+            16 => Some(ViewStateMac::Md5),
+            20 => Some(ViewStateMac::Sha1),
+            32 => Some(ViewStateMac::Sha256),
+            48 => Some(ViewStateMac::Sha384),
+            64 => Some(ViewStateMac::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ViewStateMac::Md5 => "MD5",
+            ViewStateMac::Sha1 => "HMAC-SHA1",
+            ViewStateMac::Sha256 => "HMAC-SHA256",
+            ViewStateMac::Sha384 => "HMAC-SHA384",
+            ViewStateMac::Sha512 => "HMAC-SHA512",
+        }
+    }
+}
+
+impl str::FromStr for ViewStateMac {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(ViewStateMac::Md5),
+            "sha1" | "hmacsha1" => Ok(ViewStateMac::Sha1),
+            "sha256" | "hmacsha256" => Ok(ViewStateMac::Sha256),
+            "sha384" | "hmacsha384" => Ok(ViewStateMac::Sha384),
+            "sha512" | "hmacsha512" => Ok(ViewStateMac::Sha512),
+            _ => Err(format!(
+                "Unknown ViewState MAC algorithm '{}', expected one of: md5, sha1, sha256, sha384, sha512",
+                s
+            )),
+        }
+    }
+}
+
+/// Key material for the legacy (pre .NET 4.5) ViewState MAC.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MachineKey {
+    /// The `validationKey` of `<machineKey>`, decoded from hex.
+    pub validation_key: Vec<u8>,
+    /// The `validation` algorithm, guessed from the MAC length when not set.
+    pub algorithm: Option<ViewStateMac>,
+    /// The `__VIEWSTATEGENERATOR` value, the hash of the page's type.
+    pub generator: Option<u32>,
+    /// The page's `ViewStateUserKey`, usually bound to the session.
+    pub user_key: Option<String>,
+}
+
+/// A node of the deserialized object tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateNode {
+    /// The type, e.g. `Pair` or `String`.
+    pub kind: String,
+    /// The value of scalar types.
+    pub value: Option<String>,
+    /// The items of container types.
+    pub children: Vec<StateNode>,
+}
+
+impl StateNode {
+    fn leaf(kind: impl Into<String>, value: impl ToString) -> Self {
+        StateNode {
+            kind: kind.into(),
+            value: Some(value.to_string()),
+            children: Vec::new(),
+        }
+    }
+
+    fn branch(kind: impl Into<String>, children: Vec<StateNode>) -> Self {
+        StateNode {
+            kind: kind.into(),
+            value: None,
+            children,
+        }
+    }
+
+    fn write_to(&self, depth: usize, text: &mut String) {
+        text.push_str(&"  ".repeat(depth));
+        text.push_str(&self.kind);
+        if let Some(value) = &self.value {
+            text.push_str(": ");
+            text.push_str(value);
+        }
+        text.push('\n');
+        for child in &self.children {
+            child.write_to(depth + 1, text);
+        }
+    }
+}
+
+/// A decoded ViewState.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewState {
+    /// The serialized object, including the format marker. This is what the MAC covers.
+    pub data: Vec<u8>,
+    /// The bytes after the object, which are the MAC when one is present.
+    pub mac: Vec<u8>,
+    /// The deserialized object.
+    pub tree: StateNode,
+}
+
+/// Reads ObjectStateFormatter tokens, keeping the string and type tables they refer back to.
+struct Parser<'a> {
+    data: &'a [u8],
+    position: usize,
+    strings: Vec<String>,
+    types: Vec<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("ViewState is truncated at offset {}", self.position))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, Box<dyn Error>> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Box<dyn Error>> {
+        Ok(self.bytes(N)?.try_into()?)
+    }
+
+    /// Reads a 7-bit encoded integer, as written by .NET's `BinaryWriter.Write7BitEncodedInt`.
+    fn encoded_int(&mut self) -> Result<i32, Box<dyn Error>> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value as i32);
+            }
+        }
+        Err(format!("Invalid 7-bit encoded integer at offset {}", self.position).into())
+    }
+
+    fn count(&mut self) -> Result<usize, Box<dyn Error>> {
+        let count = self.encoded_int()?;
+        usize::try_from(count).map_err(|_| format!("Negative count {}", count).into())
+    }
+
+    /// Reads a length prefixed UTF-8 string, as written by `BinaryWriter.Write(string)`.
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let length = self.count()?;
+        Ok(String::from_utf8_lossy(self.bytes(length)?).into_owned())
+    }
+
+    fn type_ref(&mut self) -> Result<String, Box<dyn Error>> {
+        let position = self.position;
+        match self.byte()? {
+            // TypeRefAdd and TypeRefAddLocal
+            0x29 | 0x2a => {
+                let name = self.string()?;
+                self.types.push(name.clone());
+                Ok(name)
+            }
+            // TypeRef
+            0x2b => {
+                let index = self.count()?;
+                self.types
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown type reference {}", index).into())
+            }
+            token => Err(format!(
+                "Expected a type at offset {}, found token 0x{:02x}",
+                position, token
+            )
+            .into()),
+        }
+    }
+
+    fn objects(&mut self, count: usize, depth: usize) -> Result<Vec<StateNode>, Box<dyn Error>> {
+        (0..count).map(|_| self.object(depth)).collect()
+    }
+
+    fn entries(&mut self, kind: &str, depth: usize) -> Result<StateNode, Box<dyn Error>> {
+        let count = self.count()?;
+        let entries = (0..count)
+            .map(|_| Ok(StateNode::branch("Entry", self.objects(2, depth)?)))
+            .collect::<Result<_, Box<dyn Error>>>()?;
+        Ok(StateNode::branch(kind, entries))
+    }
+
+    fn object(&mut self, depth: usize) -> Result<StateNode, Box<dyn Error>> {
+        if depth > MAX_DEPTH {
+            return Err("ViewState is nested too deeply".into());
+        }
+        let depth = depth + 1;
+        let position = self.position;
+        Ok(match self.byte()? {
+            0x01 => StateNode::leaf("Int16", i16::from_le_bytes(self.array()?)),
+            0x02 => StateNode::leaf("Int32", self.encoded_int()?),
+            0x03 => StateNode::leaf("Byte", self.byte()?),
+            0x04 => {
+                let first = self.byte()?;
+                let length = match first.leading_ones() {
+                    0 => 1,
+                    n => n as usize,
+                };
+                let mut bytes = vec![first];
+                bytes.extend_from_slice(self.bytes(length - 1)?);
+                StateNode::leaf("Char", format!("{:?}", String::from_utf8_lossy(&bytes)))
+            }
+            0x05 => StateNode::leaf("String", format!("{:?}", self.string()?)),
+            0x06 => {
+                let binary = i64::from_le_bytes(self.array()?);
+                // The top two bits hold the DateTimeKind, the rest are ticks.
+                let ticks = binary & 0x3fff_ffff_ffff_ffff;
+                let value = epoch::epoch_to_datetime(&ticks.to_string(), EpochFormat::DotNetTicks)
+                    .map(|date| epoch::format_datetime(&date, FixedOffset::east_opt(0).unwrap()))
+                    .unwrap_or_else(|_| ticks.to_string());
+                StateNode::leaf("DateTime", value)
+            }
+            0x07 => StateNode::leaf("Double", f64::from_le_bytes(self.array()?)),
+            0x08 => StateNode::leaf("Single", f32::from_le_bytes(self.array()?)),
+            0x09 => StateNode::leaf(
+                "Color",
+                format!("#{:08X}", u32::from_le_bytes(self.array()?)),
+            ),
+            0x0a => StateNode::leaf("KnownColor", self.encoded_int()?),
+            0x0b => {
+                let name = self.type_ref()?;
+                StateNode::leaf(format!("Enum {}", name), self.encoded_int()?)
+            }
+            0x0c => StateNode::leaf("Color", "empty"),
+            0x0f => StateNode::branch("Pair", self.objects(2, depth)?),
+            0x10 => StateNode::branch("Triplet", self.objects(3, depth)?),
+            0x14 => {
+                let name = self.type_ref()?;
+                let count = self.count()?;
+                StateNode::branch(format!("Array of {}", name), self.objects(count, depth)?)
+            }
+            0x15 => {
+                let count = self.count()?;
+                let strings = (0..count)
+                    .map(|_| Ok(StateNode::leaf("String", format!("{:?}", self.string()?))))
+                    .collect::<Result<_, Box<dyn Error>>>()?;
+                StateNode::branch("StringArray", strings)
+            }
+            0x16 => {
+                let count = self.count()?;
+                StateNode::branch("ArrayList", self.objects(count, depth)?)
+            }
+            0x17 => self.entries("Hashtable", depth)?,
+            0x18 => self.entries("HybridDictionary", depth)?,
+            0x19 => StateNode::leaf("Type", self.type_ref()?),
+            0x1b => {
+                let value = f64::from_le_bytes(self.array()?);
+                let unit = match i32::from_le_bytes(self.array()?) {
+                    1 => "px".to_string(),
+                    2 => "pt".to_string(),
+                    3 => "pc".to_string(),
+                    4 => "in".to_string(),
+                    5 => "mm".to_string(),
+                    6 => "cm".to_string(),
+                    7 => "%".to_string(),
+                    8 => "em".to_string(),
+                    9 => "ex".to_string(),
+                    other => format!(" (unit type {})", other),
+                };
+                StateNode::leaf("Unit", format!("{}{}", value, unit))
+            }
+            0x1c => StateNode::leaf("Unit", "empty"),
+            0x1d => {
+                let version = self.byte()?;
+                if version != 0 {
+                    return Err(format!("Unknown EventValidationStore version {}", version).into());
+                }
+                let count = u32::from_le_bytes(self.array()?) as usize;
+                let hashes = (0..count)
+                    .map(|_| Ok(StateNode::leaf("Hash", hex::encode(self.bytes(16)?))))
+                    .collect::<Result<_, Box<dyn Error>>>()?;
+                StateNode::branch("EventValidationStore", hashes)
+            }
+            0x1e => {
+                let string = self.string()?;
+                self.strings.push(string.clone());
+                StateNode::leaf("String", format!("{:?}", string))
+            }
+            0x1f => {
+                let index = self.byte()? as usize;
+                let string = self
+                    .strings
+                    .get(index)
+                    .ok_or_else(|| format!("Unknown string reference {}", index))?;
+                StateNode::leaf("String", format!("{:?}", string))
+            }
+            0x28 => {
+                let name = self.type_ref()?;
+                StateNode::leaf(
+                    format!("Formatted {}", name),
+                    format!("{:?}", self.string()?),
+                )
+            }
+            0x32 => {
+                let length = self.count()?;
+                StateNode::leaf("BinaryFormatter", hex::encode(self.bytes(length)?))
+            }
+            0x3c => {
+                let name = self.type_ref()?;
+                let length = self.count()?;
+                let count = self.count()?;
+                let items = (0..count)
+                    .map(|_| {
+                        let index = self.count()?;
+                        Ok(StateNode::branch(
+                            format!("[{}]", index),
+                            vec![self.object(depth)?],
+                        ))
+                    })
+                    .collect::<Result<_, Box<dyn Error>>>()?;
+                StateNode::branch(format!("SparseArray of {} [{}]", name, length), items)
+            }
+            0x64 => StateNode::branch("Null", Vec::new()),
+            0x65 => StateNode::leaf("String", "\"\""),
+            0x66 => StateNode::leaf("Int32", 0),
+            0x67 => StateNode::leaf("Boolean", true),
+            0x68 => StateNode::leaf("Boolean", false),
+            token => {
+                return Err(format!("Unknown token 0x{:02x} at offset {}", token, position).into());
+            }
+        })
+    }
+}
+
+/// Finds the ViewState blob in a form body or a `name=value` pair, or takes the input as is.
+fn field_value(data: &str) -> &str {
+    data.split('&')
+        .find_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            FIELDS.contains(&name).then_some(value)
+        })
+        .unwrap_or(data)
+}
+
+/// Decodes and deserializes a base64 ViewState, EventValidation or ControlState value, a
+/// URL encoded one or a form body holding one. Bytes after the object are kept as the MAC.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::viewstate;
+/// let state = viewstate::decode_viewstate("/wEPDwUKMTY3NzE0MjMxNWRk0lwkLc5mq1+s6+wjotDdyG+IDV8=").unwrap();
+/// assert_eq!(state.tree.kind, "Pair");
+/// assert_eq!(state.tree.children[0].children[0].value.as_deref(), Some("\"1677142315\""));
+/// assert_eq!(state.mac.len(), 20);
+/// ```
+pub fn decode_viewstate(data: &str) -> Result<ViewState, Box<dyn Error>> {
+    let value = field_value(data.trim());
+    // Form encoding turns an unescaped `+` into a space, which base64 never contains.
+    let value = decode_url(value)?.replace(' ', "+");
+    let bytes = decode_bytes(&value, ByteFormat::Base64)
+        .map_err(|e| format!("Invalid ViewState encoding: {}", e))?;
+    if !bytes.starts_with(&[0xff, 0x01]) {
+        return Err(
+            "Not an ObjectStateFormatter blob, the ViewState may be encrypted or from .NET 1.x"
+                .into(),
+        );
+    }
+    let mut parser = Parser {
+        data: &bytes,
+        position: 2,
+        strings: Vec::new(),
+        types: Vec::new(),
+    };
+    let tree = parser.object(0)?;
+    let (data, mac) = bytes.split_at(parser.position);
+    Ok(ViewState {
+        data: data.to_vec(),
+        mac: mac.to_vec(),
+        tree,
+    })
+}
+
+/// Parses a `__VIEWSTATEGENERATOR` value, which is a 32-bit hex number.
+pub fn parse_generator(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(s.trim(), 16)
+        .map_err(|_| format!("Invalid ViewState generator '{}', expected 8 hex digits", s))
+}
+
+/// Computes the legacy mode MAC of serialized ViewState data: a hash of the data followed by
+/// the little-endian generator and the UTF-16 `ViewStateUserKey`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::viewstate::{self, MachineKey, ViewStateMac};
+/// let key = MachineKey { validation_key: vec![0x0b; 20], generator: Some(0xCA0B0334), ..Default::default() };
+/// let state = viewstate::decode_viewstate("/wEPDwUKMTY3NzE0MjMxNWRk").unwrap();
+/// let mac = viewstate::compute_viewstate_mac(&state.data, &key, ViewStateMac::Sha1);
+/// assert_eq!(hex::encode(mac), "d25c242dce66ab5facebec23a2d0ddc86f880d5f");
+/// ```
+pub fn compute_viewstate_mac(data: &[u8], key: &MachineKey, algorithm: ViewStateMac) -> Vec<u8> {
+    let mut input = data.to_vec();
+    if let Some(generator) = key.generator {
+        input.extend_from_slice(&generator.to_le_bytes());
+    }
+    if let Some(user_key) = &key.user_key {
+        input.extend(user_key.encode_utf16().flat_map(u16::to_le_bytes));
+    }
+    let key_bytes = &key.validation_key;
+    match algorithm {
+        ViewStateMac::Md5 => {
+            input.extend_from_slice(key_bytes);
+            md5::compute(&input).0.to_vec()
+        }
+        ViewStateMac::Sha1 => hmac::<Hmac<Sha1>>(key_bytes, &input),
+        ViewStateMac::Sha256 => hmac::<Hmac<Sha256>>(key_bytes, &input),
+        ViewStateMac::Sha384 => hmac::<Hmac<Sha384>>(key_bytes, &input),
+        ViewStateMac::Sha512 => hmac::<Hmac<Sha512>>(key_bytes, &input),
+    }
+}
+
+/// Checks the MAC of a ViewState, with the key's algorithm or the one implied by its length.
+pub fn verify_viewstate(state: &ViewState, key: &MachineKey) -> Result<bool, Box<dyn Error>> {
+    if state.mac.is_empty() {
+        return Err("The ViewState has no MAC to verify".into());
+    }
+    let algorithm = key
+        .algorithm
+        .or_else(|| ViewStateMac::from_length(state.mac.len()))
+        .ok_or_else(|| {
+            format!(
+                "Unknown MAC algorithm for {} bytes, use --algorithm",
+                state.mac.len()
+            )
+        })?;
+    Ok(compute_viewstate_mac(&state.data, key, algorithm) == state.mac)
+}
+
+/// Replaces the MAC of a ViewState with one computed with the key, using the key's
+/// algorithm, the one implied by the current MAC, or HMAC-SHA256 (the .NET 4 default).
+/// Returns the signed ViewState in base64.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::viewstate::{self, MachineKey, ViewStateMac};
+/// let key = MachineKey {
+///     validation_key: vec![0x0b; 20],
+///     algorithm: Some(ViewStateMac::Sha1),
+///     generator: Some(0xCA0B0334),
+///     ..Default::default()
+/// };
+/// let state = viewstate::decode_viewstate("/wEPDwUKMTY3NzE0MjMxNWRk").unwrap();
+/// let signed = viewstate::sign_viewstate(&state, &key).unwrap();
+/// assert_eq!(signed, "/wEPDwUKMTY3NzE0MjMxNWRk0lwkLc5mq1+s6+wjotDdyG+IDV8=");
+/// ```
+pub fn sign_viewstate(state: &ViewState, key: &MachineKey) -> Result<String, Box<dyn Error>> {
+    let algorithm = key
+        .algorithm
+        .or_else(|| ViewStateMac::from_length(state.mac.len()))
+        .unwrap_or(ViewStateMac::Sha256);
+    let mut signed = state.data.clone();
+    signed.extend(compute_viewstate_mac(&state.data, key, algorithm));
+    Ok(String::from_utf8(encode_bytes(
+        &signed,
+        ByteFormat::Base64,
+    ))?)
+}
+
+/// Describes a decoded ViewState: its MAC and probable MAC algorithm, the result of
+/// verifying the MAC when a key is given, and the object tree.
+pub fn format_viewstate(
+    state: &ViewState,
+    key: Option<&MachineKey>,
+) -> Result<String, Box<dyn Error>> {
+    let mut text = format!(
+        "Format: ObjectStateFormatter v1\nSize: {} bytes\n",
+        state.data.len()
+    );
+    if state.mac.is_empty() {
+        text.push_str("MAC: absent, EnableViewStateMac is off\n");
+    } else {
+        let algorithm = ViewStateMac::from_length(state.mac.len())
+            .map(|algorithm| format!("probably {}", algorithm.name()))
+            .unwrap_or_else(|| "unknown algorithm".to_string());
+        text.push_str(&format!(
+            "MAC: {} ({} bytes, {})\n",
+            hex::encode(&state.mac),
+            state.mac.len(),
+            algorithm
+        ));
+        if let Some(key) = key {
+            let valid = verify_viewstate(state, key)?;
+            text.push_str(&format!(
+                "MAC check: {}\n",
+                if valid { "valid" } else { "invalid" }
+            ));
+        }
+    }
+    text.push('\n');
+    state.tree.write_to(0, &mut text);
+    text.pop();
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests_viewstate {
+    use super::*;
+
+    fn encode(bytes: &[u8]) -> String {
+        String::from_utf8(encode_bytes(bytes, ByteFormat::Base64)).unwrap()
+    }
+
+    #[test]
+    fn it_parses_every_token() {
+        let mut data = vec![0xff, 0x01, 0x16, 0x10];
+        data.extend([0x01, 0xfe, 0xff]);
+        data.extend([0x02, 0xac, 0x02]);
+        data.extend([0x03, 0x07, 0x04, b'x']);
+        data.extend([0x1e, 0x02, b'i', b'd', 0x1f, 0x00]);
+        data.extend([0x09, 0x00, 0x00, 0xff, 0xff]);
+        data.extend([0x0b, 0x29, 0x01, b'T', 0x03]);
+        data.extend([0x14, 0x2b, 0x00, 0x01, 0x65]);
+        data.extend([0x15, 0x01, 0x01, b'a']);
+        data.extend([0x17, 0x01, 0x66, 0x67]);
+        data.extend([0x1b, 0, 0, 0, 0, 0, 0, 0x59, 0x40, 0x07, 0, 0, 0]);
+        data.extend([0x3c, 0x2b, 0x00, 0x05, 0x01, 0x03, 0x68]);
+        data.extend([0x32, 0x02, 0x00, 0x01]);
+        data.extend([0x06]);
+        data.extend(638355968002500000i64.to_le_bytes());
+        data.push(0x64);
+        let state = decode_viewstate(&encode(&data)).unwrap();
+        assert!(state.mac.is_empty());
+        let text = format_viewstate(&state, None).unwrap();
+        assert!(text.contains("MAC: absent"));
+        assert_eq!(
+            text.split_once("\n\n").unwrap().1,
+            "ArrayList
+  Int16: -2
+  Int32: 300
+  Byte: 7
+  Char: \"x\"
+  String: \"id\"
+  String: \"id\"
+  Color: #FFFF0000
+  Enum T: 3
+  Array of T
+    String: \"\"
+  StringArray
+    String: \"a\"
+  Hashtable
+    Entry
+      Int32: 0
+      Boolean: true
+  Unit: 100%
+  SparseArray of T [5]
+    [3]
+      Boolean: false
+  BinaryFormatter: 0001
+  DateTime: 2023-11-14T22:13:20.250Z
+  Null"
+        );
+    }
+
+    #[test]
+    fn it_verifies_and_signs() {
+        let data = "__VIEWSTATE=%2FwEPDwUKMTY3NzE0MjMxNWRk0lwkLc5mq1%2Bs6%2BwjotDdyG%2BIDV8%3D&__VIEWSTATEGENERATOR=CA0B0334";
+        let state = decode_viewstate(data).unwrap();
+        let mut key = MachineKey {
+            validation_key: vec![0x0b; 20],
+            generator: Some(parse_generator("CA0B0334").unwrap()),
+            ..Default::default()
+        };
+        assert!(verify_viewstate(&state, &key).unwrap());
+        let text = format_viewstate(&state, Some(&key)).unwrap();
+        assert!(text.contains("(20 bytes, probably HMAC-SHA1)\nMAC check: valid\n"));
+
+        key.user_key = Some("user".to_string());
+        assert!(!verify_viewstate(&state, &key).unwrap());
+        key.algorithm = Some(ViewStateMac::Sha256);
+        let signed = decode_viewstate(&sign_viewstate(&state, &key).unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(&signed.mac),
+            "b7f5506f68b78f6bc5d62623c5340c634070c4d879b75f95e7c4b9c60d15134c"
+        );
+        assert!(verify_viewstate(&signed, &key).unwrap());
+    }
+
+    #[test]
+    fn it_rejects_invalid_viewstate() {
+        assert!(decode_viewstate("aGVsbG8=").is_err());
+        assert!(decode_viewstate(&encode(&[0xff, 0x01, 0x0f, 0x64])).is_err());
+        assert!(decode_viewstate(&encode(&[0xff, 0x01, 0x99])).is_err());
+        assert!(decode_viewstate(&encode(&[0xff, 0x01, 0x1f, 0x00])).is_err());
+        let nested = [vec![0xff, 0x01], vec![0x0f; 1000]].concat();
+        assert!(decode_viewstate(&encode(&nested)).is_err());
+        let state = decode_viewstate(&encode(&[0xff, 0x01, 0x64])).unwrap();
+        assert!(verify_viewstate(&state, &MachineKey::default()).is_err());
+        assert!(parse_generator("xyz").is_err());
+        assert!("sha3".parse::<ViewStateMac>().is_err());
+    }
+}
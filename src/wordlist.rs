@@ -0,0 +1,127 @@
+//! Multithreaded wordlist brute force of secrets, shared by the JWT and session cookie crackers.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Outcome of a wordlist brute force: the matching secrets and throughput statistics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrackResult {
+    /// Matching secrets, in wordlist order.
+    pub secrets: Vec<Vec<u8>>,
+    /// Number of candidates tried.
+    pub tried: usize,
+    /// Number of threads used.
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+impl CrackResult {
+    /// Returns the number of candidates tried per second.
+    pub fn rate(&self) -> f64 {
+        self.tried as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Tries each line of a wordlist as a secret until `matches` accepts one, spread over
+/// `threads` threads, or all CPU cores when it is `0`.
+///
+/// Lines may end in `\n` or `\r\n`. Stops at the first match unless `all_hits` is set.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::wordlist;
+/// let result = wordlist::crack_wordlist(b"red\r\ngreen\r\nblue\r\n", true, 2, |secret| secret.len() > 3);
+/// assert_eq!(result.secrets, vec![b"green".to_vec(), b"blue".to_vec()]);
+/// assert_eq!(result.tried, 3);
+/// ```
+pub fn crack_wordlist(
+    wordlist: &[u8],
+    all_hits: bool,
+    threads: usize,
+    matches: impl Fn(&[u8]) -> bool + Sync,
+) -> CrackResult {
+    let mut candidates: Vec<&[u8]> = wordlist
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+        .collect();
+    if wordlist.is_empty() || wordlist.ends_with(b"\n") {
+        candidates.pop();
+    }
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .clamp(1, candidates.len().max(1));
+
+    let found = AtomicBool::new(false);
+    let tried = AtomicUsize::new(0);
+    let start = Instant::now();
+    // Threads take every n-th candidate, so the top of the wordlist is tried first.
+    let mut hits: Vec<(usize, &[u8])> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|offset| {
+                let (candidates, found, tried, matches) = (&candidates, &found, &tried, &matches);
+                scope.spawn(move || {
+                    let mut hits = Vec::new();
+                    let mut count = 0;
+                    for index in (offset..candidates.len()).step_by(threads) {
+                        if !all_hits && found.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        count += 1;
+                        if matches(candidates[index]) {
+                            hits.push((index, candidates[index]));
+                            found.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    tried.fetch_add(count, Ordering::Relaxed);
+                    hits
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    hits.sort();
+    if !all_hits {
+        hits.truncate(1);
+    }
+    CrackResult {
+        secrets: hits
+            .into_iter()
+            .map(|(_, secret)| secret.to_vec())
+            .collect(),
+        tried: tried.into_inner(),
+        threads,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests_wordlist {
+    use super::*;
+
+    #[test]
+    fn it_cracks_wordlists() {
+        let wordlist = b"alpha\nbeta\ngamma\ndelta";
+        for threads in [0, 1, 3, 10] {
+            let result = crack_wordlist(wordlist, false, threads, |secret| secret.len() == 5);
+            assert_eq!(result.secrets, vec![b"alpha".to_vec()]);
+            assert!(result.threads >= 1 && result.threads <= 4);
+        }
+        let result = crack_wordlist(wordlist, true, 2, |secret| secret.len() == 5);
+        assert_eq!(
+            result.secrets,
+            vec![b"alpha".to_vec(), b"gamma".to_vec(), b"delta".to_vec()]
+        );
+        assert_eq!(result.tried, 4);
+
+        let result = crack_wordlist(b"", true, 4, |_| true);
+        assert!(result.secrets.is_empty());
+        assert_eq!(result.threads, 1);
+    }
+}
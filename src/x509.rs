@@ -0,0 +1,655 @@
+//! X.509 certificates and PKCS#10 certificate requests: subject, issuer, validity, subject
+//! alternative names, public key, extensions, fingerprints and SPKI pins.
+
+use crate::asn1::{self, Node, Tag, TagClass};
+use crate::encoder::{self, DigestFormat, HashAlgorithm};
+use chrono::{DateTime, Utc};
+use std::{error::Error, net::IpAddr};
+
+/// Key usage bits of RFC 5280, in bit order.
+const KEY_USAGES: [&str; 9] = [
+    "digitalSignature",
+    "nonRepudiation",
+    "keyEncipherment",
+    "dataEncipherment",
+    "keyAgreement",
+    "keyCertSign",
+    "cRLSign",
+    "encipherOnly",
+    "decipherOnly",
+];
+
+/// Whether a structure is a certificate or a certificate request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum X509Kind {
+    Certificate,
+    /// A PKCS#10 certificate signing request.
+    Request,
+}
+
+/// A certificate extension, with its value formatted for reading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension {
+    pub oid: String,
+    pub critical: bool,
+    pub value: String,
+}
+
+impl Extension {
+    /// The name of the extension, or its object identifier when it is not known.
+    pub fn name(&self) -> &str {
+        asn1::oid_name(&self.oid).unwrap_or(&self.oid)
+    }
+}
+
+/// The fields of a certificate or certificate request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    pub kind: X509Kind,
+    /// The version as written in the text form, 1 to 3 for certificates.
+    pub version: u64,
+    /// The serial number of a certificate.
+    pub serial: Vec<u8>,
+    pub signature_algorithm: String,
+    pub subject: String,
+    /// The issuer of a certificate, empty for requests.
+    pub issuer: String,
+    pub not_before: Option<DateTime<Utc>>,
+    pub not_after: Option<DateTime<Utc>>,
+    pub subject_alt_names: Vec<String>,
+    /// The key type and size, such as `RSA 2048 bit`.
+    pub public_key: String,
+    /// The DER of the `SubjectPublicKeyInfo`, which SPKI pins are a hash of.
+    pub spki: Vec<u8>,
+    /// Extensions of a certificate, or the requested ones of a request.
+    pub extensions: Vec<Extension>,
+    /// The DER of the whole certificate, which fingerprints are a hash of.
+    pub der: Vec<u8>,
+}
+
+fn invalid(what: &str) -> Box<dyn Error> {
+    format!("Invalid certificate: {}", what).into()
+}
+
+fn child<'a>(node: &'a Node, index: usize, what: &str) -> Result<&'a Node, Box<dyn Error>> {
+    node.children
+        .get(index)
+        .ok_or_else(|| invalid(&format!("missing {}", what)))
+}
+
+/// Number of significant bits of an unsigned big-endian integer.
+fn bit_length(bytes: &[u8]) -> usize {
+    match bytes.iter().position(|byte| *byte != 0) {
+        Some(first) => (bytes.len() - first) * 8 - bytes[first].leading_zeros() as usize,
+        None => 0,
+    }
+}
+
+fn unsigned(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |value, &byte| value.wrapping_shl(8) | byte as u64)
+}
+
+fn algorithm_name(node: &Node) -> String {
+    match node.children.first().and_then(Node::oid) {
+        Some(oid) => asn1::oid_name(&oid).map_or(oid, str::to_string),
+        None => "unknown".to_string(),
+    }
+}
+
+/// The short name of a distinguished name attribute, as in `CN=example.com`.
+fn attribute_name(oid: &str) -> String {
+    let short = match oid {
+        "2.5.4.3" => "CN",
+        "2.5.4.4" => "SN",
+        "2.5.4.5" => "serialNumber",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.9" => "street",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "2.5.4.42" => "GN",
+        "0.9.2342.19200300.100.1.1" => "UID",
+        "0.9.2342.19200300.100.1.25" => "DC",
+        _ => return asn1::oid_name(oid).map_or(oid.to_string(), str::to_string),
+    };
+    short.to_string()
+}
+
+/// Formats a distinguished name in the order it is encoded, such as `CN=example.com, O=Test`.
+fn format_name(name: &Node) -> String {
+    name.children
+        .iter()
+        .flat_map(|set| &set.children)
+        .filter_map(|attribute| {
+            let oid = attribute.children.first()?.oid()?;
+            let value = attribute.children.get(1)?;
+            let text = value.text().unwrap_or_else(|| hex::encode(&value.value));
+            Some(format!("{}={}", attribute_name(&oid), text))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats a `GeneralName` as OpenSSL does, such as `DNS:example.com` or `IP:10.0.0.1`.
+fn format_general_name(name: &Node) -> String {
+    if name.tag.class != TagClass::ContextSpecific {
+        return hex::encode(&name.value);
+    }
+    let text = || {
+        name.text()
+            .unwrap_or_else(|| String::from_utf8_lossy(&name.value).into_owned())
+    };
+    match name.tag.number {
+        0 => {
+            // otherName: a type identifier and an explicitly tagged value, such as the
+            // userPrincipalName of smart card certificates.
+            let oid = name
+                .children
+                .first()
+                .and_then(Node::oid)
+                .unwrap_or_default();
+            let value = name
+                .children
+                .get(1)
+                .and_then(|value| value.children.first())
+                .and_then(Node::text)
+                .unwrap_or_default();
+            let oid = match oid.as_str() {
+                "1.3.6.1.4.1.311.20.2.3" => "UPN".to_string(),
+                _ => asn1::oid_name(&oid).map_or(oid, str::to_string),
+            };
+            format!("othername:{}:{}", oid, value)
+        }
+        1 => format!("email:{}", text()),
+        2 => format!("DNS:{}", text()),
+        4 => format!(
+            "DirName:{}",
+            name.children.first().map(format_name).unwrap_or_default()
+        ),
+        6 => format!("URI:{}", text()),
+        7 => match name.value.len() {
+            4 => {
+                let octets: [u8; 4] = name.value[..].try_into().expect("length checked");
+                format!("IP:{}", IpAddr::from(octets))
+            }
+            16 => {
+                let octets: [u8; 16] = name.value[..].try_into().expect("length checked");
+                format!("IP:{}", IpAddr::from(octets))
+            }
+            _ => format!("IP:{}", hex::encode(&name.value)),
+        },
+        8 => format!(
+            "RID:{}",
+            asn1::decode_oid(&name.value).unwrap_or_else(|_| hex::encode(&name.value))
+        ),
+        number => format!("[{}]:{}", number, hex::encode(&name.value)),
+    }
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(&encoder::format_digest(bytes, DigestFormat::Colon)).into_owned()
+}
+
+/// Formats the value of an extension, the DER inside its OCTET STRING.
+fn format_extension(oid: &str, value: &Node) -> String {
+    let Some(inner) = value.children.first() else {
+        return colon_hex(&value.value);
+    };
+    let oid_names = |nodes: &[Node]| {
+        nodes
+            .iter()
+            .filter_map(Node::oid)
+            .map(|oid| asn1::oid_name(&oid).map_or(oid, str::to_string))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match oid {
+        // subjectAltName and issuerAltName.
+        "2.5.29.17" | "2.5.29.18" => inner
+            .children
+            .iter()
+            .map(format_general_name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        "2.5.29.19" => {
+            let ca = inner
+                .children
+                .iter()
+                .any(|node| node.tag.is(Tag::BOOLEAN) && node.value != [0]);
+            let path_length = inner
+                .children
+                .iter()
+                .find(|node| node.tag.is(Tag::INTEGER))
+                .map(|node| format!(", pathlen:{}", unsigned(&node.value)))
+                .unwrap_or_default();
+            format!("CA:{}{}", if ca { "TRUE" } else { "FALSE" }, path_length)
+        }
+        "2.5.29.15" => match inner.bit_string() {
+            Some((_, bits)) => KEY_USAGES
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| {
+                    bits.get(bit / 8)
+                        .is_some_and(|byte| byte & (0x80 >> (bit % 8)) != 0)
+                })
+                .map(|(_, name)| *name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => colon_hex(&value.value),
+        },
+        "2.5.29.37" => oid_names(&inner.children),
+        "2.5.29.32" => oid_names(
+            &inner
+                .children
+                .iter()
+                .filter_map(|policy| policy.children.first().cloned())
+                .collect::<Vec<_>>(),
+        ),
+        "2.5.29.14" => colon_hex(&inner.value),
+        "2.5.29.35" => inner
+            .children
+            .iter()
+            .filter(|node| node.tag.class == TagClass::ContextSpecific && node.tag.number == 0)
+            .map(|node| format!("keyid:{}", colon_hex(&node.value)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        // authorityInfoAccess and subjectInfoAccess.
+        "1.3.6.1.5.5.7.1.1" | "1.3.6.1.5.5.7.1.11" => inner
+            .children
+            .iter()
+            .filter_map(|access| {
+                let method = access.children.first()?.oid()?;
+                let location = format_general_name(access.children.get(1)?);
+                let method = asn1::oid_name(&method).map_or(method, str::to_string);
+                Some(format!("{} - {}", method, location))
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        "2.5.29.31" => {
+            // The full names of every distribution point.
+            let mut names = Vec::new();
+            let mut pending: Vec<&Node> = inner.children.iter().collect();
+            while let Some(node) = pending.pop() {
+                if node.tag.class == TagClass::ContextSpecific
+                    && !node.tag.constructed
+                    && node.tag.number == 6
+                {
+                    names.push(format_general_name(node));
+                }
+                pending.extend(node.children.iter().rev());
+            }
+            names.join(", ")
+        }
+        _ => colon_hex(&value.value),
+    }
+}
+
+fn parse_extensions(sequence: &Node) -> Vec<Extension> {
+    sequence
+        .children
+        .iter()
+        .filter_map(|extension| {
+            let oid = extension.children.first()?.oid()?;
+            let critical = extension
+                .children
+                .iter()
+                .any(|node| node.tag.is(Tag::BOOLEAN) && node.value != [0]);
+            let value = extension
+                .children
+                .iter()
+                .find(|node| node.tag.is(Tag::OCTET_STRING))?;
+            Some(Extension {
+                value: format_extension(&oid, value),
+                oid,
+                critical,
+            })
+        })
+        .collect()
+}
+
+/// Describes the key of a `SubjectPublicKeyInfo`: its type and size, such as
+/// `RSA 2048 bit, exponent 65537` or `EC P-256`.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::{asn1, x509};
+/// let spki = hex::decode("302a300506032b6570032100d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+/// let nodes = asn1::parse_der(&spki).unwrap();
+/// assert_eq!(x509::describe_public_key(&nodes[0]), "Ed25519 256 bit");
+/// ```
+pub fn describe_public_key(spki: &Node) -> String {
+    let Some(algorithm) = spki.children.first() else {
+        return "unknown".to_string();
+    };
+    let oid = algorithm
+        .children
+        .first()
+        .and_then(Node::oid)
+        .unwrap_or_default();
+    let parameters = algorithm.children.get(1);
+    let key = spki.children.get(1).and_then(Node::bit_string);
+    match oid.as_str() {
+        "1.2.840.113549.1.1.1" | "1.2.840.113549.1.1.10" => {
+            let name = if oid.ends_with(".10") {
+                "RSA-PSS"
+            } else {
+                "RSA"
+            };
+            let fields = spki
+                .children
+                .get(1)
+                .and_then(|key| key.children.first())
+                .map(|sequence| sequence.children.as_slice())
+                .unwrap_or_default();
+            match fields {
+                [modulus, exponent, ..] => format!(
+                    "{} {} bit, exponent {}",
+                    name,
+                    bit_length(&modulus.value),
+                    unsigned(&exponent.value)
+                ),
+                _ => name.to_string(),
+            }
+        }
+        "1.2.840.10045.2.1" => {
+            let curve = parameters.and_then(Node::oid).unwrap_or_default();
+            match curve.as_str() {
+                "1.2.840.10045.3.1.7" => "EC P-256".to_string(),
+                "1.3.132.0.34" => "EC P-384".to_string(),
+                "1.3.132.0.35" => "EC P-521".to_string(),
+                "1.3.132.0.10" => "EC secp256k1".to_string(),
+                "" => "EC".to_string(),
+                _ => format!("EC {}", asn1::oid_name(&curve).unwrap_or(&curve)),
+            }
+        }
+        "1.2.840.10040.4.1" => {
+            let prime = parameters.and_then(|parameters| parameters.children.first());
+            match prime {
+                Some(prime) => format!("DSA {} bit", bit_length(&prime.value)),
+                None => "DSA".to_string(),
+            }
+        }
+        "1.3.101.110" | "1.3.101.111" | "1.3.101.112" | "1.3.101.113" => format!(
+            "{} {} bit",
+            asn1::oid_name(&oid).unwrap_or(&oid),
+            key.map_or(0, |(_, bytes)| bytes.len() * 8)
+        ),
+        _ => asn1::oid_name(&oid).map_or(oid.clone(), str::to_string),
+    }
+}
+
+/// Parses the DER of a certificate or a PKCS#10 certificate request.
+pub fn parse_certificate(der: &[u8]) -> Result<Certificate, Box<dyn Error>> {
+    let nodes = asn1::parse_der(der)?;
+    // Trailing data, as the auxiliary trust settings of OpenSSL's TRUSTED CERTIFICATE, is
+    // not part of the certificate.
+    let root = nodes.first().ok_or_else(|| invalid("no data"))?;
+    let der = &der[..root.encoded_length()];
+    let info = child(root, 0, "to-be-signed part")?;
+    let signature_algorithm = algorithm_name(child(root, 1, "signature algorithm")?);
+    if !root.tag.is(Tag::SEQUENCE) || !info.tag.is(Tag::SEQUENCE) {
+        return Err(invalid("expected a SEQUENCE"));
+    }
+    let fields = &info.children;
+    let is_request = fields.len() == 4
+        && fields[0].tag.is(Tag::INTEGER)
+        && fields[3].tag.class == TagClass::ContextSpecific
+        && fields[3].tag.number == 0;
+
+    if is_request {
+        let spki = &fields[2];
+        let extensions = fields[3]
+            .children
+            .iter()
+            .filter(|attribute| {
+                attribute.children.first().and_then(Node::oid).as_deref()
+                    == Some("1.2.840.113549.1.9.14")
+            })
+            .filter_map(|attribute| attribute.children.get(1)?.children.first())
+            .flat_map(parse_extensions)
+            .collect::<Vec<_>>();
+        return Ok(Certificate {
+            kind: X509Kind::Request,
+            version: unsigned(&fields[0].value) + 1,
+            serial: Vec::new(),
+            signature_algorithm,
+            subject: format_name(&fields[1]),
+            issuer: String::new(),
+            not_before: None,
+            not_after: None,
+            subject_alt_names: subject_alt_names(&extensions),
+            public_key: describe_public_key(spki),
+            spki: encoded(der, spki),
+            extensions,
+            der: der.to_vec(),
+        });
+    }
+
+    // The version is an explicitly tagged [0], absent for version 1.
+    let (version, fields) = match fields.first() {
+        Some(first) if first.tag.class == TagClass::ContextSpecific && first.tag.number == 0 => {
+            let version = first
+                .children
+                .first()
+                .map_or(0, |node| unsigned(&node.value));
+            (version + 1, &fields[1..])
+        }
+        _ => (1, &fields[..]),
+    };
+    let [serial, _, issuer, validity, subject, spki, rest @ ..] = fields else {
+        return Err(invalid("missing fields of the to-be-signed part"));
+    };
+    let extensions = rest
+        .iter()
+        .find(|node| node.tag.class == TagClass::ContextSpecific && node.tag.number == 3)
+        .and_then(|node| node.children.first())
+        .map(parse_extensions)
+        .unwrap_or_default();
+    Ok(Certificate {
+        kind: X509Kind::Certificate,
+        version,
+        serial: serial.value.clone(),
+        signature_algorithm,
+        subject: format_name(subject),
+        issuer: format_name(issuer),
+        not_before: validity.children.first().and_then(Node::time),
+        not_after: validity.children.get(1).and_then(Node::time),
+        subject_alt_names: subject_alt_names(&extensions),
+        public_key: describe_public_key(spki),
+        spki: encoded(der, spki),
+        extensions,
+        der: der.to_vec(),
+    })
+}
+
+fn encoded(der: &[u8], node: &Node) -> Vec<u8> {
+    der[node.offset..node.offset + node.encoded_length()].to_vec()
+}
+
+fn subject_alt_names(extensions: &[Extension]) -> Vec<String> {
+    extensions
+        .iter()
+        .filter(|extension| extension.oid == "2.5.29.17")
+        .flat_map(|extension| extension.value.split(", "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The fingerprint of a certificate: a digest of its DER, as colon separated uppercase hex.
+pub fn fingerprint(der: &[u8], algorithm: HashAlgorithm) -> String {
+    colon_hex(&encoder::hash_digest(algorithm, der))
+}
+
+/// The SPKI pin of a public key: the base64 SHA-256 digest of its `SubjectPublicKeyInfo`,
+/// as in the `pin-sha256` directives of HPKP and certificate pinning configurations.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::x509;
+/// assert_eq!(x509::spki_pin(b""), "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+/// ```
+pub fn spki_pin(spki: &[u8]) -> String {
+    String::from_utf8_lossy(&encoder::format_digest(
+        &encoder::hash_digest(HashAlgorithm::Sha256, spki),
+        DigestFormat::Base64,
+    ))
+    .into_owned()
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Formats a certificate or request for reading, with the validity checked against `now`.
+pub fn format_certificate(certificate: &Certificate, now: DateTime<Utc>) -> String {
+    let mut lines = Vec::new();
+    let request = certificate.kind == X509Kind::Request;
+    lines.push(
+        if request {
+            "Certificate request"
+        } else {
+            "Certificate"
+        }
+        .to_string(),
+    );
+    lines.push(format!("Version: {}", certificate.version));
+    if !request {
+        lines.push(format!("Serial: {}", hex::encode(&certificate.serial)));
+    }
+    lines.push(format!(
+        "Signature algorithm: {}",
+        certificate.signature_algorithm
+    ));
+    lines.push(format!("Subject: {}", certificate.subject));
+    if !request {
+        let self_issued = if certificate.issuer == certificate.subject {
+            " (self-issued)"
+        } else {
+            ""
+        };
+        lines.push(format!("Issuer: {}{}", certificate.issuer, self_issued));
+    }
+    if let Some(not_before) = certificate.not_before {
+        let note = if now < not_before {
+            " (not yet valid)"
+        } else {
+            ""
+        };
+        lines.push(format!("Not before: {}{}", format_time(not_before), note));
+    }
+    if let Some(not_after) = certificate.not_after {
+        let days = (not_after - now).num_days();
+        let note = if now > not_after {
+            format!(" (expired {} days ago)", -days)
+        } else {
+            format!(" (expires in {} days)", days)
+        };
+        lines.push(format!("Not after: {}{}", format_time(not_after), note));
+    }
+    if !certificate.subject_alt_names.is_empty() {
+        lines.push(format!(
+            "Subject alternative names: {}",
+            certificate.subject_alt_names.join(", ")
+        ));
+    }
+    lines.push(format!("Public key: {}", certificate.public_key));
+    if !certificate.extensions.is_empty() {
+        lines.push(
+            if request {
+                "Requested extensions:"
+            } else {
+                "Extensions:"
+            }
+            .to_string(),
+        );
+        for extension in &certificate.extensions {
+            lines.push(format!(
+                "  {}{}: {}",
+                extension.name(),
+                if extension.critical {
+                    " (critical)"
+                } else {
+                    ""
+                },
+                extension.value
+            ));
+        }
+    }
+    if !request {
+        lines.push(format!(
+            "SHA-1 fingerprint: {}",
+            fingerprint(&certificate.der, HashAlgorithm::Sha1)
+        ));
+        lines.push(format!(
+            "SHA-256 fingerprint: {}",
+            fingerprint(&certificate.der, HashAlgorithm::Sha256)
+        ));
+    }
+    lines.push(format!(
+        "SPKI pin: pin-sha256=\"{}\"",
+        spki_pin(&certificate.spki)
+    ));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests_x509 {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_measures_integers() {
+        assert_eq!(bit_length(&[0x00, 0x80, 0x00]), 16);
+        assert_eq!(bit_length(&[0x01, 0x00, 0x01]), 17);
+        assert_eq!(bit_length(&[0x00]), 0);
+        assert_eq!(unsigned(&[0x01, 0x00, 0x01]), 65537);
+    }
+
+    #[test]
+    fn it_formats_general_names() {
+        let der = hex::decode("3015820b6578616d706c652e636f6d87040a0000018100").unwrap();
+        let nodes = asn1::parse_der(&der).unwrap();
+        let names: Vec<String> = nodes[0].children.iter().map(format_general_name).collect();
+        assert_eq!(names, vec!["DNS:example.com", "IP:10.0.0.1", "email:"]);
+    }
+
+    #[test]
+    fn it_reports_validity() {
+        let certificate = Certificate {
+            kind: X509Kind::Certificate,
+            version: 3,
+            serial: vec![0x01],
+            signature_algorithm: "ecdsa-with-SHA256".to_string(),
+            subject: "CN=a".to_string(),
+            issuer: "CN=b".to_string(),
+            not_before: Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()),
+            not_after: Some(Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap()),
+            subject_alt_names: Vec::new(),
+            public_key: "EC P-256".to_string(),
+            spki: Vec::new(),
+            extensions: vec![Extension {
+                oid: "2.5.29.19".to_string(),
+                critical: true,
+                value: "CA:FALSE".to_string(),
+            }],
+            der: Vec::new(),
+        };
+        let text = format_certificate(
+            &certificate,
+            Utc.with_ymd_and_hms(2025, 2, 10, 0, 0, 0).unwrap(),
+        );
+        assert!(text.contains("\nIssuer: CN=b\n"));
+        assert!(text.contains("\nNot after: 2025-01-31 00:00:00 UTC (expired 10 days ago)\n"));
+        assert!(text.contains("\n  basicConstraints (critical): CA:FALSE\n"));
+        let text = format_certificate(
+            &certificate,
+            Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap(),
+        );
+        assert!(text.contains("\nNot before: 2025-01-01 00:00:00 UTC (not yet valid)\n"));
+    }
+}
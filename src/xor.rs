@@ -0,0 +1,281 @@
+//! XOR encoding with repeating, incrementing and rolling keys, and key recovery by brute force.
+
+use crate::analysis::{hamming_distance, score_english};
+use std::{error::Error, str};
+
+/// How the key is applied to the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XorMode {
+    /// The key is repeated over the whole input.
+    #[default]
+    Repeating,
+    /// Each key byte is incremented by one every time the key wraps around.
+    Incrementing,
+    /// Each byte is also XORed with the previous output byte, like cipher feedback.
+    Rolling,
+}
+
+impl str::FromStr for XorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "repeating" => Ok(XorMode::Repeating),
+            "incrementing" => Ok(XorMode::Incrementing),
+            "rolling" => Ok(XorMode::Rolling),
+            _ => Err(format!(
+                "Unknown XOR mode '{}', expected one of: repeating, incrementing, rolling",
+                s
+            )),
+        }
+    }
+}
+
+/// A key recovered by brute force, with the resulting plaintext and its English score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XorCandidate {
+    pub key: Vec<u8>,
+    pub score: f64,
+    pub plaintext: Vec<u8>,
+}
+
+/// Returns the key byte used at `position` for the repeating and incrementing modes.
+fn key_byte(key: &[u8], position: usize, mode: XorMode) -> u8 {
+    let byte = key[position % key.len()];
+    match mode {
+        XorMode::Incrementing => byte.wrapping_add((position / key.len()) as u8),
+        _ => byte,
+    }
+}
+
+/// XOR encodes bytes with the key in the given mode.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::xor::{self, XorMode};
+/// let encoded = xor::encode_xor(b"hello", b"k", XorMode::Repeating).unwrap();
+/// assert_eq!(encoded, vec![0x03, 0x0e, 0x07, 0x07, 0x04]);
+/// ```
+pub fn encode_xor(data: &[u8], key: &[u8], mode: XorMode) -> Result<Vec<u8>, Box<dyn Error>> {
+    if key.is_empty() {
+        return Err("XOR key must not be empty".into());
+    }
+    let mut previous = 0u8;
+    Ok(data
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| match mode {
+            XorMode::Rolling => {
+                previous ^= b ^ key[i % key.len()];
+                previous
+            }
+            _ => b ^ key_byte(key, i, mode),
+        })
+        .collect())
+}
+
+/// Decodes XOR encoded bytes with the key in the given mode.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::xor::{self, XorMode};
+/// let encoded = xor::encode_xor(b"hello", b"key", XorMode::Rolling).unwrap();
+/// let decoded = xor::decode_xor(&encoded, b"key", XorMode::Rolling).unwrap();
+/// assert_eq!(decoded, b"hello");
+/// ```
+pub fn decode_xor(data: &[u8], key: &[u8], mode: XorMode) -> Result<Vec<u8>, Box<dyn Error>> {
+    if key.is_empty() {
+        return Err("XOR key must not be empty".into());
+    }
+    match mode {
+        XorMode::Rolling => Ok(data
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| {
+                let previous = if i == 0 { 0 } else { data[i - 1] };
+                b ^ key[i % key.len()] ^ previous
+            })
+            .collect()),
+        _ => encode_xor(data, key, mode),
+    }
+}
+
+/// Tries all 256 single-byte keys and returns the candidates ranked by English score.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::xor::{self, XorMode};
+/// let encoded = xor::encode_xor(b"attack at dawn", b"X", XorMode::Repeating).unwrap();
+/// let candidates = xor::bruteforce_single_byte_xor(&encoded);
+/// assert_eq!(candidates[0].key, b"X");
+/// assert_eq!(candidates[0].plaintext, b"attack at dawn");
+/// ```
+pub fn bruteforce_single_byte_xor(data: &[u8]) -> Vec<XorCandidate> {
+    let mut candidates: Vec<XorCandidate> = (0..=255u8)
+        .map(|key| {
+            let plaintext: Vec<u8> = data.iter().map(|b| b ^ key).collect();
+            XorCandidate {
+                key: vec![key],
+                score: score_english(&plaintext),
+                plaintext,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/// Estimates the most likely repeating-key lengths, from 1 up to `max_length`.
+///
+/// Returns `(length, normalized Hamming distance)` pairs with the lowest, most likely,
+/// distance first. Lengths that do not fit at least twice into the data are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::xor::{self, XorMode};
+/// let plaintext = b"Now that the party is jumping, with the bass kicked in and the vegas are pumping";
+/// let encoded = xor::encode_xor(plaintext, b"ICE", XorMode::Repeating).unwrap();
+/// let lengths = xor::estimate_xor_key_length(&encoded, 10);
+/// assert!(lengths.iter().take(3).any(|(length, _)| *length == 3));
+/// ```
+pub fn estimate_xor_key_length(data: &[u8], max_length: usize) -> Vec<(usize, f64)> {
+    let mut lengths: Vec<(usize, f64)> = (1..=max_length)
+        .filter(|length| data.len() >= length * 2)
+        .map(|length| {
+            let blocks: Vec<&[u8]> = data.chunks_exact(length).collect();
+            let pairs = blocks.len() - 1;
+            let distance: u32 = blocks
+                .windows(2)
+                .map(|pair| hamming_distance(pair[0], pair[1]))
+                .sum();
+            (length, distance as f64 / pairs as f64 / length as f64)
+        })
+        .collect();
+    lengths.sort_by(|a, b| a.1.total_cmp(&b.1));
+    lengths
+}
+
+/// Recovers a repeating XOR key of up to `max_length` bytes.
+///
+/// The few most likely key lengths are tried, each key byte is found by single-byte
+/// brute force over its column, and the candidates are ranked by English score,
+/// preferring shorter keys when the scores are close.
+///
+/// # Examples
+///
+/// ```
+/// use nutek_encode_lib::xor::{self, XorMode};
+/// let plaintext = b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+/// let encoded = xor::encode_xor(plaintext, b"ICE", XorMode::Repeating).unwrap();
+/// let candidates = xor::bruteforce_repeating_xor(&encoded, 10);
+/// assert_eq!(candidates[0].key, b"ICE");
+/// ```
+pub fn bruteforce_repeating_xor(data: &[u8], max_length: usize) -> Vec<XorCandidate> {
+    let mut candidates: Vec<XorCandidate> = estimate_xor_key_length(data, max_length)
+        .into_iter()
+        .take(5)
+        .map(|(length, _)| {
+            let key: Vec<u8> = (0..length)
+                .map(|offset| {
+                    let column: Vec<u8> =
+                        data.iter().skip(offset).step_by(length).copied().collect();
+                    bruteforce_single_byte_xor(&column)[0].key[0]
+                })
+                .collect();
+            let key = shortest_period(key);
+            let plaintext = encode_xor(data, &key, XorMode::Repeating).unwrap_or_default();
+            XorCandidate {
+                score: score_english(&plaintext),
+                key,
+                plaintext,
+            }
+        })
+        .collect();
+    // Longer keys fit the text slightly better by chance, so each key byte costs a little.
+    let penalized = |candidate: &XorCandidate| {
+        candidate.score - candidate.key.len() as f64 / data.len().max(1) as f64
+    };
+    candidates.sort_by(|a, b| {
+        penalized(b)
+            .total_cmp(&penalized(a))
+            .then(a.key.len().cmp(&b.key.len()))
+    });
+    candidates.dedup_by(|a, b| a.plaintext == b.plaintext);
+    candidates
+}
+
+/// Shortens a key that repeats itself, e.g. `ICEICE` becomes `ICE`.
+fn shortest_period(key: Vec<u8>) -> Vec<u8> {
+    (1..key.len())
+        .filter(|period| key.len().is_multiple_of(*period))
+        .find(|&period| key.iter().enumerate().all(|(i, b)| *b == key[i % period]))
+        .map(|period| key[..period].to_vec())
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests_xor {
+    use super::*;
+
+    #[test]
+    fn it_encodes_xor() {
+        let encoded = encode_xor(b"hello world", b"key", XorMode::Repeating).unwrap();
+        assert_eq!(hex::encode(&encoded), "030015070a591c0a0b0701");
+
+        let encoded = encode_xor(b"AAAA", b"\x01", XorMode::Incrementing).unwrap();
+        assert_eq!(encoded, vec![0x40, 0x43, 0x42, 0x45]);
+
+        let encoded = encode_xor(b"AAAA", b"\x01", XorMode::Rolling).unwrap();
+        assert_eq!(encoded, vec![0x40, 0x00, 0x40, 0x00]);
+
+        // Malformed input
+        let result = encode_xor(b"hello", b"", XorMode::Repeating);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_decodes_xor() {
+        for mode in [XorMode::Repeating, XorMode::Incrementing, XorMode::Rolling] {
+            let encoded = encode_xor(b"hello world", b"secret", mode).unwrap();
+            let decoded = decode_xor(&encoded, b"secret", mode).unwrap();
+            assert_eq!(decoded, b"hello world");
+        }
+
+        // Malformed input
+        let result = decode_xor(b"hello", b"", XorMode::Rolling);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_bruteforces_single_byte_xor() {
+        let encoded = encode_xor(
+            b"Cooking MC's like a pound of bacon",
+            b"\x58",
+            XorMode::Repeating,
+        )
+        .unwrap();
+        let candidates = bruteforce_single_byte_xor(&encoded);
+        assert_eq!(candidates.len(), 256);
+        assert_eq!(candidates[0].key, vec![0x58]);
+        assert_eq!(
+            candidates[0].plaintext,
+            b"Cooking MC's like a pound of bacon"
+        );
+    }
+
+    #[test]
+    fn it_bruteforces_repeating_xor() {
+        let plaintext =
+            b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+        let encoded = encode_xor(plaintext, b"ICE", XorMode::Repeating).unwrap();
+        let candidates = bruteforce_repeating_xor(&encoded, 8);
+        assert_eq!(candidates[0].key, b"ICE");
+        assert_eq!(candidates[0].plaintext, plaintext);
+
+        assert!(bruteforce_repeating_xor(b"a", 8).is_empty());
+    }
+}